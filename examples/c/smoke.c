@@ -0,0 +1,65 @@
+/*
+ * Exercises the C API end to end; exits non-zero on the first failed check.
+ *
+ *   cargo build --no-default-features --features ffi
+ *   cc examples/c/smoke.c -Iinclude -Ltarget/debug -lrusty_password_utility -o target/smoke
+ *   LD_LIBRARY_PATH=target/debug target/smoke
+ */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "rustypass.h"
+
+#define CHECK(condition)                                                   \
+    do {                                                                   \
+        if (!(condition)) {                                                \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,         \
+                    __LINE__, #condition);                                 \
+            return 1;                                                      \
+        }                                                                  \
+    } while (0)
+
+int main(void) {
+    char *password = NULL;
+    AnalysisReportC report;
+
+    /* Defaults: 16 lowercase letters */
+    CHECK(rustypass_generate(NULL, &password) == RUSTYPASS_OK);
+    CHECK(password != NULL && strlen(password) == 16);
+    printf("default:  %s\n", password);
+    rustypass_free_string(password);
+
+    /* Every class, with minimums and exclusions */
+    GenerateOptionsC options = rustypass_default_options();
+    options.length = 24;
+    options.include_uppercase = true;
+    options.include_digits = true;
+    options.include_symbols = true;
+    options.min_digits = 4;
+    options.excluded_chars = "0O1l";
+    CHECK(rustypass_generate(&options, &password) == RUSTYPASS_OK);
+    CHECK(strlen(password) == 24 && strpbrk(password, "0O1l") == NULL);
+    printf("options:  %s\n", password);
+    rustypass_free_string(password);
+
+    /* Errors leave the output null */
+    options.length = 4;
+    CHECK(rustypass_generate(&options, &password) == RUSTYPASS_ERR_INVALID_SETTINGS);
+    CHECK(password == NULL);
+    CHECK(rustypass_generate(NULL, NULL) == RUSTYPASS_ERR_INVALID_ARGUMENT);
+    CHECK(rustypass_analyze(NULL, &report) == RUSTYPASS_ERR_INVALID_ARGUMENT);
+    CHECK(rustypass_analyze("\xff\xfe", &report) == RUSTYPASS_ERR_INVALID_ARGUMENT);
+
+    /* Analysis */
+    CHECK(rustypass_analyze("password123", &report) == RUSTYPASS_OK);
+    CHECK(report.length == 11 && report.grade == 'F' && report.warning_count > 0);
+    CHECK(report.json != NULL && strstr(report.json, "\"grade\":\"F\"") != NULL);
+    printf("analysis: grade %c, score %u, %.1f effective bits\n", report.grade,
+           (unsigned)report.score, report.effective_entropy_bits);
+    rustypass_free_string(report.json);
+
+    rustypass_free_string(NULL);
+    puts("ok");
+    return 0;
+}
@@ -0,0 +1,198 @@
+/* Rusty Password Utility C API; see src/ffi.rs for the ownership rules */
+
+#ifndef RUSTYPASS_H
+#define RUSTYPASS_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit; regenerate it instead. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The call succeeded
+ */
+#define RUSTYPASS_OK 0
+
+/**
+ * A required pointer was null or a string was not valid UTF-8
+ */
+#define RUSTYPASS_ERR_INVALID_ARGUMENT 2
+
+/**
+ * The options cannot be used as given (same as exit status 3)
+ */
+#define RUSTYPASS_ERR_INVALID_SETTINGS 3
+
+/**
+ * No password satisfying the constraints could be generated (same as exit status 4)
+ */
+#define RUSTYPASS_ERR_UNSATISFIABLE 4
+
+/**
+ * The operating system's random source failed (same as exit status 5)
+ */
+#define RUSTYPASS_ERR_IO 5
+
+/**
+ * The library panicked; the call had no effect
+ */
+#define RUSTYPASS_ERR_PANIC 101
+
+/**
+ * Generator options, the C counterpart of `GeneratorOptions`
+ *
+ * Start from `rustypass_default_options()` and change what you need.
+ */
+typedef struct {
+  /**
+   * Password length in characters (8-128)
+   */
+  uint32_t length;
+  /**
+   * Include lowercase letters (a-z)
+   */
+  bool include_lowercase;
+  /**
+   * Include uppercase letters (A-Z)
+   */
+  bool include_uppercase;
+  /**
+   * Include digits (0-9)
+   */
+  bool include_digits;
+  /**
+   * Include symbols
+   */
+  bool include_symbols;
+  /**
+   * Extra characters added to the alphabet, or null for none
+   */
+  const char *custom_chars;
+  /**
+   * Characters never used, or null for none
+   */
+  const char *excluded_chars;
+  /**
+   * Minimum number of lowercase letters
+   */
+  uint32_t min_lowercase;
+  /**
+   * Minimum number of uppercase letters
+   */
+  uint32_t min_uppercase;
+  /**
+   * Minimum number of digits
+   */
+  uint32_t min_digits;
+  /**
+   * Minimum number of symbols
+   */
+  uint32_t min_symbols;
+} GenerateOptionsC;
+
+/**
+ * Summary of a password analysis, the C counterpart of `AnalysisReport`
+ */
+typedef struct {
+  /**
+   * Password length in characters (Unicode scalar values)
+   */
+  size_t length;
+  /**
+   * Estimated size of the alphabet the password was drawn from
+   */
+  uint32_t charset_size;
+  /**
+   * Brute-force entropy in bits
+   */
+  double entropy_bits;
+  /**
+   * Entropy in bits after dictionary and pattern penalties
+   */
+  double effective_entropy_bits;
+  /**
+   * Estimated seconds to guess the password at bcrypt speed
+   */
+  double crack_time_seconds;
+  /**
+   * Strength score from 0 to 100
+   */
+  uint8_t score;
+  /**
+   * Letter grade: 'A', 'B', 'C', 'D' or 'F'
+   */
+  char grade;
+  /**
+   * Number of weaknesses found
+   */
+  size_t warning_count;
+  /**
+   * The full report as JSON, shaped like `analyze --output json`;
+   * release it with `rustypass_free_string`
+   */
+  char *json;
+} AnalysisReportC;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Options of `generate` without flags: a 16-character lowercase password
+ */
+GenerateOptionsC rustypass_default_options(void);
+
+/**
+ * Generates one password
+ *
+ * # Arguments
+ * * `options` - Generator options, or null for the defaults
+ * * `out` - Receives the password on success (null otherwise); the caller
+ *   releases it with `rustypass_free_string`
+ *
+ * # Returns
+ * * `i32` - `RUSTYPASS_OK`, `RUSTYPASS_ERR_INVALID_ARGUMENT` (null `out`, non-UTF-8
+ *   character lists), `RUSTYPASS_ERR_INVALID_SETTINGS`, `RUSTYPASS_ERR_IO` or
+ *   `RUSTYPASS_ERR_PANIC`
+ *
+ * # Safety
+ * `options` must be null or point to a valid `GenerateOptionsC` whose character
+ * lists are null or NUL-terminated; `out` must be null or valid for writes.
+ */
+int32_t rustypass_generate(const GenerateOptionsC *options, char **out);
+
+/**
+ * Analyzes the strength of a password
+ *
+ * # Arguments
+ * * `password` - Password to analyze
+ * * `report` - Filled in on success; its `json` field must be released with
+ *   `rustypass_free_string`
+ *
+ * # Returns
+ * * `i32` - `RUSTYPASS_OK`, `RUSTYPASS_ERR_INVALID_ARGUMENT` (a null pointer or a
+ *   non-UTF-8 password) or `RUSTYPASS_ERR_PANIC`
+ *
+ * # Safety
+ * `password` must be null or NUL-terminated; `report` must be null or valid for writes.
+ */
+int32_t rustypass_analyze(const char *password, AnalysisReportC *report);
+
+/**
+ * Releases a string handed out by this library, wiping its contents first
+ *
+ * Null is ignored.
+ *
+ * # Safety
+ * `string` must be null or a pointer returned by this library that has not been
+ * released yet.
+ */
+void rustypass_free_string(char *string);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RUSTYPASS_H */
@@ -0,0 +1,675 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use serde::{Serialize, Serializer};
+use crate::i18n::{tr, trf, Msg};
+use crate::utils;
+use super::reuse::{find_reuse, ReuseCluster};
+use super::{analyze_password, AnalysisReport, AnalyzerOptions, Grade};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Number of weakest entries listed in the batch summary
+const WEAKEST_ENTRIES: usize = 5;
+
+/// Number of weakest entries kept by a streamed analysis
+const STREAM_WEAKEST_ENTRIES: usize = 10;
+
+/// Columns of the per-entry CSV output
+const BATCH_CSV_HEADER: [&str; 7] = ["line", "password", "length", "entropy_bits", "effective_entropy_bits", "score", "grade"];
+
+// ============================================================================
+// Batch Report
+// ============================================================================
+
+/// How often a password appears in the Have I Been Pwned breach corpus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreachCount {
+    /// Number of times the password was seen in breaches (0 when it was not found)
+    Seen(u64),
+    /// The lookup failed, e.g. because the service could not be reached
+    Unknown,
+}
+
+impl BreachCount {
+    /// Whether the password was found in at least one breach
+    pub fn is_breached(&self) -> bool {
+        matches!(self, BreachCount::Seen(count) if *count > 0)
+    }
+}
+
+/// Written as the count, or `unknown`
+impl fmt::Display for BreachCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BreachCount::Seen(count) => write!(f, "{}", count),
+            BreachCount::Unknown => f.write_str("unknown"),
+        }
+    }
+}
+
+/// Serialized as a number, or the string `"unknown"`
+impl Serialize for BreachCount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            BreachCount::Seen(count) => serializer.serialize_u64(*count),
+            BreachCount::Unknown => serializer.serialize_str("unknown"),
+        }
+    }
+}
+
+/// Analysis result for a single line of a password file
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchEntry {
+    /// 1-based line number in the input file
+    pub line: usize,
+    /// Password as displayed: masked unless `--show-passwords` is given
+    pub password: String,
+    /// Full analysis of the password
+    pub report: AnalysisReport,
+    /// Have I Been Pwned result (only with `--hibp`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breach_count: Option<BreachCount>,
+}
+
+/// Why a line of the input file was not analyzed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The line was empty
+    Blank,
+    /// The line was not valid UTF-8
+    InvalidUtf8,
+}
+
+/// A line of the input file that was not analyzed
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedLine {
+    /// 1-based line number in the input file
+    pub line: usize,
+    /// Reason the line was skipped
+    pub reason: SkipReason,
+}
+
+/// Aggregate statistics over all analyzed entries
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchSummary {
+    /// Number of analyzed entries
+    pub analyzed: usize,
+    /// Number of skipped lines
+    pub skipped: usize,
+    /// Number of entries per grade (every grade is present, even with zero entries)
+    pub grade_counts: BTreeMap<Grade, usize>,
+    /// Mean effective entropy in bits (0 when nothing was analyzed)
+    pub average_entropy_bits: f64,
+    /// Line numbers of the weakest entries, weakest first
+    pub weakest_lines: Vec<usize>,
+    /// Number of reuse clusters (only with `--find-reuse`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reuse_clusters: Option<usize>,
+    /// Number of entries belonging to a reuse cluster (only with `--find-reuse`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reused_entries: Option<usize>,
+    /// Number of entries found in breaches (only with `--hibp`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breached_entries: Option<usize>,
+    /// Number of entries whose breach lookup failed (only with `--hibp`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breach_unknown: Option<usize>,
+}
+
+/// Structured result of analyzing a whole password file
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchReport {
+    pub entries: Vec<BatchEntry>,
+    pub skipped: Vec<SkippedLine>,
+    /// Identical and near-duplicate groups (only with `--find-reuse`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reuse: Option<Vec<ReuseCluster>>,
+    pub summary: BatchSummary,
+}
+
+impl BatchReport {
+    /// Looks every analyzed entry up in a breach corpus and records the results
+    ///
+    /// # Arguments
+    /// * `contents` - The file contents given to `analyze_lines`
+    /// * `lookup` - Returns one `BreachCount` per password, in the order given
+    ///
+    /// # Notes
+    /// The passwords are taken from `contents` again because the entries may
+    /// only hold masked copies.
+    pub fn check_breaches(&mut self, contents: &[u8], lookup: impl FnOnce(&[&str]) -> Vec<BreachCount>) {
+        let by_line: BTreeMap<usize, &str> = lines(contents)
+            .filter_map(|(line, raw)| std::str::from_utf8(raw).ok().map(|password| (line, password)))
+            .collect();
+        let passwords: Vec<&str> = self.entries.iter().map(|entry| by_line[&entry.line]).collect();
+        let counts = lookup(&passwords);
+
+        for (entry, count) in self.entries.iter_mut().zip(counts) {
+            entry.breach_count = Some(count);
+        }
+        let count = |f: fn(&BreachCount) -> bool| self.entries.iter().filter(|e| e.breach_count.as_ref().is_some_and(f)).count();
+        self.summary.breached_entries = Some(count(BreachCount::is_breached));
+        self.summary.breach_unknown = Some(count(|c| *c == BreachCount::Unknown));
+    }
+}
+
+/// Analyzes every line of a password file
+///
+/// # Arguments
+/// * `contents` - Raw file contents; lines need not all be valid UTF-8
+/// * `options` - Scoring settings shared with single-password analysis
+/// * `show_passwords` - Keep passwords in clear text instead of masking them
+/// * `detect_reuse` - Also group identical and near-duplicate passwords
+/// * `progress` - Called once per line, e.g. to advance a progress bar
+///
+/// # Returns
+/// * `BatchReport` - Per-entry results, skipped lines, reuse clusters and summary
+///
+/// # Notes
+/// Only the line terminator (`\n` or `\r\n`) is stripped: leading, trailing
+/// and internal spaces are part of the password.
+pub fn analyze_lines(
+    contents: &[u8],
+    options: &AnalyzerOptions,
+    show_passwords: bool,
+    detect_reuse: bool,
+    mut progress: impl FnMut(),
+) -> BatchReport {
+    let mut entries = Vec::new();
+    let mut skipped = Vec::new();
+    let mut passwords: Vec<(usize, &str)> = Vec::new();
+
+    let display = |password: &str| if show_passwords { password.to_string() } else { utils::mask_password(password) };
+
+    for (line, raw) in lines(contents) {
+        progress();
+
+        if raw.is_empty() {
+            skipped.push(SkippedLine { line, reason: SkipReason::Blank });
+            continue;
+        }
+
+        let Ok(password) = std::str::from_utf8(raw) else {
+            skipped.push(SkippedLine { line, reason: SkipReason::InvalidUtf8 });
+            continue;
+        };
+
+        entries.push(BatchEntry {
+            line,
+            password: display(password),
+            report: analyze_password(password, options),
+            breach_count: None,
+        });
+        if detect_reuse {
+            passwords.push((line, password));
+        }
+    }
+
+    let reuse = detect_reuse.then(|| find_reuse(&passwords, display));
+    let mut summary = summarize(&entries, skipped.len());
+    if let Some(clusters) = &reuse {
+        summary.reuse_clusters = Some(clusters.len());
+        summary.reused_entries = Some(clusters.iter().map(|c| c.lines.len()).sum());
+    }
+
+    BatchReport { entries, skipped, reuse, summary }
+}
+
+// ============================================================================
+// Streamed Analysis
+// ============================================================================
+
+/// One of the weakest entries of a streamed analysis, always masked
+#[derive(Debug, Clone, Serialize)]
+pub struct WeakEntry {
+    /// 1-based line number in the input
+    pub line: usize,
+    /// Masked password
+    pub password: String,
+    /// Effective entropy in bits after pattern penalties
+    pub effective_entropy_bits: f64,
+    /// Letter grade
+    pub grade: Grade,
+}
+
+/// Aggregate statistics of a streamed analysis
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamSummary {
+    /// Number of lines read
+    pub lines: usize,
+    /// Number of analyzed entries
+    pub analyzed: usize,
+    /// Number of blank lines skipped
+    pub blank: usize,
+    /// Number of lines skipped because they were not valid UTF-8
+    pub invalid_utf8: usize,
+    /// Number of entries per grade (every grade is present, even with zero entries)
+    pub grade_counts: BTreeMap<Grade, usize>,
+    /// Mean effective entropy in bits (0 when nothing was analyzed)
+    pub average_entropy_bits: f64,
+    /// The weakest entries, weakest first
+    pub weakest: Vec<WeakEntry>,
+}
+
+/// Analyzes a password list line by line, without holding it in memory
+///
+/// # Arguments
+/// * `reader` - Input; lines need not all be valid UTF-8
+/// * `options` - Scoring settings shared with single-password analysis
+/// * `show_passwords` - Keep passwords in clear text in the entries handed to `on_entry`
+/// * `on_entry` - Called with each analyzed entry as soon as it is scored, e.g. to write it out
+///
+/// # Returns
+/// * `io::Result<StreamSummary>` - Statistics over the whole input, or the first
+///   error from reading or from `on_entry`
+///
+/// # Notes
+/// Lines are handled like `analyze_lines` does, but only one line and the ten
+/// weakest entries are held at a time, so memory stays flat however long the
+/// input is. The weakest entries are masked even with `show_passwords`.
+pub fn analyze_stream<R: io::BufRead>(
+    mut reader: R,
+    options: &AnalyzerOptions,
+    show_passwords: bool,
+    mut on_entry: impl FnMut(&BatchEntry) -> io::Result<()>,
+) -> io::Result<StreamSummary> {
+    let mut summary = StreamSummary {
+        lines: 0,
+        analyzed: 0,
+        blank: 0,
+        invalid_utf8: 0,
+        grade_counts: [Grade::A, Grade::B, Grade::C, Grade::D, Grade::F].into_iter().map(|g| (g, 0)).collect(),
+        average_entropy_bits: 0.0,
+        weakest: Vec::with_capacity(STREAM_WEAKEST_ENTRIES + 1),
+    };
+    let mut entropy_sum = 0.0;
+    let mut buffer = Vec::new();
+
+    loop {
+        buffer.clear();
+        if reader.read_until(b'\n', &mut buffer)? == 0 {
+            break;
+        }
+        summary.lines += 1;
+        let line = summary.lines;
+        let raw = buffer.strip_suffix(b"\n").unwrap_or(&buffer);
+        let raw = raw.strip_suffix(b"\r").unwrap_or(raw);
+
+        if raw.is_empty() {
+            summary.blank += 1;
+            continue;
+        }
+        let Ok(password) = std::str::from_utf8(raw) else {
+            summary.invalid_utf8 += 1;
+            continue;
+        };
+
+        let report = analyze_password(password, options);
+        summary.analyzed += 1;
+        *summary.grade_counts.entry(report.grade).or_insert(0) += 1;
+        entropy_sum += report.effective_entropy_bits;
+
+        // Kept sorted weakest first; ties keep the earlier line
+        let weaker = summary.weakest.len() < STREAM_WEAKEST_ENTRIES
+            || summary.weakest.last().is_some_and(|last| report.effective_entropy_bits < last.effective_entropy_bits);
+        if weaker {
+            let position = summary.weakest.partition_point(|e| e.effective_entropy_bits <= report.effective_entropy_bits);
+            summary.weakest.insert(position, WeakEntry {
+                line,
+                password: utils::mask_password(password),
+                effective_entropy_bits: report.effective_entropy_bits,
+                grade: report.grade,
+            });
+            summary.weakest.truncate(STREAM_WEAKEST_ENTRIES);
+        }
+
+        let password = if show_passwords { password.to_string() } else { utils::mask_password(password) };
+        on_entry(&BatchEntry { line, password, report, breach_count: None })?;
+    }
+
+    if summary.analyzed > 0 {
+        summary.average_entropy_bits = entropy_sum / summary.analyzed as f64;
+    }
+    Ok(summary)
+}
+
+/// Computes grade counts, average entropy and the weakest entries
+fn summarize(entries: &[BatchEntry], skipped: usize) -> BatchSummary {
+    let mut grade_counts: BTreeMap<Grade, usize> = [Grade::A, Grade::B, Grade::C, Grade::D, Grade::F]
+        .into_iter()
+        .map(|g| (g, 0))
+        .collect();
+    for entry in entries {
+        *grade_counts.entry(entry.report.grade).or_insert(0) += 1;
+    }
+
+    let average_entropy_bits = if entries.is_empty() {
+        0.0
+    } else {
+        entries.iter().map(|e| e.report.effective_entropy_bits).sum::<f64>() / entries.len() as f64
+    };
+
+    let mut by_strength: Vec<&BatchEntry> = entries.iter().collect();
+    by_strength.sort_by(|a, b| a.report.effective_entropy_bits.total_cmp(&b.report.effective_entropy_bits));
+    let weakest_lines = by_strength.iter().take(WEAKEST_ENTRIES).map(|e| e.line).collect();
+
+    BatchSummary {
+        analyzed: entries.len(),
+        skipped,
+        grade_counts,
+        average_entropy_bits,
+        weakest_lines,
+        reuse_clusters: None,
+        reused_entries: None,
+        breached_entries: None,
+        breach_unknown: None,
+    }
+}
+
+/// Splits file contents into 1-based numbered lines without their `\n` or `\r\n`
+fn lines(contents: &[u8]) -> impl Iterator<Item = (usize, &[u8])> {
+    // A trailing newline terminates the last line rather than starting a new one
+    let contents = contents.strip_suffix(b"\n").unwrap_or(contents);
+    contents
+        .split(|b| *b == b'\n')
+        .enumerate()
+        .map(|(index, raw)| (index + 1, raw.strip_suffix(b"\r").unwrap_or(raw)))
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+/// Renders a batch report as a human-readable table followed by a summary
+pub fn render_batch(report: &BatchReport) -> String {
+    let mut out = String::new();
+
+    // Size the password column to the widest displayed value (in characters)
+    let width = report
+        .entries
+        .iter()
+        .map(|e| e.password.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(tr(Msg::ColumnPassword).chars().count());
+
+    let breaches = report.summary.breached_entries.is_some();
+    out.push_str(&format!(
+        "{:>6}  {:<width$}  {:>6}  {:>8}  {}",
+        tr(Msg::ColumnLine),
+        tr(Msg::ColumnPassword),
+        tr(Msg::ColumnLength),
+        tr(Msg::ColumnEntropy),
+        tr(Msg::ColumnGrade)
+    ));
+    if breaches {
+        out.push_str(&format!("  {}\n", tr(Msg::ColumnBreaches)));
+    } else {
+        out.push('\n');
+    }
+    for entry in &report.entries {
+        out.push_str(&format!(
+            "{:>6}  {:<width$}  {:>6}  {:>8.1}  {:<5}",
+            entry.line,
+            entry.password,
+            entry.report.length,
+            entry.report.effective_entropy_bits,
+            entry.report.grade
+        ));
+        match entry.breach_count {
+            Some(count) => out.push_str(&format!("  {:>8}\n", count)),
+            None => out.push('\n'),
+        }
+    }
+
+    for skipped in &report.skipped {
+        let note = match skipped.reason {
+            SkipReason::Blank => Msg::SkippedBlank,
+            SkipReason::InvalidUtf8 => Msg::SkippedInvalidUtf8,
+        };
+        out.push_str(&format!("{}\n", trf(note, &[&skipped.line])));
+    }
+
+    if let Some(clusters) = &report.reuse {
+        out.push_str(&format!("\n{}\n", tr(Msg::ReuseClusters)));
+        if clusters.is_empty() {
+            out.push_str(&format!("  {}\n", tr(Msg::ReuseNone)));
+        }
+        for (index, cluster) in clusters.iter().enumerate() {
+            out.push_str(&format!("  #{} ({}):\n", index + 1, cluster.reason.description()));
+            for (line, member) in cluster.lines.iter().zip(&cluster.members) {
+                out.push_str(&format!("    {} {:>6}  {}\n", tr(Msg::ReuseLine), line, member));
+            }
+        }
+    }
+
+    let summary = &report.summary;
+    let width = label_width(&[
+        Msg::SummaryAnalyzed,
+        Msg::SummarySkipped,
+        Msg::SummaryAverageEntropy,
+        Msg::SummaryGrades,
+        Msg::SummaryReuse,
+        Msg::SummaryBreached,
+        Msg::SummaryWeakestLines,
+    ]);
+    out.push_str(&format!("\n{}\n", tr(Msg::SummaryHeading)));
+    push_summary_line(&mut out, Msg::SummaryAnalyzed, width, summary.analyzed);
+    push_summary_line(&mut out, Msg::SummarySkipped, width, summary.skipped);
+    push_summary_line(&mut out, Msg::SummaryAverageEntropy, width, format!("{:.1} bits", summary.average_entropy_bits));
+    push_summary_line(&mut out, Msg::SummaryGrades, width, format_grade_counts(&summary.grade_counts));
+    if let (Some(clusters), Some(entries)) = (summary.reuse_clusters, summary.reused_entries) {
+        push_summary_line(&mut out, Msg::SummaryReuse, width, trf(Msg::SummaryReuseValue, &[&entries, &clusters]));
+    }
+    if let (Some(breached), Some(unknown)) = (summary.breached_entries, summary.breach_unknown) {
+        push_summary_line(&mut out, Msg::SummaryBreached, width, trf(Msg::SummaryBreachedValue, &[&breached, &unknown]));
+    }
+    if !summary.weakest_lines.is_empty() {
+        let lines: Vec<String> = summary.weakest_lines.iter().map(|l| l.to_string()).collect();
+        push_summary_line(&mut out, Msg::SummaryWeakestLines, width, lines.join(", "));
+    }
+
+    out
+}
+
+/// Renders the statistics of a streamed analysis as human-readable text
+pub fn render_stream_summary(summary: &StreamSummary) -> String {
+    let width = label_width(&[
+        Msg::SummaryLines,
+        Msg::SummaryAnalyzed,
+        Msg::SummaryBlank,
+        Msg::SummaryInvalidUtf8,
+        Msg::SummaryAverageEntropy,
+        Msg::SummaryGrades,
+    ]);
+    let mut out = format!("{}\n", tr(Msg::SummaryHeading));
+    push_summary_line(&mut out, Msg::SummaryLines, width, summary.lines);
+    push_summary_line(&mut out, Msg::SummaryAnalyzed, width, summary.analyzed);
+    push_summary_line(&mut out, Msg::SummaryBlank, width, summary.blank);
+    push_summary_line(&mut out, Msg::SummaryInvalidUtf8, width, summary.invalid_utf8);
+    push_summary_line(&mut out, Msg::SummaryAverageEntropy, width, format!("{:.1} bits", summary.average_entropy_bits));
+    push_summary_line(&mut out, Msg::SummaryGrades, width, format_grade_counts(&summary.grade_counts));
+    if !summary.weakest.is_empty() {
+        out.push_str(&format!("  {}\n", tr(Msg::SummaryWeakest)));
+        for entry in &summary.weakest {
+            out.push_str(&format!(
+                "    {} {:>8}  {:>6.1} bits  {}  {}\n",
+                tr(Msg::ReuseLine), entry.line, entry.effective_entropy_bits, entry.grade, entry.password
+            ));
+        }
+    }
+    out
+}
+
+/// Width of the widest summary label, so the values line up in every locale
+fn label_width(labels: &[Msg]) -> usize {
+    labels.iter().map(|label| tr(*label).chars().count()).max().unwrap_or(0)
+}
+
+/// Appends one indented `label value` line of a summary
+fn push_summary_line(out: &mut String, label: Msg, width: usize, value: impl fmt::Display) {
+    out.push_str(&format!("  {:<width$} {}\n", tr(label), value));
+}
+
+/// Formats grade counts as `A=1 B=0 ...`
+fn format_grade_counts(counts: &BTreeMap<Grade, usize>) -> String {
+    counts.iter().map(|(grade, count)| format!("{}={}", grade, count)).collect::<Vec<_>>().join(" ")
+}
+
+/// Writes the per-entry results as CSV, with a header row unless `header` is false
+pub fn write_batch_csv<W: io::Write>(report: &BatchReport, header: bool, writer: W) -> csv::Result<()> {
+    let mut csv = csv::Writer::from_writer(writer);
+
+    if header {
+        let mut columns = BATCH_CSV_HEADER.to_vec();
+        if report.summary.breached_entries.is_some() {
+            columns.push("breach_count");
+        }
+        csv.write_record(columns)?;
+    }
+    for entry in &report.entries {
+        write_batch_csv_entry(&mut csv, entry)?;
+    }
+
+    csv.flush()?;
+    Ok(())
+}
+
+/// Writes the CSV header row of `write_batch_csv`, for output written one entry at a time
+pub fn write_batch_csv_header<W: io::Write>(csv: &mut csv::Writer<W>) -> csv::Result<()> {
+    csv.write_record(BATCH_CSV_HEADER)
+}
+
+/// Writes one entry as a CSV row with the columns of `write_batch_csv`
+///
+/// A `breach_count` column is added when the entry was looked up.
+pub fn write_batch_csv_entry<W: io::Write>(csv: &mut csv::Writer<W>, entry: &BatchEntry) -> csv::Result<()> {
+    let mut record = vec![
+        entry.line.to_string(),
+        entry.password.clone(),
+        entry.report.length.to_string(),
+        format!("{:.2}", entry.report.entropy_bits),
+        format!("{:.2}", entry.report.effective_entropy_bits),
+        entry.report.score.to_string(),
+        entry.report.grade.to_string(),
+    ];
+    record.extend(entry.breach_count.map(|count| count.to_string()));
+    csv.write_record(record)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufReader, Read, Write};
+
+    /// Produces `total` lines on demand, so the input never exists in memory as a whole
+    ///
+    /// Every 1000th line is blank and every 997th is not valid UTF-8.
+    struct GeneratedLines {
+        next: usize,
+        total: usize,
+        pending: Vec<u8>,
+        offset: usize,
+    }
+
+    impl Read for GeneratedLines {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.offset == self.pending.len() {
+                if self.next == self.total {
+                    return Ok(0);
+                }
+                self.next += 1;
+                self.pending.clear();
+                self.offset = 0;
+                match self.next {
+                    n if n.is_multiple_of(1000) => {}
+                    n if n.is_multiple_of(997) => self.pending.extend_from_slice(b"\xff\xfe"),
+                    n => write!(self.pending, "pw{}", n)?,
+                }
+                self.pending.extend_from_slice(if self.next.is_multiple_of(2) { b"\r\n" } else { b"\n" });
+            }
+            let count = buf.len().min(self.pending.len() - self.offset);
+            buf[..count].copy_from_slice(&self.pending[self.offset..self.offset + count]);
+            self.offset += count;
+            Ok(count)
+        }
+    }
+
+    /// Counts the lines and bytes written instead of keeping them
+    #[derive(Default)]
+    struct LineCounter {
+        lines: usize,
+        bytes: usize,
+    }
+
+    impl Write for LineCounter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.lines += buf.iter().filter(|&&byte| byte == b'\n').count();
+            self.bytes += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Streams `total` generated lines and checks every line is accounted for without being collected
+    fn stream_generated_lines(total: usize) {
+        let input = GeneratedLines { next: 0, total, pending: Vec::new(), offset: 0 };
+        let mut output = LineCounter::default();
+        let mut last_line = 0;
+        let summary = analyze_stream(BufReader::new(input), &AnalyzerOptions::default(), false, |entry| {
+            assert!(entry.line > last_line, "entries arrive in input order");
+            last_line = entry.line;
+            writeln!(output, "{},{},{}", entry.line, entry.password, entry.report.grade)
+        })
+        .unwrap();
+
+        let blank = total / 1000;
+        let invalid = (1..=total).filter(|n| n.is_multiple_of(997) && !n.is_multiple_of(1000)).count();
+        assert_eq!(summary.lines, total);
+        assert_eq!(summary.blank, blank);
+        assert_eq!(summary.invalid_utf8, invalid);
+        assert_eq!(summary.analyzed, total - blank - invalid);
+        assert_eq!(summary.grade_counts.values().sum::<usize>(), summary.analyzed);
+        // Every entry was handed on as it was scored, none were collected
+        assert_eq!(output.lines, summary.analyzed);
+        assert!(output.bytes > summary.analyzed * 10);
+        assert_eq!(summary.weakest.len(), STREAM_WEAKEST_ENTRIES);
+        assert!(summary.weakest.capacity() <= STREAM_WEAKEST_ENTRIES + 1);
+        assert!(summary.weakest.iter().all(|entry| !entry.password.starts_with("pw")), "weakest entries are masked");
+    }
+
+    #[test]
+    fn lines_stream_through_in_constant_memory() {
+        stream_generated_lines(20_000);
+    }
+
+    #[test]
+    #[ignore = "analyzes a million lines; run with `cargo test --release -- --ignored`"]
+    fn a_million_lines_stream_through_in_constant_memory() {
+        stream_generated_lines(1_000_000);
+    }
+
+    #[test]
+    fn stream_errors_from_the_writer_stop_the_analysis() {
+        let input = GeneratedLines { next: 0, total: 10, pending: Vec::new(), offset: 0 };
+        let mut seen = 0;
+        let error = analyze_stream(BufReader::new(input), &AnalyzerOptions::default(), true, |_| {
+            seen += 1;
+            Err(io::Error::from(io::ErrorKind::BrokenPipe))
+        })
+        .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+        assert_eq!(seen, 1);
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+// ============================================================================
+// Character Composition
+// ============================================================================
+
+/// Breakdown of the character classes that make up a password
+///
+/// All counts are expressed in characters (Unicode scalar values), never bytes,
+/// so multi-byte input such as emoji is counted once per character. Letters
+/// are classified with Unicode case rules, so Cyrillic or Greek capitals count
+/// as uppercase. `non_ascii` overlaps the other classes: `П` is both an
+/// uppercase letter and a non-ASCII character.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Composition {
+    /// Lowercase letters, in any script
+    pub lowercase: usize,
+    /// Uppercase letters, in any script
+    pub uppercase: usize,
+    /// Numeric characters, in any script
+    pub digits: usize,
+    /// Everything else: punctuation, emoji, caseless letters (CJK), combining marks, ...
+    pub symbols: usize,
+    /// Whitespace characters
+    pub whitespace: usize,
+    /// Characters outside the ASCII range (accented letters, emoji, ...)
+    pub non_ascii: usize,
+    /// Number of distinct characters
+    pub unique: usize,
+    /// Total number of characters (Unicode scalar values)
+    pub total: usize,
+    /// Number of grapheme clusters, i.e. what the user perceives as typed characters
+    pub graphemes: usize,
+}
+
+impl Composition {
+    /// Computes the composition of a password in a single pass over its chars
+    ///
+    /// # Arguments
+    /// * `password` - Password to inspect
+    ///
+    /// # Returns
+    /// * `Composition` - Per-class counts plus unique and total character counts
+    pub fn from_password(password: &str) -> Self {
+        let mut composition = Composition::default();
+        let mut seen: HashSet<char> = HashSet::new();
+
+        for c in password.chars() {
+            composition.total += 1;
+            seen.insert(c);
+
+            if !c.is_ascii() {
+                composition.non_ascii += 1;
+            }
+
+            // Classify the character into exactly one class
+            if c.is_lowercase() {
+                composition.lowercase += 1;
+            } else if c.is_uppercase() {
+                composition.uppercase += 1;
+            } else if c.is_numeric() {
+                composition.digits += 1;
+            } else if c.is_whitespace() {
+                composition.whitespace += 1;
+            } else {
+                composition.symbols += 1;
+            }
+        }
+
+        composition.unique = seen.len();
+        composition.graphemes = password.graphemes(true).count();
+        composition
+    }
+
+    /// Returns the share of the password taken by `count` characters, in percent
+    ///
+    /// Returns `0.0` for an empty password instead of dividing by zero.
+    pub fn percent(&self, count: usize) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        count as f64 * 100.0 / self.total as f64
+    }
+
+    /// Lists the count of every character class, in display order
+    ///
+    /// The order is lowercase, uppercase, digits, symbols, whitespace and non-ASCII.
+    pub fn classes(&self) -> [usize; 6] {
+        [self.lowercase, self.uppercase, self.digits, self.symbols, self.whitespace, self.non_ascii]
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Class counts in `classes()` order, followed by unique, total and graphemes
+    fn counts(composition: &Composition) -> [usize; 9] {
+        let [lowercase, uppercase, digits, symbols, whitespace, non_ascii] = composition.classes();
+        [
+            lowercase,
+            uppercase,
+            digits,
+            symbols,
+            whitespace,
+            non_ascii,
+            composition.unique,
+            composition.total,
+            composition.graphemes,
+        ]
+    }
+
+    #[test]
+    fn emoji_count_once_per_character_as_symbols() {
+        let password = "pass🔑word🎉";
+        assert_eq!(password.len(), 16);
+        let composition = Composition::from_password(password);
+        assert_eq!(counts(&composition), [8, 0, 0, 2, 0, 2, 9, 10, 10]);
+        assert_eq!(composition.percent(composition.symbols), 20.0);
+    }
+
+    #[test]
+    fn emoji_only_password_is_all_symbols_and_non_ascii() {
+        let composition = Composition::from_password("🔑🔑🎉🐉");
+        assert_eq!(counts(&composition), [0, 0, 0, 4, 0, 4, 3, 4, 4]);
+        assert_eq!(composition.percent(composition.symbols), 100.0);
+        assert_eq!(composition.percent(composition.non_ascii), 100.0);
+    }
+
+    #[test]
+    fn emoji_presentation_selector_is_a_separate_character_in_one_grapheme() {
+        // U+2764 HEAVY BLACK HEART followed by U+FE0F VARIATION SELECTOR-16
+        let composition = Composition::from_password("I\u{2764}\u{fe0f}u");
+        assert_eq!(counts(&composition), [1, 1, 0, 2, 0, 2, 4, 4, 3]);
+    }
+
+    #[test]
+    fn all_symbol_password_counts_only_symbols() {
+        let composition = Composition::from_password("!@#$%^&*()");
+        assert_eq!(counts(&composition), [0, 0, 0, 10, 0, 0, 10, 10, 10]);
+        assert_eq!(composition.percent(composition.symbols), 100.0);
+        assert_eq!(composition.percent(composition.lowercase), 0.0);
+    }
+
+    #[test]
+    fn repeated_symbols_count_as_one_unique_character() {
+        let composition = Composition::from_password("!!!!!!");
+        assert_eq!(counts(&composition), [0, 0, 0, 6, 0, 0, 1, 6, 6]);
+    }
+
+    #[test]
+    fn every_character_lands_in_exactly_one_class() {
+        for password in ["aB3 !", "🔑!Ü ß9", "\t\n", "ⅫⅩ٣"] {
+            let composition = Composition::from_password(password);
+            let [lowercase, uppercase, digits, symbols, whitespace, _, _, total, _] = counts(&composition);
+            assert_eq!(lowercase + uppercase + digits + symbols + whitespace, total, "{:?}", password);
+        }
+    }
+
+    #[test]
+    fn empty_password_has_no_percentages() {
+        let composition = Composition::from_password("");
+        assert_eq!(composition, Composition::default());
+        assert_eq!(composition.percent(0), 0.0);
+    }
+}
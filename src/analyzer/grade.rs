@@ -0,0 +1,167 @@
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Default minimum effective entropy (in bits) for grades A, B, C and D
+pub const DEFAULT_GRADE_THRESHOLDS: [f64; 4] = [90.0, 70.0, 55.0, 40.0];
+
+// ============================================================================
+// Grades
+// ============================================================================
+
+/// Letter grade summarizing the strength of a password
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Grade {
+    A,
+    B,
+    C,
+    D,
+    F,
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let letter = match self {
+            Grade::A => "A",
+            Grade::B => "B",
+            Grade::C => "C",
+            Grade::D => "D",
+            Grade::F => "F",
+        };
+        // Use `pad` so width and alignment specifiers work in tables
+        f.pad(letter)
+    }
+}
+
+/// Minimum effective entropy (in bits) required for each passing grade
+///
+/// Anything below the `D` threshold is graded `F`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct GradeThresholds {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+}
+
+impl Default for GradeThresholds {
+    fn default() -> Self {
+        let [a, b, c, d] = DEFAULT_GRADE_THRESHOLDS;
+        GradeThresholds { a, b, c, d }
+    }
+}
+
+impl GradeThresholds {
+    /// Assigns the grade for a given effective entropy
+    ///
+    /// Each threshold is inclusive: exactly 90 bits with the defaults is an `A`.
+    pub fn grade(&self, effective_entropy_bits: f64) -> Grade {
+        if effective_entropy_bits >= self.a {
+            Grade::A
+        } else if effective_entropy_bits >= self.b {
+            Grade::B
+        } else if effective_entropy_bits >= self.c {
+            Grade::C
+        } else if effective_entropy_bits >= self.d {
+            Grade::D
+        } else {
+            Grade::F
+        }
+    }
+}
+
+impl FromStr for GradeThresholds {
+    type Err = String;
+
+    /// Parses thresholds written as `A,B,C,D`, e.g. `90,70,55,40`
+    ///
+    /// Exactly four finite, non-negative numbers in strictly descending order are required.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s
+            .split(',')
+            .map(|part| {
+                let part = part.trim();
+                part.parse::<f64>()
+                    .ok()
+                    .filter(|v| v.is_finite() && *v >= 0.0)
+                    .ok_or_else(|| format!("'{}' is not a valid number of bits", part))
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        let [a, b, c, d] = values[..] else {
+            return Err(format!("expected exactly 4 thresholds (A,B,C,D), got {}", values.len()));
+        };
+
+        if !(a > b && b > c && c > d) {
+            return Err(String::from("thresholds must be strictly descending (A > B > C > D)"));
+        }
+
+        Ok(GradeThresholds { a, b, c, d })
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Just below, exactly on and just above every threshold grades F/D, D, C, B and A in turn
+    fn assert_boundaries(thresholds: GradeThresholds) {
+        let GradeThresholds { a, b, c, d } = thresholds;
+        for (threshold, below, on) in [(d, Grade::F, Grade::D), (c, Grade::D, Grade::C), (b, Grade::C, Grade::B), (a, Grade::B, Grade::A)] {
+            assert_eq!(thresholds.grade(threshold.next_down()), below, "just below {}", threshold);
+            assert_eq!(thresholds.grade(threshold), on, "on {}", threshold);
+            assert_eq!(thresholds.grade(threshold.next_up()), on, "just above {}", threshold);
+        }
+    }
+
+    #[test]
+    fn default_thresholds_are_inclusive() {
+        assert_boundaries(GradeThresholds::default());
+        let thresholds = GradeThresholds::default();
+        let cases = [
+            (0.0, Grade::F),
+            (39.9, Grade::F),
+            (40.0, Grade::D),
+            (54.9, Grade::D),
+            (55.0, Grade::C),
+            (69.9, Grade::C),
+            (70.0, Grade::B),
+            (89.9, Grade::B),
+            (90.0, Grade::A),
+            (f64::INFINITY, Grade::A),
+        ];
+        for (bits, grade) in cases {
+            assert_eq!(thresholds.grade(bits), grade, "{} bits", bits);
+        }
+    }
+
+    #[test]
+    fn custom_thresholds_move_the_boundaries() {
+        let thresholds: GradeThresholds = "120, 80.5, 60,0".parse().unwrap();
+        assert_eq!(thresholds, GradeThresholds { a: 120.0, b: 80.5, c: 60.0, d: 0.0 });
+        assert_boundaries(thresholds);
+        // With a D threshold of zero nothing can fail
+        assert_eq!(thresholds.grade(0.0), Grade::D);
+    }
+
+    #[test]
+    fn thresholds_must_be_four_descending_numbers() {
+        let error = |text: &str| text.parse::<GradeThresholds>().unwrap_err();
+        assert_eq!(error("90,70,55"), "expected exactly 4 thresholds (A,B,C,D), got 3");
+        assert_eq!(error("90,70,55,40,20"), "expected exactly 4 thresholds (A,B,C,D), got 5");
+        assert_eq!(error("90,70,70,40"), "thresholds must be strictly descending (A > B > C > D)");
+        assert_eq!(error("40,55,70,90"), "thresholds must be strictly descending (A > B > C > D)");
+        assert_eq!(error("90,70,x,40"), "'x' is not a valid number of bits");
+        assert_eq!(error("90,70,55,-1"), "'-1' is not a valid number of bits");
+        assert_eq!(error("inf,70,55,40"), "'inf' is not a valid number of bits");
+        assert_eq!(error(""), "'' is not a valid number of bits");
+    }
+}
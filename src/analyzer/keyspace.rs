@@ -0,0 +1,376 @@
+use std::fmt;
+use serde::Serialize;
+use crate::generator::{CharClass, Charset};
+use crate::i18n::{tr, trf, Msg};
+use crate::utils;
+use super::{compute_entropy, compute_time_to_crack_at, format_crack_time};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Attack scenarios of the crack-time table, slowest first
+///
+/// The rates follow the usual online/offline split; the slow-hash row is the
+/// bcrypt speed every other estimate of this crate assumes.
+pub const CRACK_SCENARIOS: [CrackScenario; 4] = [
+    CrackScenario {
+        name: "Online, throttled",
+        label: Msg::ScenarioOnlineThrottled,
+        guesses_per_second: 100.0 / 3600.0,
+    },
+    CrackScenario { name: "Online, unthrottled", label: Msg::ScenarioOnlineUnthrottled, guesses_per_second: 10.0 },
+    CrackScenario {
+        name: "Offline, slow hash (bcrypt)",
+        label: Msg::ScenarioOfflineSlowHash,
+        guesses_per_second: utils::BCRYPT_CRACKING_SPEED as f64,
+    },
+    CrackScenario { name: "Offline, fast hash (GPU rig)", label: Msg::ScenarioOfflineFastHash, guesses_per_second: 1e10 },
+];
+
+/// Crack times beyond this many years are shown in scientific notation, where a calendar breakdown means nothing
+const SCIENTIFIC_YEARS: f64 = 1e6;
+
+/// Length of a year in `humantime`'s reckoning (365.25 days)
+const SECONDS_PER_YEAR: f64 = 31_557_600.0;
+
+/// Pattern placeholders and the class each one draws from; every other character is literal
+const PATTERN_SLOTS: [(char, CharClass); 4] =
+    [('l', CharClass::Lowercase), ('u', CharClass::Uppercase), ('n', CharClass::Digits), ('s', CharClass::Symbols)];
+
+// ============================================================================
+// Word Lists
+// ============================================================================
+
+/// Published diceware word lists, known by name so their size need not be shipped with the words
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Wordlist {
+    /// EFF large list: 7776 words, five dice per word
+    Eff,
+    /// EFF short list: 1296 words, four dice per word
+    EffShort,
+}
+
+impl Wordlist {
+    /// Number of words in the list
+    pub fn size(&self) -> u32 {
+        match self {
+            Wordlist::Eff => 7776,
+            Wordlist::EffShort => 1296,
+        }
+    }
+
+    /// Name used on the command line and in reports, e.g. `eff-short`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Wordlist::Eff => "eff",
+            Wordlist::EffShort => "eff-short",
+        }
+    }
+}
+
+// ============================================================================
+// Keyspace
+// ============================================================================
+
+/// An attacker's guessing speed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CrackScenario {
+    /// Stable English name, used in JSON output
+    pub name: &'static str,
+    /// Translated name shown in the text table
+    #[serde(skip)]
+    pub label: Msg,
+    pub guesses_per_second: f64,
+}
+
+/// Time to exhaust a keyspace in one scenario
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CrackEstimate {
+    #[serde(flatten)]
+    pub scenario: CrackScenario,
+    /// Infinite (`null` in JSON) for keyspaces beyond `f64`; `display` still has a figure
+    pub seconds: f64,
+    /// Human-readable version of `seconds`
+    pub display: String,
+}
+
+/// What a keyspace is made of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyspaceKind {
+    /// Characters drawn from one alphabet
+    Charset,
+    /// Words drawn from a word list
+    Passphrase,
+    /// A template of per-position classes and literals
+    Pattern,
+}
+
+/// Size and guessing cost of every password a generator setting can produce
+///
+/// Only the math: nothing is generated, and the numbers assume the attacker
+/// knows the setting (alphabet, word list or pattern) but not the choices.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Keyspace {
+    pub kind: KeyspaceKind,
+    /// The setting in words, e.g. `14 characters from lowercase, uppercase, digits`
+    pub description: String,
+    /// Characters or words chosen at random (pattern literals are not counted)
+    pub random_symbols: usize,
+    /// Candidates per random symbol: alphabet or word list size; `None` when pattern positions differ
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alphabet_size: Option<u32>,
+    /// Entropy per random symbol (the average for mixed patterns)
+    pub bits_per_symbol: f64,
+    pub entropy_bits: f64,
+    /// Number of possible passwords (`2^entropy_bits`) in scientific notation, e.g. `1.23e24`
+    pub combinations: String,
+    /// Time to try every candidate, for each of `CRACK_SCENARIOS`
+    pub crack_times: Vec<CrackEstimate>,
+}
+
+impl Keyspace {
+    /// Keyspace of `length` characters drawn uniformly from `charset`, as `generate` does
+    pub fn charset(charset: &Charset, length: usize) -> Self {
+        let classes: Vec<&str> = charset.composition().iter().map(|(class, _)| class.name()).collect();
+        let description = format!("{} characters from {}", length, classes.join(", "));
+        let alphabet_size = charset.len() as u32;
+        Keyspace::new(KeyspaceKind::Charset, description, length, Some(alphabet_size), compute_entropy(alphabet_size, length))
+    }
+
+    /// Keyspace of `words` words drawn uniformly from a word list
+    pub fn passphrase(wordlist: Wordlist, words: usize) -> Self {
+        let description = format!("{} words from the {} list", words, wordlist.name());
+        Keyspace::new(KeyspaceKind::Passphrase, description, words, Some(wordlist.size()), compute_entropy(wordlist.size(), words))
+    }
+
+    /// Keyspace of a pattern such as `uuu-nnnn`
+    ///
+    /// # Arguments
+    /// * `pattern` - `l`, `u`, `n` and `s` stand for a random lowercase letter,
+    ///   uppercase letter, digit and symbol; any other character is a literal
+    ///
+    /// # Returns
+    /// * `Result<Keyspace, String>` - The keyspace, or why the pattern has no random position
+    pub fn pattern(pattern: &str) -> Result<Self, String> {
+        let sizes: Vec<u32> = pattern
+            .chars()
+            .filter_map(|c| PATTERN_SLOTS.iter().find(|(slot, _)| *slot == c))
+            .map(|(_, class)| class.charset().len() as u32)
+            .collect();
+        if sizes.is_empty() {
+            return Err(format!("pattern '{}' has no random position; use l, u, n or s", pattern));
+        }
+
+        let entropy_bits: f64 = sizes.iter().map(|size| compute_entropy(*size, 1)).sum();
+        let literals = pattern.chars().count() - sizes.len();
+        let alphabet_size = sizes.iter().all(|size| *size == sizes[0]).then_some(sizes[0]);
+        let description = match literals {
+            0 => format!("pattern '{}'", pattern),
+            1 => format!("pattern '{}' (1 literal character)", pattern),
+            _ => format!("pattern '{}' ({} literal characters)", pattern, literals),
+        };
+        Ok(Keyspace::new(KeyspaceKind::Pattern, description, sizes.len(), alphabet_size, entropy_bits))
+    }
+
+    fn new(kind: KeyspaceKind, description: String, random_symbols: usize, alphabet_size: Option<u32>, entropy_bits: f64) -> Self {
+        let crack_times = CRACK_SCENARIOS
+            .iter()
+            .map(|scenario| {
+                let seconds = compute_time_to_crack_at(entropy_bits, scenario.guesses_per_second);
+                CrackEstimate { scenario: *scenario, seconds, display: display_crack_time(entropy_bits, scenario.guesses_per_second) }
+            })
+            .collect();
+        Keyspace {
+            kind,
+            description,
+            random_symbols,
+            alphabet_size,
+            bits_per_symbol: if random_symbols == 0 { 0.0 } else { entropy_bits / random_symbols as f64 },
+            entropy_bits,
+            combinations: Scientific::from_bits(entropy_bits).to_string(),
+            crack_times,
+        }
+    }
+}
+
+/// Renders a keyspace as human-readable text with the crack-time table
+pub fn render_keyspace(keyspace: &Keyspace) -> String {
+    let symbol = match keyspace.kind {
+        KeyspaceKind::Passphrase => Msg::KeyspaceBitsPerWord,
+        KeyspaceKind::Charset | KeyspaceKind::Pattern => Msg::KeyspaceBitsPerCharacter,
+    };
+    let mut out = format!("{:<20}{}\n", tr(Msg::KeyspaceLabel), keyspace.description);
+    match keyspace.alphabet_size {
+        Some(size) if keyspace.kind == KeyspaceKind::Passphrase => {
+            out.push_str(&format!("{:<20}{}\n", tr(Msg::KeyspaceWordlist), trf(Msg::KeyspaceWords, &[&size])))
+        }
+        Some(size) => out.push_str(&format!("{:<20}{}\n", tr(Msg::KeyspaceAlphabet), size)),
+        None => {}
+    }
+    let average = if keyspace.alphabet_size.is_none() { format!(" {}", tr(Msg::KeyspaceAverage)) } else { String::new() };
+    out.push_str(&format!("{:<20}{:.2}{}\n", tr(symbol), keyspace.bits_per_symbol, average));
+    let entropy = trf(Msg::KeyspaceBits, &[&format!("{:.1}", keyspace.entropy_bits)]);
+    out.push_str(&format!("{:<20}{}\n", tr(Msg::ReportEntropy), entropy));
+    out.push_str(&format!("{:<20}{}\n", tr(Msg::KeyspaceCombinations), keyspace.combinations));
+
+    out.push_str(&format!("\n{}\n", tr(Msg::KeyspaceTimeHeading)));
+    let width = keyspace.crack_times.iter().map(|estimate| tr(estimate.scenario.label).chars().count()).max().unwrap_or(0);
+    for estimate in &keyspace.crack_times {
+        out.push_str(&format!(
+            "  {:<width$}  {:>12}/s  {}\n",
+            tr(estimate.scenario.label),
+            Scientific::from_value(estimate.scenario.guesses_per_second).to_string(),
+            estimate.display
+        ));
+    }
+    out
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// `format_crack_time`, except that astronomical durations become e.g. `1.23e15 years` instead of a clamped breakdown
+///
+/// Works in log space, so keyspaces too large for `f64` still get a figure.
+fn display_crack_time(entropy_bits: f64, guesses_per_second: f64) -> String {
+    let log10_years = entropy_bits * std::f64::consts::LOG10_2 - guesses_per_second.log10() - SECONDS_PER_YEAR.log10();
+    if log10_years >= SCIENTIFIC_YEARS.log10() {
+        format!("{} years", Scientific::from_log10(log10_years))
+    } else {
+        format_crack_time(compute_time_to_crack_at(entropy_bits, guesses_per_second))
+    }
+}
+
+/// A positive number as mantissa and power of ten, computed in log space so `2^1000` does not overflow
+struct Scientific {
+    mantissa: f64,
+    exponent: i64,
+}
+
+impl Scientific {
+    fn from_bits(bits: f64) -> Self {
+        Scientific::from_log10(bits * std::f64::consts::LOG10_2)
+    }
+
+    fn from_value(value: f64) -> Self {
+        Scientific::from_log10(value.log10())
+    }
+
+    fn from_log10(log10: f64) -> Self {
+        let mut exponent = log10.floor() as i64;
+        let mut mantissa = 10f64.powf(log10 - exponent as f64);
+        // Rounding to two decimals can carry into the next power, e.g. 9.999 -> 10.00
+        if (mantissa * 100.0).round() >= 1000.0 {
+            mantissa /= 10.0;
+            exponent += 1;
+        }
+        Scientific { mantissa, exponent }
+    }
+}
+
+impl fmt::Display for Scientific {
+    /// Writes e.g. `1.23e24`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.2}e{}", self.mantissa, self.exponent)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_bits(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} bits, expected {}", actual, expected);
+    }
+
+    #[test]
+    fn small_alphabets_have_exact_combination_counts() {
+        let digits = CharClass::Digits.charset();
+        let cases = [
+            (Keyspace::charset(&digits, 1), 10, "1.00e1"),
+            (Keyspace::charset(&digits, 4), 10, "1.00e4"),
+            (Keyspace::charset(&CharClass::Lowercase.charset(), 2), 26, "6.76e2"),
+            (Keyspace::charset(&Charset::from("ab"), 10), 2, "1.02e3"),
+            (Keyspace::passphrase(Wordlist::Eff, 1), 7776, "7.78e3"),
+            (Keyspace::passphrase(Wordlist::EffShort, 2), 1296, "1.68e6"),
+        ];
+        for (keyspace, alphabet_size, combinations) in cases {
+            assert_eq!(keyspace.alphabet_size, Some(alphabet_size), "{}", keyspace.description);
+            assert_eq!(keyspace.combinations, combinations, "{}", keyspace.description);
+            assert_bits(keyspace.bits_per_symbol, f64::from(alphabet_size).log2());
+            assert_bits(keyspace.entropy_bits, keyspace.random_symbols as f64 * f64::from(alphabet_size).log2());
+        }
+    }
+
+    #[test]
+    fn crack_times_divide_the_keyspace_by_each_rate() {
+        let keyspace = Keyspace::charset(&CharClass::Digits.charset(), 4);
+        let seconds: Vec<f64> = keyspace.crack_times.iter().map(|estimate| estimate.seconds).collect();
+        for (seconds, scenario) in seconds.iter().zip(CRACK_SCENARIOS) {
+            assert!((seconds - 10_000.0 / scenario.guesses_per_second).abs() < 1e-6, "{}", scenario.name);
+        }
+        assert_eq!(keyspace.crack_times[3].display, "less than a second");
+    }
+
+    #[test]
+    fn patterns_count_only_their_random_positions() {
+        let keyspace = Keyspace::pattern("nn-nn").unwrap();
+        assert_eq!((keyspace.random_symbols, keyspace.alphabet_size), (4, Some(10)));
+        assert_eq!(keyspace.combinations, "1.00e4");
+        assert_eq!(keyspace.description, "pattern 'nn-nn' (1 literal character)");
+
+        let mixed = Keyspace::pattern("un").unwrap();
+        assert_eq!((mixed.random_symbols, mixed.alphabet_size), (2, None));
+        assert_eq!(mixed.combinations, "2.60e2");
+        assert_bits(mixed.bits_per_symbol, 260f64.log2() / 2.0);
+
+        assert_eq!(Keyspace::pattern("--").unwrap_err(), "pattern '--' has no random position; use l, u, n or s");
+    }
+
+    #[test]
+    fn max_length_keyspaces_stay_finite() {
+        let full = CharClass::ALL.iter().fold(Charset::default(), |charset, class| charset.union(&class.charset()));
+        let alphabet = full.len() as f64;
+        let keyspace = Keyspace::charset(&full, utils::MAX_LENGTH as usize);
+        assert_bits(keyspace.entropy_bits, f64::from(utils::MAX_LENGTH) * alphabet.log2());
+
+        let exponent = (f64::from(utils::MAX_LENGTH) * alphabet.log10()).floor();
+        assert!(keyspace.combinations.ends_with(&format!("e{}", exponent)), "{}", keyspace.combinations);
+        for estimate in &keyspace.crack_times {
+            assert!(estimate.seconds.is_finite(), "{}", estimate.scenario.name);
+            assert!(estimate.display.ends_with(" years") && estimate.display.contains('e'), "{}", estimate.display);
+        }
+    }
+
+    #[test]
+    fn keyspaces_beyond_f64_saturate_but_keep_a_figure() {
+        // 200 words of 12.9 bits: about 2^2585, far past f64::MAX (about 2^1024)
+        let keyspace = Keyspace::passphrase(Wordlist::Eff, 200);
+        assert!(keyspace.entropy_bits > 1024.0);
+        assert_eq!(keyspace.combinations, "1.42e778");
+        for estimate in &keyspace.crack_times {
+            assert_eq!(estimate.seconds, f64::INFINITY, "{}", estimate.scenario.name);
+            assert!(estimate.display.ends_with(" years") && !estimate.display.contains("inf"), "{}", estimate.display);
+        }
+        // Infinite seconds become null in JSON
+        let json = serde_json::to_value(&keyspace).unwrap();
+        assert!(json["crack_times"][0]["seconds"].is_null());
+    }
+
+    #[test]
+    fn scientific_notation_rounds_into_the_next_power() {
+        assert_eq!(Scientific::from_value(9.999).to_string(), "1.00e1");
+        assert_eq!(Scientific::from_value(1e10).to_string(), "1.00e10");
+        assert_eq!(Scientific::from_value(100.0 / 3600.0).to_string(), "2.78e-2");
+        assert_eq!(Scientific::from_bits(1.0).to_string(), "2.00e0");
+    }
+}
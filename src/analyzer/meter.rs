@@ -0,0 +1,116 @@
+use colored::Colorize;
+use crate::i18n::{self, Msg};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Default width of the strength meter bar, in cells
+pub const DEFAULT_METER_WIDTH: usize = 20;
+
+/// Scores below this value fall in the weak (red) band
+const FAIR_THRESHOLD: u8 = 40;
+
+/// Scores above this value fall in the strong (green) band
+const STRONG_THRESHOLD: u8 = 70;
+
+// ============================================================================
+// Strength Meter
+// ============================================================================
+
+/// Returns the verbal label of the band a score falls in
+pub fn score_label(score: u8) -> &'static str {
+    if score < FAIR_THRESHOLD {
+        i18n::tr(Msg::Weak)
+    } else if score <= STRONG_THRESHOLD {
+        i18n::tr(Msg::Fair)
+    } else {
+        i18n::tr(Msg::Strong)
+    }
+}
+
+/// Renders a strength meter bar for a 0-100 score
+///
+/// # Arguments
+/// * `score` - Strength score, clamped to 100
+/// * `width` - Number of cells in the bar (at least 1)
+/// * `color_enabled` - Whether to use Unicode blocks and ANSI colors
+///
+/// # Returns
+/// * `String` - e.g. `[████████░░░░░░░░░░░░] 42/100  Fair`
+///
+/// # Notes
+/// Without color the bar degrades to plain ASCII (`#` and `-`) so it stays
+/// readable in logs and pipes.
+pub fn render_meter(score: u8, width: usize, color_enabled: bool) -> String {
+    let score = score.min(100);
+    let width = width.max(1);
+
+    // Round to the nearest cell so 100 always fills the bar completely
+    let filled = (score as usize * width + 50) / 100;
+    let empty = width - filled;
+    let label = score_label(score);
+
+    if !color_enabled {
+        return format!("[{}{}] {}/100  {}", "#".repeat(filled), "-".repeat(empty), score, label);
+    }
+
+    let bar = "█".repeat(filled);
+    let (bar, label) = if score < FAIR_THRESHOLD {
+        (bar.red(), label.red())
+    } else if score <= STRONG_THRESHOLD {
+        (bar.yellow(), label.yellow())
+    } else {
+        (bar.green(), label.green())
+    };
+
+    format!("[{}{}] {}/100  {}", bar, "░".repeat(empty), score, label)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_meter_snapshots() {
+        let cases = [
+            (0, 20, "[--------------------] 0/100  Weak"),
+            (39, 20, "[########------------] 39/100  Weak"),
+            (40, 20, "[########------------] 40/100  Fair"),
+            (42, 20, "[########------------] 42/100  Fair"),
+            (70, 20, "[##############------] 70/100  Fair"),
+            (71, 20, "[##############------] 71/100  Strong"),
+            (100, 20, "[####################] 100/100  Strong"),
+            (250, 10, "[##########] 100/100  Strong"),
+            (55, 10, "[######----] 55/100  Fair"),
+            (49, 1, "[-] 49/100  Fair"),
+            (50, 0, "[#] 50/100  Fair"),
+        ];
+        for (score, width, expected) in cases {
+            assert_eq!(render_meter(score, width, false), expected, "score {} width {}", score, width);
+        }
+    }
+
+    #[test]
+    fn colored_meter_snapshots() {
+        colored::control::set_override(true);
+        let cases = [
+            (10, 10, "[\u{1b}[31m█\u{1b}[0m░░░░░░░░░] 10/100  \u{1b}[31mWeak\u{1b}[0m"),
+            (50, 10, "[\u{1b}[33m█████\u{1b}[0m░░░░░] 50/100  \u{1b}[33mFair\u{1b}[0m"),
+            (100, 10, "[\u{1b}[32m██████████\u{1b}[0m] 100/100  \u{1b}[32mStrong\u{1b}[0m"),
+        ];
+        for (score, width, expected) in cases {
+            assert_eq!(render_meter(score, width, true), expected, "score {}", score);
+        }
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn labels_follow_the_bands() {
+        assert_eq!([0, 39, 40, 70, 71, 100].map(score_label), ["Weak", "Weak", "Fair", "Fair", "Strong", "Strong"]);
+    }
+}
@@ -1,3 +1,518 @@
-pub(crate) fn analyze_password(_p0: &String) -> String {
-    todo!()
-}
\ No newline at end of file
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::i18n::{tr, trf, Msg};
+use crate::utils;
+#[cfg(feature = "cli")]
+use colored::Colorize;
+
+mod batch;
+mod features;
+mod grade;
+mod keyspace;
+#[cfg(feature = "cli")]
+mod meter;
+mod patterns;
+mod reuse;
+mod token;
+
+pub use batch::{
+    analyze_lines, analyze_stream, render_batch, render_stream_summary, write_batch_csv, write_batch_csv_entry,
+    write_batch_csv_header, BatchEntry, BatchReport, BreachCount, StreamSummary, WeakEntry,
+};
+pub use features::Composition;
+pub use grade::{Grade, GradeThresholds};
+pub use keyspace::{render_keyspace, CrackEstimate, CrackScenario, Keyspace, KeyspaceKind, Wordlist, CRACK_SCENARIOS};
+#[cfg(feature = "cli")]
+pub use meter::{render_meter, DEFAULT_METER_WIDTH};
+pub use patterns::{PatternFinding, PatternKind};
+pub use token::analyze_token;
+#[cfg(feature = "cli")]
+pub use token::render_token_report;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Assumed size of the ASCII symbol pool (printable punctuation) for entropy estimation
+const ASCII_SYMBOL_POOL: u32 = 33;
+
+/// Assumed number of extra candidates per character once non-ASCII input is present
+///
+/// Roughly one additional script's alphabet plus common emoji. Attackers do not
+/// brute-force all of Unicode, so using its full size would wildly overstate
+/// strength; this conservative bucket keeps non-Latin passwords comparable.
+const NON_ASCII_POOL: u32 = 100;
+
+/// Entropy (in bits) that maps to a perfect score of 100
+const FULL_SCORE_BITS: f64 = 100.0;
+
+/// Share of a single character class (in percent) above which a warning is emitted
+const DOMINANT_CLASS_PERCENT: f64 = 80.0;
+
+/// Translated names of the classes `Composition::classes` counts, in the same order
+const CLASS_LABELS: [Msg; 6] = [
+    Msg::ClassLowercase,
+    Msg::ClassUppercase,
+    Msg::ClassDigits,
+    Msg::ClassSymbols,
+    Msg::ClassWhitespace,
+    Msg::ClassNonAscii,
+];
+
+// ============================================================================
+// Analysis Report
+// ============================================================================
+
+/// Settings that influence how passwords are scored
+#[derive(Debug, Clone, Default)]
+pub struct AnalyzerOptions {
+    /// Effective entropy thresholds for the letter grades
+    pub grade_thresholds: GradeThresholds,
+    /// Personal context (usernames, emails) that should not appear in the password
+    pub user_inputs: Vec<String>,
+}
+
+/// Structured result of a password strength analysis
+///
+/// This is the single source of truth for both the human-readable report and
+/// the JSON output of the `analyze` subcommand.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnalysisReport {
+    /// Password length in characters (Unicode scalar values)
+    pub length: usize,
+    /// Password length in grapheme clusters (what the user typed)
+    pub graphemes: usize,
+    /// Estimated size of the alphabet the password was drawn from
+    pub charset_size: u32,
+    /// Brute-force entropy in bits (`length * log2(charset_size)`)
+    pub entropy_bits: f64,
+    /// Entropy in bits after dictionary and pattern penalties
+    pub effective_entropy_bits: f64,
+    /// Letter grade derived from the effective entropy
+    pub grade: Grade,
+    /// Strength score from 0 to 100 derived from the effective entropy
+    pub score: u8,
+    /// Estimated seconds to guess the password at bcrypt speed
+    pub crack_time_seconds: f64,
+    /// Human-readable version of `crack_time_seconds`
+    pub crack_time_display: String,
+    /// Character class breakdown
+    pub composition: Composition,
+    /// Predictable segments that reduce the effective entropy
+    pub patterns: Vec<PatternFinding>,
+    /// Weaknesses detected while analyzing the password
+    pub warnings: Vec<String>,
+}
+
+/// Analyzes the strength of a password
+///
+/// # Arguments
+/// * `password` - Password to analyze
+/// * `options` - Scoring settings such as the grade thresholds
+///
+/// # Returns
+/// * `AnalysisReport` - Structured analysis results
+pub fn analyze_password(password: &str, options: &AnalyzerOptions) -> AnalysisReport {
+    let composition = Composition::from_password(password);
+    let charset_size = estimate_charset_size(&composition);
+    let entropy_bits = compute_entropy(charset_size, composition.total);
+
+    // Penalize predictable segments before grading
+    let bits_per_char = compute_entropy(charset_size, 1);
+    let patterns = patterns::detect_patterns(password, bits_per_char, &options.user_inputs);
+    let effective_entropy_bits = compute_effective_entropy(entropy_bits, bits_per_char, &patterns);
+    let crack_time_seconds = compute_time_to_crack(effective_entropy_bits);
+
+    AnalysisReport {
+        length: composition.total,
+        graphemes: composition.graphemes,
+        charset_size,
+        entropy_bits,
+        effective_entropy_bits,
+        grade: options.grade_thresholds.grade(effective_entropy_bits),
+        score: compute_score(effective_entropy_bits),
+        crack_time_seconds,
+        crack_time_display: format_crack_time(crack_time_seconds),
+        warnings: collect_warnings(&composition, &patterns),
+        composition,
+        patterns,
+    }
+}
+
+/// Renders each detector's raw findings and the entropy arithmetic behind a report
+///
+/// # Arguments
+/// * `password` - Password the report was produced for
+/// * `report` - Report of `analyze_password` for the same password and options
+/// * `options` - Scoring settings used for the report
+///
+/// # Returns
+/// * `String` - Multi-line explanation for `--verbose`; positions only, never the matched text
+pub fn render_analysis_details(password: &str, report: &AnalysisReport, options: &AnalyzerOptions) -> String {
+    let bits_per_char = compute_entropy(report.charset_size, 1);
+    let mut out = format!("{}\n", tr(Msg::DetailsFindings));
+
+    for (kind, findings) in patterns::detect_patterns_raw(password, bits_per_char, &options.user_inputs) {
+        out.push_str(&format!("  {}:", tr(pattern_label(kind))));
+        if findings.is_empty() {
+            out.push_str(&format!(" {}\n", tr(Msg::DetailsNone)));
+            continue;
+        }
+        out.push('\n');
+        for finding in findings {
+            let line = trf(
+                Msg::DetailsFinding,
+                &[
+                    &(finding.start + 1),
+                    &(finding.start + finding.length),
+                    &finding.length,
+                    &format!("{:.1}", finding.guess_bits),
+                ],
+            );
+            out.push_str(&format!("    {}\n", line));
+        }
+    }
+
+    let covered: usize = report.patterns.iter().map(|p| p.length).sum();
+    let pattern_bits: f64 = report.patterns.iter().map(|p| p.guess_bits).sum();
+    out.push_str(&format!("\n{}\n", tr(Msg::ReportEntropy)));
+    let brute_force = trf(
+        Msg::DetailsBruteForce,
+        &[
+            &report.length,
+            &report.charset_size,
+            &format!("{:.2}", bits_per_char),
+            &format!("{:.1}", report.entropy_bits),
+        ],
+    );
+    out.push_str(&format!("  {}\n", brute_force));
+    let penalties = trf(
+        Msg::DetailsPenalties,
+        &[
+            &covered,
+            &format!("{:.2}", bits_per_char),
+            &format!("{:.1}", covered as f64 * bits_per_char),
+            &format!("{:.1}", pattern_bits),
+        ],
+    );
+    out.push_str(&format!("  {}\n", penalties));
+    out.push_str(&format!("  {}", trf(Msg::DetailsEffective, &[&format!("{:.1}", report.effective_entropy_bits)])));
+    out
+}
+
+/// Renders an analysis report as human-readable text
+///
+/// # Arguments
+/// * `report` - Report to render
+/// * `meter_width` - Width of the strength meter bar
+/// * `color_enabled` - Whether ANSI colors may be used
+///
+/// # Returns
+/// * `String` - Multi-line report including the strength meter and composition mini-table
+#[cfg(feature = "cli")]
+pub fn render_report(report: &AnalysisReport, meter_width: usize, color_enabled: bool) -> String {
+    let mut out = String::new();
+
+    let grade = format!("{} {}", tr(Msg::ReportGrade), report.grade);
+    if color_enabled {
+        out.push_str(&format!("{}\n", grade.bold()));
+    } else {
+        out.push_str(&format!("{}\n", grade));
+    }
+    out.push_str(&render_meter(report.score, meter_width, color_enabled));
+    out.push_str("\n\n");
+    let length = if report.graphemes == report.length {
+        trf(Msg::ReportCharacters, &[&report.length])
+    } else {
+        trf(Msg::ReportCharactersTyped, &[&report.length, &report.graphemes])
+    };
+    out.push_str(&format!("{:<15}{}\n", tr(Msg::ReportLength), length));
+    out.push_str(&format!("{:<15}{}\n", tr(Msg::ReportCharsetSize), report.charset_size));
+    out.push_str(&format!("{:<15}{:.1} bits\n", tr(Msg::ReportEntropy), report.entropy_bits));
+    out.push_str(&format!("{:<15}{:.1} bits\n", tr(Msg::ReportEffective), report.effective_entropy_bits));
+    out.push_str(&format!("{} {}\n", tr(Msg::ReportCrackTime), report.crack_time_display));
+
+    // Composition mini-table, labels padded to the widest one
+    let composition = &report.composition;
+    out.push_str(&format!("\n{}\n", tr(Msg::ReportComposition)));
+    for (count, label) in composition.classes().into_iter().zip(CLASS_LABELS) {
+        out.push_str(&format!(
+            "  {:<22}{:>5}  {:>5.1}%\n",
+            tr(label),
+            count,
+            composition.percent(count)
+        ));
+    }
+    out.push_str(&format!(
+        "  {:<22}{:>5} / {}\n",
+        tr(Msg::ReportUnique), composition.unique, composition.total
+    ));
+
+    if !report.warnings.is_empty() {
+        out.push_str(&format!("\n{}\n", tr(Msg::ReportWarnings)));
+        for warning in &report.warnings {
+            out.push_str(&format!("  - {}\n", warning));
+        }
+    }
+
+    out
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Estimates the alphabet size from the character classes present in the password
+///
+/// Each class that appears at least once contributes its full pool size, which
+/// is what an attacker brute-forcing that class combination would have to try.
+/// Non-ASCII input adds the `NON_ASCII_POOL` bucket on top of its class.
+fn estimate_charset_size(composition: &Composition) -> u32 {
+    let mut size = 0;
+
+    if composition.lowercase > 0 {
+        size += utils::CHARS.len() as u32;
+    }
+    if composition.uppercase > 0 {
+        size += utils::UPPERCASE_CHARS.len() as u32;
+    }
+    if composition.digits > 0 {
+        size += utils::NUMBERS.len() as u32;
+    }
+    if composition.symbols > 0 {
+        size += ASCII_SYMBOL_POOL;
+    }
+    if composition.whitespace > 0 {
+        size += 1;
+    }
+    if composition.non_ascii > 0 {
+        size += NON_ASCII_POOL;
+    }
+
+    size
+}
+
+/// Computes brute-force entropy in bits for a given alphabet size and length
+pub fn compute_entropy(charset_size: u32, length: usize) -> f64 {
+    if charset_size == 0 || length == 0 {
+        return 0.0;
+    }
+    length as f64 * (charset_size as f64).log2()
+}
+
+/// Computes the entropy left once predictable segments are priced at their guess cost
+///
+/// Characters inside a finding no longer contribute `bits_per_char` each; the
+/// finding contributes its own (much smaller) `guess_bits` instead. The result
+/// never exceeds the raw brute-force entropy.
+fn compute_effective_entropy(entropy_bits: f64, bits_per_char: f64, patterns: &[PatternFinding]) -> f64 {
+    let covered: usize = patterns.iter().map(|p| p.length).sum();
+    let pattern_bits: f64 = patterns.iter().map(|p| p.guess_bits).sum();
+    let effective = entropy_bits - covered as f64 * bits_per_char + pattern_bits;
+
+    effective.clamp(0.0, entropy_bits)
+}
+
+/// Maps entropy onto a 0-100 score, saturating at `FULL_SCORE_BITS`
+fn compute_score(entropy_bits: f64) -> u8 {
+    (entropy_bits.min(FULL_SCORE_BITS) * 100.0 / FULL_SCORE_BITS).round() as u8
+}
+
+/// Estimates seconds needed to exhaust a keyspace of `entropy_bits` at bcrypt speed
+pub fn compute_time_to_crack(entropy_bits: f64) -> f64 {
+    compute_time_to_crack_at(entropy_bits, utils::BCRYPT_CRACKING_SPEED as f64)
+}
+
+/// Estimates seconds needed to exhaust a keyspace of `entropy_bits` at `guesses_per_second`
+pub fn compute_time_to_crack_at(entropy_bits: f64, guesses_per_second: f64) -> f64 {
+    entropy_bits.exp2() / guesses_per_second
+}
+
+/// Formats a crack time in seconds as a human-readable duration
+///
+/// Durations that overflow `u64` seconds are clamped, which is still
+/// billions of times the age of the universe.
+pub fn format_crack_time(seconds: f64) -> String {
+    if seconds < 1.0 {
+        return String::from("less than a second");
+    }
+    let secs = if seconds >= u64::MAX as f64 { u64::MAX } else { seconds as u64 };
+    humantime::format_duration(Duration::from_secs(secs)).to_string()
+}
+
+/// Translated description of a pattern kind, for warnings
+fn pattern_label(kind: PatternKind) -> Msg {
+    match kind {
+        PatternKind::PersonalInfo => Msg::PatternPersonalInfo,
+        PatternKind::Dictionary => Msg::PatternDictionary,
+        PatternKind::KeyboardWalk => Msg::PatternKeyboardWalk,
+        PatternKind::Sequence => Msg::PatternSequence,
+        PatternKind::Repeat => Msg::PatternRepeat,
+    }
+}
+
+/// Derives warnings from the character composition and detected patterns
+///
+/// The warnings are worded in the selected locale.
+fn collect_warnings(composition: &Composition, patterns: &[PatternFinding]) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    if composition.total == 0 {
+        warnings.push(tr(Msg::WarningEmpty).to_string());
+        return warnings;
+    }
+
+    // Flag a single class dominating the password
+    for (count, label) in composition.classes().into_iter().zip(CLASS_LABELS) {
+        let percent = composition.percent(count);
+        if percent >= DOMINANT_CLASS_PERCENT {
+            warnings.push(trf(Msg::WarningDominantClass, &[&format!("{:.0}", percent), &tr(label)]));
+        }
+    }
+
+    // Flag each predictable segment
+    for pattern in patterns {
+        warnings.push(trf(
+            Msg::WarningPattern,
+            &[&tr(pattern_label(pattern.kind)), &pattern.length, &(pattern.start + 1)],
+        ));
+    }
+
+    // Flag heavy character repetition
+    if composition.total > 1 && composition.unique * 2 <= composition.total {
+        warnings.push(trf(Msg::WarningFewUnique, &[&composition.unique, &composition.total]));
+    }
+
+    warnings
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unicode_segmentation::UnicodeSegmentation;
+
+    fn analyze(password: &str) -> AnalysisReport {
+        analyze_password(password, &AnalyzerOptions::default())
+    }
+
+    #[test]
+    fn reports_round_trip() {
+        // Patterns, warnings and non-ASCII counts all make it through
+        for password in ["", "qwerty123", "Pässwörd!!!!", "kV9#mQ2$xL7!pR4&zT8*"] {
+            let report = analyze(password);
+            let json = serde_json::to_string(&report).unwrap();
+            assert_eq!(serde_json::from_str::<AnalysisReport>(&json).unwrap(), report, "{:?}", password);
+        }
+    }
+
+    #[test]
+    fn reports_reject_unknown_fields() {
+        let report = serde_json::to_value(analyze("qwerty123")).unwrap();
+        for (path, key) in [("", "password"), ("/composition", "emoji"), ("/patterns/0", "text")] {
+            let mut value = report.clone();
+            value.pointer_mut(path).unwrap()[key] = serde_json::json!(true);
+            let error = serde_json::from_value::<AnalysisReport>(value).unwrap_err().to_string();
+            assert!(error.contains("unknown field"), "{}{}: {}", path, key, error);
+        }
+    }
+
+    #[test]
+    fn all_symbol_password_uses_only_the_symbol_pool() {
+        let report = analyze("!@#$%^&*()");
+        assert_eq!(report.composition.symbols, 10);
+        assert_eq!(report.charset_size, ASCII_SYMBOL_POOL);
+        assert!(report.warnings.contains(&String::from("100% of characters are symbols")), "{:?}", report.warnings);
+    }
+
+    #[test]
+    fn emoji_add_the_non_ascii_pool_and_count_as_symbols() {
+        let report = analyze("pass🔑word🎉");
+        assert_eq!((report.length, report.graphemes), (10, 10));
+        assert_eq!((report.composition.symbols, report.composition.non_ascii), (2, 2));
+        assert_eq!(report.charset_size, utils::CHARS.len() as u32 + ASCII_SYMBOL_POOL + NON_ASCII_POOL);
+        assert!(report.warnings.iter().all(|w| !w.contains("symbols")), "{:?}", report.warnings);
+    }
+
+    /// Checks that the numbers of a report agree with each other
+    fn assert_coherent(password: &str, report: &AnalysisReport) {
+        let composition = &report.composition;
+        assert_eq!(report.length, password.chars().count(), "{:?}", password);
+        assert_eq!(report.graphemes, password.graphemes(true).count(), "{:?}", password);
+        assert_eq!(report.entropy_bits, compute_entropy(report.charset_size, report.length), "{:?}", password);
+        assert!(report.effective_entropy_bits <= report.entropy_bits, "{:?}", password);
+        assert!(report.patterns.iter().all(|p| p.start + p.length <= report.length), "{:?}", password);
+        let classified = composition.lowercase + composition.uppercase + composition.digits;
+        assert_eq!(classified + composition.symbols + composition.whitespace, report.length, "{:?}", password);
+    }
+
+    #[test]
+    fn zwj_sequences_count_every_character_but_one_grapheme() {
+        // Man, woman, girl, boy joined by three U+200D ZERO WIDTH JOINERs: 25 bytes
+        let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}\u{200d}\u{1f466}";
+        let report = analyze(family);
+        assert_coherent(family, &report);
+        assert_eq!((family.len(), report.length, report.graphemes), (25, 7, 1));
+        assert_eq!((report.composition.symbols, report.composition.non_ascii), (7, 7));
+        assert_eq!(report.composition.unique, 5);
+        assert_eq!(report.charset_size, ASCII_SYMBOL_POOL + NON_ASCII_POOL);
+
+        let password = format!("Tr0ub4dor{}!", family);
+        let report = analyze(&password);
+        assert_coherent(&password, &report);
+        assert_eq!((report.length, report.graphemes), (17, 11));
+    }
+
+    #[test]
+    fn cjk_characters_count_once_despite_three_bytes_and_double_width() {
+        let password = "密码安全测试2024";
+        let report = analyze(password);
+        assert_coherent(password, &report);
+        assert_eq!((password.len(), report.length, report.graphemes), (22, 10, 10));
+        // Han has no case, so it is neither lowercase nor uppercase
+        assert_eq!((report.composition.symbols, report.composition.digits), (6, 4));
+        assert_eq!(report.composition.non_ascii, 6);
+        assert_eq!(report.charset_size, utils::NUMBERS.len() as u32 + ASCII_SYMBOL_POOL + NON_ASCII_POOL);
+    }
+
+    #[test]
+    fn combining_accents_are_characters_of_the_same_grapheme() {
+        // "café" and "naïve" spelled with U+0301 and U+0308 combining marks
+        let password = "cafe\u{301}nai\u{308}ve";
+        let report = analyze(password);
+        assert_coherent(password, &report);
+        assert_eq!((report.length, report.graphemes), (11, 9));
+        assert_eq!((report.composition.lowercase, report.composition.symbols), (9, 2));
+    }
+
+    #[test]
+    fn cyrillic_capitals_count_as_uppercase() {
+        let password = "Пароль123";
+        let report = analyze(password);
+        assert_coherent(password, &report);
+        assert_eq!((password.len(), report.length), (15, 9));
+        assert_eq!(report.composition.uppercase, 1);
+        assert_eq!(report.composition.lowercase, 5);
+        assert_eq!(report.composition.non_ascii, 6);
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn rendered_length_shows_characters_and_typed_graphemes() {
+        let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}";
+        let out = render_report(&analyze(family), DEFAULT_METER_WIDTH, false);
+        assert!(out.contains("5 characters (1 as typed)"), "{}", out);
+        let out = render_report(&analyze("密码安全测试"), DEFAULT_METER_WIDTH, false);
+        assert!(out.contains("6 characters\n"), "{}", out);
+    }
+
+    #[test]
+    fn reports_never_contain_the_password() {
+        let json = serde_json::to_string(&analyze("Tr0ub4dour&3x")).unwrap();
+        assert!(!json.contains("Tr0ub4dour"), "{}", json);
+    }
+}
@@ -0,0 +1,341 @@
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Common passwords and words attackers try first (lowercase, leetspeak-free)
+const COMMON_WORDS: &[&str] = &[
+    "password", "passw0rd", "welcome", "letmein", "monkey", "dragon", "master",
+    "shadow", "sunshine", "princess", "football", "baseball", "soccer", "hockey",
+    "superman", "batman", "trustno", "iloveyou", "freedom", "whatever", "secret",
+    "admin", "login", "access", "summer", "winter", "spring", "autumn", "michael",
+    "jordan", "charlie", "thomas", "hunter", "killer", "pepper", "ginger", "cookie",
+    "cheese", "flower", "banana", "orange", "purple", "silver", "golden", "dolphin",
+    "tigger", "maggie", "buster", "daniel", "andrew", "joshua", "matrix", "mustang",
+    "starwars", "computer", "internet", "hello", "love", "qazwsx", "abc123",
+    "changeme", "default", "guest", "root", "test", "user", "pass", "god",
+];
+
+/// Keyboard rows used to detect walks such as `qwerty` or `asdf`
+const KEYBOARD_ROWS: &[&str] = &["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// Minimum length of a keyboard walk worth reporting
+const MIN_KEYBOARD_WALK: usize = 4;
+
+/// Minimum length of an ascending/descending sequence worth reporting
+const MIN_SEQUENCE: usize = 3;
+
+/// Minimum length of a run of the same character worth reporting
+const MIN_REPEAT: usize = 3;
+
+/// Approximate guesses (in bits) to pick a keyboard walk: row, start, length and direction
+const KEYBOARD_WALK_BITS: f64 = 8.0;
+
+/// Approximate guesses (in bits) to pick a sequence: start character and direction
+const SEQUENCE_BITS: f64 = 7.0;
+
+/// Extra guesses (in bits) to cover capitalization and leetspeak variants of a word
+const WORD_VARIANT_BITS: f64 = 1.0;
+
+/// Minimum length of a personal input (e.g. username) worth matching
+const MIN_PERSONAL_INPUT: usize = 3;
+
+/// Approximate guesses (in bits) to try a personal input known to the attacker
+const PERSONAL_INPUT_BITS: f64 = 2.0;
+
+// ============================================================================
+// Pattern Findings
+// ============================================================================
+
+/// Kind of predictable structure found in a password
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternKind {
+    /// Personal information such as the account's username
+    PersonalInfo,
+    /// Common password or dictionary word (leetspeak and case insensitive)
+    Dictionary,
+    /// Adjacent keys on a QWERTY keyboard row
+    KeyboardWalk,
+    /// Consecutive characters such as `abc` or `987`
+    Sequence,
+    /// The same character repeated
+    Repeat,
+}
+
+impl PatternKind {
+    /// Human-readable description of the pattern kind
+    pub fn description(&self) -> &'static str {
+        match self {
+            PatternKind::PersonalInfo => "personal information (e.g. username)",
+            PatternKind::Dictionary => "common word or password",
+            PatternKind::KeyboardWalk => "keyboard walk",
+            PatternKind::Sequence => "character sequence",
+            PatternKind::Repeat => "repeated character",
+        }
+    }
+}
+
+/// A predictable segment of a password
+///
+/// Positions are character indices, never byte offsets. The matched text is
+/// deliberately not stored so findings can be logged or exported safely.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatternFinding {
+    /// Kind of pattern detected
+    pub kind: PatternKind,
+    /// Index of the first character of the segment
+    pub start: usize,
+    /// Number of characters in the segment
+    pub length: usize,
+    /// Estimated bits an attacker spends guessing this segment
+    pub guess_bits: f64,
+}
+
+/// Finds predictable patterns in a password
+///
+/// # Arguments
+/// * `password` - Password to inspect
+/// * `bits_per_char` - Brute-force bits per character, used to cost repeats
+/// * `user_inputs` - Personal context an attacker may know (usernames, emails, ...)
+///
+/// # Returns
+/// * `Vec<PatternFinding>` - Non-overlapping findings, sorted by position
+///
+/// # Algorithm
+/// Detectors run from most to least specific (personal info, dictionary,
+/// keyboard walk, sequence, repeat); each only considers characters not
+/// already claimed by an earlier finding, so penalties are never applied twice.
+pub fn detect_patterns(password: &str, bits_per_char: f64, user_inputs: &[String]) -> Vec<PatternFinding> {
+    let chars: Vec<char> = password.chars().collect();
+    let normalized: Vec<char> = chars.iter().map(|c| normalize_char(*c)).collect();
+    let lowered: Vec<char> = chars.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+    let mut covered = vec![false; chars.len()];
+    let mut findings = Vec::new();
+
+    find_personal_inputs(&normalized, user_inputs, &mut covered, &mut findings);
+    find_dictionary_words(&normalized, &mut covered, &mut findings);
+    find_keyboard_walks(&lowered, &mut covered, &mut findings);
+    find_sequences(&chars, &mut covered, &mut findings);
+    find_repeats(&chars, &mut covered, &mut findings, bits_per_char);
+
+    findings.sort_by_key(|f| f.start);
+    findings
+}
+
+/// Runs every detector on its own, before overlaps are resolved and penalties applied
+///
+/// # Arguments
+/// Same as `detect_patterns`
+///
+/// # Returns
+/// * `Vec<(PatternKind, Vec<PatternFinding>)>` - Findings of each detector, in the
+///   order `detect_patterns` runs them; a segment may appear under several detectors
+pub fn detect_patterns_raw(password: &str, bits_per_char: f64, user_inputs: &[String]) -> Vec<(PatternKind, Vec<PatternFinding>)> {
+    let chars: Vec<char> = password.chars().collect();
+    let normalized: Vec<char> = chars.iter().map(|c| normalize_char(*c)).collect();
+    let lowered: Vec<char> = chars.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+
+    [
+        PatternKind::PersonalInfo,
+        PatternKind::Dictionary,
+        PatternKind::KeyboardWalk,
+        PatternKind::Sequence,
+        PatternKind::Repeat,
+    ]
+    .into_iter()
+    .map(|kind| {
+        // A fresh coverage map per detector, so no detector hides another's findings
+        let mut covered = vec![false; chars.len()];
+        let mut findings = Vec::new();
+        match kind {
+            PatternKind::PersonalInfo => find_personal_inputs(&normalized, user_inputs, &mut covered, &mut findings),
+            PatternKind::Dictionary => find_dictionary_words(&normalized, &mut covered, &mut findings),
+            PatternKind::KeyboardWalk => find_keyboard_walks(&lowered, &mut covered, &mut findings),
+            PatternKind::Sequence => find_sequences(&chars, &mut covered, &mut findings),
+            PatternKind::Repeat => find_repeats(&chars, &mut covered, &mut findings, bits_per_char),
+        }
+        findings.sort_by_key(|f| f.start);
+        (kind, findings)
+    })
+    .collect()
+}
+
+// ============================================================================
+// Detectors
+// ============================================================================
+
+/// Lowercases a character and undoes common leetspeak substitutions
+pub(super) fn normalize_char(c: char) -> char {
+    match c {
+        '@' | '4' => 'a',
+        '3' => 'e',
+        '1' | '!' => 'i',
+        '0' => 'o',
+        '$' | '5' => 's',
+        '7' => 't',
+        _ => c.to_lowercase().next().unwrap_or(c),
+    }
+}
+
+/// Returns true when none of the characters in `start..start + len` are covered yet
+fn is_free(covered: &[bool], start: usize, len: usize) -> bool {
+    covered[start..start + len].iter().all(|c| !c)
+}
+
+/// Records a finding and marks its characters as covered
+fn claim(covered: &mut [bool], findings: &mut Vec<PatternFinding>, finding: PatternFinding) {
+    for flag in &mut covered[finding.start..finding.start + finding.length] {
+        *flag = true;
+    }
+    findings.push(finding);
+}
+
+/// Detects personal inputs such as usernames, including the local part of an email
+fn find_personal_inputs(normalized: &[char], user_inputs: &[String], covered: &mut [bool], findings: &mut Vec<PatternFinding>) {
+    let mut words: Vec<Vec<char>> = Vec::new();
+    for input in user_inputs {
+        words.push(input.chars().map(normalize_char).collect());
+        // `jane.doe@example.com` is also guessable as `jane.doe`
+        if let Some((local, _)) = input.split_once('@') {
+            words.push(local.chars().map(normalize_char).collect());
+        }
+    }
+    words.retain(|w| w.len() >= MIN_PERSONAL_INPUT);
+
+    find_words(normalized, words, PatternKind::PersonalInfo, PERSONAL_INPUT_BITS, covered, findings);
+}
+
+/// Detects common words, leetspeak and case insensitive
+fn find_dictionary_words(normalized: &[char], covered: &mut [bool], findings: &mut Vec<PatternFinding>) {
+    let words: Vec<Vec<char>> = COMMON_WORDS
+        .iter()
+        .map(|w| w.chars().map(normalize_char).collect())
+        .collect();
+    let guess_bits = (COMMON_WORDS.len() as f64).log2() + WORD_VARIANT_BITS;
+
+    find_words(normalized, words, PatternKind::Dictionary, guess_bits, covered, findings);
+}
+
+/// Claims every free occurrence of the given words, longest first so `password` wins over `pass`
+fn find_words(
+    normalized: &[char],
+    mut words: Vec<Vec<char>>,
+    kind: PatternKind,
+    guess_bits: f64,
+    covered: &mut [bool],
+    findings: &mut Vec<PatternFinding>,
+) {
+    words.sort_by_key(|w| std::cmp::Reverse(w.len()));
+
+    for word in &words {
+        if word.is_empty() || word.len() > normalized.len() {
+            continue;
+        }
+        for start in 0..=normalized.len() - word.len() {
+            if normalized[start..start + word.len()] == word[..] && is_free(covered, start, word.len()) {
+                claim(covered, findings, PatternFinding { kind, start, length: word.len(), guess_bits });
+            }
+        }
+    }
+}
+
+/// Detects runs of adjacent keys along a keyboard row, in either direction
+fn find_keyboard_walks(lowered: &[char], covered: &mut [bool], findings: &mut Vec<PatternFinding>) {
+    let rows: Vec<Vec<char>> = KEYBOARD_ROWS.iter().map(|r| r.chars().collect()).collect();
+
+    // Position of a character on the keyboard as (row, column)
+    let locate = |c: char| -> Option<(usize, usize)> {
+        rows.iter()
+            .enumerate()
+            .find_map(|(r, row)| row.iter().position(|k| *k == c).map(|col| (r, col)))
+    };
+
+    let mut start = 0;
+    while start < lowered.len() {
+        let mut end = start + 1;
+        let mut direction: Option<isize> = None;
+
+        // Extend the walk while each key neighbours the previous one on the same row
+        while end < lowered.len() {
+            let (Some((r1, c1)), Some((r2, c2))) = (locate(lowered[end - 1]), locate(lowered[end])) else {
+                break;
+            };
+            let step = c2 as isize - c1 as isize;
+            if r1 != r2 || step.abs() != 1 || direction.is_some_and(|d| d != step) {
+                break;
+            }
+            direction = Some(step);
+            end += 1;
+        }
+
+        let len = end - start;
+        if len >= MIN_KEYBOARD_WALK && is_free(covered, start, len) {
+            claim(covered, findings, PatternFinding {
+                kind: PatternKind::KeyboardWalk,
+                start,
+                length: len,
+                guess_bits: KEYBOARD_WALK_BITS,
+            });
+            start = end;
+        } else {
+            start += 1;
+        }
+    }
+}
+
+/// Detects ascending or descending runs of consecutive code points (`abc`, `321`)
+fn find_sequences(chars: &[char], covered: &mut [bool], findings: &mut Vec<PatternFinding>) {
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = start + 1;
+        let mut direction: Option<i64> = None;
+
+        while end < chars.len() {
+            let step = chars[end] as i64 - chars[end - 1] as i64;
+            if step.abs() != 1 || direction.is_some_and(|d| d != step) {
+                break;
+            }
+            direction = Some(step);
+            end += 1;
+        }
+
+        let len = end - start;
+        if len >= MIN_SEQUENCE && is_free(covered, start, len) {
+            claim(covered, findings, PatternFinding {
+                kind: PatternKind::Sequence,
+                start,
+                length: len,
+                guess_bits: SEQUENCE_BITS,
+            });
+            start = end;
+        } else {
+            start += 1;
+        }
+    }
+}
+
+/// Detects runs of the same character (`aaa`, `!!!!`)
+fn find_repeats(chars: &[char], covered: &mut [bool], findings: &mut Vec<PatternFinding>, bits_per_char: f64) {
+    let mut start = 0;
+    while start < chars.len() {
+        let end = chars[start..]
+            .iter()
+            .position(|c| *c != chars[start])
+            .map_or(chars.len(), |offset| start + offset);
+
+        let len = end - start;
+        if len >= MIN_REPEAT && is_free(covered, start, len) {
+            // Guessing a repeat costs one character plus the run length
+            claim(covered, findings, PatternFinding {
+                kind: PatternKind::Repeat,
+                start,
+                length: len,
+                guess_bits: bits_per_char + (len as f64).log2(),
+            });
+        }
+        start = end;
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use serde::Serialize;
+use crate::i18n::{tr, Msg};
+use super::patterns::normalize_char;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Maximum Levenshtein distance for two passwords to count as near-duplicates
+const MAX_EDIT_DISTANCE: usize = 2;
+
+// ============================================================================
+// Reuse Clusters
+// ============================================================================
+
+/// Why passwords were grouped together, from closest to loosest relationship
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReuseReason {
+    /// Byte-for-byte identical
+    Identical,
+    /// Identical ignoring case
+    IgnoringCase,
+    /// Identical apart from a trailing counter (`Summer2023` / `Summer2024`)
+    TrailingCounter,
+    /// Within a small edit distance of each other
+    NearDuplicate,
+}
+
+impl ReuseReason {
+    /// Human-readable description of the reason, in the current language
+    pub fn description(&self) -> &'static str {
+        tr(match self {
+            ReuseReason::Identical => Msg::ReuseIdentical,
+            ReuseReason::IgnoringCase => Msg::ReuseIgnoringCase,
+            ReuseReason::TrailingCounter => Msg::ReuseTrailingCounter,
+            ReuseReason::NearDuplicate => Msg::ReuseNearDuplicate,
+        })
+    }
+}
+
+/// A group of passwords that are the same or nearly the same
+#[derive(Debug, Clone, Serialize)]
+pub struct ReuseCluster {
+    /// Loosest relationship that links the members together
+    pub reason: ReuseReason,
+    /// 1-based line numbers of every member, in file order
+    pub lines: Vec<usize>,
+    /// Members as displayed (masked unless passwords are shown), matching `lines`
+    pub members: Vec<String>,
+}
+
+/// Groups identical and near-duplicate passwords
+///
+/// # Arguments
+/// * `passwords` - `(line, password)` pairs
+/// * `display` - Turns a password into its displayed form (e.g. masked)
+///
+/// # Returns
+/// * `Vec<ReuseCluster>` - Clusters of two or more entries, ordered by first line
+///
+/// # Algorithm
+/// 1. Exact duplicates are grouped with a hash map, so every later stage
+///    works on distinct values only
+/// 2. Distinct values are bucketed by their lowercase form, then by their
+///    lowercase form stripped of trailing digits
+/// 3. Near-duplicates are only searched for (pairwise Levenshtein on the
+///    leetspeak-normalized values) inside buckets sharing the same stem, the
+///    normalized letters before any trailing digits or symbols; this avoids
+///    an all-pairs scan
+/// 4. Links from every stage are merged with a union-find; a cluster reports
+///    the loosest reason among the links that formed it
+pub fn find_reuse(passwords: &[(usize, &str)], display: impl Fn(&str) -> String) -> Vec<ReuseCluster> {
+    // Stage 1: exact duplicates
+    let mut distinct: Vec<(&str, Vec<usize>)> = Vec::new();
+    let mut index_of: HashMap<&str, usize> = HashMap::new();
+    for (line, password) in passwords {
+        match index_of.get(password) {
+            Some(index) => distinct[*index].1.push(*line),
+            None => {
+                index_of.insert(password, distinct.len());
+                distinct.push((password, vec![*line]));
+            }
+        }
+    }
+
+    let mut sets = DisjointSets::new(distinct.len());
+
+    // Stage 2: same value ignoring case, then same value ignoring a trailing counter
+    let lowered: Vec<String> = distinct.iter().map(|(p, _)| p.to_lowercase()).collect();
+    link_buckets(&mut sets, lowered.iter().cloned(), ReuseReason::IgnoringCase);
+    link_buckets(
+        &mut sets,
+        lowered.iter().map(|l| l.trim_end_matches(|c: char| c.is_ascii_digit()).to_string()),
+        ReuseReason::TrailingCounter,
+    );
+
+    // Stage 3: near-duplicates, compared pairwise only within a stem bucket
+    let normalized: Vec<String> = lowered.iter().map(|l| l.chars().map(normalize_char).collect()).collect();
+    let mut stems: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, lower) in lowered.iter().enumerate() {
+        let stem: String = lower
+            .trim_end_matches(|c: char| !c.is_alphabetic())
+            .chars()
+            .map(normalize_char)
+            .filter(|c| c.is_alphabetic())
+            .collect();
+        if !stem.is_empty() {
+            stems.entry(stem).or_default().push(index);
+        }
+    }
+    for bucket in stems.values() {
+        for (i, a) in bucket.iter().enumerate() {
+            for b in &bucket[i + 1..] {
+                if sets.find(*a) != sets.find(*b) && within_distance(&normalized[*a], &normalized[*b], MAX_EDIT_DISTANCE) {
+                    sets.union(*a, *b, ReuseReason::NearDuplicate);
+                }
+            }
+        }
+    }
+
+    // Collect clusters: merged groups, plus exact duplicates that were never merged
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for index in 0..distinct.len() {
+        groups.entry(sets.find(index)).or_default().push(index);
+    }
+
+    let mut clusters: Vec<ReuseCluster> = groups
+        .into_iter()
+        .filter_map(|(root, members)| {
+            let mut lines: Vec<(usize, &str)> = members
+                .iter()
+                .flat_map(|m| distinct[*m].1.iter().map(|line| (*line, distinct[*m].0)))
+                .collect();
+            if lines.len() < 2 {
+                return None;
+            }
+            lines.sort_unstable_by_key(|(line, _)| *line);
+            Some(ReuseCluster {
+                reason: sets.reason[root],
+                members: lines.iter().map(|(_, password)| display(password)).collect(),
+                lines: lines.into_iter().map(|(line, _)| line).collect(),
+            })
+        })
+        .collect();
+
+    clusters.sort_by_key(|c| c.lines[0]);
+    clusters
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Union-find over distinct passwords, tracking the loosest reason per set
+struct DisjointSets {
+    parent: Vec<usize>,
+    reason: Vec<ReuseReason>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> Self {
+        DisjointSets { parent: (0..len).collect(), reason: vec![ReuseReason::Identical; len] }
+    }
+
+    fn find(&mut self, index: usize) -> usize {
+        let mut root = index;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        // Path compression keeps later lookups flat
+        let mut current = index;
+        while self.parent[current] != root {
+            let next = self.parent[current];
+            self.parent[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize, reason: ReuseReason) {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        let merged = self.reason[root_a].max(self.reason[root_b]);
+
+        if root_a != root_b {
+            self.parent[root_b] = root_a;
+            self.reason[root_a] = merged.max(reason);
+        }
+    }
+}
+
+/// Links every pair of distinct passwords sharing the same key
+fn link_buckets(sets: &mut DisjointSets, keys: impl Iterator<Item = String>, reason: ReuseReason) {
+    let mut first_with_key: HashMap<String, usize> = HashMap::new();
+    for (index, key) in keys.enumerate() {
+        if key.is_empty() {
+            continue;
+        }
+        match first_with_key.get(&key) {
+            Some(first) => sets.union(*first, index, reason),
+            None => {
+                first_with_key.insert(key, index);
+            }
+        }
+    }
+}
+
+/// Returns true when the Levenshtein distance between `a` and `b` is at most `max`
+///
+/// Works on characters, and bails out early when the length difference alone exceeds `max`.
+fn within_distance(a: &str, b: &str, max: usize) -> bool {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > max {
+        return false;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        // Every later row only grows, so stop as soon as the whole row exceeds `max`
+        if current.iter().all(|d| *d > max) {
+            return false;
+        }
+        previous = current;
+    }
+
+    previous[b.len()] <= max
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Clusters of `passwords` numbered from line 1, displayed as they are
+    fn clusters(passwords: &[&str]) -> Vec<ReuseCluster> {
+        let numbered: Vec<(usize, &str)> = passwords.iter().enumerate().map(|(index, p)| (index + 1, *p)).collect();
+        find_reuse(&numbered, str::to_string)
+    }
+
+    /// The reason and lines of every cluster
+    fn summary(passwords: &[&str]) -> Vec<(ReuseReason, Vec<usize>)> {
+        clusters(passwords).into_iter().map(|c| (c.reason, c.lines)).collect()
+    }
+
+    #[test]
+    fn each_reason_is_detected() {
+        assert_eq!(summary(&["hunter2", "other", "hunter2"]), [(ReuseReason::Identical, vec![1, 3])]);
+        assert_eq!(summary(&["Password", "password"]), [(ReuseReason::IgnoringCase, vec![1, 2])]);
+        assert_eq!(summary(&["Summer2023", "Summer2024"]), [(ReuseReason::TrailingCounter, vec![1, 2])]);
+        assert_eq!(summary(&["P@ssw0rd!", "Passw0rd1"]), [(ReuseReason::NearDuplicate, vec![1, 2])]);
+    }
+
+    #[test]
+    fn unrelated_passwords_are_not_clustered() {
+        assert!(clusters(&["correct horse", "battery staple", "Tr0ub4dour&3"]).is_empty());
+        assert!(clusters(&[]).is_empty());
+        assert!(clusters(&["alone"]).is_empty());
+    }
+
+    #[test]
+    fn merged_clusters_report_the_loosest_reason() {
+        // Identical and case-insensitive links first, then a counter joins them
+        assert_eq!(
+            summary(&["Summer2023", "Summer2023", "SUMMER2023", "summer2024"]),
+            [(ReuseReason::TrailingCounter, vec![1, 2, 3, 4])]
+        );
+        // A near-duplicate pulls the whole group down to the loosest reason
+        assert_eq!(
+            summary(&["Password1", "password1", "Passw0rd1", "Password1"]),
+            [(ReuseReason::NearDuplicate, vec![1, 2, 3, 4])]
+        );
+    }
+
+    #[test]
+    fn all_digit_passwords_are_only_clustered_when_identical() {
+        // No letters means no stem, so neither counters nor edit distance apply
+        assert!(clusters(&["123456", "123457", "1234567", "654321"]).is_empty());
+        assert_eq!(summary(&["123456", "000000", "123456"]), [(ReuseReason::Identical, vec![1, 3])]);
+    }
+
+    #[test]
+    fn lines_and_members_follow_file_order() {
+        let numbered = [(3, "Winter2020"), (7, "apple"), (10, "winter2020"), (12, "Apple"), (15, "Winter2021")];
+        let clusters = find_reuse(&numbered, |p| p.to_uppercase());
+        assert_eq!(clusters.len(), 2);
+        // Clusters are ordered by their first line, members match their lines
+        assert_eq!(clusters[0].lines, [3, 10, 15]);
+        assert_eq!(clusters[0].members, ["WINTER2020", "WINTER2020", "WINTER2021"]);
+        assert_eq!(clusters[1].lines, [7, 12]);
+        assert_eq!(clusters[1].members, ["APPLE", "APPLE"]);
+    }
+
+    #[test]
+    fn distance_is_inclusive_of_the_limit() {
+        assert!(within_distance("abcdef", "abcdef", MAX_EDIT_DISTANCE));
+        assert!(within_distance("abcdef", "abXdeY", MAX_EDIT_DISTANCE));
+        assert!(!within_distance("abcdef", "aXcYeZ", MAX_EDIT_DISTANCE));
+        // Insertions and deletions count as one edit each
+        assert!(within_distance("abc", "abcde", MAX_EDIT_DISTANCE));
+        assert!(!within_distance("abc", "abcdef", MAX_EDIT_DISTANCE));
+        assert!(within_distance("", "ab", MAX_EDIT_DISTANCE));
+        assert!(!within_distance("", "abc", MAX_EDIT_DISTANCE));
+        // Characters, not bytes
+        assert!(within_distance("ääää", "aaää", MAX_EDIT_DISTANCE));
+        assert!(within_distance("kitten", "sitting", 3));
+        assert!(!within_distance("kitten", "sitting", 2));
+    }
+}
@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+use std::io;
+use serde::Serialize;
+use crate::analyzer::{self, AnalysisReport, AnalyzerOptions, BreachCount, Grade};
+use crate::i18n::{self, Msg};
+use crate::utils;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Header names recognized as the password column (lowercase)
+const PASSWORD_HEADERS: &[&str] = &["password", "login_password", "pass"];
+
+/// Header names recognized as the username column (lowercase)
+const USERNAME_HEADERS: &[&str] = &["username", "login_username", "user", "email", "login"];
+
+/// Header names recognized as the URL column (lowercase)
+const URL_HEADERS: &[&str] = &["url", "login_uri", "origin_url", "website", "uri"];
+
+// ============================================================================
+// Audit Report
+// ============================================================================
+
+/// Priority of a finding, most urgent first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Severity {
+    /// Found in a breach corpus (only with `--hibp`)
+    Breached,
+    /// Graded D or F
+    Weak,
+    /// Same password used for more than one URL
+    Reused,
+    /// Nothing to report
+    Ok,
+}
+
+/// Audit result for a single credential
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct AuditFinding {
+    /// 1-based data row number (the header is not counted)
+    pub(crate) row: usize,
+    pub(crate) url: String,
+    pub(crate) username: String,
+    /// Password as displayed: masked unless `--show-passwords` is given
+    pub(crate) password: String,
+    pub(crate) severity: Severity,
+    /// Other rows using the same password for a different URL
+    pub(crate) reused_with: Vec<usize>,
+    /// Times the password was seen in breaches (only with `--hibp`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) breach_count: Option<BreachCount>,
+    pub(crate) report: AnalysisReport,
+}
+
+/// Structured result of auditing a credentials export
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct AuditReport {
+    /// Findings sorted by severity, then weakest first
+    pub(crate) findings: Vec<AuditFinding>,
+    /// Credentials found in breaches (only with `--hibp`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) breached: Option<usize>,
+    /// Credentials whose breach lookup failed (only with `--hibp`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) breach_unknown: Option<usize>,
+    pub(crate) weak: usize,
+    pub(crate) reused: usize,
+    pub(crate) ok: usize,
+    /// Rows without a password (e.g. passkey-only entries), left out of the findings
+    pub(crate) without_password: usize,
+}
+
+/// Breach lookup for `--hibp`: one count per password, in the order given
+pub(crate) type BreachLookup<'a> = dyn Fn(&[&str]) -> Vec<BreachCount> + 'a;
+
+/// Column overrides for exports with unusual headers
+///
+/// Each value is either a header name (case insensitive) or a 1-based column number.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ColumnOverrides {
+    pub(crate) password: Option<String>,
+    pub(crate) username: Option<String>,
+}
+
+/// Audits a browser-exported credentials CSV
+///
+/// # Arguments
+/// * `reader` - CSV source with a header row
+/// * `columns` - Optional overrides for the password and username columns
+/// * `options` - Scoring settings; the username of each row is added as personal context
+/// * `show_passwords` - Keep passwords in clear text instead of masking them
+/// * `breaches` - Breach lookup for the passwords; `None` skips the check
+///
+/// # Returns
+/// * `Result<AuditReport, String>` - Prioritized findings, or a description of the parse error
+pub(crate) fn audit_csv<R: io::Read>(
+    reader: R,
+    columns: &ColumnOverrides,
+    options: &AnalyzerOptions,
+    show_passwords: bool,
+    breaches: Option<&BreachLookup>,
+) -> Result<AuditReport, String> {
+    let mut csv = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers = csv.headers().map_err(|e| format!("cannot read CSV header: {}", e))?.clone();
+
+    let password_col = resolve_column(&headers, columns.password.as_deref(), PASSWORD_HEADERS)?
+        .ok_or_else(|| String::from("no password column found; use --password-col"))?;
+    let username_col = resolve_column(&headers, columns.username.as_deref(), USERNAME_HEADERS)?;
+    let url_col = resolve_column(&headers, None, URL_HEADERS)?;
+
+    // Parse every row first so reuse can be detected across the whole export. Rows
+    // without a password have nothing to audit, and would all "share" the empty one.
+    let mut rows: Vec<(usize, String, String, String)> = Vec::new();
+    let mut without_password = 0;
+    for (index, record) in csv.records().enumerate() {
+        let record = record.map_err(|e| format!("row {}: {}", index + 1, e))?;
+        let field = |col: Option<usize>| col.and_then(|c| record.get(c)).unwrap_or("").to_string();
+        let password = field(Some(password_col));
+        if password.is_empty() {
+            without_password += 1;
+            continue;
+        }
+        rows.push((index + 1, field(url_col), field(username_col), password));
+    }
+
+    // Map each password to the rows using it
+    let mut by_password: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, (_, _, _, password)) in rows.iter().enumerate() {
+        by_password.entry(password.as_str()).or_default().push(index);
+    }
+
+    let breach_counts = breaches.map(|lookup| {
+        let passwords: Vec<&str> = rows.iter().map(|(_, _, _, password)| password.as_str()).collect();
+        lookup(&passwords)
+    });
+
+    let mut findings = Vec::with_capacity(rows.len());
+    for (index, (row, url, username, password)) in rows.iter().enumerate() {
+        let mut row_options = options.clone();
+        if !username.is_empty() {
+            row_options.user_inputs.push(username.clone());
+        }
+        let report = analyzer::analyze_password(password, &row_options);
+
+        // Reuse only counts when the same password protects a different site
+        let reused_with: Vec<usize> = by_password[password.as_str()]
+            .iter()
+            .filter(|other| **other != index && rows[**other].1 != *url)
+            .map(|other| rows[*other].0)
+            .collect();
+
+        let breach_count = breach_counts.as_ref().map(|counts| counts[index]);
+        let severity = if breach_count.is_some_and(|count| count.is_breached()) {
+            Severity::Breached
+        } else if matches!(report.grade, Grade::D | Grade::F) {
+            Severity::Weak
+        } else if !reused_with.is_empty() {
+            Severity::Reused
+        } else {
+            Severity::Ok
+        };
+
+        findings.push(AuditFinding {
+            row: *row,
+            url: url.clone(),
+            username: username.clone(),
+            password: if show_passwords { password.clone() } else { utils::mask_password(password) },
+            severity,
+            reused_with,
+            breach_count,
+            report,
+        });
+    }
+
+    findings.sort_by(|a, b| {
+        a.severity
+            .cmp(&b.severity)
+            .then(a.report.effective_entropy_bits.total_cmp(&b.report.effective_entropy_bits))
+    });
+
+    let count = |severity: Severity| findings.iter().filter(|f| f.severity == severity).count();
+    let unknown = findings.iter().filter(|f| f.breach_count == Some(BreachCount::Unknown)).count();
+    Ok(AuditReport {
+        breached: breach_counts.is_some().then(|| count(Severity::Breached)),
+        breach_unknown: breach_counts.is_some().then_some(unknown),
+        weak: count(Severity::Weak),
+        reused: count(Severity::Reused),
+        ok: count(Severity::Ok),
+        without_password,
+        findings,
+    })
+}
+
+/// Renders an audit report as a prioritized, human-readable findings list
+pub(crate) fn render_audit(report: &AuditReport) -> String {
+    let mut out = String::new();
+
+    let labels = [Msg::AuditBreached, Msg::AuditWeak, Msg::AuditReused, Msg::AuditOk];
+    let label_width = labels.iter().map(|label| i18n::tr(*label).chars().count()).max().unwrap_or(0);
+
+    for finding in &report.findings {
+        let label = match finding.severity {
+            Severity::Breached => Msg::AuditBreached,
+            Severity::Weak => Msg::AuditWeak,
+            Severity::Reused => Msg::AuditReused,
+            Severity::Ok => Msg::AuditOk,
+        };
+        out.push_str(&format!(
+            "[{:<label_width$}] {} {:>4}  {}  {}  {}  {}",
+            i18n::tr(label),
+            i18n::tr(Msg::AuditRow),
+            finding.row,
+            finding.url,
+            finding.username,
+            finding.password,
+            i18n::trf(
+                Msg::GradeAndBits,
+                &[&finding.report.grade, &format!("{:.1}", finding.report.effective_entropy_bits)]
+            )
+        ));
+        if !finding.reused_with.is_empty() {
+            let rows: Vec<String> = finding.reused_with.iter().map(|r| r.to_string()).collect();
+            out.push_str(&format!("  {}", i18n::trf(Msg::AuditReusedIn, &[&rows.join(", ")])));
+        }
+        match finding.breach_count {
+            Some(BreachCount::Seen(0)) | None => {}
+            Some(count @ BreachCount::Seen(_)) => {
+                out.push_str(&format!("  {}", i18n::trf(Msg::AuditSeenInBreaches, &[&count])));
+            }
+            Some(BreachCount::Unknown) => out.push_str(&format!("  {}", i18n::tr(Msg::AuditBreachCheckFailed))),
+        }
+        out.push('\n');
+    }
+
+    let breached =
+        report.breached.map_or_else(String::new, |breached| i18n::trf(Msg::AuditSummaryBreached, &[&breached]));
+    let summary = i18n::trf(
+        Msg::AuditSummary,
+        &[&breached, &report.weak, &report.reused, &report.ok, &report.findings.len()],
+    );
+    out.push_str(&format!("\n{}\n", summary));
+    if let Some(unknown) = report.breach_unknown.filter(|unknown| *unknown > 0) {
+        out.push_str(&format!("{}\n", i18n::trf(Msg::AuditUnchecked, &[&unknown])));
+    }
+    if report.without_password > 0 {
+        out.push_str(&format!("{}\n", i18n::trf(Msg::AuditWithoutPassword, &[&report.without_password])));
+    }
+    out
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Finds a column by explicit override, or by matching known header names
+///
+/// # Returns
+/// * `Ok(Some(index))` - 0-based column index
+/// * `Ok(None)` - No override given and no known header found
+/// * `Err(String)` - The override does not match any column
+fn resolve_column(headers: &csv::StringRecord, wanted: Option<&str>, known: &[&str]) -> Result<Option<usize>, String> {
+    let find = |name: &str| headers.iter().position(|h| h.trim().eq_ignore_ascii_case(name));
+
+    match wanted {
+        Some(wanted) => {
+            // Numeric overrides are 1-based column numbers
+            if let Ok(number) = wanted.parse::<usize>() {
+                return if number >= 1 && number <= headers.len() {
+                    Ok(Some(number - 1))
+                } else {
+                    Err(format!("column {} is out of range (1-{})", number, headers.len()))
+                };
+            }
+            find(wanted)
+                .map(Some)
+                .ok_or_else(|| format!("no column named '{}' in header", wanted))
+        }
+        None => Ok(known.iter().find_map(|name| find(name))),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Strong enough to grade A, so only reuse or a breach can flag it
+    const STRONG: &str = "Xk#9vQ2!mZp7@Lr4wT";
+    const OTHER_STRONG: &str = "bN8$qW3^yH6&uJ1*eR";
+
+    fn audit(csv: &str) -> AuditReport {
+        audit_with(csv, &ColumnOverrides::default(), true)
+    }
+
+    fn audit_with(csv: &str, columns: &ColumnOverrides, show_passwords: bool) -> AuditReport {
+        audit_csv(csv.as_bytes(), columns, &AnalyzerOptions::default(), show_passwords, None).unwrap()
+    }
+
+    /// Findings as `(row, severity)`, in report order
+    fn severities(report: &AuditReport) -> Vec<(usize, Severity)> {
+        report.findings.iter().map(|f| (f.row, f.severity)).collect()
+    }
+
+    fn finding(report: &AuditReport, row: usize) -> &AuditFinding {
+        report.findings.iter().find(|f| f.row == row).unwrap()
+    }
+
+    #[test]
+    fn quoted_fields_keep_their_commas_and_quotes() {
+        let report = audit("name,url,username,password\nsite,https://a.com,\"doe, jane\",\"pa,ss\"\"word\"\n");
+        assert_eq!(report.findings[0].username, "doe, jane");
+        assert_eq!(report.findings[0].password, "pa,ss\"word");
+    }
+
+    #[test]
+    fn browser_headers_are_detected() {
+        let chrome = audit(&format!("name,url,username,password,note\nA,https://a.com,alice,{},\n", STRONG));
+        let firefox = audit(&format!(
+            "\"url\",\"username\",\"password\",\"httpRealm\",\"formActionOrigin\",\"guid\"\n\"https://a.com\",\"alice\",\"{}\",,\"\",\"{{1}}\"\n",
+            STRONG
+        ));
+        let bitwarden = audit(&format!("folder,name,login_uri,login_username,login_password\n,A,https://a.com,alice,{}\n", STRONG));
+        for report in [chrome, firefox, bitwarden] {
+            let finding = &report.findings[0];
+            assert_eq!((finding.url.as_str(), finding.username.as_str(), finding.password.as_str()), ("https://a.com", "alice", STRONG));
+        }
+    }
+
+    #[test]
+    fn password_column_can_be_named_or_numbered() {
+        let csv = format!("site,who,secret\nhttps://a.com,alice,{}\n", STRONG);
+        let options = AnalyzerOptions::default();
+        assert_eq!(audit_csv(csv.as_bytes(), &ColumnOverrides::default(), &options, true, None).unwrap_err(), "no password column found; use --password-col");
+
+        for password in ["Secret", "3"] {
+            let columns = ColumnOverrides { password: Some(String::from(password)), username: Some(String::from("2")) };
+            let report = audit_with(&csv, &columns, true);
+            assert_eq!((report.findings[0].username.as_str(), report.findings[0].password.as_str()), ("alice", STRONG));
+        }
+
+        for (password, error) in [("4", "column 4 is out of range (1-3)"), ("0", "column 0 is out of range (1-3)"), ("pin", "no column named 'pin' in header")] {
+            let columns = ColumnOverrides { password: Some(String::from(password)), username: None };
+            assert_eq!(audit_csv(csv.as_bytes(), &columns, &options, true, None).unwrap_err(), error);
+        }
+    }
+
+    #[test]
+    fn reuse_only_counts_across_sites() {
+        let report = audit(&format!(
+            "url,username,password\nhttps://a.com,alice,{0}\nhttps://a.com,alice2,{0}\nhttps://b.com,alice,{0}\nhttps://c.com,bob,{1}\n",
+            STRONG, OTHER_STRONG
+        ));
+        // Rows 1 and 2 share a site with each other, but both share the password with row 3
+        assert_eq!(finding(&report, 1).reused_with, [3]);
+        assert_eq!(finding(&report, 2).reused_with, [3]);
+        assert_eq!(finding(&report, 3).reused_with, [1, 2]);
+        assert_eq!(finding(&report, 4).severity, Severity::Ok);
+        assert_eq!((report.reused, report.ok), (3, 1));
+
+        let same_site = audit(&format!("url,username,password\nhttps://a.com,alice,{0}\nhttps://a.com,bob,{0}\n", STRONG));
+        assert_eq!(severities(&same_site), [(1, Severity::Ok), (2, Severity::Ok)]);
+    }
+
+    #[test]
+    fn findings_are_ordered_by_severity_then_weakness() {
+        let csv = format!(
+            "url,username,password\nhttps://a.com,a,{0}\nhttps://b.com,b,password\nhttps://c.com,c,{1}\nhttps://d.com,d,{1}\nhttps://e.com,e,abc\nhttps://f.com,f,Tr0ub4dour&3x!Q\n",
+            STRONG, OTHER_STRONG
+        );
+        let lookup = |passwords: &[&str]| {
+            passwords.iter().map(|p| if *p == "Tr0ub4dour&3x!Q" { BreachCount::Seen(3) } else { BreachCount::Seen(0) }).collect()
+        };
+        let report = audit_csv(csv.as_bytes(), &ColumnOverrides::default(), &AnalyzerOptions::default(), false, Some(&lookup)).unwrap();
+
+        let order = severities(&report);
+        assert_eq!(order[0], (6, Severity::Breached));
+        // "abc" is weaker than "password", so it comes first among the weak ones
+        assert_eq!(order[1..3], [(5, Severity::Weak), (2, Severity::Weak)]);
+        assert_eq!(order[3..5].iter().map(|(_, s)| *s).collect::<Vec<_>>(), [Severity::Reused, Severity::Reused]);
+        assert_eq!(order[5], (1, Severity::Ok));
+        assert_eq!((report.breached, report.breach_unknown, report.weak, report.reused, report.ok), (Some(1), Some(0), 2, 2, 1));
+
+        // Masked unless asked otherwise
+        assert!(report.findings.iter().all(|f| f.password.contains('*')));
+        assert_eq!(finding(&report, 1).password, utils::mask_password(STRONG));
+        assert_eq!(finding(&report, 2).password, "p******d");
+    }
+
+    #[test]
+    fn rows_without_a_password_are_skipped() {
+        let report = audit(&format!(
+            "url,username,password\nhttps://a.com,passkey,\nhttps://b.com,alice,{0}\nhttps://c.com,passkey2,\nhttps://d.com,bob,{1}\n",
+            STRONG, OTHER_STRONG
+        ));
+        assert_eq!(report.without_password, 2);
+        // Row numbers still count the skipped rows, and the empty passwords are not "reused"
+        assert_eq!(severities(&report), [(2, Severity::Ok), (4, Severity::Ok)]);
+        assert!(report.findings.iter().all(|f| f.reused_with.is_empty()));
+        assert!(render_audit(&report).contains("2 row(s) without a password were skipped"));
+    }
+}
@@ -0,0 +1,135 @@
+use colored::Colorize;
+use figlet_rs::FIGfont;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// First banner line
+const BANNER_TITLE: &str = "Rusty";
+
+/// Default second banner line, replaced by `--banner-text`
+pub(crate) const DEFAULT_BANNER_TEXT: &str = "Password Utility";
+
+/// Narrowest terminal (in columns) that gets the ASCII art banner
+const MIN_ART_WIDTH: usize = 60;
+
+/// FIGlet fonts compiled into the binary, so loading them cannot fail at runtime
+const STANDARD_FONT: &str = include_str!("../assets/fonts/standard.flf");
+const SLANT_FONT: &str = include_str!("../assets/fonts/slant.flf");
+const SMALL_FONT: &str = include_str!("../assets/fonts/small.flf");
+
+// ============================================================================
+// Banner
+// ============================================================================
+
+/// Embedded FIGlet fonts selectable with `--banner-font`
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BannerFont {
+    /// The classic FIGlet font
+    Standard,
+    /// Italic variant of standard
+    Slant,
+    /// Compact font for narrower terminals
+    Small,
+}
+
+impl BannerFont {
+    /// Contents of the embedded `.flf` file
+    fn source(&self) -> &'static str {
+        match self {
+            BannerFont::Standard => STANDARD_FONT,
+            BannerFont::Slant => SLANT_FONT,
+            BannerFont::Small => SMALL_FONT,
+        }
+    }
+}
+
+/// Prints the application banner on stderr
+///
+/// # Arguments
+/// * `font` - Embedded FIGlet font to draw with
+/// * `text` - Second banner line
+///
+/// # Notes
+/// Never fails: the plain-text banner is printed instead whenever the art
+/// cannot be drawn or does not fit the terminal.
+pub(crate) fn print_banner(font: BannerFont, text: &str) {
+    let width = console::Term::stderr().size_checked().map(|(_, columns)| columns as usize);
+    eprintln!("{}", render_banner(font, text, width).green());
+}
+
+/// Renders the banner for a terminal of `width` columns (`None` when unknown)
+///
+/// # Returns
+/// * `String` - ASCII art when the font loads and every line fits, otherwise
+///   the plain-text banner, truncated to the terminal width
+pub(crate) fn render_banner(font: BannerFont, text: &str, width: Option<usize>) -> String {
+    let art = width
+        .is_none_or(|w| w >= MIN_ART_WIDTH)
+        .then(|| render_art(font, text))
+        .flatten()
+        .filter(|art| width.is_none_or(|w| art.lines().all(|line| line.chars().count() <= w)));
+
+    art.unwrap_or_else(|| {
+        [format!("[+] {} [+]", BANNER_TITLE), format!("[>] {} [<]", text)]
+            .iter()
+            .map(|line| match width {
+                Some(w) => line.chars().take(w).collect(),
+                None => line.clone(),
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    })
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Draws both banner lines with a FIGlet font, or `None` if any step fails
+fn render_art(font: BannerFont, text: &str) -> Option<String> {
+    let font = FIGfont::from_content(font.source()).ok()?;
+    let title = font.convert(BANNER_TITLE)?;
+    let subtitle = font.convert(text)?;
+    Some(format!("{}{}", title, subtitle).trim_end().to_string())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ValueEnum;
+
+    #[test]
+    fn every_embedded_font_loads() {
+        for font in BannerFont::value_variants() {
+            assert!(FIGfont::from_content(font.source()).is_ok(), "{:?}", font);
+            assert!(render_art(*font, DEFAULT_BANNER_TEXT).is_some(), "{:?}", font);
+        }
+    }
+
+    #[test]
+    fn narrow_terminals_get_the_plain_banner() {
+        assert_eq!(render_banner(BannerFont::Standard, "Tools", Some(40)), "[+] Rusty [+]\n[>] Tools [<]");
+        assert_eq!(render_banner(BannerFont::Standard, "Tools", Some(8)), "[+] Rust\n[>] Tool");
+    }
+
+    #[test]
+    fn art_that_does_not_fit_falls_back_instead_of_wrapping() {
+        let long = "A banner text far too long for any terminal";
+        assert_eq!(render_banner(BannerFont::Standard, long, Some(80)), format!("[+] Rusty [+]\n[>] {} [<]", long));
+        let art = render_banner(BannerFont::Small, "Tools", Some(200));
+        assert!(!art.starts_with("[+]"), "{}", art);
+        assert!(art.lines().all(|line| line.chars().count() <= 200));
+    }
+
+    #[test]
+    fn text_the_font_cannot_draw_does_not_panic() {
+        let banner = render_banner(BannerFont::Slant, "Schlüssel 🔑", None);
+        assert!(!banner.is_empty());
+    }
+}
@@ -0,0 +1,382 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use rusty_password_utility::error::RustyPassError;
+use rusty_password_utility::generator::{os_rng, GeneratorOptions, PasswordGenerator};
+use rusty_password_utility::i18n::{tr, trf, Msg};
+use rusty_password_utility::policy::PolicyPreset;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Linear sub-buckets per power of two in the latency histogram (about 6% resolution)
+const SUB_BUCKETS: u64 = 16;
+
+/// log2 of `SUB_BUCKETS`
+const SUB_BUCKET_BITS: u32 = 4;
+
+/// Histogram buckets needed to cover every `u64` nanosecond count
+const BUCKETS: usize = ((64 - SUB_BUCKET_BITS as usize) + 1) * SUB_BUCKETS as usize;
+
+/// Percentiles reported for the per-password latency
+const PERCENTILES: [(&str, f64); 3] = [("p50", 50.0), ("p90", 90.0), ("p99", 99.0)];
+
+// ============================================================================
+// Cases
+// ============================================================================
+
+/// One generator setting to measure
+#[derive(Debug, Clone)]
+pub(crate) struct BenchCase {
+    /// Stable identifier, as in `bench --output json`
+    pub(crate) name: &'static str,
+    /// Name shown in the table, in the selected language
+    pub(crate) label: Msg,
+    pub(crate) options: GeneratorOptions,
+    /// Policy every password is checked against, as with `generate --policy`
+    pub(crate) policy: Option<PolicyPreset>,
+}
+
+/// The representative settings `bench` runs: the default password, a long
+/// all-classes one, and the policy path with its rejection loop
+///
+/// There are no passphrase or pattern cases: `generate` only draws characters,
+/// and `Keyspace::passphrase` / `Keyspace::pattern` size a keyspace without
+/// generating anything, so there is nothing to time for them yet.
+pub(crate) fn default_cases() -> Vec<BenchCase> {
+    let all_classes = |length| GeneratorOptions {
+        length,
+        include_uppercase: true,
+        include_digits: true,
+        include_symbols: true,
+        ..GeneratorOptions::default()
+    };
+    vec![
+        BenchCase { name: "16 chars, lowercase", label: Msg::BenchDefault, options: GeneratorOptions::default(), policy: None },
+        BenchCase { name: "32 chars, all classes", label: Msg::BenchAllClasses, options: all_classes(32), policy: None },
+        BenchCase {
+            name: "16 chars, all classes, NIST policy",
+            label: Msg::BenchPolicy,
+            options: all_classes(16),
+            policy: Some(PolicyPreset::Nist),
+        },
+    ]
+}
+
+// ============================================================================
+// Results
+// ============================================================================
+
+/// Per-password latency percentiles in nanoseconds
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Latency {
+    pub(crate) p50: u64,
+    pub(crate) p90: u64,
+    pub(crate) p99: u64,
+    pub(crate) max: u64,
+}
+
+/// Throughput of one case
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct BenchResult {
+    pub(crate) name: &'static str,
+    #[serde(skip)]
+    pub(crate) label: Msg,
+    pub(crate) passwords: u64,
+    pub(crate) passwords_per_second: f64,
+    /// Output rate in megabytes (10^6 bytes) per second, one newline per password included
+    pub(crate) mb_per_second: f64,
+    pub(crate) latency_ns: Latency,
+}
+
+/// Everything `bench` measured, as printed by `bench --output json`
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct BenchReport {
+    pub(crate) version: &'static str,
+    pub(crate) duration_seconds: f64,
+    pub(crate) threads: u32,
+    pub(crate) cases: Vec<BenchResult>,
+}
+
+/// Measures one case for `duration`, with `threads` generators running side by side
+///
+/// # Arguments
+/// * `case` - Generator setting to measure
+/// * `duration` - Wall-clock time each worker keeps generating
+/// * `threads` - Workers, each with its own generator and OS-seeded RNG
+///
+/// # Returns
+/// * `Result<BenchResult, RustyPassError>` - Combined throughput of all workers, or
+///   the first error of building a generator or of generating
+///
+/// # Notes
+/// Every password goes through `std::hint::black_box`, so the compiler cannot
+/// drop the work. Latencies include reading the clock once per password.
+pub(crate) fn run_case(case: &BenchCase, duration: Duration, threads: u32) -> Result<BenchResult, RustyPassError> {
+    let start = Instant::now();
+    let workers: Vec<Result<Worker, RustyPassError>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads).map(|_| scope.spawn(|| run_worker(case, duration))).collect();
+        handles.into_iter().map(|handle| handle.join().expect("benchmark worker panicked")).collect()
+    });
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let mut total = Worker::default();
+    for worker in workers {
+        total.merge(&worker?);
+    }
+    Ok(BenchResult {
+        name: case.name,
+        label: case.label,
+        passwords: total.passwords,
+        passwords_per_second: total.passwords as f64 / elapsed,
+        mb_per_second: total.bytes as f64 / elapsed / 1e6,
+        latency_ns: Latency {
+            p50: total.latencies.percentile(PERCENTILES[0].1),
+            p90: total.latencies.percentile(PERCENTILES[1].1),
+            p99: total.latencies.percentile(PERCENTILES[2].1),
+            max: total.latencies.max,
+        },
+    })
+}
+
+/// Renders the results as a table
+pub(crate) fn render_report(report: &BenchReport) -> String {
+    let case = tr(Msg::BenchCase);
+    let width = report.cases.iter().map(|result| tr(result.label).chars().count()).max().unwrap_or(0).max(case.chars().count());
+    let mut out = format!(
+        "{}\n\n",
+        trf(Msg::BenchHeading, &[&format!("{:.1}", report.duration_seconds), &report.threads])
+    );
+    out.push_str(&format!(
+        "{:<width$}  {:>12}  {:>8}  {:>9}  {:>9}  {:>9}  {:>9}\n",
+        case,
+        tr(Msg::BenchPasswordsPerSecond),
+        "MB/s",
+        PERCENTILES[0].0,
+        PERCENTILES[1].0,
+        PERCENTILES[2].0,
+        "max"
+    ));
+    for result in &report.cases {
+        let latency = &result.latency_ns;
+        out.push_str(&format!(
+            "{:<width$}  {:>12.0}  {:>8.2}  {:>9}  {:>9}  {:>9}  {:>9}\n",
+            tr(result.label),
+            result.passwords_per_second,
+            result.mb_per_second,
+            format_nanos(latency.p50),
+            format_nanos(latency.p90),
+            format_nanos(latency.p99),
+            format_nanos(latency.max)
+        ));
+    }
+    out
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Counters of one worker thread
+#[derive(Debug, Default)]
+struct Worker {
+    passwords: u64,
+    bytes: u64,
+    latencies: Histogram,
+}
+
+impl Worker {
+    fn merge(&mut self, other: &Worker) {
+        self.passwords += other.passwords;
+        self.bytes += other.bytes;
+        self.latencies.merge(&other.latencies);
+    }
+}
+
+/// Generates passwords until `duration` has passed
+fn run_worker(case: &BenchCase, duration: Duration) -> Result<Worker, RustyPassError> {
+    let rng = os_rng()?;
+    let policy = case.policy.map(|preset| preset.policy());
+    let mut generator = match &policy {
+        Some(policy) => PasswordGenerator::with_policy(&case.options, policy, rng)?,
+        None => PasswordGenerator::new(&case.options, rng)?,
+    };
+
+    let mut worker = Worker::default();
+    let start = Instant::now();
+    let mut last = start;
+    while last.duration_since(start) < duration {
+        let password = generator.next_password()?;
+        // The end of one measurement is the start of the next, so the clock is read once per password
+        let now = Instant::now();
+        worker.latencies.record(now.duration_since(last).as_nanos() as u64);
+        worker.passwords += 1;
+        worker.bytes += password.len() as u64 + 1;
+        black_box(password);
+        last = now;
+    }
+    Ok(worker)
+}
+
+/// Log-linear histogram of nanosecond latencies: constant memory however many passwords are measured
+#[derive(Debug)]
+struct Histogram {
+    counts: Vec<u64>,
+    total: u64,
+    max: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Histogram { counts: vec![0; BUCKETS], total: 0, max: 0 }
+    }
+}
+
+impl Histogram {
+    fn record(&mut self, nanos: u64) {
+        self.counts[bucket(nanos)] += 1;
+        self.total += 1;
+        self.max = self.max.max(nanos);
+    }
+
+    fn merge(&mut self, other: &Histogram) {
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+        self.total += other.total;
+        self.max = self.max.max(other.max);
+    }
+
+    /// Upper bound of the bucket holding the given percentile, capped at the largest value seen
+    fn percentile(&self, percent: f64) -> u64 {
+        let rank = ((percent / 100.0 * self.total as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return bucket_upper_bound(index).min(self.max);
+            }
+        }
+        self.max
+    }
+}
+
+/// Histogram bucket of a value: exact below `SUB_BUCKETS`, then `SUB_BUCKETS` slices per power of two
+fn bucket(value: u64) -> usize {
+    if value < SUB_BUCKETS {
+        return value as usize;
+    }
+    let magnitude = 63 - value.leading_zeros();
+    let shift = magnitude - SUB_BUCKET_BITS;
+    let slice = (value >> shift) - SUB_BUCKETS;
+    ((shift as u64 + 1) * SUB_BUCKETS + slice) as usize
+}
+
+/// Largest value that falls into a bucket
+fn bucket_upper_bound(index: usize) -> u64 {
+    let index = index as u64;
+    if index < SUB_BUCKETS {
+        return index;
+    }
+    let shift = index / SUB_BUCKETS - 1;
+    let slice = index % SUB_BUCKETS + SUB_BUCKETS;
+    let bound = ((slice as u128 + 1) << shift) - 1;
+    bound.min(u64::MAX as u128) as u64
+}
+
+/// Formats nanoseconds with a unit that keeps the number short, e.g. `850ns`, `1.2µs`, `3.4ms`
+fn format_nanos(nanos: u64) -> String {
+    match nanos {
+        0..1_000 => format!("{}ns", nanos),
+        1_000..1_000_000 => format!("{:.1}µs", nanos as f64 / 1e3),
+        1_000_000..1_000_000_000 => format!("{:.1}ms", nanos as f64 / 1e6),
+        _ => format!("{:.2}s", nanos as f64 / 1e9),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram(values: impl IntoIterator<Item = u64>) -> Histogram {
+        let mut histogram = Histogram::default();
+        for value in values {
+            histogram.record(value);
+        }
+        histogram
+    }
+
+    #[test]
+    fn small_values_have_exact_buckets() {
+        for value in 0..SUB_BUCKETS {
+            assert_eq!(bucket(value), value as usize);
+            assert_eq!(bucket_upper_bound(bucket(value)), value);
+        }
+    }
+
+    #[test]
+    fn buckets_split_each_power_of_two() {
+        // 16-31 still have one bucket per value; from 32 on a bucket spans two, then four, ...
+        assert_eq!([bucket(16), bucket(31), bucket(32), bucket(33), bucket(34)], [16, 31, 32, 32, 33]);
+        assert_eq!([bucket_upper_bound(16), bucket_upper_bound(31), bucket_upper_bound(32)], [16, 31, 33]);
+        assert_eq!(bucket(64), 48);
+        assert_eq!(bucket(u64::MAX), BUCKETS - 1);
+        assert_eq!(bucket_upper_bound(BUCKETS - 1), u64::MAX);
+    }
+
+    #[test]
+    fn every_value_is_within_its_bucket() {
+        let values = (0..4096).chain((0..64).flat_map(|shift| [1u64 << shift, (1u64 << shift) - 1, (1u64 << shift) + 1]));
+        for value in values.chain([u64::MAX - 1, u64::MAX]) {
+            let index = bucket(value);
+            assert!(bucket_upper_bound(index) >= value, "{}", value);
+            if index > 0 {
+                assert!(bucket_upper_bound(index - 1) < value, "{}", value);
+            }
+        }
+    }
+
+    #[test]
+    fn percentiles_of_a_known_distribution() {
+        let uniform = histogram(1..=100);
+        // 50 lands in the 50-51 bucket; 99 and 100 are reported exactly
+        assert_eq!(uniform.percentile(50.0), 51);
+        assert_eq!(uniform.percentile(99.0), 99);
+        assert_eq!(uniform.percentile(100.0), 100);
+        assert_eq!(uniform.percentile(0.0), 1);
+        assert_eq!(Histogram::default().percentile(50.0), 0);
+
+        // A bucket's upper bound never exceeds the largest value seen
+        let outlier = histogram([10, 10, 10, 1_000_001]);
+        assert_eq!(outlier.percentile(50.0), 10);
+        assert_eq!(outlier.percentile(99.0), 1_000_001);
+    }
+
+    #[test]
+    fn merged_histograms_match_one_histogram_of_everything() {
+        let mut merged = histogram(1..=60);
+        merged.merge(&histogram(61..=100));
+        let whole = histogram(1..=100);
+        assert_eq!((merged.total, merged.max), (100, 100));
+        assert_eq!(merged.counts, whole.counts);
+        for percent in [50.0, 90.0, 99.0] {
+            assert_eq!(merged.percentile(percent), whole.percentile(percent));
+        }
+    }
+
+    #[test]
+    fn nanoseconds_switch_units() {
+        assert_eq!(format_nanos(0), "0ns");
+        assert_eq!(format_nanos(999), "999ns");
+        assert_eq!(format_nanos(1_000), "1.0µs");
+        assert_eq!(format_nanos(1_250), "1.2µs");
+        assert_eq!(format_nanos(3_400_000), "3.4ms");
+        assert_eq!(format_nanos(1_000_000_000), "1.00s");
+        assert_eq!(format_nanos(u64::MAX), "18446744073.71s");
+    }
+}
@@ -0,0 +1,260 @@
+use std::fs;
+use std::path::Path;
+use serde::Serialize;
+use zeroize::Zeroizing;
+use crate::cli::OutputFormat;
+use crate::generator::GeneratedPassword;
+use crate::hash;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Pre-hashing schemes for `chpasswd -e`
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ChpasswdHash {
+    /// SHA-512 crypt (`$6$`), read by every glibc-based distribution
+    Sha512crypt,
+}
+
+/// One generated account, as written to the records
+#[derive(Debug, Serialize)]
+pub(crate) struct Credential<'a> {
+    pub(crate) username: &'a str,
+    pub(crate) password: &'a str,
+    /// With `--hash-alg`, the hash handed to `chpasswd -e`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) hash: Option<String>,
+}
+
+// ============================================================================
+// Users
+// ============================================================================
+
+/// Reads the usernames of a `--users-file`, one per line
+///
+/// Surrounding whitespace is trimmed; blank lines and lines starting with `#`
+/// are skipped.
+///
+/// # Returns
+/// * `Result<Vec<String>, String>` - The usernames in file order, or an error
+///   naming the line of an unusable or duplicate name, or for a file without any
+pub(crate) fn read_users(path: &Path) -> Result<Vec<String>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let mut users: Vec<(usize, String)> = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let username = line.trim();
+        if username.is_empty() || username.starts_with('#') {
+            continue;
+        }
+        if let Some(c) = username.chars().find(|c| *c == ':' || c.is_whitespace() || c.is_control()) {
+            return Err(format!(
+                "{}:{}: '{}' cannot be a username: it contains '{}'",
+                path.display(),
+                number,
+                username,
+                c.escape_default()
+            ));
+        }
+        if let Some((first, _)) = users.iter().find(|(_, existing)| existing == username) {
+            return Err(format!("{}:{}: '{}' is a duplicate of line {}", path.display(), number, username, first));
+        }
+        users.push((number, username.to_string()));
+    }
+
+    if users.is_empty() {
+        return Err(format!("{} lists no usernames", path.display()));
+    }
+    log::debug!("{} username(s) from {}", users.len(), path.display());
+    Ok(users.into_iter().map(|(_, username)| username).collect())
+}
+
+/// Pairs each user with a generated password, hashing it when asked to
+///
+/// # Arguments
+/// * `users` - Usernames from `read_users`
+/// * `passwords` - One generated password per user
+/// * `hash_alg` - Scheme for `chpasswd -e`, if any
+pub(crate) fn credentials<'a>(
+    users: &'a [String],
+    passwords: &'a [GeneratedPassword],
+    hash_alg: Option<ChpasswdHash>,
+) -> Result<Vec<Credential<'a>>, String> {
+    users
+        .iter()
+        .zip(passwords)
+        .map(|(username, generated)| {
+            let hash = match hash_alg {
+                Some(ChpasswdHash::Sha512crypt) => Some(hash::hash_sha512crypt(&generated.password)?),
+                None => None,
+            };
+            Ok(Credential { username, password: &generated.password, hash })
+        })
+        .collect()
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+/// Renders the `username:password` lines `chpasswd` reads, or `username:hash` for `chpasswd -e`
+pub(crate) fn render_lines(credentials: &[Credential]) -> Zeroizing<String> {
+    join_lines(credentials, |credential| credential.hash.as_deref().unwrap_or(credential.password))
+}
+
+/// Renders the records kept for the administrator
+///
+/// # Arguments
+/// * `credentials` - Generated accounts
+/// * `format` - `Text` gives `username:password` lines whether or not a hash was
+///   requested; `Csv` (columns `username,password[,hash]`) and `Json` include the hash
+/// * `header` - With CSV, write the header row
+pub(crate) fn render_records(credentials: &[Credential], format: OutputFormat, header: bool) -> Result<Zeroizing<String>, String> {
+    match format {
+        OutputFormat::Text => Ok(join_lines(credentials, |credential| credential.password)),
+        OutputFormat::Json => serde_json::to_string_pretty(credentials)
+            .map(|json| Zeroizing::new(format!("{}\n", json)))
+            .map_err(|e| format!("failed to serialize credentials: {}", e)),
+        OutputFormat::Csv => {
+            let mut buffer = Zeroizing::new(Vec::new());
+            write_csv(credentials, header, &mut *buffer).map_err(|e| format!("failed to write CSV: {}", e))?;
+            Ok(Zeroizing::new(String::from_utf8_lossy(&buffer).into_owned()))
+        }
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Joins `username:value` lines
+fn join_lines<'a>(credentials: &'a [Credential], value: impl Fn(&'a Credential) -> &'a str) -> Zeroizing<String> {
+    let mut lines = Zeroizing::new(String::new());
+    for credential in credentials {
+        lines.push_str(credential.username);
+        lines.push(':');
+        lines.push_str(value(credential));
+        lines.push('\n');
+    }
+    lines
+}
+
+/// Writes the records as CSV, with a `hash` column when any record has one
+fn write_csv<W: std::io::Write>(credentials: &[Credential], header: bool, writer: W) -> csv::Result<()> {
+    let hashed = credentials.iter().any(|credential| credential.hash.is_some());
+    let mut csv = csv::Writer::from_writer(writer);
+    if header {
+        csv.write_record(if hashed { &["username", "password", "hash"][..] } else { &["username", "password"][..] })?;
+    }
+    for credential in credentials {
+        let mut record = vec![credential.username, credential.password];
+        record.extend(credential.hash.as_deref());
+        csv.write_record(record)?;
+    }
+    csv.flush()?;
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::GenerationFlags;
+
+    /// Writes `contents` as a users file and reads it back
+    fn users(contents: &str) -> Result<Vec<String>, String> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.txt");
+        fs::write(&path, contents).unwrap();
+        read_users(&path).map_err(|e| e.replace(&path.display().to_string(), "users.txt"))
+    }
+
+    fn generated(passwords: &[&str]) -> Vec<GeneratedPassword> {
+        let flags = GenerationFlags { uppercase_chars: true, special_chars: false, numbers: true, policy: None };
+        passwords.iter().map(|p| GeneratedPassword::new(p.to_string(), flags.clone(), 62)).collect()
+    }
+
+    #[test]
+    fn blank_lines_and_comments_are_skipped() {
+        assert_eq!(users("# staff\nalice\n\n  bob  \r\n\t\n#carol\ndave").unwrap(), ["alice", "bob", "dave"]);
+    }
+
+    #[test]
+    fn duplicates_name_both_lines() {
+        assert_eq!(users("alice\nbob\n# again\nalice\n").unwrap_err(), "users.txt:4: 'alice' is a duplicate of line 1");
+    }
+
+    #[test]
+    fn unusable_usernames_are_rejected() {
+        assert_eq!(users("alice\nbob:x\n").unwrap_err(), "users.txt:2: 'bob:x' cannot be a username: it contains ':'");
+        assert_eq!(users("bob smith\n").unwrap_err(), "users.txt:1: 'bob smith' cannot be a username: it contains ' '");
+        assert_eq!(users("bob\tsmith\n").unwrap_err(), "users.txt:1: 'bob\tsmith' cannot be a username: it contains '\\t'");
+    }
+
+    #[test]
+    fn files_without_usernames_are_rejected() {
+        for contents in ["", "\n\n", "# nobody yet\n"] {
+            assert_eq!(users(contents).unwrap_err(), "users.txt lists no usernames", "{:?}", contents);
+        }
+    }
+
+    #[test]
+    fn lines_pair_users_with_passwords_or_hashes() {
+        let names = [String::from("alice"), String::from("bob")];
+        let passwords = generated(&["Secret1", "Secret2"]);
+
+        let plain = credentials(&names, &passwords, None).unwrap();
+        assert_eq!(*render_lines(&plain), "alice:Secret1\nbob:Secret2\n");
+
+        let hashed = credentials(&names, &passwords, Some(ChpasswdHash::Sha512crypt)).unwrap();
+        let lines = render_lines(&hashed);
+        let lines: Vec<&str> = lines.lines().collect();
+        assert!(lines[0].starts_with("alice:$6$") && lines[1].starts_with("bob:$6$"), "{:?}", lines);
+        assert!(!lines.iter().any(|line| line.contains("Secret")));
+        // The records for the administrator keep the passwords either way
+        assert_eq!(*render_records(&hashed, OutputFormat::Text, true).unwrap(), "alice:Secret1\nbob:Secret2\n");
+    }
+
+    #[test]
+    fn csv_has_a_hash_column_only_with_hashes() {
+        let names = [String::from("alice")];
+        let passwords = generated(&["Sec,ret\"1"]);
+
+        let plain = credentials(&names, &passwords, None).unwrap();
+        assert_eq!(*render_records(&plain, OutputFormat::Csv, true).unwrap(), "username,password\nalice,\"Sec,ret\"\"1\"\n");
+        assert_eq!(*render_records(&plain, OutputFormat::Csv, false).unwrap(), "alice,\"Sec,ret\"\"1\"\n");
+
+        let hashed = credentials(&names, &passwords, Some(ChpasswdHash::Sha512crypt)).unwrap();
+        let csv = render_records(&hashed, OutputFormat::Csv, true).unwrap();
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        assert_eq!(reader.headers().unwrap(), vec!["username", "password", "hash"]);
+        let record = reader.records().next().unwrap().unwrap();
+        assert_eq!((&record[0], &record[1]), ("alice", "Sec,ret\"1"));
+        assert!(record[2].starts_with("$6$"));
+    }
+
+    #[test]
+    fn json_is_an_array_of_credentials() {
+        let names = [String::from("alice"), String::from("bob")];
+        let passwords = generated(&["Secret1", "Secret2"]);
+
+        let plain = credentials(&names, &passwords, None).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&render_records(&plain, OutputFormat::Json, true).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{"username": "alice", "password": "Secret1"}, {"username": "bob", "password": "Secret2"}])
+        );
+
+        let hashed = credentials(&names, &passwords, Some(ChpasswdHash::Sha512crypt)).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&render_records(&hashed, OutputFormat::Json, true).unwrap()).unwrap();
+        let mut keys: Vec<&str> = json[0].as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(keys, ["hash", "password", "username"]);
+        assert!(json[1]["hash"].as_str().unwrap().starts_with("$6$"));
+    }
+}
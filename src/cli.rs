@@ -1,5 +1,39 @@
-use clap::{Parser, Subcommand};
-use crate::utils::DEFAULT_LENGTH;
+use std::path::PathBuf;
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use crate::analyzer::{GradeThresholds, Wordlist, DEFAULT_METER_WIDTH};
+use crate::banner::{BannerFont, DEFAULT_BANNER_TEXT};
+use crate::clipboard::DEFAULT_CLEAR_SECONDS;
+use crate::generator::GeneratorOptions;
+use crate::chpasswd::ChpasswdHash;
+use crate::export::ExportFormat;
+use crate::hash::{parse_memory, CostOptions, HashAlgorithm};
+use crate::i18n::{self, Locale, Msg};
+use crate::policy::PolicyPreset;
+use crate::output;
+use crate::utils::{self, DEFAULT_LENGTH};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Exit status of a command-line usage error; 2 is kept for failed checks
+pub(crate) const EXIT_USAGE: i32 = 1;
+
+/// Exit-code contract shown at the end of `--help`
+const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0    Success; for check, verify and analyze --min-bits, the password passed
+  1    Usage error or any other error
+  2    check, verify or analyze --min-bits found the password failing
+  3    Invalid generator settings, options document or policy file
+  4    No password satisfying the policy could be generated
+  5    An input file cannot be read
+  130  Interrupted at a prompt";
+
+/// Range endpoint of the Pwned Passwords API; the 5-character hash prefix is appended
+const DEFAULT_HIBP_API: &str = "https://api.pwnedpasswords.com/range/";
 
 // ============================================================================
 // Command-Line Arguments
@@ -9,24 +43,186 @@ use crate::utils::DEFAULT_LENGTH;
 ///
 /// Uses the `clap` crate to parse command-line arguments with sensible defaults.
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, after_help = EXIT_CODES_HELP)]
 pub(crate) struct Cli {
-    /// subcommand to execute
+    /// When to use colors: auto (terminal only), always or never
+    #[arg(long, global = true, value_enum, value_name = "WHEN", default_value_t = ColorMode::Auto)]
+    pub(crate) color: ColorMode,
+
+    /// Disable colored output (same as --color never)
+    #[arg(long, global = true, default_value_t = false)]
+    pub(crate) no_color: bool,
+
+    /// Do not print the ASCII art logo
+    #[arg(long, global = true, default_value_t = false)]
+    pub(crate) no_logo: bool,
+
+    /// Language of messages meant for people (default: from LC_ALL, LC_MESSAGES or LANG)
+    #[arg(long, global = true, value_enum, value_name = "LANG")]
+    pub(crate) lang: Option<Locale>,
+
+    /// Font of the ASCII art logo
+    #[arg(long, global = true, value_enum, value_name = "FONT", default_value_t = BannerFont::Standard)]
+    pub(crate) banner_font: BannerFont,
+
+    /// Second line of the ASCII art logo
+    #[arg(long, global = true, value_name = "TEXT", default_value = DEFAULT_BANNER_TEXT)]
+    pub(crate) banner_text: String,
+
+    /// Read defaults from this configuration file instead of the platform default
+    /// (~/.config/rustypass/config.toml on Linux)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub(crate) config: Option<PathBuf>,
+
+    /// Use the generate defaults of this `[profile.NAME]` from the configuration file
+    #[arg(long, global = true, value_name = "NAME")]
+    pub(crate) profile: Option<String>,
+
+    /// Print only the result itself (implies --no-logo); `generate` prints just the password
+    #[arg(short, long, global = true, default_value_t = false)]
+    pub(crate) quiet: bool,
+
+    /// Explain the result on stderr: effective settings and entropy math for `generate`,
+    /// raw detector findings for `analyze`; also enables debug logs (-vv: trace logs)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    pub(crate) verbose: u8,
+
+    /// subcommand to execute; without one, `generate` runs with the options below
     #[command(subcommand)]
-    pub(crate) command: Commands,
+    pub(crate) command: Option<Commands>,
+
+    /// `generate` options accepted without the subcommand, e.g. `-l 24 -u -n`
+    #[command(flatten, next_help_heading = "Generate Options (without a subcommand)")]
+    pub(crate) generate: GenerateArgs,
+}
+
+impl Cli {
+    /// Parses a command line like `Cli::parse_from`, also telling whether it was bare
+    ///
+    /// # Returns
+    /// * `(Cli, bool, ArgMatches)` - The arguments; whether neither a subcommand
+    ///   nor a top-level `generate` option was given (global flags such as
+    ///   `--no-logo` may be); and the matches, to tell given flags from defaults
+    ///
+    /// # Notes
+    /// Top-level `generate` options next to a subcommand (`-l 24 analyze`) are a
+    /// usage error rather than silently ignored. Clap's
+    /// `args_conflicts_with_subcommands` cannot be used for this because it
+    /// also rejects global flags written before the subcommand.
+    pub(crate) fn parse_bare<I, T>(args: I) -> (Cli, bool, ArgMatches)
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let mut command = Cli::command();
+        let matches = command.try_get_matches_from_mut(args).unwrap_or_else(|e| exit_on_error(e));
+        let generate_options = GenerateArgs::command()
+            .get_arguments()
+            .filter(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine))
+            .map(|arg| arg.get_long().map_or_else(|| arg.get_id().to_string(), |long| format!("--{}", long)))
+            .collect::<Vec<String>>();
+
+        if let (Some((name, _)), Some(option)) = (matches.subcommand(), generate_options.first()) {
+            let error = command.error(
+                    ErrorKind::ArgumentConflict,
+                    format!("the subcommand '{}' cannot be used with '{}'; pass generate options after `generate` or without a subcommand", name, option),
+                );
+            exit_on_error(error);
+        }
+        let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| exit_on_error(e));
+        let bare = matches.subcommand().is_none() && generate_options.is_empty();
+        (cli, bare, matches)
+    }
+
+    /// Output format selected for the subcommand
+    pub(crate) fn output_format(&self) -> OutputFormat {
+        match &self.command {
+            Some(Commands::Generate(args)) => args.output,
+            Some(Commands::Analyze(args)) => args.output,
+            Some(Commands::Audit(args)) => args.output,
+            Some(Commands::Check(args)) => args.output,
+            Some(Commands::Entropy(args)) => args.output,
+            Some(Commands::Bench(args)) => args.output,
+            Some(Commands::Doctor(args)) => args.output,
+            Some(
+                Commands::Hash(_)
+                | Commands::Verify(_)
+                | Commands::Config(_)
+                | Commands::History(_)
+                | Commands::Retrieve(_)
+                | Commands::Delete(_)
+                | Commands::ClipboardHolder(_),
+            ) => OutputFormat::Text,
+            // Before `generate` is filled in for a bare invocation
+            None => self.generate.output,
+        }
+    }
+
+    /// Generate options of the selected subcommand, or the top-level ones without a subcommand
+    pub(crate) fn generate_args_mut(&mut self) -> Option<&mut GenerateArgs> {
+        match &mut self.command {
+            Some(Commands::Generate(args)) => Some(args),
+            None => Some(&mut self.generate),
+            _ => None,
+        }
+    }
+
+    /// Whether the selected subcommand produces machine-readable output (JSON, CSV or raw bytes)
+    pub(crate) fn machine_readable(&self) -> bool {
+        let raw = matches!(&self.command, Some(Commands::Generate(args)) if args.raw);
+        raw || self.output_format() != OutputFormat::Text
+    }
 }
 
 // SUBOCOMMANDS ENUM
 #[derive(Subcommand, Debug)]
 pub(crate) enum Commands {
     /// Generate a new random password
-    Generate(GenerateArgs),
+    Generate(Box<GenerateArgs>),
 
     /// Analyze the strength of an existing password
     Analyze(AnalyzeArgs),
+
+    /// Audit a browser-exported credentials CSV (url,username,password)
+    Audit(AuditArgs),
+
+    /// Check a password against a compliance policy (exit code 0 = pass, 2 = fail)
+    Check(CheckArgs),
+
+    /// Show the entropy and crack-time math of a generator setting, without generating anything
+    Entropy(EntropyArgs),
+
+    /// Measure password generation throughput and latency on this machine
+    Bench(BenchArgs),
+
+    /// Hash a password (bcrypt, argon2id, scrypt or PBKDF2) for a config file or credential store
+    Hash(HashArgs),
+
+    /// Check a password against a bcrypt, argon2id, scrypt or PBKDF2 hash (exit code 0 = match, 2 = no match)
+    Verify(VerifyArgs),
+
+    /// Diagnose the terminal, clipboard, random source and features (exit code 1 on failures)
+    Doctor(DoctorArgs),
+
+    /// Manage the configuration file
+    Config(ConfigArgs),
+
+    /// List, show or delete passwords saved with `generate --save`
+    History(HistoryArgs),
+
+    /// Print a password saved with `generate --store NAME` (requires the `keyring` feature)
+    Retrieve(CredentialArgs),
+
+    /// Remove a password saved with `generate --store NAME` (requires the `keyring` feature)
+    Delete(CredentialArgs),
+
+    /// Internal: keeps a copied password on the clipboard and clears it later
+    #[command(name = "clipboard-holder", hide = true)]
+    ClipboardHolder(ClipboardHolderArgs),
 }
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
+#[command(group = clap::ArgGroup::new("handoff").args(["copy", "copy_osc52", "store"]))]
 pub(crate) struct GenerateArgs {
     /// Password length (must be between 8 and 128 characters)
     #[arg(short, long, default_value_t = DEFAULT_LENGTH)]
@@ -43,11 +239,677 @@ pub(crate) struct GenerateArgs {
     /// Include numeric digits (0-9) in the password
     #[arg(short, long, default_value_t = false)]
     pub(crate) numbers: bool,
+
+    /// Number of passwords to generate
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub(crate) count: u32,
+
+    /// Output format (text, json or csv); to write a file, use -O/--output-file
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    pub(crate) output: OutputFormat,
+
+    /// With --output csv, leave out the header row (for appending to an existing file)
+    #[arg(long, default_value_t = false)]
+    pub(crate) no_header: bool,
+
+    /// Write exactly the password bytes: no label, no color, no trailing newline
+    #[arg(long, default_value_t = false, conflicts_with = "output")]
+    pub(crate) raw: bool,
+
+    /// With --raw, separate passwords with NUL instead of newline (for `xargs -0`)
+    #[arg(long = "print0", default_value_t = false, requires = "raw")]
+    pub(crate) print0: bool,
+
+    /// Also draw the password as a QR code on stderr (requires the `qr` feature)
+    #[arg(long, default_value_t = false)]
+    pub(crate) qr: bool,
+
+    /// Draw the QR code for a light terminal background
+    #[arg(long, default_value_t = false, requires = "qr")]
+    pub(crate) qr_invert: bool,
+
+    /// Print each password through a template, e.g. "{password}\t{entropy:.1}" (see the README for placeholders)
+    #[arg(long, value_name = "TEMPLATE", conflicts_with_all = ["output", "raw", "mask", "copy", "copy_osc52"])]
+    pub(crate) format: Option<String>,
+
+    /// Spell the password out with the NATO phonetic alphabet (on stderr)
+    #[arg(long, default_value_t = false, conflicts_with_all = ["raw", "mask", "copy", "copy_osc52"])]
+    pub(crate) spell: bool,
+
+    /// Run the analyzer on each password and print its report after it
+    /// (an `analysis` key with --output json, extra columns with --output csv)
+    #[arg(long, default_value_t = false, conflicts_with_all = ["raw", "mask", "copy", "copy_osc52", "format", "output_file"])]
+    pub(crate) analyze: bool,
+
+    /// Show the password masked (first and last characters only) with its length and strength
+    #[arg(long, default_value_t = false, conflicts_with_all = ["output", "raw"])]
+    pub(crate) mask: bool,
+
+    /// With --mask, reveal the full password right away instead of asking
+    #[arg(long, default_value_t = false, requires = "mask")]
+    pub(crate) reveal: bool,
+
+    /// Write the password(s) to this file (mode 0600 on Unix) instead of stdout; with
+    /// --users-file (and no --export), keep the records there while the chpasswd lines still go to stdout
+    #[arg(short = 'O', long, value_name = "PATH")]
+    pub(crate) output_file: Option<PathBuf>,
+
+    /// Overwrite the --output-file if it already exists
+    #[arg(long, default_value_t = false, requires = "output_file")]
+    pub(crate) force: bool,
+
+    /// Copy the password to the clipboard instead of printing it (falls back to --copy-osc52
+    /// when there is no local clipboard)
+    #[arg(long, default_value_t = false, conflicts_with_all = ["output", "raw", "output_file"])]
+    pub(crate) copy: bool,
+
+    /// Copy the password through the terminal with an OSC 52 escape sequence, which reaches
+    /// the local clipboard over SSH (the terminal must allow it; never cleared automatically)
+    #[arg(long = "copy-osc52", default_value_t = false, conflicts_with_all = ["output", "raw", "output_file"])]
+    pub(crate) copy_osc52: bool,
+
+    /// With --copy, --copy-osc52 or --store, also print the password
+    #[arg(long, default_value_t = false, requires = "handoff")]
+    pub(crate) show: bool,
+
+    /// With --copy, seconds before the clipboard is cleared
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_CLEAR_SECONDS, requires = "copy",
+          value_parser = clap::value_parser!(u64).range(1..))]
+    pub(crate) clear_clipboard: u64,
+
+    /// Also print an htpasswd line `USERNAME:$2y$...` (bcrypt, cost 12) for web server basic auth
+    #[arg(long, value_name = "USERNAME", conflicts_with_all = ["output", "raw", "output_file", "format", "mask", "analyze", "spell", "store", "copy", "copy_osc52"])]
+    pub(crate) htpasswd: Option<String>,
+
+    /// With --htpasswd, hash with Apache's legacy MD5 scheme ($apr1$) for servers without bcrypt
+    #[arg(long, default_value_t = false, requires = "htpasswd")]
+    pub(crate) apr1: bool,
+
+    /// With --htpasswd, write the line into this htpasswd file, replacing USERNAME's entry
+    #[arg(long, value_name = "PATH", requires = "htpasswd")]
+    pub(crate) append: Option<PathBuf>,
+
+    /// Print `NAME='password'` lines for a .env file; repeat it or separate names with commas
+    /// (a single NAME with --count N gives NAME_1 to NAME_N)
+    #[arg(long, value_name = "NAME", value_delimiter = ',',
+          conflicts_with_all = ["output", "raw", "output_file", "format", "mask", "analyze", "spell", "store", "copy", "copy_osc52", "htpasswd"])]
+    pub(crate) env_key: Vec<String>,
+
+    /// With --env-key, write the lines into this dotenv file, replacing keys it already has
+    #[arg(long, value_name = "PATH", requires = "env_key")]
+    pub(crate) append_env: Option<PathBuf>,
+
+    /// Generate a password for each username in this file (one per line; blank lines and
+    /// # comments are skipped) and print `username:password` lines for chpasswd
+    #[arg(long, value_name = "PATH",
+          conflicts_with_all = ["count", "raw", "format", "mask", "analyze", "spell", "qr", "store", "copy", "copy_osc52", "htpasswd", "env_key"])]
+    pub(crate) users_file: Option<PathBuf>,
+
+    /// With --users-file, print `username:hash` lines for `chpasswd -e` instead
+    #[arg(long, value_enum, value_name = "ALG", requires = "users_file")]
+    pub(crate) hash_alg: Option<ChpasswdHash>,
+
+    /// Write the password(s) in a password manager's import format (to -O/--output-file, or
+    /// stdout); with --users-file, each entry gets its user
+    #[arg(long, value_enum, value_name = "FORMAT",
+          conflicts_with_all = ["output", "no_header", "raw", "format", "mask", "analyze", "spell", "qr", "store", "copy", "copy_osc52", "htpasswd", "env_key", "hash_alg"])]
+    pub(crate) export: Option<ExportFormat>,
+
+    /// With --export, the start of every entry's title (`PREFIX 1`.. or `PREFIX alice` with --users-file)
+    #[arg(long, value_name = "TEXT", requires = "export")]
+    pub(crate) title_prefix: Option<String>,
+
+    /// With --export, the website every entry is for
+    #[arg(long, value_name = "URL", requires = "export")]
+    pub(crate) url: Option<String>,
+
+    /// Save the password in the OS credential store as `rustypass/NAME` instead of printing it
+    /// (requires the `keyring` feature; read it back with `retrieve NAME`)
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["output", "raw", "output_file", "format", "mask", "analyze", "spell"])]
+    pub(crate) store: Option<String>,
+
+    /// Also save the password(s), encrypted with a master passphrase, to the history
+    /// (asks for the passphrase; see the `history` command)
+    #[arg(long, default_value_t = false, requires = "label")]
+    pub(crate) save: bool,
+
+    /// With --save, what the password is for, e.g. "example.com"
+    #[arg(long, value_name = "TEXT", requires = "save")]
+    pub(crate) label: Option<String>,
+
+    /// Generate a password compliant with a built-in policy
+    #[arg(long, value_enum, conflicts_with = "policy_file")]
+    pub(crate) policy: Option<PolicyPreset>,
+
+    /// Generate a password compliant with a policy loaded from a TOML file
+    #[arg(long, value_name = "PATH")]
+    pub(crate) policy_file: Option<PathBuf>,
+
+    /// Load generator options from a JSON document; flags given here apply on top
+    #[arg(long, value_name = "PATH")]
+    pub(crate) options_file: Option<PathBuf>,
+
+    /// Draw from a ChaCha20 RNG seeded with N, so the same options and seed always give the
+    /// same passwords. FOR TESTS ONLY: anyone who knows the seed knows every password
+    #[arg(long, value_name = "N",
+          conflicts_with_all = ["store", "save", "copy", "copy_osc52", "htpasswd", "env_key", "users_file", "export"])]
+    pub(crate) seed: Option<u64>,
+
+    /// Contents of --options-file, loaded before the configuration is applied
+    #[arg(skip)]
+    pub(crate) options_document: Option<GeneratorOptions>,
+}
+
+impl From<&GenerateArgs> for GeneratorOptions {
+    /// Maps the generate flags onto generator options, starting from the options
+    /// document if one was loaded; otherwise lowercase letters are always included.
+    /// The length is passed on as layered, so one from an options document is
+    /// rejected by the generator rather than replaced.
+    fn from(args: &GenerateArgs) -> Self {
+        GeneratorOptions {
+            length: args.length,
+            include_uppercase: args.uppercase_chars,
+            include_digits: args.numbers,
+            include_symbols: args.special_chars,
+            ..args.options_document.clone().unwrap_or_default()
+        }
+    }
+}
+
+/// Prints a clap error and exits
+///
+/// Help and version requests exit with 0 as usual; usage errors exit with
+/// `EXIT_USAGE` instead of clap's 2, which is reserved for failed checks.
+fn exit_on_error(error: clap::Error) -> ! {
+    if !error.use_stderr() {
+        error.exit();
+    }
+    let _ = error.print();
+    std::process::exit(EXIT_USAGE);
+}
+
+/// Parses `--min-bits`: a finite, non-negative number of bits
+fn parse_bits(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(bits) if bits.is_finite() && bits >= 0.0 => Ok(bits),
+        _ => Err(format!("'{}' is not a number of bits (expected e.g. 70 or 62.5)", value)),
+    }
+}
+
+/// Validates and sets the password length within acceptable bounds
+///
+/// # Arguments
+/// * `args_length` - Length requested on the command line
+///
+/// # Returns
+/// * `u32` - Validated password length (defaults to DEFAULT_LENGTH if out of bounds)
+///
+/// # Notes
+/// If the requested length is outside the range [MIN_LENGTH, MAX_LENGTH],
+/// a warning is printed on stderr and DEFAULT_LENGTH is used instead. This
+/// keeps a literal `-l` forgiving; lengths from anywhere else reach
+/// `PasswordGenerator::new`, which rejects them with `RustyPassError::InvalidLength`.
+pub(crate) fn set_length(args_length: u32) -> u32 {
+    let mut length = args_length;
+
+    // Check if length is within valid bounds. If not, reset to default.
+    if !(utils::MIN_LENGTH..=utils::MAX_LENGTH).contains(&args_length) {
+        output::warn(&i18n::trf(
+            Msg::LengthOutOfRange,
+            &[&args_length, &utils::MIN_LENGTH, &utils::MAX_LENGTH, &utils::DEFAULT_LENGTH],
+        ));
+        length = utils::DEFAULT_LENGTH;
+    }
+
+    length
+}
+
+#[derive(Parser, Debug)]
+#[command(after_help = "Exit codes: 0 = compliant, 2 = not compliant, 1 = error.")]
+pub(crate) struct CheckArgs {
+    /// password to check; omit it to be prompted without echo
+    #[arg(conflicts_with_all = ["stdin", "password_env", "confirm"])]
+    pub(crate) password: Option<String>,
+
+    #[command(flatten)]
+    pub(crate) secret: SecretInputArgs,
+
+    /// Built-in policy to check against
+    #[arg(long, value_enum, required_unless_present = "policy_file", conflicts_with = "policy_file")]
+    pub(crate) policy: Option<PolicyPreset>,
+
+    /// Policy loaded from a TOML file
+    #[arg(long, value_name = "PATH")]
+    pub(crate) policy_file: Option<PathBuf>,
+
+    /// Output format of the compliance report (text or json)
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    pub(crate) output: OutputFormat,
+}
+
+#[derive(Parser, Debug)]
+#[command(after_help = "Without --words or --pattern the keyspace is a character set, as for `generate`. \
+Pattern letters: l = lowercase, u = uppercase, n = digit, s = symbol; anything else is a literal.")]
+pub(crate) struct EntropyArgs {
+    /// Password length of the character-set keyspace
+    #[arg(short, long, default_value_t = DEFAULT_LENGTH, value_parser = clap::value_parser!(u32).range(1..))]
+    pub(crate) length: u32,
+
+    /// Include uppercase characters (A-Z) in the character set
+    #[arg(short, long, default_value_t = false)]
+    pub(crate) uppercase_chars: bool,
+
+    /// Include special characters (!@#$%^&*_-+=<>?) in the character set
+    #[arg(short, long, default_value_t = false)]
+    pub(crate) special_chars: bool,
+
+    /// Include numeric digits (0-9) in the character set
+    #[arg(short, long, default_value_t = false)]
+    pub(crate) numbers: bool,
+
+    /// Number of words of a passphrase keyspace
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with_all = ["length", "uppercase_chars", "special_chars", "numbers", "pattern"])]
+    pub(crate) words: Option<u32>,
+
+    /// Word list the passphrase words are drawn from
+    #[arg(long, value_enum, default_value_t = Wordlist::Eff, requires = "words")]
+    pub(crate) wordlist_name: Wordlist,
+
+    /// Template keyspace such as uuu-nnnn
+    #[arg(long, conflicts_with_all = ["length", "uppercase_chars", "special_chars", "numbers"])]
+    pub(crate) pattern: Option<String>,
+
+    /// Output format (text or json)
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    pub(crate) output: OutputFormat,
 }
 
 #[derive(Parser, Debug)]
+#[command(after_help = "Cases: 16 lowercase characters (the default), 32 characters from all classes, and 16 \
+characters from all classes under the NIST policy. Passphrases and patterns are not benchmarked, as `generate` \
+does not produce them. Throughput counts the output bytes `generate` would print, one newline per password included.")]
+pub(crate) struct BenchArgs {
+    /// How long each case generates (e.g. 3s, 500ms, 1m)
+    #[arg(long, value_name = "DURATION", default_value = "3s", value_parser = parse_bench_duration)]
+    pub(crate) duration: std::time::Duration,
+
+    /// Generators running side by side, each on its own thread
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub(crate) threads: u32,
+
+    /// Output format (text or json)
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    pub(crate) output: OutputFormat,
+}
+
+/// Parses `bench --duration`, which must not be zero
+fn parse_bench_duration(value: &str) -> Result<std::time::Duration, String> {
+    match humantime::parse_duration(value).map_err(|e| e.to_string())? {
+        duration if duration.is_zero() => Err(String::from("the duration must be longer than zero")),
+        duration => Ok(duration),
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(after_help = "Defaults follow the OWASP Password Storage Cheat Sheet: argon2id with 19 MiB, 2 iterations \
+and 1 lane; bcrypt cost 12; scrypt N = 2^17, r = 8, p = 1; PBKDF2-HMAC-SHA256 with 600000 iterations. \
+The password is read like `analyze` reads it: --stdin, --password-env or a prompt without echo.")]
+#[command(group = clap::ArgGroup::new("generate_options")
+    .args(["length", "uppercase_chars", "special_chars", "numbers"])
+    .multiple(true)
+    .requires("generate")
+    .conflicts_with_all(["stdin", "password_env", "confirm"]))]
+pub(crate) struct HashArgs {
+    /// Hash algorithm
+    #[arg(long, value_enum, default_value_t = HashAlgorithm::Argon2id)]
+    pub(crate) alg: HashAlgorithm,
+
+    /// Work factor: bcrypt cost (4-31, default 12) or scrypt log2(N) (10-24, default 17)
+    #[arg(long)]
+    pub(crate) cost: Option<u32>,
+
+    /// argon2id memory in KiB, or with a k, m or g suffix (default 19m)
+    #[arg(long, value_name = "SIZE", value_parser = parse_memory)]
+    pub(crate) memory: Option<u32>,
+
+    /// argon2id passes (default 2) or PBKDF2 iterations (default 600000)
+    #[arg(long)]
+    pub(crate) iterations: Option<u32>,
+
+    /// argon2id lanes or scrypt p (default 1)
+    #[arg(long)]
+    pub(crate) parallelism: Option<u32>,
+
+    /// Generate a new password and print it (unless --quiet) followed by its hash
+    #[arg(long, default_value_t = false, conflicts_with_all = ["stdin", "password_env", "confirm"])]
+    pub(crate) generate: bool,
+
+    /// With --generate: password length (must be between 8 and 128 characters)
+    #[arg(short, long, default_value_t = DEFAULT_LENGTH)]
+    pub(crate) length: u32,
+
+    /// With --generate: include uppercase characters (A-Z)
+    #[arg(short, long, default_value_t = false)]
+    pub(crate) uppercase_chars: bool,
+
+    /// With --generate: include special characters (!@#$%^&*_-+=<>?)
+    #[arg(short, long, default_value_t = false)]
+    pub(crate) special_chars: bool,
+
+    /// With --generate: include numeric digits (0-9)
+    #[arg(short, long, default_value_t = false)]
+    pub(crate) numbers: bool,
+
+    /// Print an htpasswd line `USERNAME:$2y$...` instead of the bare hash (bcrypt; --cost applies)
+    #[arg(long, value_name = "USERNAME", conflicts_with = "alg")]
+    pub(crate) htpasswd: Option<String>,
+
+    /// With --htpasswd, hash with Apache's legacy MD5 scheme ($apr1$) for servers without bcrypt
+    #[arg(long, default_value_t = false, requires = "htpasswd", conflicts_with_all = ["cost", "memory", "iterations", "parallelism"])]
+    pub(crate) apr1: bool,
+
+    /// With --htpasswd, write the line into this htpasswd file, replacing USERNAME's entry
+    #[arg(long, value_name = "PATH", requires = "htpasswd")]
+    pub(crate) append: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub(crate) secret: SecretInputArgs,
+}
+
+impl HashArgs {
+    /// The cost options as given, for `HashParams::resolve`
+    pub(crate) fn cost_options(&self) -> CostOptions {
+        CostOptions { cost: self.cost, memory_kib: self.memory, iterations: self.iterations, parallelism: self.parallelism }
+    }
+}
+
+impl From<&HashArgs> for GeneratorOptions {
+    /// Maps the `hash --generate` flags onto generator options, like `generate` does
+    fn from(args: &HashArgs) -> Self {
+        GeneratorOptions {
+            length: set_length(args.length),
+            include_uppercase: args.uppercase_chars,
+            include_digits: args.numbers,
+            include_symbols: args.special_chars,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(after_help = "The format is detected from the hash's prefix: $2b$ (also $2a$, $2x$, $2y$), $argon2id$, \
+$scrypt$ or $pbkdf2-sha256$. Put the hash in single quotes so the shell leaves its $ signs alone. \
+Exit codes: 0 = match, 2 = no match, 1 = malformed hash or error.")]
+pub(crate) struct VerifyArgs {
+    /// hash to check the password against, e.g. '$argon2id$v=19$m=19456,t=2,p=1$...'
+    pub(crate) hash: String,
+
+    #[command(flatten)]
+    pub(crate) secret: SecretInputArgs,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct DoctorArgs {
+    /// Output format of the diagnostics (text or json)
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    pub(crate) output: OutputFormat,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct ConfigArgs {
+    #[command(subcommand)]
+    pub(crate) action: ConfigAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum ConfigAction {
+    /// Write a commented configuration template (to --config PATH or the platform default)
+    Init(ConfigInitArgs),
+
+    /// Print every profile with the generate settings it results in
+    ListProfiles,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct ConfigInitArgs {
+    /// Overwrite the configuration file if it already exists
+    #[arg(long, default_value_t = false)]
+    pub(crate) force: bool,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct HistoryArgs {
+    #[command(subcommand)]
+    pub(crate) action: HistoryAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum HistoryAction {
+    /// List the labels and dates of saved passwords (no passphrase needed)
+    List,
+
+    /// Decrypt and print the passwords saved under a label (asks for the master passphrase)
+    Show(HistoryShowArgs),
+
+    /// Delete the history and every password in it
+    Purge(HistoryPurgeArgs),
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct HistoryShowArgs {
+    /// Label given to `generate --save --label`
+    pub(crate) label: String,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct HistoryPurgeArgs {
+    /// Confirm the deletion; nothing is deleted without it
+    #[arg(long, default_value_t = false)]
+    pub(crate) yes: bool,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct CredentialArgs {
+    /// Name given to `generate --store`
+    pub(crate) name: String,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct ClipboardHolderArgs {
+    /// Seconds before the clipboard is cleared
+    #[arg(long)]
+    pub(crate) clear_after: u64,
+}
+
+/// Alternative sources for a secret, shared by every subcommand that reads one
+///
+/// Sources are mutually exclusive; when none is given the user is prompted
+/// without echo (only if stdin is a terminal).
+#[derive(Args, Debug)]
+pub(crate) struct SecretInputArgs {
+    /// Read the password from stdin (at most one trailing newline is stripped)
+    #[arg(long, alias = "from-stdin", default_value_t = false, conflicts_with = "password_env")]
+    pub(crate) stdin: bool,
+
+    /// Read the password from this environment variable, then remove it from the environment
+    #[arg(long, value_name = "VAR")]
+    pub(crate) password_env: Option<String>,
+
+    /// Ask for the password twice when prompting, to catch typos
+    #[arg(long, default_value_t = false, conflicts_with_all = ["stdin", "password_env"])]
+    pub(crate) confirm: bool,
+}
+
+/// Breach checks against the Have I Been Pwned range API, shared by `analyze --file` and `audit`
+///
+/// Only the first five hex characters of each password's SHA-1 hash are sent.
+#[derive(Args, Debug)]
+pub(crate) struct HibpArgs {
+    /// Look every password up in the Have I Been Pwned breach corpus (needs the `network` feature)
+    #[arg(long, default_value_t = false, requires = "file")]
+    pub(crate) hibp: bool,
+
+    /// How long downloaded ranges stay in the on-disk cache (e.g. 24h, 7d; 0 disables the cache)
+    #[arg(long, value_name = "DURATION", default_value = "24h", value_parser = humantime::parse_duration, requires = "hibp")]
+    pub(crate) hibp_cache_ttl: std::time::Duration,
+
+    /// Range endpoint to query, for a mirror or a local copy of the corpus
+    #[arg(long, value_name = "URL", default_value = DEFAULT_HIBP_API, requires = "hibp")]
+    pub(crate) hibp_api: String,
+}
+
+#[derive(Parser, Debug)]
+#[command(after_help = "Password sources: a positional PASSWORD, --stdin and --password-env are mutually \
+exclusive. When none is given, the password is prompted for without echo if stdin is a terminal.
+
+Exit codes: 0 = analyzed (and at least --min-bits), 2 = below --min-bits, 1 = usage or other error.
+For a pre-commit hook or CI gate: printf '%s' \"$SECRET\" | rusty_password_utility analyze --stdin --min-bits 70 --check")]
+#[command(group = clap::ArgGroup::new("batch").args(["file", "stream"]))]
 pub(crate) struct AnalyzeArgs {
-    /// password to analyze
-    #[arg(required = true)]
-    pub(crate) password: String,
-}
\ No newline at end of file
+    /// password to analyze; omit it to be prompted without echo (arguments are visible in `ps` and shell history)
+    #[arg(conflicts_with_all = ["stdin", "password_env", "confirm"])]
+    pub(crate) password: Option<String>,
+
+    #[command(flatten)]
+    pub(crate) secret: SecretInputArgs,
+
+    /// Analyze every line of a file instead of a single password
+    #[arg(short, long, conflicts_with_all = ["password", "stdin", "password_env", "confirm"])]
+    pub(crate) file: Option<PathBuf>,
+
+    /// Analyze every line of stdin as it arrives, printing each result at once (memory stays
+    /// flat for inputs of any size)
+    #[arg(long, default_value_t = false,
+          conflicts_with_all = ["password", "file", "stdin", "password_env", "confirm", "find_reuse", "report"])]
+    pub(crate) stream: bool,
+
+    /// With --stream, print only the summary (grades, average entropy, ten weakest entries)
+    #[arg(long, default_value_t = false, requires = "stream")]
+    pub(crate) summary_only: bool,
+
+    /// Minimum effective entropy in bits; a weaker password makes analyze exit with status 2
+    #[arg(long, value_name = "BITS", value_parser = parse_bits, conflicts_with_all = ["file", "stream", "token"])]
+    pub(crate) min_bits: Option<f64>,
+
+    /// With --min-bits, print nothing when the password passes and one line on stderr when it fails
+    #[arg(long, default_value_t = false, requires = "min_bits", conflicts_with_all = ["output", "report"])]
+    pub(crate) check: bool,
+
+    /// Treat the input as an API key or token: detect its encoding and skip dictionary checks
+    #[arg(long, default_value_t = false, conflicts_with_all = ["file", "stream", "report"])]
+    pub(crate) token: bool,
+
+    /// Show passwords in clear text in batch results instead of masking them
+    #[arg(long, default_value_t = false, requires = "batch")]
+    pub(crate) show_passwords: bool,
+
+    /// Group identical and near-duplicate passwords in the file
+    #[arg(long, default_value_t = false, requires = "file")]
+    pub(crate) find_reuse: bool,
+
+    #[command(flatten)]
+    pub(crate) hibp: HibpArgs,
+
+    /// Output format of the analysis report (csv requires --file or --stream; with --stream,
+    /// json prints one object per line followed by the summary)
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    pub(crate) output: OutputFormat,
+
+    /// With --output csv, leave out the header row (for appending to an existing file)
+    #[arg(long, default_value_t = false, requires = "batch")]
+    pub(crate) no_header: bool,
+
+    /// Render a Markdown or HTML report instead of the regular output
+    #[arg(long, value_name = "FORMAT", conflicts_with = "output")]
+    pub(crate) report: Option<ReportFormat>,
+
+    /// Write the report to this file (mode 0600 on Unix) instead of stdout
+    #[arg(long, value_name = "PATH", requires = "report")]
+    pub(crate) report_file: Option<PathBuf>,
+
+    /// Overwrite the --report-file if it already exists
+    #[arg(long, default_value_t = false, requires = "report_file")]
+    pub(crate) force: bool,
+
+    /// Width of the strength meter bar
+    #[arg(long, default_value_t = DEFAULT_METER_WIDTH)]
+    pub(crate) meter_width: usize,
+
+    /// Minimum effective entropy bits for grades A,B,C,D (strictly descending)
+    #[arg(long, value_name = "A,B,C,D", default_value = "90,70,55,40")]
+    pub(crate) grade_thresholds: GradeThresholds,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct AuditArgs {
+    /// CSV file exported from a browser or password manager
+    pub(crate) file: PathBuf,
+
+    /// Password column, by header name or 1-based number (auto-detected by default)
+    #[arg(long)]
+    pub(crate) password_col: Option<String>,
+
+    /// Username column, by header name or 1-based number (auto-detected by default)
+    #[arg(long)]
+    pub(crate) username_col: Option<String>,
+
+    /// Output format of the audit report (text or json)
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    pub(crate) output: OutputFormat,
+
+    /// Render a Markdown or HTML report instead of the regular output
+    #[arg(long, value_name = "FORMAT", conflicts_with = "output")]
+    pub(crate) report: Option<ReportFormat>,
+
+    /// Write the report to this file (mode 0600 on Unix) instead of stdout
+    #[arg(long, value_name = "PATH", requires = "report")]
+    pub(crate) report_file: Option<PathBuf>,
+
+    /// Overwrite the --report-file if it already exists
+    #[arg(long, default_value_t = false, requires = "report_file")]
+    pub(crate) force: bool,
+
+    /// Show passwords in clear text instead of masking them
+    #[arg(long, default_value_t = false)]
+    pub(crate) show_passwords: bool,
+
+    #[command(flatten)]
+    pub(crate) hibp: HibpArgs,
+
+    /// Minimum effective entropy bits for grades A,B,C,D (strictly descending)
+    #[arg(long, value_name = "A,B,C,D", default_value = "90,70,55,40")]
+    pub(crate) grade_thresholds: GradeThresholds,
+}
+
+/// Output formats supported by the subcommands
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    /// Human-readable text
+    Text,
+    /// Pretty-printed JSON document
+    Json,
+    /// RFC 4180 CSV with a header row
+    Csv,
+}
+
+/// Document formats for shareable reports
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ReportFormat {
+    /// GitHub-flavored Markdown
+    Md,
+    /// Single HTML file with inline CSS
+    Html,
+}
+
+/// Color modes selectable with `--color`
+#[derive(ValueEnum, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ColorMode {
+    /// Colors on a terminal, unless NO_COLOR is set or CLICOLOR_FORCE forces them
+    Auto,
+    /// Always use colors
+    Always,
+    /// Never use colors
+    Never,
+}
@@ -0,0 +1,234 @@
+use std::io::Write;
+use std::time::Duration;
+#[cfg(feature = "clipboard")]
+use std::io::{self, BufRead, BufReader, Read};
+#[cfg(feature = "clipboard")]
+use std::process::{Command, Stdio};
+use base64::Engine;
+use zeroize::Zeroizing;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Default number of seconds before a copied password is cleared
+pub(crate) const DEFAULT_CLEAR_SECONDS: u64 = 45;
+
+/// Hidden subcommand that owns the clipboard in the background
+#[cfg(feature = "clipboard")]
+pub(crate) const HOLDER_SUBCOMMAND: &str = "clipboard-holder";
+
+/// Line the holder prints once the clipboard has been set
+#[cfg(feature = "clipboard")]
+const READY: &str = "ok";
+
+/// Error reported by `copy_with_clear` when the binary was built without the `clipboard` feature
+#[cfg(not(feature = "clipboard"))]
+const NOT_COMPILED_IN: &str = "system clipboard support is not compiled in; rebuild with `--features clipboard`";
+
+/// Largest base64 payload sent in one OSC 52 sequence
+///
+/// The sequence has no formal limit, but terminals cap it (hterm at 100000
+/// bytes, tmux and others lower); 74994 is the limit most clients settled on.
+const MAX_OSC52_PAYLOAD: usize = 74_994;
+
+/// Controlling terminal, written to directly so redirected stdout and stderr stay clean
+#[cfg(unix)]
+const TERMINAL_DEVICE: &str = "/dev/tty";
+#[cfg(windows)]
+const TERMINAL_DEVICE: &str = "CONOUT$";
+
+// ============================================================================
+// Clipboard Copy
+// ============================================================================
+
+/// Copies a secret to the system clipboard and clears it after a delay
+///
+/// # Arguments
+/// * `secret` - Value to copy
+/// * `clear_after` - Delay before the clipboard is cleared
+///
+/// # Returns
+/// * `Result<(), String>` - An error message when no clipboard is available
+///
+/// # Notes
+/// The clipboard is owned by a detached copy of this program (see
+/// `run_holder`), because on X11 and Wayland the content disappears with the
+/// process that set it, and the countdown must outlive this process anyway.
+/// The secret is handed over through a pipe, never on the command line.
+#[cfg(feature = "clipboard")]
+pub(crate) fn copy_with_clear(secret: &str, clear_after: Duration) -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("cannot locate executable: {}", e))?;
+    let mut holder = Command::new(exe)
+        .args(["--no-logo", HOLDER_SUBCOMMAND, "--clear-after", &clear_after.as_secs().to_string()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("cannot start clipboard holder: {}", e))?;
+
+    {
+        // Dropping stdin closes the pipe, which tells the holder the secret is complete
+        let mut stdin = holder.stdin.take().expect("stdin is piped");
+        stdin
+            .write_all(secret.as_bytes())
+            .map_err(|e| format!("cannot hand over password: {}", e))?;
+    }
+
+    // Wait until the holder reports that the clipboard is set (or why it is not)
+    let mut status = String::new();
+    let stdout = holder.stdout.take().expect("stdout is piped");
+    BufReader::new(stdout)
+        .read_line(&mut status)
+        .map_err(|e| format!("clipboard holder failed: {}", e))?;
+
+    match status.trim_end() {
+        READY => Ok(()),
+        "" => Err(String::from("clipboard holder exited unexpectedly")),
+        error => Err(error.to_string()),
+    }
+}
+
+/// Fallback when the binary was built without the `clipboard` feature
+#[cfg(not(feature = "clipboard"))]
+pub(crate) fn copy_with_clear(_secret: &str, _clear_after: Duration) -> Result<(), String> {
+    Err(String::from(NOT_COMPILED_IN))
+}
+
+/// Entry point of the hidden holder process
+///
+/// Reads the secret from stdin, puts it on the clipboard, reports readiness on
+/// stdout, waits `clear_after`, then clears the clipboard only if it still
+/// holds our value, so anything the user copied since is left alone.
+#[cfg(feature = "clipboard")]
+pub(crate) fn run_holder(clear_after: Duration) {
+    let mut secret = Zeroizing::new(String::new());
+    if io::stdin().read_to_string(&mut secret).is_err() {
+        println!("cannot read password from parent process");
+        return;
+    }
+
+    let mut clipboard = match arboard::Clipboard::new() {
+        Ok(clipboard) => clipboard,
+        Err(e) => {
+            println!("no clipboard available: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = clipboard.set_text(secret.as_str()) {
+        println!("cannot copy to clipboard: {}", e);
+        return;
+    }
+
+    println!("{}", READY);
+    let _ = io::stdout().flush();
+
+    std::thread::sleep(clear_after);
+    let current = clipboard.get_text().map(Zeroizing::new);
+    if current.is_ok_and(|current| *current == *secret) {
+        let _ = clipboard.clear();
+    }
+}
+
+/// Fallback when the binary was built without the `clipboard` feature
+#[cfg(not(feature = "clipboard"))]
+pub(crate) fn run_holder(_clear_after: Duration) {
+    println!("{}", NOT_COMPILED_IN);
+}
+
+// ============================================================================
+// OSC 52
+// ============================================================================
+
+/// Copies a secret through the terminal with an OSC 52 escape sequence
+///
+/// # Arguments
+/// * `secret` - Value to copy
+///
+/// # Returns
+/// * `Result<(), String>` - An error message when the secret is too large or
+///   there is no controlling terminal to write to
+///
+/// # Notes
+/// The terminal emulator, not this host, owns the clipboard, so this works
+/// over SSH with terminals that allow it (iTerm2, kitty, WezTerm, xterm with
+/// `allowWindowOps`, ...). Whether the terminal accepted the sequence cannot
+/// be detected, and the clipboard is not cleared afterwards.
+pub(crate) fn copy_osc52(secret: &str) -> Result<(), String> {
+    let sequence = osc52_sequence(secret, std::env::var_os("TMUX").is_some())?;
+    let mut terminal = std::fs::OpenOptions::new()
+        .write(true)
+        .open(TERMINAL_DEVICE)
+        .map_err(|e| format!("cannot open the terminal for OSC 52: {}", e))?;
+    terminal
+        .write_all(sequence.as_bytes())
+        .and_then(|_| terminal.flush())
+        .map_err(|e| format!("cannot write to the terminal: {}", e))
+}
+
+/// Builds the OSC 52 sequence that sets the clipboard (`c`) to `secret`
+///
+/// # Arguments
+/// * `secret` - Value to copy
+/// * `tmux` - Wrap the sequence in a tmux passthrough (`ESC P tmux; ... ESC \`),
+///   doubling the escape characters inside it, so tmux forwards it to the outer terminal
+///
+/// # Returns
+/// * `Result<Zeroizing<String>, String>` - The sequence, or an error when the
+///   encoded secret exceeds `MAX_OSC52_PAYLOAD`
+fn osc52_sequence(secret: &str, tmux: bool) -> Result<Zeroizing<String>, String> {
+    let payload = Zeroizing::new(base64::engine::general_purpose::STANDARD.encode(secret.as_bytes()));
+    if payload.len() > MAX_OSC52_PAYLOAD {
+        return Err(format!(
+            "the password is too large for OSC 52 ({} encoded bytes, at most {})",
+            payload.len(),
+            MAX_OSC52_PAYLOAD
+        ));
+    }
+
+    let sequence = if tmux {
+        format!("\x1bPtmux;\x1b\x1b]52;c;{}\x07\x1b\\", payload.as_str())
+    } else {
+        format!("\x1b]52;c;{}\x07", payload.as_str())
+    };
+    Ok(Zeroizing::new(sequence))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osc52_sequence_is_exact() {
+        assert_eq!(osc52_sequence("hunter2", false).unwrap().as_str(), "\x1b]52;c;aHVudGVyMg==\x07");
+        assert_eq!(osc52_sequence("", false).unwrap().as_str(), "\x1b]52;c;\x07");
+    }
+
+    #[test]
+    fn osc52_sequence_is_wrapped_for_tmux() {
+        assert_eq!(
+            osc52_sequence("hunter2", true).unwrap().as_str(),
+            "\x1bPtmux;\x1b\x1b]52;c;aHVudGVyMg==\x07\x1b\\"
+        );
+    }
+
+    #[test]
+    fn osc52_payload_is_limited() {
+        // Base64 grows in steps of four, so 56244 bytes encode to 74992 and one more byte to 74996
+        let largest = "a".repeat(56_244);
+        let sequence = osc52_sequence(&largest, false).unwrap();
+        assert_eq!(sequence.len(), "\x1b]52;c;\x07".len() + 74_992);
+
+        let too_large = "a".repeat(56_245);
+        for tmux in [false, true] {
+            assert_eq!(
+                osc52_sequence(&too_large, tmux).unwrap_err(),
+                "the password is too large for OSC 52 (74996 encoded bytes, at most 74994)"
+            );
+        }
+    }
+}
@@ -0,0 +1,818 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, ValueEnum};
+use serde::{Deserialize, Deserializer};
+use crate::analyzer::GradeThresholds;
+use crate::cli::{self, Cli, ColorMode, Commands, OutputFormat};
+use crate::i18n::{self, Locale};
+use crate::output;
+use crate::policy::PolicyPreset;
+use crate::utils;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Directory name under the platform configuration directory
+pub(crate) const APP_NAME: &str = "rustypass";
+
+/// File name of the configuration file
+const CONFIG_FILE: &str = "config.toml";
+
+/// Values accepted for boolean environment variables, true ones first
+const ENV_TRUE: [&str; 4] = ["1", "true", "yes", "on"];
+const ENV_FALSE: [&str; 4] = ["0", "false", "no", "off"];
+
+/// Commented template written by `config init`; every key is at its built-in default
+pub(crate) const TEMPLATE: &str = r#"# Configuration for rusty_password_utility
+#
+# Command-line flags always take precedence over these values, and so do
+# RUSTYPASS_* environment variables (RUSTYPASS_LENGTH, RUSTYPASS_PROFILE, ...).
+# For `color` and `lang`, NO_COLOR, CLICOLOR_FORCE, LC_ALL, LC_MESSAGES and
+# LANG do too. Uncomment a line to change its default.
+
+# When to use colors: "auto", "always" or "never"
+# color = "auto"
+
+# Skip the ASCII art logo
+# no_logo = false
+
+# Language of messages meant for people: "en" or "de"
+# lang = "en"
+
+# What a run without a subcommand or generate options does: "wizard" (on a
+# terminal, when built with the wizard feature; otherwise generate),
+# "generate" or "help"
+# default_command = "wizard"
+
+# Profile used when --profile is not given
+# default_profile = "work"
+
+[generate]
+# Password length (8-128)
+# length = 16
+
+# Character classes added to lowercase letters
+# uppercase_chars = false
+# special_chars = false
+# numbers = false
+
+# Built-in policy every password must satisfy: "nist", "pci" or "ad"
+# policy = "nist"
+
+[analyze]
+# Minimum effective entropy bits for grades A,B,C,D (also used by `audit`)
+# grade_thresholds = "90,70,55,40"
+
+# Named sets of [generate] values, selected with --profile NAME. A profile
+# overrides [generate]; command-line flags override both.
+# [profile.work]
+# policy = "ad"
+# length = 20
+#
+# [profile.banking]
+# length = 32
+# uppercase_chars = true
+# numbers = true
+"#;
+
+// ============================================================================
+// Configuration File
+// ============================================================================
+
+/// What a run without a subcommand or generate options does
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum DefaultCommand {
+    /// Start the setup wizard on a terminal when built with it, otherwise generate
+    #[default]
+    Wizard,
+    /// Always generate a password
+    Generate,
+    /// Print the usage and exit with status 2
+    Help,
+}
+
+impl DefaultCommand {
+    fn name(&self) -> &'static str {
+        match self {
+            DefaultCommand::Wizard => "wizard",
+            DefaultCommand::Generate => "generate",
+            DefaultCommand::Help => "help",
+        }
+    }
+}
+
+/// Defaults read from the configuration file; absent keys are `None`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct Config {
+    pub(crate) color: Option<ColorMode>,
+    pub(crate) no_logo: Option<bool>,
+    pub(crate) lang: Option<Locale>,
+    pub(crate) default_command: Option<DefaultCommand>,
+    pub(crate) default_profile: Option<String>,
+    #[serde(default)]
+    pub(crate) generate: GenerateConfig,
+    #[serde(default)]
+    pub(crate) analyze: AnalyzeConfig,
+    /// `[profile.NAME]` tables, in name order
+    #[serde(default)]
+    pub(crate) profile: BTreeMap<String, GenerateConfig>,
+}
+
+/// `[generate]` table, and the shape of every `[profile.NAME]` table
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct GenerateConfig {
+    #[serde(default, deserialize_with = "deserialize_length")]
+    pub(crate) length: Option<u32>,
+    pub(crate) uppercase_chars: Option<bool>,
+    pub(crate) special_chars: Option<bool>,
+    pub(crate) numbers: Option<bool>,
+    pub(crate) policy: Option<PolicyPreset>,
+}
+
+/// `[analyze]` table
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct AnalyzeConfig {
+    #[serde(default, deserialize_with = "deserialize_from_str")]
+    pub(crate) grade_thresholds: Option<GradeThresholds>,
+}
+
+/// A configuration file as found on disk
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LoadedConfig {
+    /// File that was looked for; `None` when the platform has no configuration directory
+    pub(crate) path: Option<PathBuf>,
+    /// Whether the file existed and was read
+    pub(crate) found: bool,
+    pub(crate) config: Config,
+    /// Dotted paths of keys that were not recognized, e.g. `generate.lenght`
+    pub(crate) unknown_keys: Vec<String>,
+}
+
+/// Default location of the configuration file on this platform
+///
+/// `~/.config/rustypass/config.toml` on Linux (honoring `XDG_CONFIG_HOME`),
+/// `~/Library/Application Support/rustypass/config.toml` on macOS and
+/// `%APPDATA%\rustypass\config\config.toml` on Windows.
+pub(crate) fn default_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", APP_NAME).map(|dirs| dirs.config_dir().join(CONFIG_FILE))
+}
+
+/// Reads the configuration file
+///
+/// # Arguments
+/// * `explicit` - Path given with `--config`; the platform default is used otherwise
+///
+/// # Returns
+/// * `Result<LoadedConfig, String>` - The configuration (empty when the default
+///   file does not exist), or a message naming the file, the line and the key
+///
+/// # Notes
+/// A missing file is only an error when it was named with `--config`.
+/// Unknown keys are collected in `unknown_keys` rather than rejected, so a
+/// file written for a newer version still loads.
+pub(crate) fn load(explicit: Option<&Path>) -> Result<LoadedConfig, String> {
+    let Some(path) = explicit.map(Path::to_path_buf).or_else(default_path) else {
+        return Ok(LoadedConfig::default());
+    };
+
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && explicit.is_none() => {
+            return Ok(LoadedConfig { path: Some(path), ..Default::default() });
+        }
+        Err(e) => return Err(format!("cannot read {}: {}", path.display(), e)),
+    };
+
+    let mut unknown_keys = Vec::new();
+    let config = toml::Deserializer::parse(&text)
+        .and_then(|deserializer| serde_ignored::deserialize(deserializer, |key| unknown_keys.push(key.to_string())))
+        .map_err(|e| format!("{}: {}", path.display(), e.to_string().trim_end()))?;
+
+    Ok(LoadedConfig { path: Some(path), found: true, config, unknown_keys })
+}
+
+// ============================================================================
+// Environment Variables
+// ============================================================================
+
+/// Settings read from `RUSTYPASS_*` environment variables; unset or empty ones are `None`
+///
+/// They sit between the configuration file (and profile) and the command
+/// line. Unlike flags, boolean variables can also switch a class off.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EnvConfig {
+    /// `RUSTYPASS_CONFIG`, used in place of `--config`
+    pub(crate) config: Option<PathBuf>,
+    /// `RUSTYPASS_PROFILE`
+    pub(crate) profile: Option<String>,
+    /// `RUSTYPASS_COLOR`
+    pub(crate) color: Option<ColorMode>,
+    /// `RUSTYPASS_NO_LOGO`
+    pub(crate) no_logo: Option<bool>,
+    /// `RUSTYPASS_QUIET`
+    pub(crate) quiet: Option<bool>,
+    /// `RUSTYPASS_OUTPUT`, for whichever command runs
+    pub(crate) output: Option<OutputFormat>,
+    /// `RUSTYPASS_LENGTH`
+    pub(crate) length: Option<u32>,
+    /// `RUSTYPASS_UPPERCASE`
+    pub(crate) uppercase_chars: Option<bool>,
+    /// `RUSTYPASS_SPECIAL`
+    pub(crate) special_chars: Option<bool>,
+    /// `RUSTYPASS_NUMBERS`
+    pub(crate) numbers: Option<bool>,
+    /// `RUSTYPASS_COUNT`
+    pub(crate) count: Option<u32>,
+    /// `RUSTYPASS_POLICY`
+    pub(crate) policy: Option<PolicyPreset>,
+    /// `RUSTYPASS_GRADE_THRESHOLDS`
+    pub(crate) grade_thresholds: Option<GradeThresholds>,
+}
+
+impl EnvConfig {
+    /// Reads every `RUSTYPASS_*` setting from the process environment
+    ///
+    /// # Returns
+    /// * `Result<EnvConfig, String>` - The values that are set, or a message naming
+    ///   the first variable with a value that cannot be used
+    ///
+    /// # Notes
+    /// Booleans accept `1`, `true`, `yes` and `on` or `0`, `false`, `no` and
+    /// `off`, in any case. `RUSTYPASS_LOG` is read by the logger, not here.
+    pub(crate) fn from_env() -> Result<EnvConfig, String> {
+        Ok(EnvConfig {
+            config: env_var("RUSTYPASS_CONFIG")?.map(PathBuf::from),
+            profile: env_var("RUSTYPASS_PROFILE")?,
+            color: env_enum("RUSTYPASS_COLOR")?,
+            no_logo: env_bool("RUSTYPASS_NO_LOGO")?,
+            quiet: env_bool("RUSTYPASS_QUIET")?,
+            output: env_enum("RUSTYPASS_OUTPUT")?,
+            length: env_parsed("RUSTYPASS_LENGTH", |v| v.parse::<u32>().map_err(|e| e.to_string()).and_then(check_length))?,
+            uppercase_chars: env_bool("RUSTYPASS_UPPERCASE")?,
+            special_chars: env_bool("RUSTYPASS_SPECIAL")?,
+            numbers: env_bool("RUSTYPASS_NUMBERS")?,
+            count: env_parsed("RUSTYPASS_COUNT", |v| match v.parse::<u32>() {
+                Ok(0) => Err(String::from("must be at least 1")),
+                parsed => parsed.map_err(|e| e.to_string()),
+            })?,
+            policy: env_enum("RUSTYPASS_POLICY")?,
+            grade_thresholds: env_parsed("RUSTYPASS_GRADE_THRESHOLDS", GradeThresholds::from_str)?,
+        })
+    }
+}
+
+// ============================================================================
+// Effective Settings
+// ============================================================================
+
+/// Where the effective value of a setting came from, lowest precedence first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Source {
+    Default,
+    Config,
+    Profile,
+    Environment,
+    OptionsFile,
+    Flag,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Source::Default => "default",
+            Source::Config => "config file",
+            Source::Profile => "profile",
+            Source::Environment => "environment",
+            Source::OptionsFile => "options file",
+            Source::Flag => "command line",
+        })
+    }
+}
+
+/// Effective value of one configurable setting
+#[derive(Debug, Clone)]
+pub(crate) struct Setting {
+    pub(crate) key: &'static str,
+    pub(crate) value: String,
+    pub(crate) source: Source,
+}
+
+impl Config {
+    /// Fills in every option the command line left at its default
+    ///
+    /// # Arguments
+    /// * `env` - Values of the `RUSTYPASS_*` environment variables
+    /// * `cli` - Parsed arguments, updated in place
+    /// * `matches` - Matches `cli` was parsed from, to tell given flags from defaults
+    ///
+    /// # Returns
+    /// * `Result<Vec<Setting>, String>` - The effective value and source of each
+    ///   setting that applies to the selected command, for `--verbose`; or an
+    ///   error naming the available profiles when the selected one does not exist
+    ///
+    /// # Notes
+    /// Precedence is built-in default < config file < profile < environment < flag.
+    /// Boolean flags such as `-u` can only switch a class on, so a class
+    /// enabled in the file cannot be switched off from the command line
+    /// (but can be with an environment variable). `RUSTYPASS_COLOR` wins over
+    /// `NO_COLOR` and `CLICOLOR_FORCE`.
+    pub(crate) fn apply(&self, env: &EnvConfig, cli: &mut Cli, matches: &ArgMatches) -> Result<Vec<Setting>, String> {
+        let mut settings = Vec::new();
+        let profile = self.selected_profile(cli.profile.as_deref().or(env.profile.as_deref()))?;
+
+        let source = if given_anywhere(matches, "color") || given_anywhere(matches, "no_color") {
+            Source::Flag
+        } else if env.color.is_some() || output::color_from_env().is_some() {
+            layer(&mut cli.color, over(env.color, None));
+            Source::Environment
+        } else {
+            layer(&mut cli.color, pick(self.color, None))
+        };
+        let color = match (cli.no_color, cli.color, source, output::color_from_env()) {
+            (true, _, _, _) => ColorMode::Never,
+            (_, ColorMode::Auto, Source::Environment, Some(true)) => ColorMode::Always,
+            (_, ColorMode::Auto, Source::Environment, Some(false)) => ColorMode::Never,
+            _ => cli.color,
+        };
+        settings.push(Setting { key: "color", value: value_name(color), source });
+
+        let source = if given_anywhere(matches, "no_logo") {
+            Source::Flag
+        } else {
+            layer(&mut cli.no_logo, over(env.no_logo, pick(self.no_logo, None)))
+        };
+        settings.push(Setting { key: "no_logo", value: cli.no_logo.to_string(), source });
+
+        // --verbose and --quiet conflict, so an explicit -v keeps the variable from applying
+        if !given_anywhere(matches, "quiet") && cli.verbose == 0 {
+            layer(&mut cli.quiet, over(env.quiet, None));
+        }
+
+        let source = if cli.lang.is_some() {
+            Source::Flag
+        } else if i18n::locale_from_env().is_some() {
+            Source::Environment
+        } else if self.lang.is_some() {
+            Source::Config
+        } else {
+            Source::Default
+        };
+        let locale = i18n::detect_locale(cli.lang, self.lang);
+        settings.push(Setting { key: "lang", value: value_name(locale), source });
+
+        let source = if self.default_command.is_some() { Source::Config } else { Source::Default };
+        let default_command = self.default_command.unwrap_or_default();
+        settings.push(Setting { key: "default_command", value: default_command.name().to_string(), source });
+
+        let source = match (&cli.profile, &env.profile, &self.default_profile) {
+            (Some(_), _, _) => Source::Flag,
+            (None, Some(_), _) => Source::Environment,
+            (None, None, Some(_)) => Source::Config,
+            (None, None, None) => Source::Default,
+        };
+        let name = profile.map_or_else(|| String::from("none"), |(name, _)| name.to_string());
+        settings.push(Setting { key: "profile", value: name, source });
+        let profile = profile.map(|(_, profile)| profile);
+
+        // Every command but `config` has an --output; generate's is at the top level without a subcommand
+        let output = match &mut cli.command {
+            Some(Commands::Generate(args)) => matches.subcommand_matches("generate").map(|m| (&mut args.output, m)),
+            Some(Commands::Analyze(args)) => matches.subcommand_matches("analyze").map(|m| (&mut args.output, m)),
+            Some(Commands::Audit(args)) => matches.subcommand_matches("audit").map(|m| (&mut args.output, m)),
+            Some(Commands::Check(args)) => matches.subcommand_matches("check").map(|m| (&mut args.output, m)),
+            Some(Commands::Entropy(args)) => matches.subcommand_matches("entropy").map(|m| (&mut args.output, m)),
+            Some(Commands::Bench(args)) => matches.subcommand_matches("bench").map(|m| (&mut args.output, m)),
+            Some(Commands::Doctor(args)) => matches.subcommand_matches("doctor").map(|m| (&mut args.output, m)),
+            None => Some((&mut cli.generate.output, matches)),
+            _ => None,
+        };
+        if let Some((format, sub_matches)) = output {
+            let root = Cli::command();
+            let command = matches.subcommand_name().and_then(|name| root.find_subcommand(name)).unwrap_or(&root);
+            // Flags that rule out --output (e.g. --copy or --report) also rule out the variable
+            let source = if given(sub_matches, "output") || conflicting_flag_given(command, sub_matches, "output") {
+                Source::Flag
+            } else {
+                layer(format, over(env.output, None))
+            };
+            settings.push(Setting { key: "output", value: value_name(*format), source });
+        }
+
+        // Generate options live on the subcommand, or at the top level without one
+        let generate = match &mut cli.command {
+            Some(Commands::Generate(args)) => matches.subcommand_matches("generate").map(|m| (&mut **args, m)),
+            None => Some((&mut cli.generate, matches)),
+            _ => None,
+        };
+        if let Some((args, matches)) = generate {
+            let global = &self.generate;
+            // An options document replaces everything below the command line
+            let document = args.options_document.clone();
+
+            let source = if given(matches, "length") {
+                // Only a literal -l falls back to the default when out of range
+                args.length = cli::set_length(args.length);
+                Source::Flag
+            } else {
+                let picked = over(env.length, pick(global.length, profile.and_then(|p| p.length)));
+                layer(&mut args.length, document.as_ref().map(|d| (d.length, Source::OptionsFile)).or(picked))
+            };
+            settings.push(Setting { key: "length", value: args.length.to_string(), source });
+            for (key, value, documented, from_env, configured, profiled) in [
+                ("uppercase_chars", &mut args.uppercase_chars, document.as_ref().map(|d| d.include_uppercase), env.uppercase_chars, global.uppercase_chars, profile.and_then(|p| p.uppercase_chars)),
+                ("special_chars", &mut args.special_chars, document.as_ref().map(|d| d.include_symbols), env.special_chars, global.special_chars, profile.and_then(|p| p.special_chars)),
+                ("numbers", &mut args.numbers, document.as_ref().map(|d| d.include_digits), env.numbers, global.numbers, profile.and_then(|p| p.numbers)),
+            ] {
+                let source = if given(matches, key) {
+                    Source::Flag
+                } else {
+                    let picked = over(from_env, pick(configured, profiled));
+                    layer(value, documented.map(|value| (value, Source::OptionsFile)).or(picked))
+                };
+                settings.push(Setting { key, value: value.to_string(), source });
+            }
+
+            let source = if given(matches, "count") { Source::Flag } else { layer(&mut args.count, over(env.count, None)) };
+            settings.push(Setting { key: "count", value: args.count.to_string(), source });
+
+            // A policy file on the command line replaces any configured preset
+            let source = if given(matches, "policy") || given(matches, "policy_file") {
+                Source::Flag
+            } else {
+                let configured = over(env.policy, pick(global.policy, profile.and_then(|p| p.policy)));
+                layer(&mut args.policy, configured.map(|(policy, source)| (Some(policy), source)))
+            };
+            let value = match (&args.policy_file, args.policy) {
+                (Some(path), _) => path.display().to_string(),
+                (None, Some(policy)) => value_name(policy),
+                (None, None) => String::from("none"),
+            };
+            settings.push(Setting { key: "policy", value, source });
+        }
+
+        let thresholds = match &mut cli.command {
+            Some(Commands::Analyze(args)) => matches.subcommand_matches("analyze").map(|m| (&mut args.grade_thresholds, m)),
+            Some(Commands::Audit(args)) => matches.subcommand_matches("audit").map(|m| (&mut args.grade_thresholds, m)),
+            _ => None,
+        };
+        if let Some((thresholds, matches)) = thresholds {
+            let source = if given(matches, "grade_thresholds") {
+                Source::Flag
+            } else {
+                layer(thresholds, over(env.grade_thresholds, pick(self.analyze.grade_thresholds, None)))
+            };
+            let GradeThresholds { a, b, c, d } = *thresholds;
+            settings.push(Setting { key: "grade_thresholds", value: format!("{},{},{},{}", a, b, c, d), source });
+        }
+
+        Ok(settings)
+    }
+
+    /// Looks up the profile named by `--profile`, or else by `default_profile`
+    ///
+    /// # Returns
+    /// * `Result<Option<(&str, &GenerateConfig)>, String>` - The profile, `None`
+    ///   when none is selected, or an error listing the defined profiles
+    pub(crate) fn selected_profile<'a>(&'a self, flag: Option<&'a str>) -> Result<Option<(&'a str, &'a GenerateConfig)>, String> {
+        let Some(name) = flag.or(self.default_profile.as_deref()) else {
+            return Ok(None);
+        };
+        match self.profile.get(name) {
+            Some(profile) => Ok(Some((name, profile))),
+            None if self.profile.is_empty() => {
+                Err(format!("unknown profile '{}' (no profiles are defined in the configuration file)", name))
+            }
+            None => Err(format!(
+                "unknown profile '{}' (available: {})",
+                name,
+                self.profile.keys().cloned().collect::<Vec<String>>().join(", ")
+            )),
+        }
+    }
+}
+
+/// Renders every profile with the generate settings it results in, for `config list-profiles`
+///
+/// Values come from the profile, then `[generate]`, then the built-in
+/// defaults; command-line flags would still override them.
+pub(crate) fn render_profiles(config: &Config) -> String {
+    let mut out = String::new();
+    for (name, profile) in &config.profile {
+        let marker = if config.default_profile.as_deref() == Some(name) { " (default_profile)" } else { "" };
+        out.push_str(&format!("{}{}\n", name, marker));
+
+        let global = &config.generate;
+        let settings = [
+            resolved("length", pick(global.length, profile.length), utils::DEFAULT_LENGTH, |v| v.to_string()),
+            resolved("uppercase_chars", pick(global.uppercase_chars, profile.uppercase_chars), false, |v| v.to_string()),
+            resolved("special_chars", pick(global.special_chars, profile.special_chars), false, |v| v.to_string()),
+            resolved("numbers", pick(global.numbers, profile.numbers), false, |v| v.to_string()),
+            resolved("policy", pick(global.policy.map(Some), profile.policy.map(Some)), None, |v| {
+                v.map_or_else(|| String::from("none"), value_name)
+            }),
+        ];
+        let value_width = settings.iter().map(|s| s.value.len()).max().unwrap_or(0);
+        for setting in &settings {
+            out.push_str(&format!("  {:<15}  {:<value_width$}  ({})\n", setting.key, setting.value, setting.source));
+        }
+    }
+    out.trim_end().to_string()
+}
+
+/// Renders where the configuration came from and every effective setting, for `--verbose`
+pub(crate) fn render_settings(loaded: &LoadedConfig, settings: &[Setting]) -> String {
+    let file = match (&loaded.path, loaded.found) {
+        (Some(path), true) => format!("{}", path.display()),
+        (Some(path), false) => format!("none ({} does not exist)", path.display()),
+        (None, _) => String::from("none (no configuration directory)"),
+    };
+    let width = settings.iter().map(|s| s.key.len()).max().unwrap_or(0);
+    let value_width = settings.iter().map(|s| s.value.len()).max().unwrap_or(0);
+
+    let mut out = format!("Configuration file: {}\nEffective settings:\n", file);
+    for setting in settings {
+        out.push_str(&format!(
+            "  {:<width$}  {:<value_width$}  ({})\n",
+            setting.key, setting.value, setting.source
+        ));
+    }
+    out.trim_end().to_string()
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Picks the profile value over the `[generate]` (or other global) one, with its source
+fn pick<T>(configured: Option<T>, profiled: Option<T>) -> Option<(T, Source)> {
+    profiled.map(|value| (value, Source::Profile)).or(configured.map(|value| (value, Source::Config)))
+}
+
+/// Picks the environment value over whatever the configuration file provides
+fn over<T>(from_env: Option<T>, picked: Option<(T, Source)>) -> Option<(T, Source)> {
+    from_env.map(|value| (value, Source::Environment)).or(picked)
+}
+
+/// Replaces `value` with the picked one, if any, and reports which was used
+fn layer<T>(value: &mut T, picked: Option<(T, Source)>) -> Source {
+    match picked {
+        Some((picked, source)) => {
+            *value = picked;
+            source
+        }
+        None => Source::Default,
+    }
+}
+
+/// Describes a setting from its picked value, falling back to the built-in default
+fn resolved<T>(key: &'static str, picked: Option<(T, Source)>, default: T, show: impl Fn(T) -> String) -> Setting {
+    let (value, source) = picked.unwrap_or((default, Source::Default));
+    Setting { key, value: show(value), source }
+}
+
+/// Whether an argument defined on this command was given on the command line
+fn given(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::CommandLine)
+}
+
+/// Whether a global argument was given, before or after the subcommand
+fn given_anywhere(matches: &ArgMatches, id: &str) -> bool {
+    given(matches, id) || matches.subcommand().is_some_and(|(_, sub)| given_anywhere(sub, id))
+}
+
+/// Whether a flag that clap declares as conflicting with `id` was given
+fn conflicting_flag_given(command: &clap::Command, matches: &ArgMatches, id: &str) -> bool {
+    command
+        .get_arguments()
+        .filter(|arg| !arg.is_global_set() && given(matches, arg.get_id().as_str()))
+        .any(|arg| command.get_arg_conflicts_with(arg).iter().any(|other| other.get_id() == id))
+}
+
+/// Value of an environment variable, `None` when unset or empty
+fn env_var(name: &str) -> Result<Option<String>, String> {
+    match std::env::var(name) {
+        Ok(value) if value.is_empty() => Ok(None),
+        Ok(value) => Ok(Some(value)),
+        Err(std::env::VarError::NotPresent) => Ok(None),
+        Err(std::env::VarError::NotUnicode(_)) => Err(format!("{} is not valid UTF-8", name)),
+    }
+}
+
+/// Parses an environment variable, naming it and its value in the error
+fn env_parsed<T>(name: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<Option<T>, String> {
+    env_var(name)?
+        .map(|value| parse(value.trim()).map_err(|e| format!("invalid value '{}' for {}: {}", value, name, e)))
+        .transpose()
+}
+
+/// Parses a boolean environment variable such as `RUSTYPASS_NUMBERS=yes`
+fn env_bool(name: &str) -> Result<Option<bool>, String> {
+    env_parsed(name, |value| {
+        let value = value.to_ascii_lowercase();
+        if ENV_TRUE.contains(&value.as_str()) {
+            Ok(true)
+        } else if ENV_FALSE.contains(&value.as_str()) {
+            Ok(false)
+        } else {
+            Err(format!("expected one of {}, {}", ENV_TRUE.join(", "), ENV_FALSE.join(", ")))
+        }
+    })
+}
+
+/// Parses an environment variable holding a value name, e.g. `RUSTYPASS_OUTPUT=json`
+fn env_enum<T: ValueEnum>(name: &str) -> Result<Option<T>, String> {
+    env_parsed(name, |value| {
+        T::from_str(value, true).map_err(|_| {
+            let names: Vec<String> = T::value_variants().iter().cloned().map(value_name).collect();
+            format!("expected one of {}", names.join(", "))
+        })
+    })
+}
+
+/// Name of a value as written on the command line and in the file, e.g. `always`
+fn value_name<T: ValueEnum>(value: T) -> String {
+    value.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default()
+}
+
+/// Reads the password length, rejecting values `generate` would not accept
+fn deserialize_length<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u32>, D::Error> {
+    let length = u32::deserialize(deserializer)?;
+    check_length(length).map(Some).map_err(serde::de::Error::custom)
+}
+
+/// Rejects a password length `generate` would not accept
+fn check_length(length: u32) -> Result<u32, String> {
+    if !(utils::MIN_LENGTH..=utils::MAX_LENGTH).contains(&length) {
+        return Err(format!("length must be between {} and {}, got {}", utils::MIN_LENGTH, utils::MAX_LENGTH, length));
+    }
+    Ok(length)
+}
+
+/// Reads a value written the same way as its command-line flag, e.g. `"90,70,55,40"`
+fn deserialize_from_str<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = String>,
+{
+    let text = String::deserialize(deserializer)?;
+    text.parse().map(Some).map_err(serde::de::Error::custom)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Applies a configuration file and the environment to a command line
+    fn resolve(config: &str, env: &EnvConfig, args: &[&str]) -> Result<Vec<Setting>, String> {
+        let config: Config = toml::from_str(config).unwrap();
+        let (mut cli, _, matches) = Cli::parse_bare(std::iter::once("rustypass").chain(args.iter().copied()));
+        config.apply(env, &mut cli, &matches)
+    }
+
+    /// Effective value and source of one setting
+    fn effective(config: &str, env: &EnvConfig, args: &[&str], key: &str) -> (String, Source) {
+        let settings = resolve(config, env, args).unwrap();
+        let setting = settings.iter().find(|setting| setting.key == key).unwrap_or_else(|| panic!("no {} setting", key));
+        (setting.value.clone(), setting.source)
+    }
+
+    #[test]
+    fn flags_win_over_the_config_file_and_the_default() {
+        let configured = "[generate]\nlength = 24\nnumbers = true\n[analyze]\ngrade_thresholds = \"95,80,60,40\"";
+        let cases: &[(&str, &[&str], &str, &str, Source)] = &[
+            ("", &[], "length", "16", Source::Default),
+            (configured, &[], "length", "24", Source::Config),
+            (configured, &["generate"], "length", "24", Source::Config),
+            (configured, &["-l", "32"], "length", "32", Source::Flag),
+            (configured, &["generate", "-l", "32"], "length", "32", Source::Flag),
+            // A flag set to the built-in default still wins
+            (configured, &["-l", "16"], "length", "16", Source::Flag),
+            ("", &[], "numbers", "false", Source::Default),
+            (configured, &[], "numbers", "true", Source::Config),
+            (configured, &["-n"], "numbers", "true", Source::Flag),
+            ("", &["analyze", "x"], "grade_thresholds", "90,70,55,40", Source::Default),
+            (configured, &["analyze", "x"], "grade_thresholds", "95,80,60,40", Source::Config),
+            (configured, &["analyze", "x", "--grade-thresholds", "85,65,45,25"], "grade_thresholds", "85,65,45,25", Source::Flag),
+        ];
+        for (config, args, key, value, source) in cases {
+            assert_eq!(effective(config, &EnvConfig::default(), args, key), (value.to_string(), *source), "{:?} with {:?}", config, args);
+        }
+    }
+
+    #[test]
+    fn settings_of_other_commands_are_not_reported() {
+        let settings = resolve("[generate]\nlength = 24", &EnvConfig::default(), &["analyze", "x"]).unwrap();
+        assert!(settings.iter().all(|setting| setting.key != "length"));
+        let settings = resolve("", &EnvConfig::default(), &[]).unwrap();
+        assert!(settings.iter().all(|setting| setting.key != "grade_thresholds"));
+    }
+
+    #[test]
+    fn out_of_range_lengths_are_rejected_when_loading() {
+        let error = toml::from_str::<Config>("[generate]\nlength = 3").unwrap_err().to_string();
+        assert!(error.contains("length must be between"), "{}", error);
+    }
+
+    const PROFILES: &str = "default_profile = \"web\"\n\
+        [generate]\nlength = 24\nspecial_chars = true\n\
+        [profile.web]\nlength = 20\nnumbers = true\n\
+        [profile.short]\nlength = 10\nspecial_chars = false\n";
+
+    #[test]
+    fn profiles_win_over_the_config_file_and_lose_to_flags() {
+        let cases: &[(&[&str], &str, &str, Source)] = &[
+            // default_profile selects "web"
+            (&[], "length", "20", Source::Profile),
+            (&[], "numbers", "true", Source::Profile),
+            // Keys the profile leaves out come from [generate]
+            (&[], "special_chars", "true", Source::Config),
+            (&[], "uppercase_chars", "false", Source::Default),
+            (&["--profile", "short"], "length", "10", Source::Profile),
+            // A profile can switch off a class [generate] switched on
+            (&["--profile", "short"], "special_chars", "false", Source::Profile),
+            (&["--profile", "short"], "numbers", "false", Source::Default),
+            (&["--profile", "short", "-l", "12"], "length", "12", Source::Flag),
+            (&["generate", "--profile", "short", "-s"], "special_chars", "true", Source::Flag),
+            (&["--profile", "short"], "profile", "short", Source::Flag),
+            (&[], "profile", "web", Source::Config),
+        ];
+        for (args, key, value, source) in cases {
+            assert_eq!(effective(PROFILES, &EnvConfig::default(), args, key), (value.to_string(), *source), "{:?}", args);
+        }
+        assert_eq!(effective("", &EnvConfig::default(), &[], "profile"), (String::from("none"), Source::Default));
+    }
+
+    #[test]
+    fn unknown_profiles_name_the_available_ones() {
+        let error = resolve(PROFILES, &EnvConfig::default(), &["--profile", "wifi"]).unwrap_err();
+        assert_eq!(error, "unknown profile 'wifi' (available: short, web)");
+        let error = resolve("default_profile = \"web\"", &EnvConfig::default(), &[]).unwrap_err();
+        assert_eq!(error, "unknown profile 'web' (no profiles are defined in the configuration file)");
+    }
+
+    #[test]
+    fn length_follows_flag_environment_profile_config_default() {
+        let env = EnvConfig { length: Some(30), ..Default::default() };
+        let none = EnvConfig::default();
+        let cases: &[(&str, &EnvConfig, &[&str], &str, Source)] = &[
+            ("", &none, &[], "16", Source::Default),
+            ("[generate]\nlength = 24", &none, &[], "24", Source::Config),
+            (PROFILES, &none, &[], "20", Source::Profile),
+            (PROFILES, &env, &[], "30", Source::Environment),
+            (PROFILES, &env, &["-l", "12"], "12", Source::Flag),
+            (PROFILES, &env, &["generate", "--length", "12"], "12", Source::Flag),
+        ];
+        for (config, env, args, value, source) in cases {
+            assert_eq!(effective(config, env, args, "length"), (value.to_string(), *source), "{:?} with {:?}", config, args);
+        }
+    }
+
+    #[test]
+    fn environment_wins_over_the_config_file_and_loses_to_flags() {
+        let env = EnvConfig {
+            special_chars: Some(false),
+            numbers: Some(true),
+            count: Some(3),
+            output: Some(OutputFormat::Json),
+            grade_thresholds: Some("85,65,45,25".parse().unwrap()),
+            ..Default::default()
+        };
+        let configured = "[generate]\nspecial_chars = true\n[analyze]\ngrade_thresholds = \"95,80,60,40\"";
+        let cases: &[(&[&str], &str, &str, Source)] = &[
+            // Unlike -s, a variable can switch a class off
+            (&[], "special_chars", "false", Source::Environment),
+            (&["-s"], "special_chars", "true", Source::Flag),
+            (&[], "numbers", "true", Source::Environment),
+            (&[], "count", "3", Source::Environment),
+            (&["-c", "5"], "count", "5", Source::Flag),
+            (&[], "output", "json", Source::Environment),
+            (&["-o", "text"], "output", "text", Source::Flag),
+            (&["analyze", "x"], "output", "json", Source::Environment),
+            (&["analyze", "x"], "grade_thresholds", "85,65,45,25", Source::Environment),
+            (&["analyze", "x", "--grade-thresholds", "80,60,40,20"], "grade_thresholds", "80,60,40,20", Source::Flag),
+        ];
+        for (args, key, value, source) in cases {
+            assert_eq!(effective(configured, &env, args, key), (value.to_string(), *source), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn profile_variable_loses_to_the_flag() {
+        let env = EnvConfig { profile: Some(String::from("short")), ..Default::default() };
+        assert_eq!(effective(PROFILES, &env, &[], "profile"), (String::from("short"), Source::Environment));
+        assert_eq!(effective(PROFILES, &env, &[], "length"), (String::from("10"), Source::Profile));
+        assert_eq!(effective(PROFILES, &env, &["--profile", "web"], "length"), (String::from("20"), Source::Profile));
+
+        let env = EnvConfig { profile: Some(String::from("wifi")), ..Default::default() };
+        assert_eq!(resolve(PROFILES, &env, &[]).unwrap_err(), "unknown profile 'wifi' (available: short, web)");
+    }
+}
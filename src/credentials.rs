@@ -0,0 +1,173 @@
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Prefix of every service name, e.g. `rustypass/github`
+const SERVICE_PREFIX: &str = "rustypass";
+
+/// Account the passwords are stored under; the service name carries the user's name for them
+#[cfg(feature = "keyring")]
+const ACCOUNT: &str = "rustypass";
+
+// ============================================================================
+// OS Credential Store
+// ============================================================================
+
+/// Service name a password called `name` is stored under
+pub(crate) fn service_name(name: &str) -> String {
+    format!("{}/{}", SERVICE_PREFIX, name)
+}
+
+/// Saves a password in the OS credential store, replacing any stored under the same name
+///
+/// Secret Service on Linux and the BSDs, the Keychain on macOS and the
+/// Credential Manager on Windows.
+///
+/// # Errors
+/// A locked store or a session without a credential service, with a hint on how to fix it.
+#[cfg(feature = "keyring")]
+pub(crate) fn store(name: &str, password: &str) -> Result<(), String> {
+    store_in(&entry(name)?, name, password)
+}
+
+/// Reads a password from the OS credential store
+#[cfg(feature = "keyring")]
+pub(crate) fn retrieve(name: &str) -> Result<zeroize::Zeroizing<String>, String> {
+    retrieve_from(&entry(name)?, name)
+}
+
+/// Removes a password from the OS credential store
+#[cfg(feature = "keyring")]
+pub(crate) fn delete(name: &str) -> Result<(), String> {
+    delete_from(&entry(name)?, name)
+}
+
+/// Fallback when the binary was built without the `keyring` feature
+#[cfg(not(feature = "keyring"))]
+pub(crate) fn store(_name: &str, _password: &str) -> Result<(), String> {
+    Err(not_compiled_in())
+}
+
+/// Fallback when the binary was built without the `keyring` feature
+#[cfg(not(feature = "keyring"))]
+pub(crate) fn retrieve(_name: &str) -> Result<zeroize::Zeroizing<String>, String> {
+    Err(not_compiled_in())
+}
+
+/// Fallback when the binary was built without the `keyring` feature
+#[cfg(not(feature = "keyring"))]
+pub(crate) fn delete(_name: &str) -> Result<(), String> {
+    Err(not_compiled_in())
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Credential store entry for a name
+#[cfg(feature = "keyring")]
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    if name.trim().is_empty() {
+        return Err(String::from("the name of a stored password cannot be empty"));
+    }
+    keyring::Entry::new(&service_name(name), ACCOUNT).map_err(|e| describe(name, e))
+}
+
+/// Saves a password in an entry, reporting errors against `name`
+#[cfg(feature = "keyring")]
+fn store_in(entry: &keyring::Entry, name: &str, password: &str) -> Result<(), String> {
+    entry.set_password(password).map_err(|e| describe(name, e))
+}
+
+/// Reads the password of an entry, reporting errors against `name`
+#[cfg(feature = "keyring")]
+fn retrieve_from(entry: &keyring::Entry, name: &str) -> Result<zeroize::Zeroizing<String>, String> {
+    entry.get_password().map(zeroize::Zeroizing::new).map_err(|e| describe(name, e))
+}
+
+/// Removes the password of an entry, reporting errors against `name`
+#[cfg(feature = "keyring")]
+fn delete_from(entry: &keyring::Entry, name: &str) -> Result<(), String> {
+    entry.delete_credential().map_err(|e| describe(name, e))
+}
+
+/// Turns a credential store error into a message that says what to do about it
+#[cfg(feature = "keyring")]
+fn describe(name: &str, error: keyring::Error) -> String {
+    use keyring::Error;
+
+    match error {
+        Error::NoEntry => format!("nothing is stored under '{}'", service_name(name)),
+        Error::NoStorageAccess(e) => format!(
+            "the credential store is locked or refused access ({}); unlock it, e.g. by logging in to the desktop session, and try again",
+            e
+        ),
+        Error::PlatformFailure(e) => format!(
+            "the credential store is unavailable ({}); headless sessions need a running Secret Service such as gnome-keyring or KeePassXC",
+            e
+        ),
+        Error::Ambiguous(entries) => {
+            format!("{} credentials match '{}'; remove the duplicates in your keyring manager", entries.len(), service_name(name))
+        }
+        e => format!("credential store error for '{}': {}", service_name(name), e),
+    }
+}
+
+/// Message shown when the binary was built without the `keyring` feature
+#[cfg(not(feature = "keyring"))]
+fn not_compiled_in() -> String {
+    String::from("credential store support is not compiled in; rebuild with `--features keyring`")
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(all(test, feature = "keyring"))]
+mod tests {
+    use super::*;
+
+    /// Entry for `name` in the in-memory mock store instead of the OS one
+    ///
+    /// Mock credentials keep the password in the entry itself, so a test has
+    /// to reuse one entry rather than go through `store` and `retrieve`.
+    fn mock_entry(name: &str) -> keyring::Entry {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+        entry(name).unwrap()
+    }
+
+    #[test]
+    fn store_retrieve_and_delete_round_trip() {
+        let entry = mock_entry("github");
+
+        store_in(&entry, "github", "correct horse").unwrap();
+        assert_eq!(*retrieve_from(&entry, "github").unwrap(), "correct horse");
+
+        store_in(&entry, "github", "battery staple").unwrap();
+        assert_eq!(*retrieve_from(&entry, "github").unwrap(), "battery staple");
+
+        delete_from(&entry, "github").unwrap();
+    }
+
+    #[test]
+    fn a_deleted_password_is_reported_as_missing() {
+        let entry = mock_entry("mail");
+        store_in(&entry, "mail", "hunter2").unwrap();
+        delete_from(&entry, "mail").unwrap();
+
+        assert_eq!(retrieve_from(&entry, "mail").unwrap_err(), "nothing is stored under 'rustypass/mail'");
+        assert_eq!(delete_from(&entry, "mail").unwrap_err(), "nothing is stored under 'rustypass/mail'");
+    }
+
+    #[test]
+    fn an_empty_name_is_rejected() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+
+        for name in ["", "   "] {
+            assert_eq!(entry(name).unwrap_err(), "the name of a stored password cannot be empty");
+            assert_eq!(store(name, "hunter2").unwrap_err(), "the name of a stored password cannot be empty");
+            assert_eq!(retrieve(name).unwrap_err(), "the name of a stored password cannot be empty");
+            assert_eq!(delete(name).unwrap_err(), "the name of a stored password cannot be empty");
+        }
+    }
+}
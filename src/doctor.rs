@@ -0,0 +1,524 @@
+use std::io::IsTerminal;
+use rand::TryRngCore;
+use serde::Serialize;
+use crate::config::LoadedConfig;
+use crate::i18n::{self, Locale};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Terminal width (in columns) below which the banner falls back to plain text
+const MIN_BANNER_WIDTH: u16 = 60;
+
+/// Optional cargo features and whether this binary was built with them
+const FEATURES: [(&str, bool); 4] = [
+    ("clipboard", cfg!(feature = "clipboard")),
+    ("qr", cfg!(feature = "qr")),
+    ("wizard", cfg!(feature = "wizard")),
+    ("keyring", cfg!(feature = "keyring")),
+];
+
+// ============================================================================
+// Diagnostics
+// ============================================================================
+
+/// Outcome of a single environment check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl Status {
+    /// Fixed-width tag shown in front of each check
+    fn tag(&self) -> &'static str {
+        match self {
+            Status::Ok => "[ OK ]",
+            Status::Warn => "[WARN]",
+            Status::Fail => "[FAIL]",
+        }
+    }
+}
+
+/// Result of probing one aspect of the runtime environment
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Check {
+    /// Short name of what was probed
+    pub(crate) name: &'static str,
+    pub(crate) status: Status,
+    /// What was found
+    pub(crate) detail: String,
+    /// How to fix a warning or failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) hint: Option<String>,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, detail: String) -> Self {
+        Check { name, status, detail, hint: None }
+    }
+
+    fn hint(mut self, hint: &str) -> Self {
+        self.hint = Some(hint.to_string());
+        self
+    }
+}
+
+/// Runs every check in display order
+///
+/// # Arguments
+/// * `probe` - Where the observations come from: the running system, or a stub in tests
+/// * `color_enabled` - Whether colors were resolved as enabled for this run
+/// * `lang` - Value of `--lang`, if given
+/// * `config` - Result of loading the configuration file
+pub(crate) fn run_checks(probe: &dyn Probe, color_enabled: bool, lang: Option<Locale>, config: &Result<LoadedConfig, String>) -> Vec<Check> {
+    let configured_lang = config.as_ref().ok().and_then(|loaded| loaded.config.lang);
+    vec![
+        check_terminals(probe),
+        check_terminal_width(probe),
+        check_color(probe, color_enabled),
+        check_clipboard(probe),
+        check_rng(probe),
+        check_locale(probe, lang, configured_lang),
+        check_config(config),
+        check_features(&probe.features()),
+    ]
+}
+
+/// Renders checks as one line each, followed by an indented hint where there is one
+pub(crate) fn render_checks(checks: &[Check]) -> String {
+    let width = checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    let mut out = String::new();
+
+    for check in checks {
+        out.push_str(&format!("{} {:<width$}  {}\n", check.status.tag(), check.name, check.detail));
+        if let Some(hint) = &check.hint {
+            out.push_str(&format!("       {:<width$}  -> {}\n", "", hint));
+        }
+    }
+    out
+}
+
+// ============================================================================
+// Probes
+// ============================================================================
+
+/// What the clipboard probe found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ClipboardProbe {
+    /// The binary was built without the `clipboard` feature
+    NotCompiled,
+    /// There is no display server whose clipboard could be opened
+    NoDisplay,
+    /// The clipboard of this backend opened, or failed with this error
+    Backend(&'static str, Result<(), String>),
+}
+
+/// Where the checks get their observations: the running system, or a stub that answers in tests
+pub(crate) trait Probe {
+    /// Whether stdout and stderr are terminals
+    fn terminals(&self) -> (bool, bool);
+    /// Width of the terminal on stderr in columns, if it is one
+    fn terminal_width(&self) -> Option<u16>;
+    /// Whether `NO_COLOR` is set to a non-empty value
+    fn no_color(&self) -> bool;
+    /// Whether the system clipboard can be opened
+    fn clipboard(&self) -> ClipboardProbe;
+    /// Draws from the operating system random number generator
+    fn rng(&self) -> Result<(), String>;
+    /// The locale variable in effect, as `(name, value)`
+    fn locale_env(&self) -> Option<(&'static str, String)>;
+    /// Optional cargo features and whether this binary was built with them
+    fn features(&self) -> Vec<(&'static str, bool)>;
+}
+
+/// Probes the process, its terminal and the operating system
+pub(crate) struct SystemProbe;
+
+impl Probe for SystemProbe {
+    fn terminals(&self) -> (bool, bool) {
+        (std::io::stdout().is_terminal(), std::io::stderr().is_terminal())
+    }
+
+    fn terminal_width(&self) -> Option<u16> {
+        console::Term::stderr().size_checked().map(|(_, columns)| columns)
+    }
+
+    fn no_color(&self) -> bool {
+        std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+    }
+
+    fn clipboard(&self) -> ClipboardProbe {
+        if !cfg!(feature = "clipboard") {
+            return ClipboardProbe::NotCompiled;
+        }
+        match clipboard_backend() {
+            Some(backend) => ClipboardProbe::Backend(backend, open_clipboard()),
+            None => ClipboardProbe::NoDisplay,
+        }
+    }
+
+    fn rng(&self) -> Result<(), String> {
+        let mut bytes = [0u8; 32];
+        rand::rngs::OsRng.try_fill_bytes(&mut bytes).map_err(|e| e.to_string())
+    }
+
+    fn locale_env(&self) -> Option<(&'static str, String)> {
+        i18n::locale_from_env()
+    }
+
+    fn features(&self) -> Vec<(&'static str, bool)> {
+        FEATURES.to_vec()
+    }
+}
+
+// ============================================================================
+// Checks
+// ============================================================================
+
+/// Whether stdout and stderr are terminals, which decides logo, colors and labels
+fn check_terminals(probe: &dyn Probe) -> Check {
+    let describe = |terminal: bool| if terminal { "terminal" } else { "redirected" };
+    let (stdout, stderr) = probe.terminals();
+    let detail = format!("stdout: {}, stderr: {}", describe(stdout), describe(stderr));
+
+    if stdout && stderr {
+        Check::new("terminal", Status::Ok, detail)
+    } else {
+        Check::new("terminal", Status::Warn, detail)
+            .hint("logo, colors, labels and progress bars are off for redirected streams")
+    }
+}
+
+/// Width of the terminal, which decides whether the ASCII art banner fits
+fn check_terminal_width(probe: &dyn Probe) -> Check {
+    match probe.terminal_width() {
+        Some(columns) if columns >= MIN_BANNER_WIDTH => {
+            Check::new("terminal width", Status::Ok, format!("{} columns", columns))
+        }
+        Some(columns) => Check::new("terminal width", Status::Warn, format!("{} columns", columns))
+            .hint("the logo is printed as plain text below 60 columns"),
+        None => Check::new("terminal width", Status::Warn, String::from("unknown"))
+            .hint("stderr is not a terminal; the logo is printed as plain text"),
+    }
+}
+
+/// Whether colors are used, and why
+fn check_color(probe: &dyn Probe, color_enabled: bool) -> Check {
+    let detail = match (color_enabled, probe.no_color()) {
+        (true, _) => String::from("enabled"),
+        (false, true) => String::from("disabled (NO_COLOR is set)"),
+        (false, false) => String::from("disabled"),
+    };
+
+    let check = Check::new("color", Status::Ok, detail);
+    if color_enabled { check } else { check.hint("pass --color always to force colors") }
+}
+
+/// Whether the system clipboard can be opened for `generate --copy`, and through which backend
+fn check_clipboard(probe: &dyn Probe) -> Check {
+    match probe.clipboard() {
+        ClipboardProbe::NotCompiled => Check::new("clipboard", Status::Warn, String::from("not compiled in"))
+            .hint("--copy will copy through the terminal (OSC 52) instead; rebuild with --features clipboard for the system clipboard"),
+        ClipboardProbe::NoDisplay => Check::new("clipboard", Status::Fail, String::from("no display server found"))
+            .hint("--copy will copy through the terminal (OSC 52) instead; over SSH that is the local clipboard"),
+        ClipboardProbe::Backend(backend, Ok(())) => Check::new("clipboard", Status::Ok, format!("available ({})", backend)),
+        ClipboardProbe::Backend(backend, Err(e)) => {
+            Check::new("clipboard", Status::Fail, format!("{} clipboard unavailable: {}", backend, e))
+                .hint("--copy will copy through the terminal (OSC 52) instead, if the terminal allows it")
+        }
+    }
+}
+
+/// Whether the operating system random number generator answers
+fn check_rng(probe: &dyn Probe) -> Check {
+    match probe.rng() {
+        Ok(()) => Check::new("random source", Status::Ok, String::from("operating system RNG responds")),
+        Err(e) => Check::new("random source", Status::Fail, format!("operating system RNG failed: {}", e))
+            .hint("passwords cannot be generated safely on this system"),
+    }
+}
+
+/// Language of user-facing messages and where it came from
+fn check_locale(probe: &dyn Probe, lang: Option<Locale>, configured: Option<Locale>) -> Check {
+    let from_env = probe.locale_env();
+    let locale = i18n::resolve_locale(lang, from_env.as_ref().map(|(_, value)| value.as_str()), configured);
+    let source = match (lang, from_env, configured) {
+        (Some(_), _, _) => String::from("--lang"),
+        (None, Some((var, value)), _) => format!("{}={}", var, value),
+        (None, None, Some(_)) => String::from("config file"),
+        (None, None, None) => String::from("default"),
+    };
+    let name = match locale {
+        Locale::En => "English",
+        Locale::De => "German",
+    };
+
+    Check::new("locale", Status::Ok, format!("{} (from {})", name, source))
+}
+
+/// Which configuration file is read, and whether it parses
+fn check_config(config: &Result<LoadedConfig, String>) -> Check {
+    match config {
+        // Parse errors span several lines: keep the location and the message
+        Err(e) => Check::new("config", Status::Fail, match (e.lines().next(), e.lines().last()) {
+            (Some(first), Some(last)) if first != last => format!("{}: {}", first, last),
+            _ => e.clone(),
+        })
+            .hint("fix the file, or write a fresh one with `config init --force`"),
+        Ok(LoadedConfig { path: None, .. }) => Check::new("config", Status::Warn, String::from("no configuration directory"))
+            .hint("pass --config PATH to use a configuration file"),
+        Ok(LoadedConfig { path: Some(path), found: false, .. }) => {
+            Check::new("config", Status::Ok, format!("none ({} does not exist)", path.display()))
+                .hint("run `config init` to create one")
+        }
+        Ok(LoadedConfig { path: Some(path), unknown_keys, .. }) if !unknown_keys.is_empty() => Check::new(
+            "config",
+            Status::Warn,
+            format!("{} parsed; unknown keys ignored: {}", path.display(), unknown_keys.join(", ")),
+        )
+        .hint("check the key names against `config init` output"),
+        Ok(LoadedConfig { path: Some(path), .. }) => Check::new("config", Status::Ok, format!("{} parsed", path.display())),
+    }
+}
+
+/// Optional features compiled into this binary
+fn check_features(features: &[(&'static str, bool)]) -> Check {
+    let list: Vec<String> = features
+        .iter()
+        .map(|(name, enabled)| format!("{} {}", name, if *enabled { "yes" } else { "no" }))
+        .collect();
+    let check = Check::new("features", Status::Ok, list.join(", "));
+
+    if features.iter().all(|(_, enabled)| *enabled) {
+        check
+    } else {
+        check.hint("rebuild with --features clipboard,qr,wizard,keyring to enable the missing ones")
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Clipboard backend arboard will use on this platform, or `None` without a display server
+fn clipboard_backend() -> Option<&'static str> {
+    if cfg!(target_os = "windows") {
+        Some("Windows")
+    } else if cfg!(target_os = "macos") {
+        Some("macOS")
+    } else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        Some("Wayland")
+    } else if std::env::var_os("DISPLAY").is_some() {
+        Some("X11")
+    } else {
+        None
+    }
+}
+
+/// Opens the system clipboard once, to see whether `--copy` could use it
+#[cfg(feature = "clipboard")]
+fn open_clipboard() -> Result<(), String> {
+    arboard::Clipboard::new().map(drop).map_err(|e| e.to_string())
+}
+
+/// Without the `clipboard` feature there is no system clipboard to open
+#[cfg(not(feature = "clipboard"))]
+fn open_clipboard() -> Result<(), String> {
+    Err(String::from("not compiled in"))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Answers every probe from its fields; the default is a healthy system
+    #[derive(Clone)]
+    struct StubProbe {
+        terminals: (bool, bool),
+        width: Option<u16>,
+        no_color: bool,
+        clipboard: ClipboardProbe,
+        rng: Result<(), String>,
+        locale_env: Option<(&'static str, String)>,
+        features: Vec<(&'static str, bool)>,
+    }
+
+    impl Default for StubProbe {
+        fn default() -> Self {
+            StubProbe {
+                terminals: (true, true),
+                width: Some(80),
+                no_color: false,
+                clipboard: ClipboardProbe::Backend("X11", Ok(())),
+                rng: Ok(()),
+                locale_env: None,
+                features: FEATURES.iter().map(|(name, _)| (*name, true)).collect(),
+            }
+        }
+    }
+
+    impl Probe for StubProbe {
+        fn terminals(&self) -> (bool, bool) {
+            self.terminals
+        }
+
+        fn terminal_width(&self) -> Option<u16> {
+            self.width
+        }
+
+        fn no_color(&self) -> bool {
+            self.no_color
+        }
+
+        fn clipboard(&self) -> ClipboardProbe {
+            self.clipboard.clone()
+        }
+
+        fn rng(&self) -> Result<(), String> {
+            self.rng.clone()
+        }
+
+        fn locale_env(&self) -> Option<(&'static str, String)> {
+            self.locale_env.clone()
+        }
+
+        fn features(&self) -> Vec<(&'static str, bool)> {
+            self.features.clone()
+        }
+    }
+
+    fn loaded(path: &str, found: bool, unknown_keys: &[&str]) -> Result<LoadedConfig, String> {
+        Ok(LoadedConfig {
+            path: Some(PathBuf::from(path)),
+            found,
+            unknown_keys: unknown_keys.iter().map(|key| key.to_string()).collect(),
+            ..Default::default()
+        })
+    }
+
+    /// Status and detail of one check
+    fn outcome(check: Check) -> (Status, String) {
+        (check.status, check.detail)
+    }
+
+    #[test]
+    fn healthy_system_passes_every_check() {
+        let checks = run_checks(&StubProbe::default(), true, None, &loaded("/etc/rustypass.toml", true, &[]));
+        let names: Vec<&str> = checks.iter().map(|c| c.name).collect();
+        assert_eq!(names, ["terminal", "terminal width", "color", "clipboard", "random source", "locale", "config", "features"]);
+        assert!(checks.iter().all(|c| c.status == Status::Ok), "{:#?}", checks);
+        assert!(checks.iter().all(|c| c.hint.is_none()), "{:#?}", checks);
+    }
+
+    #[test]
+    fn redirected_streams_warn() {
+        let probe = |terminals| StubProbe { terminals, ..Default::default() };
+        assert_eq!(outcome(check_terminals(&probe((true, true)))), (Status::Ok, String::from("stdout: terminal, stderr: terminal")));
+        assert_eq!(outcome(check_terminals(&probe((false, true)))), (Status::Warn, String::from("stdout: redirected, stderr: terminal")));
+        assert_eq!(outcome(check_terminals(&probe((true, false)))), (Status::Warn, String::from("stdout: terminal, stderr: redirected")));
+    }
+
+    #[test]
+    fn narrow_or_unknown_widths_warn() {
+        let probe = |width| StubProbe { width, ..Default::default() };
+        assert_eq!(outcome(check_terminal_width(&probe(Some(60)))), (Status::Ok, String::from("60 columns")));
+        assert_eq!(outcome(check_terminal_width(&probe(Some(59)))), (Status::Warn, String::from("59 columns")));
+        assert_eq!(outcome(check_terminal_width(&probe(None))), (Status::Warn, String::from("unknown")));
+    }
+
+    #[test]
+    fn color_is_informational() {
+        let probe = |no_color| StubProbe { no_color, ..Default::default() };
+        assert_eq!(outcome(check_color(&probe(true), true)), (Status::Ok, String::from("enabled")));
+        assert_eq!(outcome(check_color(&probe(true), false)), (Status::Ok, String::from("disabled (NO_COLOR is set)")));
+        let check = check_color(&probe(false), false);
+        assert_eq!(check.detail, "disabled");
+        assert_eq!(check.hint.as_deref(), Some("pass --color always to force colors"));
+    }
+
+    #[test]
+    fn clipboard_fails_without_a_usable_backend() {
+        let probe = |clipboard| StubProbe { clipboard, ..Default::default() };
+        let cases = [
+            (ClipboardProbe::Backend("Wayland", Ok(())), Status::Ok, "available (Wayland)"),
+            (ClipboardProbe::Backend("X11", Err(String::from("connection refused"))), Status::Fail, "X11 clipboard unavailable: connection refused"),
+            (ClipboardProbe::NoDisplay, Status::Fail, "no display server found"),
+            (ClipboardProbe::NotCompiled, Status::Warn, "not compiled in"),
+        ];
+        for (clipboard, status, detail) in cases {
+            let check = check_clipboard(&probe(clipboard));
+            assert_eq!((check.status, check.detail.as_str()), (status, detail));
+            assert_eq!(check.hint.is_some(), status != Status::Ok, "{}", detail);
+        }
+    }
+
+    #[test]
+    fn rng_failure_fails() {
+        assert_eq!(outcome(check_rng(&StubProbe::default())), (Status::Ok, String::from("operating system RNG responds")));
+        let probe = StubProbe { rng: Err(String::from("getrandom: ENOSYS")), ..Default::default() };
+        let check = check_rng(&probe);
+        assert_eq!((check.status, check.detail.as_str()), (Status::Fail, "operating system RNG failed: getrandom: ENOSYS"));
+        assert_eq!(check.hint.as_deref(), Some("passwords cannot be generated safely on this system"));
+    }
+
+    #[test]
+    fn locale_names_its_source() {
+        let german = StubProbe { locale_env: Some(("LANG", String::from("de_DE.UTF-8"))), ..Default::default() };
+        let cases = [
+            (StubProbe::default(), Some(Locale::De), None, "German (from --lang)"),
+            (german.clone(), None, Some(Locale::En), "German (from LANG=de_DE.UTF-8)"),
+            (german, Some(Locale::En), None, "English (from --lang)"),
+            (StubProbe::default(), None, Some(Locale::De), "German (from config file)"),
+            (StubProbe::default(), None, None, "English (from default)"),
+        ];
+        for (probe, lang, configured, detail) in cases {
+            assert_eq!(outcome(check_locale(&probe, lang, configured)), (Status::Ok, detail.to_string()));
+        }
+    }
+
+    #[test]
+    fn config_problems_warn_or_fail() {
+        let cases = [
+            (loaded("/c.toml", true, &[]), Status::Ok, "/c.toml parsed"),
+            (loaded("/c.toml", false, &[]), Status::Ok, "none (/c.toml does not exist)"),
+            (loaded("/c.toml", true, &["generate.lenght"]), Status::Warn, "/c.toml parsed; unknown keys ignored: generate.lenght"),
+            (Ok(LoadedConfig::default()), Status::Warn, "no configuration directory"),
+            (Err(String::from("cannot read /c.toml: denied")), Status::Fail, "cannot read /c.toml: denied"),
+            (Err(String::from("/c.toml: TOML parse error at line 2\n  |\n2 | x =\n  |    ^\ninvalid string")), Status::Fail, "/c.toml: TOML parse error at line 2: invalid string"),
+        ];
+        for (config, status, detail) in cases {
+            assert_eq!(outcome(check_config(&config)), (status, detail.to_string()));
+        }
+    }
+
+    #[test]
+    fn missing_features_only_add_a_hint() {
+        let check = check_features(&[("clipboard", true), ("qr", false)]);
+        assert_eq!((check.status, check.detail.as_str()), (Status::Ok, "clipboard yes, qr no"));
+        assert!(check.hint.is_some());
+        assert!(check_features(&[("clipboard", true)]).hint.is_none());
+    }
+
+    #[test]
+    fn render_aligns_names_and_indents_hints() {
+        let probe = StubProbe { width: Some(40), ..Default::default() };
+        let checks = [check_terminal_width(&probe), check_rng(&probe)];
+        let rendered = render_checks(&checks);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines, [
+            "[WARN] terminal width  40 columns",
+            "                       -> the logo is printed as plain text below 60 columns",
+            "[ OK ] random source   operating system RNG responds",
+        ]);
+        assert!(rendered.ends_with('\n'));
+    }
+}
@@ -0,0 +1,288 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use zeroize::Zeroizing;
+use crate::output;
+
+// ============================================================================
+// Keys and Lines
+// ============================================================================
+
+/// Checks a key name for a dotenv file
+///
+/// # Returns
+/// * `Result<bool, String>` - Whether the name follows the `[A-Z_][A-Z0-9_]*`
+///   convention, or an error for a name dotenv parsers cannot read back (empty,
+///   or with characters other than ASCII letters, digits, `_` and `.`)
+pub(crate) fn check_key(key: &str) -> Result<bool, String> {
+    if key.is_empty() {
+        return Err("an --env-key name is empty".to_string());
+    }
+    if let Some(c) = key.chars().find(|c| !(c.is_ascii_alphanumeric() || *c == '_' || *c == '.')) {
+        return Err(format!("'{}' cannot be used as a dotenv key: it contains '{}'", key, c.escape_default()));
+    }
+    let conventional = !key.starts_with(|c: char| c.is_ascii_digit())
+        && key.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    Ok(conventional)
+}
+
+/// Pairs the `--env-key` names with the number of passwords to generate
+///
+/// # Arguments
+/// * `keys` - Names as given
+/// * `count` - `--count`; 1 takes one password per name
+///
+/// # Returns
+/// * `Result<Vec<String>, String>` - One name per password: the names themselves,
+///   or `NAME_1` to `NAME_N` for a single name with `--count N`; an error for
+///   duplicate names or a count that matches neither
+pub(crate) fn expand_keys(keys: &[String], count: u32) -> Result<Vec<String>, String> {
+    if let Some(duplicate) = keys.iter().enumerate().find_map(|(i, key)| keys[..i].contains(key).then_some(key)) {
+        return Err(format!("--env-key {} is given more than once", duplicate));
+    }
+    match (keys, count) {
+        (_, 1) => Ok(keys.to_vec()),
+        ([key], count) => Ok((1..=count).map(|n| format!("{}_{}", key, n)).collect()),
+        (keys, count) if keys.len() == count as usize => Ok(keys.to_vec()),
+        (keys, count) => Err(format!("--count {} does not match the {} --env-key names; drop --count", count, keys.len())),
+    }
+}
+
+/// Quotes a value for a dotenv file
+///
+/// Single quotes keep `$`, `#`, spaces and line breaks literal. A `'` or `\`
+/// closes the quotes and is written backslash-escaped before they reopen
+/// (`'it'\''s'`): some dotenv parsers (python-dotenv) treat a backslash inside
+/// single quotes as an escape while dotenvy and POSIX shells do not, and
+/// outside quotes all of them read `\'` and `\\` as the bare character.
+pub(crate) fn quote(value: &str) -> Zeroizing<String> {
+    let mut quoted = Zeroizing::new(String::with_capacity(value.len() + 2));
+    let mut inside = false;
+    for c in value.chars() {
+        if c == '\'' || c == '\\' {
+            if inside {
+                quoted.push('\'');
+                inside = false;
+            }
+            quoted.push('\\');
+        } else if !inside {
+            quoted.push('\'');
+            inside = true;
+        }
+        quoted.push(c);
+    }
+    if inside {
+        quoted.push('\'');
+    } else if value.is_empty() {
+        quoted.push_str("''");
+    }
+    quoted
+}
+
+/// Builds a `KEY='value'` line, without a line break
+pub(crate) fn line(key: &str, value: &str) -> Zeroizing<String> {
+    Zeroizing::new(format!("{}={}", key, quote(value).as_str()))
+}
+
+// ============================================================================
+// Files
+// ============================================================================
+
+/// Number of keys `update_file` added and replaced
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Summary {
+    pub(crate) added: usize,
+    pub(crate) replaced: usize,
+}
+
+/// Writes `KEY=value` lines into a dotenv file, replacing keys it already has
+///
+/// # Arguments
+/// * `path` - Dotenv file; created (mode 0600 on Unix) when it does not exist
+/// * `entries` - Key and line from `line` for each value
+///
+/// # Returns
+/// * `Result<Summary, String>` - How many keys were added and replaced
+///
+/// # Notes
+/// The file is replaced atomically with `output::replace_file`. Comments,
+/// blank lines and other keys keep their exact bytes, multi-line quoted values
+/// included. A replaced key stays where it was (with its `export ` prefix, if
+/// any) and later duplicates of it are dropped; new keys go at the end.
+pub(crate) fn update_file(path: &Path, entries: &[(String, Zeroizing<String>)]) -> Result<Summary, String> {
+    let existing = Zeroizing::new(match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(format!("cannot read {}: {}", path.display(), e)),
+    });
+    let (contents, summary) = merge(&existing, entries);
+
+    output::replace_file(path, &contents).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+    log::debug!("{:?} in {}", summary, path.display());
+    Ok(summary)
+}
+
+/// Quoting state at the end of a line, for values spanning several lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quote {
+    None,
+    Single,
+    Double,
+}
+
+/// Puts the entries into the contents of a dotenv file
+fn merge(existing: &[u8], entries: &[(String, Zeroizing<String>)]) -> (Zeroizing<Vec<u8>>, Summary) {
+    let mut contents = Zeroizing::new(Vec::with_capacity(existing.len() + entries.iter().map(|(_, line)| line.len() + 1).sum::<usize>()));
+    let mut written = vec![false; entries.len()];
+    let mut summary = Summary::default();
+    // Inside a value that continues from an earlier line, and whether that value is being dropped
+    let mut open = Quote::None;
+    let mut dropping = false;
+
+    for line in existing.split_inclusive(|&b| b == b'\n') {
+        if open != Quote::None {
+            open = scan(open, line);
+            if !dropping {
+                contents.extend_from_slice(line);
+            }
+            continue;
+        }
+
+        let Some((prefix, key, value)) = split_assignment(line) else {
+            contents.extend_from_slice(line);
+            continue;
+        };
+        open = scan(Quote::None, value);
+        dropping = match entries.iter().position(|(entry_key, _)| entry_key.as_bytes() == key) {
+            None => false,
+            Some(index) if written[index] => true,
+            Some(index) => {
+                contents.extend_from_slice(prefix);
+                contents.extend_from_slice(entries[index].1.as_bytes());
+                // Keep the line's own ending, CRLF included
+                contents.extend_from_slice(if line.ends_with(b"\r\n") { b"\r\n" } else { b"\n" });
+                written[index] = true;
+                summary.replaced += 1;
+                true
+            }
+        };
+        if !dropping {
+            contents.extend_from_slice(line);
+        }
+    }
+
+    for ((_, line), _) in entries.iter().zip(&written).filter(|(_, written)| !**written) {
+        if contents.last().is_some_and(|&b| b != b'\n') {
+            contents.push(b'\n');
+        }
+        contents.extend_from_slice(line.as_bytes());
+        contents.push(b'\n');
+        summary.added += 1;
+    }
+    (contents, summary)
+}
+
+/// Splits `[whitespace][export ]KEY=value` into the prefix up to the key, the key and the value
+fn split_assignment(line: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
+    let indent = line.iter().take_while(|b| b.is_ascii_whitespace()).count();
+    let key_start = if line[indent..].starts_with(b"export ") {
+        indent + b"export ".len() + line[indent + b"export ".len()..].iter().take_while(|b| **b == b' ').count()
+    } else {
+        indent
+    };
+    let equals = key_start + line[key_start..].iter().position(|&b| b == b'=')?;
+    let key = line[key_start..equals].trim_ascii_end();
+    if key.is_empty() || key.starts_with(b"#") {
+        return None;
+    }
+    Some((&line[..key_start], key, &line[equals + 1..]))
+}
+
+/// Follows quoting through a value (or a continuation line), returning the state at its end
+fn scan(mut state: Quote, bytes: &[u8]) -> Quote {
+    let mut escaped = false;
+    let mut previous = b' ';
+    for &b in bytes {
+        match state {
+            _ if escaped => escaped = false,
+            Quote::Single if b == b'\'' => state = Quote::None,
+            Quote::Double if b == b'\\' => escaped = true,
+            Quote::Double if b == b'"' => state = Quote::None,
+            Quote::None if b == b'\\' => escaped = true,
+            Quote::None if b == b'\'' => state = Quote::Single,
+            Quote::None if b == b'"' => state = Quote::Double,
+            // An unquoted ` #` starts a comment, whose quotes do not count
+            Quote::None if b == b'#' && previous.is_ascii_whitespace() => return Quote::None,
+            _ => {}
+        }
+        previous = b;
+    }
+    state
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Awkward values: every character dotenv or a shell treats specially, alone and together
+    const VALUES: [&str; 12] = [
+        "plain",
+        "",
+        "it's",
+        "back\\slash",
+        "trailing\\",
+        "\\'\\''",
+        "say \"hi\"",
+        "$HOME and ${PATH}",
+        "a #not a comment",
+        "two\nlines",
+        "\nleading and trailing\n",
+        "'\"$#\\\n mixed \\n",
+    ];
+
+    /// Reads a dotenv document back with dotenvy, in order
+    fn parse(contents: &[u8]) -> Vec<(String, String)> {
+        dotenvy::from_read_iter(contents).map(|item| item.unwrap()).collect()
+    }
+
+    #[test]
+    fn quoted_values_read_back_with_dotenvy() {
+        let lines: Vec<String> = VALUES.iter().enumerate().map(|(i, value)| line(&format!("KEY_{}", i), value).to_string()).collect();
+        let parsed = parse(format!("{}\n", lines.join("\n")).as_bytes());
+
+        assert_eq!(parsed.len(), VALUES.len());
+        for (i, (key, value)) in parsed.iter().enumerate() {
+            assert_eq!(key, &format!("KEY_{}", i));
+            assert_eq!(value, VALUES[i], "{}", lines[i]);
+        }
+    }
+
+    #[test]
+    fn merged_files_read_back_with_dotenvy() {
+        let existing = "# secrets\nexport KEY_3='old'\nOTHER=\"multi\nline\"\nKEY_3='duplicate'\n";
+        let entries: Vec<(String, Zeroizing<String>)> = VALUES.iter().enumerate().map(|(i, value)| (format!("KEY_{}", i), line(&format!("KEY_{}", i), value))).collect();
+        let (contents, summary) = merge(existing.as_bytes(), &entries);
+        assert_eq!(summary, Summary { added: VALUES.len() - 1, replaced: 1 });
+
+        let parsed = parse(&contents);
+        assert_eq!(parsed[0], (String::from("KEY_3"), String::from(VALUES[3])));
+        assert_eq!(parsed[1], (String::from("OTHER"), String::from("multi\nline")));
+        assert_eq!(parsed.len(), VALUES.len() + 1);
+        for (key, value) in &parsed[2..] {
+            let index: usize = key.strip_prefix("KEY_").unwrap().parse().unwrap();
+            assert_eq!(value, VALUES[index], "{}", key);
+        }
+    }
+
+    #[test]
+    fn quoting_is_minimal() {
+        assert_eq!(quote("abc").as_str(), "'abc'");
+        assert_eq!(quote("").as_str(), "''");
+        assert_eq!(quote("it's").as_str(), "'it'\\''s'");
+        assert_eq!(quote("\\").as_str(), "\\\\");
+    }
+}
@@ -0,0 +1,187 @@
+use std::io;
+use std::path::PathBuf;
+
+// ============================================================================
+// Exit Codes
+// ============================================================================
+
+/// The generator options, an options document or a policy file cannot be used as given
+pub const EXIT_INVALID_SETTINGS: i32 = 3;
+
+/// A policy was valid, but no password satisfying it could be generated
+pub const EXIT_UNSATISFIABLE: i32 = 4;
+
+/// An input file or the operating system's random source could not be read
+pub const EXIT_IO: i32 = 5;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors of the generation path and the file loaders
+///
+/// Other commands still report plain `String` messages through `Context::fail`.
+#[derive(Debug, thiserror::Error)]
+pub enum RustyPassError {
+    /// The requested length is outside the supported range
+    #[error("a length of {got} is not supported; choose between {min} and {max} characters")]
+    InvalidLength { got: u32, min: u32, max: u32 },
+
+    /// No character is left to draw from
+    #[error("the character set is empty: include at least one class or custom character")]
+    EmptyCharset,
+
+    /// The options contradict each other, e.g. minimums longer than the password
+    #[error("{0}")]
+    ConflictingConstraints(String),
+
+    /// No candidate satisfied the policy, optionally with the reason it never could
+    #[error(
+        "could not generate a password satisfying policy '{policy}'{}",
+        reason.as_deref().map(|r| format!(": {}", r)).unwrap_or_default()
+    )]
+    PolicyUnsatisfiable { policy: String, reason: Option<String> },
+
+    /// A policy file is not valid TOML or has unknown keys
+    #[error("{}: {message}", path.display())]
+    InvalidPolicy { path: PathBuf, message: String },
+
+    /// An options document is not valid JSON or has unknown keys
+    #[error("{}: {message}", path.display())]
+    InvalidOptions { path: PathBuf, message: String },
+
+    /// The operating system could not seed the random number generator
+    #[error("the operating system's random number generator failed: {0}")]
+    RandomSource(String),
+
+    /// A file could not be read
+    #[error("cannot read {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
+
+impl RustyPassError {
+    /// Exit status the process ends with when this error is fatal
+    ///
+    /// # Returns
+    /// * `EXIT_INVALID_SETTINGS` - Invalid length, empty charset, conflicting constraints or a broken
+    ///   options or policy file
+    /// * `EXIT_UNSATISFIABLE` - A valid policy no password could satisfy
+    /// * `EXIT_IO` - An unreadable input file or random source
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RustyPassError::InvalidLength { .. }
+            | RustyPassError::EmptyCharset
+            | RustyPassError::ConflictingConstraints(_)
+            | RustyPassError::InvalidPolicy { .. }
+            | RustyPassError::InvalidOptions { .. } => EXIT_INVALID_SETTINGS,
+            RustyPassError::PolicyUnsatisfiable { .. } => EXIT_UNSATISFIABLE,
+            RustyPassError::RandomSource(_) | RustyPassError::Io { .. } => EXIT_IO,
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{os_rng, Charset, GeneratorOptions, PasswordGenerator};
+    use crate::policy::Policy;
+
+    fn build(options: GeneratorOptions) -> RustyPassError {
+        PasswordGenerator::new(&options, os_rng().unwrap()).unwrap_err()
+    }
+
+    #[test]
+    fn lengths_outside_the_range_are_invalid_length() {
+        for length in [0, 7, 129, u32::MAX] {
+            let error = build(GeneratorOptions { length, ..Default::default() });
+            assert!(matches!(error, RustyPassError::InvalidLength { got, min: 8, max: 128 } if got == length), "{:?}", error);
+            assert_eq!(error.exit_code(), EXIT_INVALID_SETTINGS);
+        }
+        let error = build(GeneratorOptions { length: 7, ..Default::default() });
+        assert_eq!(error.to_string(), "a length of 7 is not supported; choose between 8 and 128 characters");
+    }
+
+    #[test]
+    fn no_class_at_all_is_empty_charset() {
+        let error = build(GeneratorOptions { include_lowercase: false, ..Default::default() });
+        assert!(matches!(error, RustyPassError::EmptyCharset), "{:?}", error);
+        assert_eq!(error.exit_code(), EXIT_INVALID_SETTINGS);
+    }
+
+    #[test]
+    fn custom_characters_all_excluded_is_empty_charset() {
+        let options = GeneratorOptions {
+            include_lowercase: false,
+            custom_charset: Charset::from("xyz"),
+            exclude: Charset::from("zyx"),
+            ..Default::default()
+        };
+        assert!(matches!(build(options), RustyPassError::EmptyCharset));
+    }
+
+    #[test]
+    fn contradicting_options_are_conflicting_constraints() {
+        for (options, message) in [
+            (
+                GeneratorOptions { min_digits: 2, ..Default::default() },
+                "a minimum of 2 is set for digits characters, which are not included",
+            ),
+            (
+                GeneratorOptions { include_digits: true, exclude: Charset::from("0123456789"), ..Default::default() },
+                "every digits character is excluded",
+            ),
+            (
+                GeneratorOptions { length: 8, include_digits: true, min_digits: 5, min_lowercase: 4, ..Default::default() },
+                "the minimum counts need 9 characters, but the password has only 8",
+            ),
+        ] {
+            let error = build(options);
+            assert!(matches!(&error, RustyPassError::ConflictingConstraints(m) if m == message), "{:?}", error);
+            assert_eq!(error.exit_code(), EXIT_INVALID_SETTINGS);
+        }
+    }
+
+    #[test]
+    fn an_impossible_policy_is_unsatisfiable() {
+        let policy = Policy { name: String::from("huge"), min_length: 300, ..Default::default() };
+        let error = PasswordGenerator::with_policy(&GeneratorOptions::default(), &policy, os_rng().unwrap()).unwrap_err();
+        assert!(matches!(&error, RustyPassError::PolicyUnsatisfiable { policy, .. } if policy == "huge"), "{:?}", error);
+        assert_eq!(error.exit_code(), EXIT_UNSATISFIABLE);
+        assert_eq!(error.to_string(), "could not generate a password satisfying policy 'huge'");
+    }
+
+    #[test]
+    fn every_variant_maps_to_its_exit_code() {
+        let io = || io::Error::new(io::ErrorKind::NotFound, "gone");
+        for (error, code) in [
+            (RustyPassError::InvalidLength { got: 1, min: 8, max: 128 }, EXIT_INVALID_SETTINGS),
+            (RustyPassError::EmptyCharset, EXIT_INVALID_SETTINGS),
+            (RustyPassError::ConflictingConstraints(String::from("x")), EXIT_INVALID_SETTINGS),
+            (RustyPassError::InvalidPolicy { path: PathBuf::from("p.toml"), message: String::from("x") }, EXIT_INVALID_SETTINGS),
+            (RustyPassError::InvalidOptions { path: PathBuf::from("o.json"), message: String::from("x") }, EXIT_INVALID_SETTINGS),
+            (RustyPassError::PolicyUnsatisfiable { policy: String::from("p"), reason: Some(String::from("why")) }, EXIT_UNSATISFIABLE),
+            (RustyPassError::RandomSource(String::from("x")), EXIT_IO),
+            (RustyPassError::Io { path: PathBuf::from("f"), source: io() }, EXIT_IO),
+        ] {
+            assert_eq!(error.exit_code(), code, "{:?}", error);
+        }
+    }
+
+    #[test]
+    fn messages_name_the_file_and_keep_the_source() {
+        let error = RustyPassError::Io { path: PathBuf::from("missing.toml"), source: io::Error::new(io::ErrorKind::NotFound, "gone") };
+        assert_eq!(error.to_string(), "cannot read missing.toml: gone");
+        assert!(std::error::Error::source(&error).is_some());
+
+        let error = RustyPassError::PolicyUnsatisfiable { policy: String::from("p"), reason: Some(String::from("no length fits")) };
+        assert_eq!(error.to_string(), "could not generate a password satisfying policy 'p': no length fits");
+    }
+}
@@ -0,0 +1,298 @@
+use std::borrow::Cow;
+use serde::Serialize;
+use zeroize::Zeroizing;
+use crate::generator::{GeneratedPassword, GenerationMetadata};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Column layout KeePass and KeePassXC read when importing a CSV file
+const KEEPASS_HEADER: [&str; 6] = ["Group", "Title", "Username", "Password", "URL", "Notes"];
+
+/// Group the imported entries land in; KeePassXC maps it to the database's root group
+const KEEPASS_GROUP: &str = "Root";
+
+/// Bitwarden's item type for logins
+const BITWARDEN_LOGIN: u8 = 1;
+
+/// Title of the entries when `--title-prefix` is not given
+pub(crate) const DEFAULT_TITLE_PREFIX: &str = "Generated password";
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Password manager import formats for `generate --export`
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ExportFormat {
+    /// CSV with the `Group,Title,Username,Password,URL,Notes` columns KeePass and KeePassXC import
+    KeepassCsv,
+    /// Bitwarden's unencrypted JSON export, with one login item per password
+    BitwardenJson,
+}
+
+/// One generated password, as written to an export
+#[derive(Debug)]
+pub(crate) struct Entry<'a> {
+    pub(crate) title: String,
+    pub(crate) username: &'a str,
+    pub(crate) password: &'a str,
+    /// `--url`, or empty
+    pub(crate) url: &'a str,
+}
+
+/// Top level of a Bitwarden JSON import
+///
+/// Only the fields the importer reads are written; it assigns item IDs itself.
+/// `folders` is always empty, so the items land outside any folder. Tests read
+/// an export back into these structs, rejecting any field they do not declare.
+#[derive(Debug, Serialize)]
+#[cfg_attr(test, derive(PartialEq, serde::Deserialize), serde(deny_unknown_fields))]
+struct BitwardenExport<'a> {
+    encrypted: bool,
+    folders: Vec<serde_json::Value>,
+    items: Vec<BitwardenItem<'a>>,
+}
+
+/// One login item
+#[derive(Debug, Serialize)]
+#[cfg_attr(test, derive(PartialEq, serde::Deserialize), serde(deny_unknown_fields))]
+struct BitwardenItem<'a> {
+    #[serde(rename = "type")]
+    kind: u8,
+    name: Cow<'a, str>,
+    notes: Cow<'a, str>,
+    favorite: bool,
+    login: BitwardenLogin<'a>,
+}
+
+/// Login of an item; an entry without a user gets `"username": null`, as Bitwarden exports it
+#[derive(Debug, Serialize)]
+#[cfg_attr(test, derive(PartialEq, serde::Deserialize), serde(deny_unknown_fields))]
+struct BitwardenLogin<'a> {
+    uris: Vec<BitwardenUri<'a>>,
+    username: Option<Cow<'a, str>>,
+    password: Cow<'a, str>,
+    totp: Option<Cow<'a, str>>,
+}
+
+/// Website of a login; `match: null` keeps the account's default URI matching
+#[derive(Debug, Serialize)]
+#[cfg_attr(test, derive(PartialEq, serde::Deserialize), serde(deny_unknown_fields))]
+struct BitwardenUri<'a> {
+    #[serde(rename = "match")]
+    match_detection: Option<u8>,
+    uri: Cow<'a, str>,
+}
+
+// ============================================================================
+// Entries
+// ============================================================================
+
+/// Titles the generated passwords and pairs them with their users
+///
+/// # Arguments
+/// * `passwords` - Generated passwords
+/// * `users` - Usernames from `--users-file`, one per password, if any
+/// * `title_prefix` - Start of every title
+/// * `url` - Website of every entry, or empty
+///
+/// # Returns
+/// * `Vec<Entry>` - Titled `PREFIX alice` with users, `PREFIX 1` to `PREFIX N`
+///   without, and just the prefix for a single password without a user
+pub(crate) fn entries<'a>(
+    passwords: &'a [GeneratedPassword],
+    users: Option<&'a [String]>,
+    title_prefix: &str,
+    url: &'a str,
+) -> Vec<Entry<'a>> {
+    passwords
+        .iter()
+        .enumerate()
+        .map(|(index, generated)| {
+            let username = users.map_or("", |users| users[index].as_str());
+            let title = match (username, passwords.len()) {
+                ("", 1) => title_prefix.to_string(),
+                ("", _) => format!("{} {}", title_prefix, index + 1),
+                (username, _) => format!("{} {}", title_prefix, username),
+            };
+            Entry { title, username, password: &generated.password, url }
+        })
+        .collect()
+}
+
+/// Describes how and when the passwords were generated, for the entries' notes
+///
+/// # Arguments
+/// * `metadata` - Effective settings of the run
+/// * `timestamp` - RFC 3339 time of the run
+pub(crate) fn notes(metadata: &GenerationMetadata, timestamp: &str) -> String {
+    let mut notes = format!(
+        "Generated by {} {} on {}: length {}, {}, {:.1} bits of entropy",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        timestamp,
+        metadata.length,
+        metadata.classes.join(", "),
+        metadata.entropy_bits
+    );
+    if let Some(policy) = &metadata.policy {
+        notes.push_str(&format!(", policy {}", policy));
+    }
+    notes
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+/// Renders the entries in an import format
+///
+/// # Arguments
+/// * `entries` - Entries from `entries`
+/// * `format` - Import format
+/// * `notes` - Notes attached to every entry, from `notes`
+///
+/// # Returns
+/// * `Result<Zeroizing<String>, String>` - The file contents, or an error if they could not be written
+pub(crate) fn render(entries: &[Entry], format: ExportFormat, notes: &str) -> Result<Zeroizing<String>, String> {
+    match format {
+        ExportFormat::KeepassCsv => {
+            let mut buffer = Zeroizing::new(Vec::new());
+            write_keepass_csv(entries, notes, &mut *buffer).map_err(|e| format!("failed to write CSV: {}", e))?;
+            Ok(Zeroizing::new(String::from_utf8_lossy(&buffer).into_owned()))
+        }
+        ExportFormat::BitwardenJson => serde_json::to_string_pretty(&bitwarden(entries, notes))
+            .map(|json| Zeroizing::new(format!("{}\n", json)))
+            .map_err(|e| format!("failed to serialize the Bitwarden export: {}", e)),
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Writes the entries as KeePass CSV, quoting every field the way KeePassXC exports them
+fn write_keepass_csv<W: std::io::Write>(entries: &[Entry], notes: &str, writer: W) -> csv::Result<()> {
+    let mut csv = csv::WriterBuilder::new().quote_style(csv::QuoteStyle::Always).from_writer(writer);
+    csv.write_record(KEEPASS_HEADER)?;
+    for entry in entries {
+        csv.write_record([KEEPASS_GROUP, &entry.title, entry.username, entry.password, entry.url, notes])?;
+    }
+    csv.flush()?;
+    Ok(())
+}
+
+/// Builds Bitwarden login items for the entries
+fn bitwarden<'a>(entries: &'a [Entry], notes: &'a str) -> BitwardenExport<'a> {
+    let items = entries
+        .iter()
+        .map(|entry| BitwardenItem {
+            kind: BITWARDEN_LOGIN,
+            name: Cow::Borrowed(&entry.title),
+            notes: Cow::Borrowed(notes),
+            favorite: false,
+            login: BitwardenLogin {
+                uris: (!entry.url.is_empty())
+                    .then_some(BitwardenUri { match_detection: None, uri: Cow::Borrowed(entry.url) })
+                    .into_iter()
+                    .collect(),
+                username: (!entry.username.is_empty()).then_some(Cow::Borrowed(entry.username)),
+                password: Cow::Borrowed(entry.password),
+                totp: None,
+            },
+        })
+        .collect();
+    BitwardenExport { encrypted: false, folders: Vec::new(), items }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::GenerationFlags;
+
+    fn entry<'a>(title: &str, username: &'a str, password: &'a str, url: &'a str) -> Entry<'a> {
+        Entry { title: title.to_string(), username, password, url }
+    }
+
+    #[test]
+    fn keepass_csv_has_the_keepassxc_header_and_quotes_every_field() {
+        let entries = [entry("Mail alice", "alice", "a,b\"c", "https://mail.example.com"), entry("Mail 2", "", "plain", "")];
+        let csv = render(&entries, ExportFormat::KeepassCsv, "Generated, \"quoted\"").unwrap();
+
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(r#""Group","Title","Username","Password","URL","Notes""#));
+        assert_eq!(
+            lines.next(),
+            Some(r#""Root","Mail alice","alice","a,b""c","https://mail.example.com","Generated, ""quoted""""#)
+        );
+        assert_eq!(lines.next(), Some(r#""Root","Mail 2","","plain","","Generated, ""quoted""""#));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn keepass_csv_reads_back_with_the_csv_crate() {
+        let passwords = ["comma,separated", "\"quoted\"", "both,\"of\",them", "new\nline", " spaced "];
+        let entries: Vec<Entry> = passwords.iter().map(|password| entry("Title", "user", password, "")).collect();
+        let csv = render(&entries, ExportFormat::KeepassCsv, "notes").unwrap();
+
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        assert_eq!(reader.headers().unwrap(), KEEPASS_HEADER.as_slice());
+        let records: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(records.len(), passwords.len());
+        for (record, password) in records.iter().zip(passwords) {
+            assert_eq!(record, vec![KEEPASS_GROUP, "Title", "user", password, "", "notes"]);
+        }
+    }
+
+    #[test]
+    fn titles_are_numbered_or_named_after_the_user() {
+        let flags = GenerationFlags { uppercase_chars: false, special_chars: false, numbers: false, policy: None };
+        let generated = |password: &str| GeneratedPassword::new(password.to_string(), flags.clone(), 26);
+        let passwords = [generated("one"), generated("two")];
+        let users = [String::from("alice"), String::from("bob")];
+
+        let titles = |users: Option<&[String]>, count: usize| -> Vec<String> {
+            entries(&passwords[..count], users, "Prefix", "").into_iter().map(|e| e.title).collect()
+        };
+        assert_eq!(titles(None, 1), ["Prefix"]);
+        assert_eq!(titles(None, 2), ["Prefix 1", "Prefix 2"]);
+        assert_eq!(titles(Some(&users), 2), ["Prefix alice", "Prefix bob"]);
+    }
+
+    #[test]
+    fn bitwarden_json_reads_back_into_the_export_structs() {
+        let entries = [
+            entry("Mail alice", "alice", "a\"b\\c", "https://mail.example.com"),
+            entry("Mail 2", "", "pl\u{e4}in", ""),
+        ];
+        let json = render(&entries, ExportFormat::BitwardenJson, "Generated\nby rustypass").unwrap();
+
+        let parsed: BitwardenExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, bitwarden(&entries, "Generated\nby rustypass"));
+        assert!(!parsed.encrypted);
+        assert!(parsed.folders.is_empty());
+        let login = &parsed.items[0].login;
+        assert_eq!((parsed.items[0].kind, login.password.as_ref()), (BITWARDEN_LOGIN, "a\"b\\c"));
+        assert_eq!(login.uris[0].uri, "https://mail.example.com");
+        assert_eq!(parsed.items[1].login.username, None);
+        assert!(parsed.items[1].login.uris.is_empty());
+    }
+
+    #[test]
+    fn bitwarden_json_uses_the_importer_field_names() {
+        let json = render(&[entry("Title", "", "secret", "https://example.com")], ExportFormat::BitwardenJson, "").unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let item = &value["items"][0];
+        assert_eq!(item["type"], 1);
+        assert_eq!(item["login"]["username"], serde_json::Value::Null);
+        assert_eq!(item["login"]["totp"], serde_json::Value::Null);
+        assert_eq!(item["login"]["uris"][0]["match"], serde_json::Value::Null);
+        assert!(json.ends_with("}\n"));
+    }
+}
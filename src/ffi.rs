@@ -0,0 +1,294 @@
+//! C bindings for embedding the generator and analyzer in non-Rust applications
+//!
+//! The matching header is `include/rustypass.h`, generated with
+//! `cbindgen --config cbindgen.toml --output include/rustypass.h`.
+//!
+//! # Ownership
+//! - Every string crossing the boundary is UTF-8 and NUL-terminated.
+//! - Strings passed in are only borrowed for the duration of the call.
+//! - Strings handed out (`rustypass_generate`'s password, `AnalysisReportC::json`)
+//!   belong to the caller, who must release each exactly once with
+//!   `rustypass_free_string`, never with `free`.
+//!
+//! # Errors
+//! Every function that can fail returns `RUSTYPASS_OK` or one of the
+//! `RUSTYPASS_ERR_*` codes; output parameters are only valid on success.
+//! A panic never unwinds into the caller: it is caught and reported as
+//! `RUSTYPASS_ERR_PANIC`.
+
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use zeroize::Zeroizing;
+use crate::analyzer::{self, AnalyzerOptions};
+use crate::error::{EXIT_INVALID_SETTINGS, EXIT_IO, EXIT_UNSATISFIABLE};
+use crate::generator::{os_rng, Charset, GeneratorOptions, PasswordGenerator};
+
+// ============================================================================
+// Error Codes
+// ============================================================================
+
+/// The call succeeded
+pub const RUSTYPASS_OK: i32 = 0;
+
+/// A required pointer was null or a string was not valid UTF-8
+pub const RUSTYPASS_ERR_INVALID_ARGUMENT: i32 = 2;
+
+/// The options cannot be used as given (same as exit status 3)
+pub const RUSTYPASS_ERR_INVALID_SETTINGS: i32 = 3;
+
+/// No password satisfying the constraints could be generated (same as exit status 4)
+pub const RUSTYPASS_ERR_UNSATISFIABLE: i32 = 4;
+
+/// The operating system's random source failed (same as exit status 5)
+pub const RUSTYPASS_ERR_IO: i32 = 5;
+
+/// The library panicked; the call had no effect
+pub const RUSTYPASS_ERR_PANIC: i32 = 101;
+
+// The codes must keep matching the process exit statuses they are documented as
+const _: () = assert!(
+    RUSTYPASS_ERR_INVALID_SETTINGS == EXIT_INVALID_SETTINGS
+        && RUSTYPASS_ERR_UNSATISFIABLE == EXIT_UNSATISFIABLE
+        && RUSTYPASS_ERR_IO == EXIT_IO
+);
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Generator options, the C counterpart of `GeneratorOptions`
+///
+/// Start from `rustypass_default_options()` and change what you need.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GenerateOptionsC {
+    /// Password length in characters (8-128)
+    pub length: u32,
+    /// Include lowercase letters (a-z)
+    pub include_lowercase: bool,
+    /// Include uppercase letters (A-Z)
+    pub include_uppercase: bool,
+    /// Include digits (0-9)
+    pub include_digits: bool,
+    /// Include symbols
+    pub include_symbols: bool,
+    /// Extra characters added to the alphabet, or null for none
+    pub custom_chars: *const c_char,
+    /// Characters never used, or null for none
+    pub excluded_chars: *const c_char,
+    /// Minimum number of lowercase letters
+    pub min_lowercase: u32,
+    /// Minimum number of uppercase letters
+    pub min_uppercase: u32,
+    /// Minimum number of digits
+    pub min_digits: u32,
+    /// Minimum number of symbols
+    pub min_symbols: u32,
+}
+
+/// Summary of a password analysis, the C counterpart of `AnalysisReport`
+#[repr(C)]
+#[derive(Debug)]
+pub struct AnalysisReportC {
+    /// Password length in characters (Unicode scalar values)
+    pub length: usize,
+    /// Estimated size of the alphabet the password was drawn from
+    pub charset_size: u32,
+    /// Brute-force entropy in bits
+    pub entropy_bits: f64,
+    /// Entropy in bits after dictionary and pattern penalties
+    pub effective_entropy_bits: f64,
+    /// Estimated seconds to guess the password at bcrypt speed
+    pub crack_time_seconds: f64,
+    /// Strength score from 0 to 100
+    pub score: u8,
+    /// Letter grade: 'A', 'B', 'C', 'D' or 'F'
+    pub grade: c_char,
+    /// Number of weaknesses found
+    pub warning_count: usize,
+    /// The full report as JSON, shaped like `analyze --output json`;
+    /// release it with `rustypass_free_string`
+    pub json: *mut c_char,
+}
+
+// ============================================================================
+// Exported Functions
+// ============================================================================
+
+/// Options of `generate` without flags: a 16-character lowercase password
+#[unsafe(no_mangle)]
+pub extern "C" fn rustypass_default_options() -> GenerateOptionsC {
+    let options = GeneratorOptions::default();
+    GenerateOptionsC {
+        length: options.length,
+        include_lowercase: options.include_lowercase,
+        include_uppercase: options.include_uppercase,
+        include_digits: options.include_digits,
+        include_symbols: options.include_symbols,
+        custom_chars: ptr::null(),
+        excluded_chars: ptr::null(),
+        min_lowercase: options.min_lowercase as u32,
+        min_uppercase: options.min_uppercase as u32,
+        min_digits: options.min_digits as u32,
+        min_symbols: options.min_symbols as u32,
+    }
+}
+
+/// Generates one password
+///
+/// # Arguments
+/// * `options` - Generator options, or null for the defaults
+/// * `out` - Receives the password on success (null otherwise); the caller
+///   releases it with `rustypass_free_string`
+///
+/// # Returns
+/// * `i32` - `RUSTYPASS_OK`, `RUSTYPASS_ERR_INVALID_ARGUMENT` (null `out`, non-UTF-8
+///   character lists), `RUSTYPASS_ERR_INVALID_SETTINGS`, `RUSTYPASS_ERR_IO` or
+///   `RUSTYPASS_ERR_PANIC`
+///
+/// # Safety
+/// `options` must be null or point to a valid `GenerateOptionsC` whose character
+/// lists are null or NUL-terminated; `out` must be null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rustypass_generate(options: *const GenerateOptionsC, out: *mut *mut c_char) -> i32 {
+    guard(|| {
+        if out.is_null() {
+            return Err(RUSTYPASS_ERR_INVALID_ARGUMENT);
+        }
+        // SAFETY: `out` is non-null and the caller guarantees it is valid for writes
+        unsafe { *out = ptr::null_mut() };
+
+        // SAFETY: the caller guarantees `options` is null or valid, and so are its strings
+        let options = match unsafe { options.as_ref() } {
+            Some(options) => unsafe { options.to_options() }?,
+            None => GeneratorOptions::default(),
+        };
+        let rng = os_rng().map_err(|e| e.exit_code())?;
+        let password = PasswordGenerator::new(&options, rng)
+            .and_then(|mut generator| generator.next_password())
+            .map_err(|e| e.exit_code())?;
+
+        // SAFETY: checked non-null above
+        unsafe { *out = into_c_string(password)? };
+        Ok(())
+    })
+}
+
+/// Analyzes the strength of a password
+///
+/// # Arguments
+/// * `password` - Password to analyze
+/// * `report` - Filled in on success; its `json` field must be released with
+///   `rustypass_free_string`
+///
+/// # Returns
+/// * `i32` - `RUSTYPASS_OK`, `RUSTYPASS_ERR_INVALID_ARGUMENT` (a null pointer or a
+///   non-UTF-8 password) or `RUSTYPASS_ERR_PANIC`
+///
+/// # Safety
+/// `password` must be null or NUL-terminated; `report` must be null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rustypass_analyze(password: *const c_char, report: *mut AnalysisReportC) -> i32 {
+    guard(|| {
+        if report.is_null() {
+            return Err(RUSTYPASS_ERR_INVALID_ARGUMENT);
+        }
+        // SAFETY: the caller guarantees `password` is null or NUL-terminated
+        let password = unsafe { borrow_str(password) }?.ok_or(RUSTYPASS_ERR_INVALID_ARGUMENT)?;
+
+        let analysis = analyzer::analyze_password(password, &AnalyzerOptions::default());
+        let json = serde_json::to_string(&analysis).map_err(|_| RUSTYPASS_ERR_PANIC)?;
+        let summary = AnalysisReportC {
+            length: analysis.length,
+            charset_size: analysis.charset_size,
+            entropy_bits: analysis.entropy_bits,
+            effective_entropy_bits: analysis.effective_entropy_bits,
+            crack_time_seconds: analysis.crack_time_seconds,
+            score: analysis.score,
+            grade: analysis.grade.to_string().as_bytes()[0] as c_char,
+            warning_count: analysis.warnings.len(),
+            json: into_c_string(json)?,
+        };
+
+        // SAFETY: checked non-null above; the caller guarantees it is valid for writes
+        unsafe { report.write(summary) };
+        Ok(())
+    })
+}
+
+/// Releases a string handed out by this library, wiping its contents first
+///
+/// Null is ignored.
+///
+/// # Safety
+/// `string` must be null or a pointer returned by this library that has not been
+/// released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rustypass_free_string(string: *mut c_char) {
+    if string.is_null() {
+        return;
+    }
+    // SAFETY: the caller guarantees the pointer came from `CString::into_raw` here
+    let bytes = Zeroizing::new(unsafe { CString::from_raw(string) }.into_bytes_with_nul());
+    drop(bytes);
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+impl GenerateOptionsC {
+    /// Converts to `GeneratorOptions`, borrowing the character lists
+    ///
+    /// # Safety
+    /// The character lists must be null or NUL-terminated.
+    unsafe fn to_options(self) -> Result<GeneratorOptions, i32> {
+        // SAFETY: forwarded from the caller
+        let charset = |chars: *const c_char| unsafe { borrow_str(chars) }.map(|chars| Charset::from(chars.unwrap_or_default()));
+        Ok(GeneratorOptions {
+            length: self.length,
+            include_lowercase: self.include_lowercase,
+            include_uppercase: self.include_uppercase,
+            include_digits: self.include_digits,
+            include_symbols: self.include_symbols,
+            custom_charset: charset(self.custom_chars)?,
+            exclude: charset(self.excluded_chars)?,
+            min_lowercase: self.min_lowercase as usize,
+            min_uppercase: self.min_uppercase as usize,
+            min_digits: self.min_digits as usize,
+            min_symbols: self.min_symbols as usize,
+        })
+    }
+}
+
+/// Runs the body of an exported function, turning a panic into `RUSTYPASS_ERR_PANIC`
+fn guard(body: impl FnOnce() -> Result<(), i32>) -> i32 {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => RUSTYPASS_OK,
+        Ok(Err(code)) => code,
+        Err(_) => RUSTYPASS_ERR_PANIC,
+    }
+}
+
+/// Borrows a C string as UTF-8
+///
+/// # Returns
+/// * `Result<Option<&str>, i32>` - `None` for a null pointer, or
+///   `RUSTYPASS_ERR_INVALID_ARGUMENT` for invalid UTF-8
+///
+/// # Safety
+/// `string` must be null or NUL-terminated and outlive the returned borrow.
+unsafe fn borrow_str<'a>(string: *const c_char) -> Result<Option<&'a str>, i32> {
+    if string.is_null() {
+        return Ok(None);
+    }
+    // SAFETY: forwarded from the caller
+    let string = unsafe { CStr::from_ptr(string) };
+    string.to_str().map(Some).map_err(|_| RUSTYPASS_ERR_INVALID_ARGUMENT)
+}
+
+/// Hands a string over to the caller, who releases it with `rustypass_free_string`
+fn into_c_string(string: String) -> Result<*mut c_char, i32> {
+    CString::new(string).map(CString::into_raw).map_err(|_| RUSTYPASS_ERR_INVALID_ARGUMENT)
+}
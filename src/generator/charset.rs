@@ -0,0 +1,230 @@
+use std::fmt;
+use rand::Rng;
+use rand::prelude::IndexedRandom;
+use serde::{Deserialize, Serialize};
+use crate::utils;
+
+// ============================================================================
+// Character Classes
+// ============================================================================
+
+/// Built-in character categories a password can be required to contain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass {
+    /// a-z
+    Lowercase,
+    /// A-Z
+    Uppercase,
+    /// `SPECIAL_CHARS`
+    Symbols,
+    /// 0-9
+    Digits,
+}
+
+impl CharClass {
+    /// Every class, in the order messages and metadata list them
+    pub const ALL: [CharClass; 4] = [CharClass::Lowercase, CharClass::Uppercase, CharClass::Symbols, CharClass::Digits];
+
+    /// Name used in messages and metadata, e.g. `digits`
+    pub fn name(&self) -> &'static str {
+        match self {
+            CharClass::Lowercase => "lowercase",
+            CharClass::Uppercase => "uppercase",
+            CharClass::Symbols => "symbols",
+            CharClass::Digits => "digits",
+        }
+    }
+
+    /// Every member of the class
+    pub fn charset(&self) -> Charset {
+        let members = match self {
+            CharClass::Lowercase => utils::CHARS,
+            CharClass::Uppercase => utils::UPPERCASE_CHARS,
+            CharClass::Symbols => utils::SPECIAL_CHARS,
+            CharClass::Digits => utils::NUMBERS,
+        };
+        Charset::from(members)
+    }
+}
+
+// ============================================================================
+// Charset
+// ============================================================================
+
+/// A set of characters passwords are drawn from
+///
+/// Members are kept sorted and without duplicates, so `len` is the alphabet
+/// size used for entropy and sets compare equal regardless of how they were
+/// built. Any Unicode scalar value can be a member. Serialized as a plain
+/// string of its members, e.g. `"0Ol1"`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct Charset {
+    chars: Vec<char>,
+}
+
+impl Charset {
+    /// Builds the alphabet for the `generate` flags; lowercase letters are always included
+    ///
+    /// # Arguments
+    /// * `uppercase` - Include uppercase letters (A-Z)
+    /// * `symbols` - Include special characters (`SPECIAL_CHARS`)
+    /// * `digits` - Include numbers (0-9)
+    pub fn from_flags(uppercase: bool, symbols: bool, digits: bool) -> Self {
+        [(CharClass::Uppercase, uppercase), (CharClass::Symbols, symbols), (CharClass::Digits, digits)]
+            .into_iter()
+            .filter(|(_, included)| *included)
+            .fold(CharClass::Lowercase.charset(), |charset, (class, _)| charset.union(&class.charset()))
+    }
+
+    /// Members of either set
+    pub fn union(&self, other: &Charset) -> Charset {
+        self.chars.iter().chain(&other.chars).copied().collect()
+    }
+
+    /// Members of this set that are not in `other`, e.g. the alphabet without excluded characters
+    pub fn difference(&self, other: &Charset) -> Charset {
+        Charset { chars: self.chars.iter().copied().filter(|c| !other.contains(*c)).collect() }
+    }
+
+    /// Members of both sets
+    pub fn intersection(&self, other: &Charset) -> Charset {
+        Charset { chars: self.chars.iter().copied().filter(|c| other.contains(*c)).collect() }
+    }
+
+    /// Number of distinct members
+    pub fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    /// Whether the set has no members
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    /// Whether `c` is a member
+    pub fn contains(&self, c: char) -> bool {
+        self.chars.binary_search(&c).is_ok()
+    }
+
+    /// Members of each built-in class present in the set, in `CharClass::ALL` order
+    ///
+    /// Members outside every class (custom characters such as `é`) are not
+    /// counted in any category.
+    pub fn composition(&self) -> Vec<(CharClass, Charset)> {
+        CharClass::ALL
+            .into_iter()
+            .map(|class| (class, self.intersection(&class.charset())))
+            .filter(|(_, members)| !members.is_empty())
+            .collect()
+    }
+
+    /// Picks a member uniformly at random
+    ///
+    /// # Returns
+    /// * `Option<char>` - A member, or `None` for an empty set
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<char> {
+        self.chars.choose(rng).copied()
+    }
+}
+
+impl FromIterator<char> for Charset {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut chars: Vec<char> = iter.into_iter().collect();
+        chars.sort_unstable();
+        chars.dedup();
+        Charset { chars }
+    }
+}
+
+impl From<&str> for Charset {
+    fn from(members: &str) -> Self {
+        members.chars().collect()
+    }
+}
+
+impl From<String> for Charset {
+    fn from(members: String) -> Self {
+        Charset::from(members.as_str())
+    }
+}
+
+impl From<Charset> for String {
+    fn from(charset: Charset) -> Self {
+        charset.to_string()
+    }
+}
+
+impl fmt::Display for Charset {
+    /// Writes the members as one string, in ascending order
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chars.iter().try_for_each(|c| write!(f, "{}", c))
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicates_are_removed_and_members_sorted() {
+        let charset = Charset::from("cabbac");
+        assert_eq!(charset.len(), 3);
+        assert_eq!(charset.to_string(), "abc");
+        assert_eq!(charset, Charset::from("abc"));
+    }
+
+    #[test]
+    fn union_deduplicates_overlapping_sets() {
+        let charset = Charset::from("abc").union(&Charset::from("bcd"));
+        assert_eq!(charset.to_string(), "abcd");
+    }
+
+    #[test]
+    fn difference_can_empty_a_category() {
+        let charset = Charset::from_flags(false, false, true).difference(&CharClass::Digits.charset());
+        assert_eq!(charset, CharClass::Lowercase.charset());
+        let classes: Vec<CharClass> = charset.composition().into_iter().map(|(class, _)| class).collect();
+        assert_eq!(classes, [CharClass::Lowercase]);
+    }
+
+    #[test]
+    fn from_flags_sizes() {
+        assert_eq!(Charset::from_flags(false, false, false).len(), 26);
+        assert_eq!(Charset::from_flags(true, false, false).len(), 52);
+        assert_eq!(Charset::from_flags(true, false, true).len(), 62);
+        assert_eq!(Charset::from_flags(true, true, true).len(), 26 + 26 + 10 + utils::SPECIAL_CHARS.chars().count());
+    }
+
+    #[test]
+    fn unicode_members_count_as_one_and_belong_to_no_class() {
+        let charset = Charset::from("éé日🔑a");
+        assert_eq!(charset.len(), 4);
+        assert!(charset.contains('日'));
+        assert!(charset.contains('🔑'));
+        let composition = charset.composition();
+        assert_eq!(composition.len(), 1);
+        assert_eq!(composition[0].1, Charset::from("a"));
+    }
+
+    #[test]
+    fn sample_draws_members_and_none_when_empty() {
+        let mut rng = rand::rng();
+        let charset = Charset::from("xyz");
+        for _ in 0..100 {
+            assert!(charset.contains(charset.sample(&mut rng).unwrap()));
+        }
+        assert_eq!(Charset::default().sample(&mut rng), None);
+    }
+
+    #[test]
+    fn serializes_as_a_plain_string() {
+        let charset = Charset::from("ba");
+        assert_eq!(serde_json::to_string(&charset).unwrap(), "\"ab\"");
+        assert_eq!(serde_json::from_str::<Charset>("\"bba\"").unwrap(), charset);
+    }
+}
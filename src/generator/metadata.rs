@@ -0,0 +1,132 @@
+use std::time::Duration;
+use serde::Serialize;
+use crate::analyzer;
+use super::GeneratorOptions;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Random number generator owned by `PasswordGenerator`
+const RNG_SOURCE: &str = "rand StdRng (ChaCha12 CSPRNG, seeded from the operating system)";
+
+/// Random number generator of `seeded_rng`
+const SEEDED_RNG_SOURCE: &str = "rand_chacha ChaCha20Rng (fixed seed: reproducible, not secret)";
+
+// ============================================================================
+// Generation Metadata
+// ============================================================================
+
+/// Effective settings and timing of a `generate` run
+///
+/// Shown by `--verbose` on stderr and included in the JSON output, so both
+/// always describe the same run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GenerationMetadata {
+    /// Final password length after defaulting and policy resolution
+    pub length: u32,
+    /// Enabled character classes, lowercase always first
+    pub classes: Vec<&'static str>,
+    /// Exact alphabet the passwords are drawn from
+    pub charset: String,
+    /// Number of characters in `charset`
+    pub charset_size: u32,
+    /// Theoretical entropy in bits (`length * log2(charset_size)`)
+    pub entropy_bits: f64,
+    /// Number of possible passwords (`charset_size ^ length`), exact when it
+    /// fits in 128 bits and in scientific notation otherwise
+    pub possible_passwords: String,
+    /// Random number generator used
+    pub rng: &'static str,
+    /// Policy the passwords satisfy, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy: Option<String>,
+    /// Number of passwords generated
+    pub count: u32,
+    /// Wall-clock time spent generating
+    pub elapsed_seconds: f64,
+    /// Passwords generated per second, for batch runs only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub passwords_per_second: Option<f64>,
+}
+
+impl GenerationMetadata {
+    /// Describes a finished run
+    ///
+    /// # Arguments
+    /// * `settings` - Effective options the passwords were generated with (see `resolve_settings`)
+    /// * `policy` - Name of the policy, if any
+    /// * `count` - Number of passwords generated
+    /// * `elapsed` - Time spent generating them
+    pub fn new(settings: &GeneratorOptions, policy: Option<String>, count: u32, elapsed: Duration) -> Self {
+        let charset = settings.charset();
+        let charset_size = charset.len() as u32;
+        let classes = settings.classes().iter().map(|class| class.class.name()).collect();
+
+        let elapsed_seconds = elapsed.as_secs_f64();
+        let passwords_per_second = (count > 1 && elapsed_seconds > 0.0).then(|| count as f64 / elapsed_seconds);
+
+        GenerationMetadata {
+            length: settings.length,
+            classes,
+            charset: charset.to_string(),
+            charset_size,
+            entropy_bits: analyzer::compute_entropy(charset_size, settings.length as usize),
+            possible_passwords: format_keyspace(charset_size, settings.length),
+            rng: RNG_SOURCE,
+            policy,
+            count,
+            elapsed_seconds,
+            passwords_per_second,
+        }
+    }
+
+    /// Records that the passwords came from `seeded_rng` instead of the operating system
+    pub fn seeded(self) -> Self {
+        GenerationMetadata { rng: SEEDED_RNG_SOURCE, ..self }
+    }
+}
+
+/// Renders generation metadata as human-readable text
+pub fn render_metadata(metadata: &GenerationMetadata) -> String {
+    let mut out = String::from("Generation details:\n");
+    out.push_str(&format!("  Length:             {}\n", metadata.length));
+    out.push_str(&format!("  Classes:            {}\n", metadata.classes.join(", ")));
+    out.push_str(&format!("  Alphabet size:      {}\n", metadata.charset_size));
+    out.push_str(&format!("  Charset:            {}\n", metadata.charset));
+    out.push_str(&format!(
+        "  Entropy:            {} x log2({}) = {:.1} bits\n",
+        metadata.length, metadata.charset_size, metadata.entropy_bits
+    ));
+    out.push_str(&format!("  Possible passwords: {}\n", metadata.possible_passwords));
+    out.push_str(&format!("  RNG:                {}\n", metadata.rng));
+    if let Some(policy) = &metadata.policy {
+        out.push_str(&format!("  Policy:             {}\n", policy));
+    }
+    out.push_str(&format!("  Elapsed:            {:.3} ms", metadata.elapsed_seconds * 1000.0));
+    if let Some(rate) = metadata.passwords_per_second {
+        out.push_str(&format!(" for {} passwords ({:.0} per second)", metadata.count, rate));
+    }
+    out
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Formats `base ^ exponent` exactly when it fits in a `u128`, otherwise as `m.mme+N`
+fn format_keyspace(base: u32, exponent: u32) -> String {
+    if let Some(exact) = (base as u128).checked_pow(exponent) {
+        return exact.to_string();
+    }
+
+    let log10 = exponent as f64 * (base as f64).log10();
+    let mut magnitude = log10.floor();
+    let mut mantissa = 10f64.powf(log10 - magnitude);
+    // Rounding to two decimals can carry over, e.g. 9.997 -> 10.00
+    if (mantissa * 100.0).round() >= 1000.0 {
+        mantissa /= 10.0;
+        magnitude += 1.0;
+    }
+    format!("{:.2}e+{}", mantissa, magnitude)
+}
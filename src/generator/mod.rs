@@ -1,124 +1,394 @@
-use rand::prelude::{IndexedRandom, SliceRandom};
+use std::fmt;
+use std::io;
+use serde::{Deserialize, Serialize};
+use crate::analyzer;
+use crate::policy::Policy;
 use crate::utils;
 
-/// Validates and sets the password length within acceptable bounds
-///
-/// # Arguments
-/// * `args` - Reference to the parsed command-line arguments
+mod charset;
+mod metadata;
+mod options;
+mod stream;
+
+pub use charset::{CharClass, Charset};
+pub use metadata::{render_metadata, GenerationMetadata};
+pub use options::GeneratorOptions;
+pub use stream::{os_rng, seeded_rng, PasswordGenerator};
+
+/// Maximum number of passwords generated while looking for one that satisfies a policy
+const MAX_POLICY_ATTEMPTS: usize = 1000;
+
+/// A generated password together with the settings and strength estimate behind it
 ///
-/// # Returns
-/// * `u32` - Validated password length (defaults to DEFAULT_LENGTH if out of bounds)
+/// Field names match `AnalysisReport` where they overlap (`length`,
+/// `charset_size`, `entropy_bits`, `crack_time_seconds`), so tools can read
+/// both outputs the same way.
 ///
-/// # Notes
-/// If the requested length is outside the range [MIN_LENGTH, MAX_LENGTH],
-/// a warning is printed and DEFAULT_LENGTH is used instead.
-fn set_length (args_length: u32) -> u32 {
-    let mut length = args_length;
+/// The password never leaves by accident: `Debug` prints `<redacted>`, and
+/// serializing leaves the `password` field out. Output that is meant to carry
+/// the password, such as `generate --output json`, opts in with `with_secret`.
+/// Deserializing requires the field, so a document without the password is
+/// never mistaken for a complete one.
+#[derive(Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GeneratedPassword {
+    /// The password itself; only serialized through `with_secret`
+    pub password: String,
+    /// Password length in characters
+    pub length: usize,
+    /// Flags the password was requested with
+    pub flags: GenerationFlags,
+    /// Size of the alphabet the password was drawn from
+    pub charset_size: u32,
+    /// Entropy in bits (`length * log2(charset_size)`)
+    pub entropy_bits: f64,
+    /// Estimated seconds to guess the password at bcrypt speed
+    pub crack_time_seconds: f64,
+}
+
+/// Serializes a `GeneratedPassword` including the password, see `GeneratedPassword::with_secret`
+#[derive(Debug, Clone, Copy)]
+pub struct WithSecret<'a>(&'a GeneratedPassword);
+
+/// The serialized form of `GeneratedPassword`, with or without the password
+#[derive(Serialize)]
+struct SerializedPassword<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    password: Option<&'a str>,
+    length: usize,
+    flags: &'a GenerationFlags,
+    charset_size: u32,
+    entropy_bits: f64,
+    crack_time_seconds: f64,
+}
+
+/// Generation flags as given on the command line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenerationFlags {
+    pub uppercase_chars: bool,
+    pub special_chars: bool,
+    pub numbers: bool,
+    /// Name of the policy the password satisfies, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<String>,
+}
+
+impl GeneratedPassword {
+    /// Describes a generated password
+    ///
+    /// # Arguments
+    /// * `password` - The generated password
+    /// * `flags` - Flags the password was requested with
+    /// * `charset_size` - Size of the effective alphabet it was drawn from
+    ///   (`Charset::len()` after exclusions, custom characters and policy rules)
+    pub fn new(password: String, flags: GenerationFlags, charset_size: u32) -> Self {
+        let length = password.chars().count();
+        let entropy_bits = analyzer::compute_entropy(charset_size, length);
+
+        GeneratedPassword {
+            password,
+            length,
+            flags,
+            charset_size,
+            entropy_bits,
+            crack_time_seconds: analyzer::compute_time_to_crack(entropy_bits),
+        }
+    }
 
-    // Check if length is within valid bounds. If not, reset to default.
-    if args_length < utils::MIN_LENGTH || args_length > utils::MAX_LENGTH {
-        length = utils::DEFAULT_LENGTH;
+    /// Opts in to serializing the password in plaintext
+    ///
+    /// # Returns
+    /// * `WithSecret` - Serializes like `self` with the `password` field added
+    pub fn with_secret(&self) -> WithSecret<'_> {
+        WithSecret(self)
     }
 
-    length
+    /// The fields to serialize, with the password only when `secret` is true
+    fn serialized(&self, secret: bool) -> SerializedPassword<'_> {
+        SerializedPassword {
+            password: secret.then_some(self.password.as_str()),
+            length: self.length,
+            flags: &self.flags,
+            charset_size: self.charset_size,
+            entropy_bits: self.entropy_bits,
+            crack_time_seconds: self.crack_time_seconds,
+        }
+    }
 }
 
-/// Creates a character set based on user-specified options
-///
-/// # Arguments
-/// * `args` - Reference to the parsed command-line arguments
-///
-/// # Returns
-/// * `String` - Character set to use for password generation
-///
-/// # Character Set Composition
-/// - Always includes lowercase letters (a-z)
-/// - Optionally includes uppercase letters (A-Z)
-/// - Optionally includes special characters (!@#$%^&*_-+=<>?)
-/// - Optionally includes numbers (0-9)
-fn create_charset(up_chars: bool, spec_chars: bool, num_chars: bool) -> String {
-    // Start with lowercase letters as the base character set
-    let mut charset = String::from(utils::CHARS);
+impl Serialize for GeneratedPassword {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.serialized(false).serialize(serializer)
+    }
+}
 
-    // Add uppercase letters if requested
-    if up_chars {
-        charset.push_str(utils::UPPERCASE_CHARS);
+impl Serialize for WithSecret<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialized(true).serialize(serializer)
     }
+}
 
-    // Add special characters if requested
-    if spec_chars {
-        charset.push_str(utils::SPECIAL_CHARS);
+impl fmt::Debug for GeneratedPassword {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GeneratedPassword")
+            .field("password", &"<redacted>")
+            .field("length", &self.length)
+            .field("flags", &self.flags)
+            .field("charset_size", &self.charset_size)
+            .field("entropy_bits", &self.entropy_bits)
+            .field("crack_time_seconds", &self.crack_time_seconds)
+            .finish()
     }
+}
+
+/// A generated password with the full analyzer report, for `generate --analyze`
+///
+/// Serializes as the `GeneratedPassword` fields plus an `analysis` object
+/// shaped exactly like the output of `analyze --output json`. The password is
+/// included when `generated` comes from `with_secret`.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalyzedPassword<'a> {
+    #[serde(flatten)]
+    pub generated: WithSecret<'a>,
+    pub analysis: &'a analyzer::AnalysisReport,
+}
 
-    // Add numbers if requested
-    if num_chars {
-        charset.push_str(utils::NUMBERS);
+/// Writes generated passwords as RFC 4180 CSV, with a header row unless `header` is false
+///
+/// Columns are `index` (from 1), `password`, `length`, `entropy_bits` and
+/// `grade`. The grade is the one `analyze` gives: on the effective entropy
+/// after pattern penalties, not on `entropy_bits`. Passwords are quoted by the
+/// CSV writer whenever they contain commas, quotes or line breaks, so they
+/// read back unchanged.
+///
+/// # Arguments
+/// * `analyses` - One analyzer report per password (`generate --analyze`), which
+///   appends the `effective_entropy_bits`, `score`, `effective_grade` and
+///   `warnings` (separated by `; `) columns
+pub fn write_generated_csv<W: io::Write>(
+    passwords: &[GeneratedPassword],
+    analyses: Option<&[analyzer::AnalysisReport]>,
+    header: bool,
+    writer: W,
+) -> csv::Result<()> {
+    let mut csv = csv::Writer::from_writer(writer);
+    let options = analyzer::AnalyzerOptions::default();
+
+    if header {
+        let mut columns = vec!["index", "password", "length", "entropy_bits", "grade"];
+        if analyses.is_some() {
+            columns.extend(["effective_entropy_bits", "score", "effective_grade", "warnings"]);
+        }
+        csv.write_record(columns)?;
+    }
+    for (index, generated) in passwords.iter().enumerate() {
+        let analysis = analyses.and_then(|analyses| analyses.get(index));
+        let grade = match analysis {
+            Some(report) => report.grade,
+            None => analyzer::analyze_password(&generated.password, &options).grade,
+        };
+        let mut record = vec![
+            (index + 1).to_string(),
+            generated.password.clone(),
+            generated.length.to_string(),
+            format!("{:.2}", generated.entropy_bits),
+            grade.to_string(),
+        ];
+        if let Some(report) = analysis {
+            record.extend([
+                format!("{:.2}", report.effective_entropy_bits),
+                report.score.to_string(),
+                report.grade.to_string(),
+                report.warnings.join("; "),
+            ]);
+        }
+        csv.write_record(&record)?;
     }
 
-    charset
+    csv.flush()?;
+    Ok(())
 }
 
-/// Generates a random password from the given character set
+/// Applies defaulting and policy requirements to the requested options
 ///
 /// # Arguments
-/// * `length` - Desired length of the password
-/// * `charset` - String containing all characters to choose from
+/// * `options` - Requested options
+/// * `policy` - Policy whose rules may enable more classes, raise minimum counts,
+///   exclude its banned characters and move the length
 ///
 /// # Returns
-/// * `String` - Randomly generated password
-///
-/// # Algorithm
-/// For each character position:
-/// 1. Shuffle the character set for added randomness
-/// 2. Randomly select one character from the shuffled set
-/// 3. Append it to the password
-pub fn compute_password(args_length: u32, up_chars: bool, spec_chars: bool, num_chars: bool) -> String {
-    // Initialize the random number generator
-    let mut rng = rand::rng();
+/// * `Option<GeneratorOptions>` - The effective options, or `None` if the policy
+///   bounds leave no supported length
+pub fn resolve_settings(options: &GeneratorOptions, policy: Option<&Policy>) -> Option<GeneratorOptions> {
+    let Some(policy) = policy else {
+        return Some(options.clone());
+    };
+
+    // Classes in the order they are enabled: uppercase, digits, symbols
+    let mut classes = [
+        options.include_uppercase || policy.min_uppercase > 0,
+        options.include_digits || policy.min_digits > 0,
+        options.include_symbols || policy.min_symbols > 0,
+    ];
+    let lowercase = options.include_lowercase || policy.min_lowercase > 0 || policy.min_letters > 0;
+
+    // Enable further classes until the minimum is met
+    for index in 0..classes.len() {
+        let enabled = usize::from(lowercase) + classes.iter().filter(|c| **c).count();
+        if enabled >= policy.min_classes {
+            break;
+        }
+        classes[index] = true;
+    }
+    let [include_uppercase, include_digits, include_symbols] = classes;
+
+    // Fit the length to the policy bounds
+    let mut length = options.length.max(policy.min_length as u32);
+    if let Some(max) = policy.max_length {
+        length = length.min(max as u32);
+    }
+    if !(utils::MIN_LENGTH..=utils::MAX_LENGTH).contains(&length) {
+        log::debug!("policy '{}' leaves no supported length (resolved {})", policy.name, length);
+        return None;
+    }
+
+    let exclude = options.exclude.union(&Charset::from(policy.banned_chars.as_str()));
+    let settings = GeneratorOptions {
+        length,
+        include_lowercase: lowercase,
+        include_uppercase,
+        include_digits,
+        include_symbols,
+        exclude,
+        min_lowercase: options.min_lowercase.max(policy.min_lowercase),
+        min_uppercase: options.min_uppercase.max(policy.min_uppercase),
+        min_digits: options.min_digits.max(policy.min_digits),
+        min_symbols: options.min_symbols.max(policy.min_symbols),
+        ..options.clone()
+    };
+    log::debug!("settings resolved with policy '{}': {:?}", policy.name, settings);
+    Some(settings)
+}
 
-    // Initialize empty password string
-    let mut password_chars : Vec<char> = Vec::new();
+// ============================================================================
+// Tests
+// ============================================================================
 
-    // Set the password length and character set
-    let length = set_length(args_length);
-    let charset = create_charset(up_chars, spec_chars, num_chars);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // Convert charset to a vector of characters for shuffling
-    let mut charset = charset.chars().collect::<Vec<char>>();
+    fn generate(options: &GeneratorOptions) -> GeneratedPassword {
+        PasswordGenerator::new(options, os_rng().unwrap()).unwrap().next().unwrap()
+    }
+
+    #[test]
+    fn entropy_uses_the_effective_alphabet_after_exclusions() {
+        let options = GeneratorOptions { length: 12, exclude: Charset::from("abcdefghijklmnopqrstuvwx"), ..Default::default() };
+        let generated = generate(&options);
+        assert!(generated.password.chars().all(|c| c == 'y' || c == 'z'));
+        assert_eq!(generated.charset_size, 2);
+        assert!((generated.entropy_bits - 12.0).abs() < 1e-9);
+    }
 
-    // Shuffle the charset for better randomness
-    charset.shuffle(&mut rng);
+    #[test]
+    fn entropy_of_digits_only_counts_ten_symbols() {
+        let options = GeneratorOptions { include_lowercase: false, include_digits: true, ..Default::default() };
+        let generated = generate(&options);
+        assert_eq!(generated.charset_size, 10);
+        assert!((generated.entropy_bits - 16.0 * 10f64.log2()).abs() < 1e-9);
+    }
 
-    // Ensure at least one character from each selected category is included
-    let chars: Vec<char> = utils::CHARS.chars().collect();
-    password_chars.push(*chars.choose(&mut rng).expect("No characters available"));
+    #[test]
+    fn entropy_counts_custom_characters() {
+        let options = GeneratorOptions { custom_charset: Charset::from("äöü"), ..Default::default() };
+        assert_eq!(generate(&options).charset_size, 29);
+    }
+
+    #[test]
+    fn entropy_follows_policy_banned_characters() {
+        let policy = Policy { name: String::from("test"), banned_chars: String::from("abc"), ..Default::default() };
+        let mut stream = PasswordGenerator::with_policy(&GeneratorOptions::default(), &policy, os_rng().unwrap()).unwrap();
+        let generated = stream.next().unwrap();
+        assert_eq!(generated.charset_size, 23);
+        assert!(!generated.password.contains(['a', 'b', 'c']));
+    }
 
-    if up_chars {
-        // Ensure at least one uppercase character is included
-        let upper_chars: Vec<char> = utils::UPPERCASE_CHARS.chars().collect();
-        password_chars.push(*upper_chars.choose(&mut rng).expect("No uppercase characters available"));
+    #[test]
+    fn debug_output_never_contains_the_password() {
+        let generated = generate(&GeneratorOptions { length: 32, ..Default::default() });
+        for debug in [format!("{:?}", generated), format!("{:#?}", generated)] {
+            assert!(!debug.contains(&generated.password), "{}", debug);
+            assert!(debug.contains("<redacted>"), "{}", debug);
+        }
     }
-    if spec_chars {
-        // Ensure at least one special character is included
-        let special_chars: Vec<char> = utils::SPECIAL_CHARS.chars().collect();
-        password_chars.push(*special_chars.choose(&mut rng).expect("No special characters available"));
+
+    #[test]
+    fn serialization_leaves_the_password_out_unless_asked() {
+        let generated = generate(&GeneratorOptions { length: 32, ..Default::default() });
+        let redacted = serde_json::to_value(&generated).unwrap();
+        assert!(redacted.get("password").is_none(), "{}", redacted);
+        assert!(!redacted.to_string().contains(&generated.password));
+        // A document without the password does not read back as a complete one
+        assert!(serde_json::from_value::<GeneratedPassword>(redacted).unwrap_err().to_string().contains("missing field `password`"));
+
+        let revealed = serde_json::to_value(generated.with_secret()).unwrap();
+        assert_eq!(revealed["password"], generated.password.as_str());
+        assert!(!format!("{:?}", generated.with_secret()).contains(&generated.password));
     }
-    if num_chars {
-        // Ensure at least one numeric character is included
-        let number_chars: Vec<char> = utils::NUMBERS.chars().collect();
-        password_chars.push(*number_chars.choose(&mut rng).expect("No numeric characters available"));
+
+    #[test]
+    fn generated_passwords_round_trip() {
+        let policy = Policy { name: String::from("short"), min_length: 8, ..Default::default() };
+        let mut stream = PasswordGenerator::with_policy(&GeneratorOptions::default(), &policy, os_rng().unwrap()).unwrap();
+        for generated in [generate(&GeneratorOptions::default()), stream.next().unwrap()] {
+            let json = serde_json::to_string(&generated.with_secret()).unwrap();
+            assert_eq!(serde_json::from_str::<GeneratedPassword>(&json).unwrap(), generated);
+        }
     }
 
-    let sub_lenght = password_chars.len() as u32;
+    #[test]
+    fn generated_passwords_reject_unknown_fields() {
+        let mut value = serde_json::to_value(generate(&GeneratorOptions::default()).with_secret()).unwrap();
+        value["score"] = serde_json::json!(50);
+        assert!(serde_json::from_value::<GeneratedPassword>(value.clone()).unwrap_err().to_string().contains("unknown field"));
 
-    // Generate each character of the password
-    for _ in sub_lenght..length {
-        // Randomly choose one character and append to password
-        password_chars.push(*charset.choose(&mut rng).expect("Empty character set"));
+        value.as_object_mut().unwrap().remove("score");
+        value["flags"]["symbols"] = serde_json::json!(true);
+        assert!(serde_json::from_value::<GeneratedPassword>(value).unwrap_err().to_string().contains("unknown field"));
     }
 
-    // Shuffle the final password characters to avoid predictable patterns
-    password_chars.shuffle(&mut rng);
+    #[test]
+    fn csv_reads_back_with_analyzer_grades() {
+        let flags = GenerationFlags { uppercase_chars: false, special_chars: true, numbers: false, policy: None };
+        let passwords = [
+            GeneratedPassword::new(String::from("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"), flags.clone(), 26),
+            GeneratedPassword::new(String::from("q,w\"e\nr t,y\"u\r\nio"), flags, 94),
+        ];
+        let mut csv = Vec::new();
+        write_generated_csv(&passwords, None, true, &mut csv).unwrap();
 
-    password_chars.iter().collect()
-}
\ No newline at end of file
+        let mut reader = csv::Reader::from_reader(csv.as_slice());
+        assert_eq!(reader.headers().unwrap(), vec!["index", "password", "length", "entropy_bits", "grade"]);
+        let records: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(records.len(), passwords.len());
+        for (record, generated) in records.iter().zip(&passwords) {
+            let report = analyzer::analyze_password(&generated.password, &analyzer::AnalyzerOptions::default());
+            assert_eq!(&record[1], generated.password);
+            assert_eq!(record[2].parse::<usize>().unwrap(), generated.length);
+            assert_eq!(&record[4], report.grade.to_string());
+        }
+        // 150 raw bits, but a single repeated letter: the grade follows the analyzer, not the raw entropy
+        assert_ne!(&records[0][4], analyzer::GradeThresholds::default().grade(passwords[0].entropy_bits).to_string());
+    }
+
+    #[test]
+    fn shared_fields_have_the_analyzer_names() {
+        let generated = serde_json::to_value(generate(&GeneratorOptions::default())).unwrap();
+        let analyzed = serde_json::to_value(analyzer::analyze_password("abc", &analyzer::AnalyzerOptions::default())).unwrap();
+        for key in ["length", "charset_size", "entropy_bits", "crack_time_seconds"] {
+            assert!(generated.get(key).is_some() && analyzed.get(key).is_some(), "{}", key);
+        }
+    }
+}
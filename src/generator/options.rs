@@ -0,0 +1,237 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::error::RustyPassError;
+use crate::utils;
+use super::charset::{CharClass, Charset};
+
+// ============================================================================
+// Generator Options
+// ============================================================================
+
+/// Everything that decides how a password is generated
+///
+/// Built with `Default` and struct-update syntax, e.g.
+/// `GeneratorOptions { length: 24, include_digits: true, ..Default::default() }`,
+/// or from the command line with `GeneratorOptions::from(&args)`. New
+/// generation features add a field here instead of widening function
+/// signatures.
+///
+/// As a document (`generate --options-file`) the keys match the config file
+/// and the JSON flags (`uppercase_chars`, `special_chars`, `numbers`) and the
+/// policy file (`min_digits`, ...); missing keys take their defaults and
+/// unknown keys are rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeneratorOptions {
+    /// Password length in characters, between `MIN_LENGTH` and `MAX_LENGTH`
+    pub length: u32,
+    /// Include lowercase letters (a-z)
+    #[serde(rename = "lowercase_chars")]
+    pub include_lowercase: bool,
+    /// Include uppercase letters (A-Z)
+    #[serde(rename = "uppercase_chars")]
+    pub include_uppercase: bool,
+    /// Include digits (0-9)
+    #[serde(rename = "numbers")]
+    pub include_digits: bool,
+    /// Include symbols (`SPECIAL_CHARS`)
+    #[serde(rename = "special_chars")]
+    pub include_symbols: bool,
+    /// Extra characters added to the alphabet on top of the enabled classes
+    #[serde(rename = "custom_chars")]
+    pub custom_charset: Charset,
+    /// Characters never used, even when their class is enabled
+    #[serde(rename = "excluded_chars")]
+    pub exclude: Charset,
+    /// Minimum number of lowercase letters (at least one whenever the class is enabled)
+    pub min_lowercase: usize,
+    /// Minimum number of uppercase letters
+    pub min_uppercase: usize,
+    /// Minimum number of digits
+    pub min_digits: usize,
+    /// Minimum number of symbols
+    pub min_symbols: usize,
+}
+
+impl Default for GeneratorOptions {
+    /// A `DEFAULT_LENGTH` password of lowercase letters, as `generate` without flags
+    fn default() -> Self {
+        GeneratorOptions {
+            length: utils::DEFAULT_LENGTH,
+            include_lowercase: true,
+            include_uppercase: false,
+            include_digits: false,
+            include_symbols: false,
+            custom_charset: Charset::default(),
+            exclude: Charset::default(),
+            min_lowercase: 0,
+            min_uppercase: 0,
+            min_digits: 0,
+            min_symbols: 0,
+        }
+    }
+}
+
+/// An enabled character class, with its excluded characters already removed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassOptions {
+    /// The class
+    pub class: CharClass,
+    /// Characters the class may contribute
+    pub chars: Charset,
+    /// Characters the password must contain from this class
+    pub required: usize,
+}
+
+impl GeneratorOptions {
+    /// Loads an options document from a JSON file
+    ///
+    /// # Returns
+    /// * `Result<GeneratorOptions, RustyPassError>` - The options, `Io` if the file
+    ///   cannot be read, or `InvalidOptions` naming the file, line and offending key
+    pub fn from_file(path: &Path) -> Result<GeneratorOptions, RustyPassError> {
+        let text = std::fs::read_to_string(path).map_err(|source| RustyPassError::Io { path: path.to_path_buf(), source })?;
+        serde_json::from_str(&text).map_err(|e| RustyPassError::InvalidOptions { path: path.to_path_buf(), message: e.to_string() })
+    }
+
+    /// Enabled classes in alphabet order: lowercase, uppercase, symbols, digits
+    pub fn classes(&self) -> Vec<ClassOptions> {
+        self.all_classes()
+            .into_iter()
+            .filter(|(_, included, _)| *included)
+            .map(|(class, _, min)| ClassOptions { class, chars: class.charset().difference(&self.exclude), required: min.max(1) })
+            .collect()
+    }
+
+    /// Every character a password may be drawn from
+    pub fn charset(&self) -> Charset {
+        self.classes()
+            .iter()
+            .fold(self.custom_charset.clone(), |charset, class| charset.union(&class.chars))
+            .difference(&self.exclude)
+    }
+
+    /// Checks that a password can be generated at all
+    ///
+    /// # Errors
+    /// * `InvalidLength` - The length is outside `MIN_LENGTH..=MAX_LENGTH`
+    /// * `ConflictingConstraints` - A minimum count is set for a class that is not
+    ///   included, every character of an included class is excluded, or the
+    ///   minimum counts add up to more than the length
+    /// * `EmptyCharset` - Nothing is left to draw from (no class and no custom characters)
+    pub fn validate(&self) -> Result<(), RustyPassError> {
+        if !(utils::MIN_LENGTH..=utils::MAX_LENGTH).contains(&self.length) {
+            return Err(RustyPassError::InvalidLength { got: self.length, min: utils::MIN_LENGTH, max: utils::MAX_LENGTH });
+        }
+
+        for (class, included, min) in self.all_classes() {
+            if !included && min > 0 {
+                return Err(RustyPassError::ConflictingConstraints(format!(
+                    "a minimum of {} is set for {} characters, which are not included",
+                    min,
+                    class.name()
+                )));
+            }
+        }
+
+        let classes = self.classes();
+        if let Some(class) = classes.iter().find(|class| class.chars.is_empty()) {
+            return Err(RustyPassError::ConflictingConstraints(format!("every {} character is excluded", class.class.name())));
+        }
+        if self.charset().is_empty() {
+            return Err(RustyPassError::EmptyCharset);
+        }
+
+        let required: usize = classes.iter().map(|class| class.required).sum();
+        let length = self.length as usize;
+        if required > length {
+            return Err(RustyPassError::ConflictingConstraints(format!(
+                "the minimum counts need {} characters, but the password has only {}",
+                required, length
+            )));
+        }
+        Ok(())
+    }
+
+    /// Inclusion and minimum of every class, enabled or not
+    fn all_classes(&self) -> [(CharClass, bool, usize); 4] {
+        [
+            (CharClass::Lowercase, self.include_lowercase, self.min_lowercase),
+            (CharClass::Uppercase, self.include_uppercase, self.min_uppercase),
+            (CharClass::Symbols, self.include_symbols, self.min_symbols),
+            (CharClass::Digits, self.include_digits, self.min_digits),
+        ]
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Options with every field away from its default
+    fn custom() -> GeneratorOptions {
+        GeneratorOptions {
+            length: 24,
+            include_lowercase: false,
+            include_uppercase: true,
+            include_digits: true,
+            include_symbols: true,
+            custom_charset: Charset::from("äö"),
+            exclude: Charset::from("0O"),
+            min_lowercase: 0,
+            min_uppercase: 2,
+            min_digits: 3,
+            min_symbols: 1,
+        }
+    }
+
+    #[test]
+    fn documents_round_trip() {
+        let options = custom();
+        let json = serde_json::to_string(&options).unwrap();
+        assert_eq!(serde_json::from_str::<GeneratorOptions>(&json).unwrap(), options);
+    }
+
+    #[test]
+    fn document_keys_match_the_config_and_policy_files() {
+        let document = serde_json::to_value(custom()).unwrap();
+        let mut keys: Vec<&str> = document.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            [
+                "custom_chars", "excluded_chars", "length", "lowercase_chars", "min_digits", "min_lowercase",
+                "min_symbols", "min_uppercase", "numbers", "special_chars", "uppercase_chars",
+            ]
+        );
+        assert_eq!(document["custom_chars"], "äö");
+        assert_eq!(document["excluded_chars"], "0O");
+    }
+
+    #[test]
+    fn missing_keys_take_their_defaults() {
+        assert_eq!(serde_json::from_str::<GeneratorOptions>("{}").unwrap(), GeneratorOptions::default());
+        let options: GeneratorOptions = serde_json::from_str(r#"{"length": 20, "numbers": true}"#).unwrap();
+        assert_eq!(options, GeneratorOptions { length: 20, include_digits: true, ..Default::default() });
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        // A typo must not silently fall back to the default
+        for document in [r#"{"lenght": 20}"#, r#"{"include_digits": true}"#, r#"{"length": 20, "extra": null}"#] {
+            let error = serde_json::from_str::<GeneratorOptions>(document).unwrap_err().to_string();
+            assert!(error.contains("unknown field"), "{}: {}", document, error);
+        }
+    }
+
+    #[test]
+    fn mistyped_values_are_rejected() {
+        assert!(serde_json::from_str::<GeneratorOptions>(r#"{"length": "20"}"#).is_err());
+        assert!(serde_json::from_str::<GeneratorOptions>(r#"{"numbers": 1}"#).is_err());
+        assert!(serde_json::from_str::<GeneratorOptions>(r#"{"min_digits": -1}"#).is_err());
+    }
+}
@@ -0,0 +1,350 @@
+use rand::{CryptoRng, Rng, SeedableRng};
+use rand::prelude::SliceRandom;
+use rand::rngs::StdRng;
+use rand_chacha::ChaCha20Rng;
+use crate::error::RustyPassError;
+use crate::policy::Policy;
+use super::charset::Charset;
+use super::options::ClassOptions;
+use super::{resolve_settings, GeneratedPassword, GenerationFlags, GeneratorOptions, MAX_POLICY_ATTEMPTS};
+
+// ============================================================================
+// Password Stream
+// ============================================================================
+
+/// An endless, lazily evaluated stream of passwords for one set of options
+///
+/// The options are validated (and a policy folded in) once, when the
+/// generator is built; every `next` only draws characters. The generator
+/// owns the random number generator it is given, so independently built
+/// generators never share state and, with a `Send` RNG such as the
+/// `os_rng()` one, can be moved into worker threads. Any cryptographically
+/// secure RNG works, e.g. a seeded ChaCha RNG for reproducible output.
+///
+/// ```ignore
+/// let passwords: Vec<_> = PasswordGenerator::new(&options, os_rng()?)?.take(1_000_000).collect();
+/// ```
+///
+/// # Notes
+/// Without a policy the stream never ends. With one, it ends early when no
+/// compliant candidate is found within `MAX_POLICY_ATTEMPTS` tries; `failure`
+/// then says why.
+#[derive(Debug)]
+pub struct PasswordGenerator<R = StdRng> {
+    /// Effective options, with any policy already applied
+    settings: GeneratorOptions,
+    /// Enabled classes and the characters each may contribute
+    classes: Vec<ClassOptions>,
+    /// Full alphabet the remaining positions are drawn from
+    charset: Charset,
+    /// Policy every password must satisfy, if any
+    policy: Option<Policy>,
+    /// Flags recorded on every generated password
+    flags: GenerationFlags,
+    rng: R,
+    /// Why the stream ended early
+    failure: Option<RustyPassError>,
+}
+
+// Moving a generator into a worker thread must keep compiling
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+    assert_send::<PasswordGenerator>();
+};
+
+/// Random number generator for password generation, seeded from the operating system
+///
+/// # Errors
+/// `RandomSource` when the operating system cannot provide a seed.
+pub fn os_rng() -> Result<StdRng, RustyPassError> {
+    StdRng::try_from_os_rng().map_err(|e| RustyPassError::RandomSource(e.to_string()))
+}
+
+/// Random number generator that draws the same passwords for the same seed
+///
+/// ChaCha20 keeps its output stream fixed across platforms and rand
+/// versions, which is what makes the result reproducible. The passwords are
+/// only as secret as the seed: this is for tests and `generate --seed`,
+/// never for real credentials.
+pub fn seeded_rng(seed: u64) -> ChaCha20Rng {
+    ChaCha20Rng::seed_from_u64(seed)
+}
+
+impl<R: Rng + CryptoRng> PasswordGenerator<R> {
+    /// Builds a generator for the given options
+    ///
+    /// # Arguments
+    /// * `options` - Length, character classes, exclusions and minimum counts
+    /// * `rng` - Random number generator every password is drawn with
+    ///
+    /// # Errors
+    /// Any error of `GeneratorOptions::validate`.
+    pub fn new(options: &GeneratorOptions, rng: R) -> Result<Self, RustyPassError> {
+        options.validate()?;
+        Ok(Self::build(options.clone(), None, Self::requested_flags(options, None), rng))
+    }
+
+    /// Builds a generator whose passwords all satisfy a policy
+    ///
+    /// # Arguments
+    /// * `options` - Requested options; the length is moved into the policy bounds and
+    ///   the policy may enable more classes, raise minimum counts and exclude characters
+    /// * `policy` - Rules every password must satisfy
+    /// * `rng` - Random number generator every password is drawn with
+    ///
+    /// # Errors
+    /// `PolicyUnsatisfiable` if the policy leaves no supported length or conflicts
+    /// with the options.
+    pub fn with_policy(options: &GeneratorOptions, policy: &Policy, rng: R) -> Result<Self, RustyPassError> {
+        let not_satisfiable = |reason: Option<String>| RustyPassError::PolicyUnsatisfiable { policy: policy.name.clone(), reason };
+        let settings = resolve_settings(options, Some(policy)).ok_or_else(|| not_satisfiable(None))?;
+        settings.validate().map_err(|e| not_satisfiable(Some(e.to_string())))?;
+        Ok(Self::build(settings, Some(policy.clone()), Self::requested_flags(options, Some(policy)), rng))
+    }
+
+    /// Generates the next password
+    ///
+    /// # Returns
+    /// * `Result<String, RustyPassError>` - A password, or `PolicyUnsatisfiable` when
+    ///   no candidate satisfied the policy within `MAX_POLICY_ATTEMPTS` tries
+    ///
+    /// # Algorithm
+    /// 1. Pick the minimum number of characters (at least one) from each enabled class
+    /// 2. Fill the remaining positions from the full character set
+    /// 3. Shuffle the result so the required characters are not at predictable positions
+    /// 4. With a policy, repeat until `Policy::evaluate` accepts the candidate, so
+    ///    generation and `check` always agree
+    pub fn next_password(&mut self) -> Result<String, RustyPassError> {
+        let length = self.settings.length as usize;
+        let Some(policy) = &self.policy else {
+            return draw(&self.classes, &self.charset, length, &mut self.rng);
+        };
+
+        for attempt in 1..=MAX_POLICY_ATTEMPTS {
+            let password = draw(&self.classes, &self.charset, length, &mut self.rng)?;
+            if policy.evaluate(&password).passed {
+                log::trace!("policy '{}' satisfied after {} attempt(s)", policy.name, attempt);
+                return Ok(password);
+            }
+        }
+        log::debug!("policy '{}' not satisfied within {} attempts", policy.name, MAX_POLICY_ATTEMPTS);
+        Err(RustyPassError::PolicyUnsatisfiable { policy: policy.name.clone(), reason: None })
+    }
+
+    /// Why the stream ended early, if it did
+    pub fn failure(&self) -> Option<&RustyPassError> {
+        self.failure.as_ref()
+    }
+
+    /// Effective options the passwords are generated with
+    pub fn settings(&self) -> &GeneratorOptions {
+        &self.settings
+    }
+
+    /// Prepares the per-password state for already validated settings
+    fn build(settings: GeneratorOptions, policy: Option<Policy>, flags: GenerationFlags, rng: R) -> Self {
+        let classes = settings.classes();
+        let charset = settings.charset();
+        log::trace!(
+            "charset: {} characters (classes: {:?}, custom: {}, excluded: {})",
+            charset.len(),
+            classes.iter().map(|class| class.class.name()).collect::<Vec<_>>(),
+            settings.custom_charset.len(),
+            settings.exclude.len()
+        );
+
+        PasswordGenerator { settings, classes, charset, policy, flags, rng, failure: None }
+    }
+
+    /// Flags as requested, before a policy enabled further classes
+    fn requested_flags(options: &GeneratorOptions, policy: Option<&Policy>) -> GenerationFlags {
+        GenerationFlags {
+            uppercase_chars: options.include_uppercase,
+            special_chars: options.include_symbols,
+            numbers: options.include_digits,
+            policy: policy.map(|policy| policy.name.clone()),
+        }
+    }
+}
+
+impl<R: Rng + CryptoRng> Iterator for PasswordGenerator<R> {
+    type Item = GeneratedPassword;
+
+    /// Generates the next password with its strength estimate
+    ///
+    /// Returns `None` only once a policy could not be satisfied; see `failure`.
+    fn next(&mut self) -> Option<GeneratedPassword> {
+        if self.failure.is_some() {
+            return None;
+        }
+        match self.next_password() {
+            Ok(password) => Some(GeneratedPassword::new(password, self.flags.clone(), self.charset.len() as u32)),
+            Err(e) => {
+                self.failure = Some(e);
+                None
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Draws one candidate password
+///
+/// # Arguments
+/// * `classes` - Enabled classes and how many characters each must contribute
+/// * `charset` - Full alphabet for the remaining positions
+/// * `length` - Password length in characters
+/// * `rng` - Random number generator to draw with
+///
+/// # Notes
+/// The draw order (class minimums in `CharClass::ALL` order, then the fill, then
+/// one shuffle) is part of the output for a given RNG stream: changing it
+/// changes every password a seeded RNG produces.
+fn draw<R: Rng + CryptoRng + ?Sized>(classes: &[ClassOptions], charset: &Charset, length: usize, rng: &mut R) -> Result<String, RustyPassError> {
+    let mut password_chars: Vec<char> = Vec::with_capacity(length);
+
+    // Ensure the minimum number of characters from each selected category is included
+    for class in classes {
+        for _ in 0..class.required {
+            password_chars.push(class.chars.sample(rng).ok_or(RustyPassError::EmptyCharset)?);
+        }
+    }
+
+    // Generate each remaining character of the password
+    for _ in password_chars.len()..length {
+        password_chars.push(charset.sample(rng).ok_or(RustyPassError::EmptyCharset)?);
+    }
+
+    // Shuffle the final password characters to avoid predictable patterns
+    password_chars.shuffle(rng);
+
+    Ok(password_chars.iter().collect())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::CharClass;
+
+    fn stream(options: &GeneratorOptions) -> PasswordGenerator {
+        PasswordGenerator::new(options, os_rng().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn independent_generators_are_not_correlated() {
+        let options = GeneratorOptions::default();
+        let first: Vec<String> = stream(&options).take(1000).map(|generated| generated.password.clone()).collect();
+        let second: Vec<String> = stream(&options).take(1000).map(|generated| generated.password.clone()).collect();
+
+        assert!(first.iter().zip(&second).all(|(a, b)| a != b));
+        // Aligned positions agree by chance one time in 26; allow wide slack either way
+        let matches = first.iter().zip(&second).flat_map(|(a, b)| a.chars().zip(b.chars())).filter(|(a, b)| a == b).count();
+        let expected = 1000.0 * 16.0 / 26.0;
+        assert!((expected * 0.5..expected * 1.5).contains(&(matches as f64)), "{} matching positions", matches);
+    }
+
+    #[test]
+    fn a_generator_does_not_repeat_itself() {
+        let passwords: std::collections::HashSet<String> = stream(&GeneratorOptions::default()).take(1000).map(|generated| generated.password.clone()).collect();
+        assert_eq!(passwords.len(), 1000);
+    }
+
+    #[test]
+    fn yielded_passwords_carry_the_effective_charset_size() {
+        let options = GeneratorOptions { include_uppercase: true, include_digits: true, exclude: Charset::from("0O1lI"), ..Default::default() };
+        let mut generator = stream(&options);
+        let expected = generator.settings().charset().len() as u32;
+        assert_eq!(expected, 62 - 5);
+        for generated in generator.by_ref().take(20) {
+            assert_eq!(generated.charset_size, expected);
+            assert_eq!(generated.length, 16);
+        }
+    }
+
+    #[test]
+    fn every_password_has_each_enabled_class() {
+        let options = GeneratorOptions { length: 8, include_uppercase: true, include_digits: true, include_symbols: true, ..Default::default() };
+        for generated in stream(&options).take(200) {
+            let classes: Vec<CharClass> = generated.password.chars().collect::<Charset>().composition().into_iter().map(|(class, _)| class).collect();
+            assert_eq!(classes, CharClass::ALL);
+        }
+    }
+
+    #[test]
+    fn stream_ends_with_failure_when_a_policy_is_unsatisfiable() {
+        // Every character is a run of at least one, so no candidate passes
+        let policy = Policy { name: String::from("no-runs"), max_repeats: Some(0), ..Default::default() };
+        let mut generator = PasswordGenerator::with_policy(&GeneratorOptions::default(), &policy, os_rng().unwrap()).unwrap();
+        assert!(generator.next().is_none());
+        assert!(matches!(generator.failure(), Some(RustyPassError::PolicyUnsatisfiable { .. })));
+        assert!(generator.next().is_none());
+    }
+
+    #[test]
+    fn generators_can_move_into_worker_threads() {
+        let generator = stream(&GeneratorOptions::default());
+        let count = std::thread::spawn(move || generator.take(10).count()).join().unwrap();
+        assert_eq!(count, 10);
+    }
+
+    /// First passwords of a seeded generator; a change here means the sampling order changed
+    fn seeded(options: &GeneratorOptions, seed: u64) -> Vec<String> {
+        PasswordGenerator::new(options, seeded_rng(seed)).unwrap().take(3).map(|generated| generated.password.clone()).collect()
+    }
+
+    #[test]
+    fn seeded_output_is_pinned_for_the_default_options() {
+        assert_eq!(seeded(&GeneratorOptions::default(), 42), ["vxeceiqzrnevaukl", "btojzycikszdhstq", "kttmpmrxjqchated"]);
+    }
+
+    #[test]
+    fn seeded_output_is_pinned_for_every_class_with_minimums() {
+        let options = GeneratorOptions {
+            length: 12,
+            include_uppercase: true,
+            include_digits: true,
+            include_symbols: true,
+            min_digits: 3,
+            min_symbols: 2,
+            ..Default::default()
+        };
+        assert_eq!(seeded(&options, 7), ["TA<1Gd69I?@7", "?mR9J63+Yr@>", "eR875!n>I6_&"]);
+    }
+
+    #[test]
+    fn seeded_output_is_pinned_for_custom_characters_and_exclusions() {
+        let options = GeneratorOptions {
+            length: 10,
+            include_lowercase: false,
+            custom_charset: Charset::from("äöü日🔑"),
+            include_digits: true,
+            exclude: Charset::from("0"),
+            ..Default::default()
+        };
+        assert_eq!(seeded(&options, 0), ["1日7531日🔑2ü", "127äö4283ä", "ü85öö55352"]);
+    }
+
+    #[test]
+    fn seeded_output_is_pinned_under_a_policy() {
+        // Candidates go through the policy check, so the retry loop cannot reorder the draws either
+        let options = GeneratorOptions { length: 8, include_uppercase: true, include_digits: true, ..Default::default() };
+        let policy = crate::policy::PolicyPreset::Ad.policy();
+        let mut generator = PasswordGenerator::with_policy(&options, &policy, seeded_rng(1)).unwrap();
+        let passwords: Vec<String> = generator.by_ref().take(2).map(|generated| generated.password.clone()).collect();
+        assert_eq!(passwords, ["XQQT926i", "IFE3xFCd"]);
+    }
+
+    #[test]
+    fn the_seed_alone_decides_the_output() {
+        let options = GeneratorOptions::default();
+        assert_eq!(seeded(&options, 99), seeded(&options, 99));
+        assert_ne!(seeded(&options, 99), seeded(&options, 100));
+    }
+}
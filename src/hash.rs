@@ -0,0 +1,849 @@
+use argon2::password_hash::{self, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use rand::TryRngCore;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// bcrypt work factor (2^12 rounds); OWASP asks for at least 10
+pub(crate) const DEFAULT_BCRYPT_COST: u32 = 12;
+
+/// argon2id memory in KiB (19 MiB), OWASP's baseline configuration
+pub(crate) const DEFAULT_ARGON2_MEMORY_KIB: u32 = 19 * 1024;
+
+/// argon2id passes over memory for `DEFAULT_ARGON2_MEMORY_KIB`
+pub(crate) const DEFAULT_ARGON2_ITERATIONS: u32 = 2;
+
+/// argon2id lanes
+pub(crate) const DEFAULT_ARGON2_PARALLELISM: u32 = 1;
+
+/// scrypt CPU/memory cost as log2(N); N = 2^17 with r = 8 uses 128 MiB
+pub(crate) const DEFAULT_SCRYPT_LOG_N: u32 = 17;
+
+/// scrypt parallelization parameter p
+pub(crate) const DEFAULT_SCRYPT_PARALLELISM: u32 = 1;
+
+/// PBKDF2-HMAC-SHA256 iterations recommended by OWASP
+pub(crate) const DEFAULT_PBKDF2_ITERATIONS: u32 = 600_000;
+
+/// scrypt block size r, fixed at the value every recommendation assumes
+const SCRYPT_BLOCK_SIZE: u32 = 8;
+
+/// Random salt length in bytes, for every algorithm
+const SALT_LEN: usize = 16;
+
+/// Derived key length in bytes for scrypt and PBKDF2
+const OUTPUT_LEN: usize = 32;
+
+/// Longest password bcrypt hashes whole: it reads 72 bytes, the terminating NUL included
+const BCRYPT_MAX_PASSWORD_BYTES: usize = 71;
+
+/// Length of a bcrypt hash: `$2b$`, two cost digits, `$`, 22 salt and 31 hash characters
+const BCRYPT_HASH_LEN: usize = 60;
+
+/// Prefixes of the bcrypt versions `verify` accepts
+const BCRYPT_PREFIXES: [&str; 4] = ["$2a$", "$2b$", "$2x$", "$2y$"];
+
+/// Magic prefix of Apache's MD5-crypt variant
+const APR1_MAGIC: &str = "$apr1$";
+
+/// APR1 salt length in characters, the most the scheme reads
+const APR1_SALT_LEN: usize = 8;
+
+/// Alphabet of crypt(3)-style base64, used by MD5-crypt and SHA-512 crypt
+const CRYPT_BASE64: &[u8; 64] = b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Shortest hash value `verify` accepts, in bytes; anything shorter was cut off
+const MIN_HASH_OUTPUT_LEN: usize = 16;
+
+/// Valid ranges; below them the hashes are too cheap, above them too slow to be useful
+const BCRYPT_COST_RANGE: std::ops::RangeInclusive<u32> = 4..=31;
+const SCRYPT_LOG_N_RANGE: std::ops::RangeInclusive<u32> = 10..=24;
+const MAX_PARALLELISM: u32 = 255;
+const MAX_ARGON2_MEMORY_KIB: u32 = 4 * 1024 * 1024;
+const MIN_PBKDF2_ITERATIONS: u32 = 1000;
+
+// ============================================================================
+// Algorithms
+// ============================================================================
+
+/// Password hashing algorithms of the `hash` subcommand
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum HashAlgorithm {
+    /// bcrypt (`$2b$`), tuned with --cost
+    Bcrypt,
+    /// Argon2id (`$argon2id$`), tuned with --memory, --iterations and --parallelism
+    Argon2id,
+    /// scrypt (`$scrypt$`), tuned with --cost (log2 N) and --parallelism
+    Scrypt,
+    /// PBKDF2-HMAC-SHA256 (`$pbkdf2-sha256$`), tuned with --iterations
+    #[value(name = "pbkdf2-sha256")]
+    Pbkdf2Sha256,
+}
+
+impl HashAlgorithm {
+    /// Name used on the command line and in messages
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Bcrypt => "bcrypt",
+            HashAlgorithm::Argon2id => "argon2id",
+            HashAlgorithm::Scrypt => "scrypt",
+            HashAlgorithm::Pbkdf2Sha256 => "pbkdf2-sha256",
+        }
+    }
+
+    /// Cost options the algorithm accepts, as flag names
+    fn accepted_options(&self) -> &'static [&'static str] {
+        match self {
+            HashAlgorithm::Bcrypt => &["--cost"],
+            HashAlgorithm::Argon2id => &["--memory", "--iterations", "--parallelism"],
+            HashAlgorithm::Scrypt => &["--cost", "--parallelism"],
+            HashAlgorithm::Pbkdf2Sha256 => &["--iterations"],
+        }
+    }
+}
+
+// ============================================================================
+// Parameters
+// ============================================================================
+
+/// Cost options as given on the command line; unset ones take the algorithm's default
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct CostOptions {
+    /// bcrypt work factor, or scrypt log2(N)
+    pub(crate) cost: Option<u32>,
+    /// argon2id memory in KiB
+    pub(crate) memory_kib: Option<u32>,
+    /// argon2id passes or PBKDF2 iterations
+    pub(crate) iterations: Option<u32>,
+    /// argon2id lanes or scrypt p
+    pub(crate) parallelism: Option<u32>,
+}
+
+/// Validated parameters of one algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HashParams {
+    Bcrypt { cost: u32 },
+    Argon2id { memory_kib: u32, iterations: u32, parallelism: u32 },
+    Scrypt { log_n: u32, parallelism: u32 },
+    Pbkdf2Sha256 { iterations: u32 },
+}
+
+impl HashParams {
+    /// Fills in defaults and validates the cost options for an algorithm
+    ///
+    /// # Arguments
+    /// * `algorithm` - Algorithm to hash with
+    /// * `options` - Cost options as given on the command line
+    ///
+    /// # Returns
+    /// * `Result<HashParams, String>` - The parameters, or an error naming an option
+    ///   the algorithm does not take or a value outside its valid range
+    pub(crate) fn resolve(algorithm: HashAlgorithm, options: &CostOptions) -> Result<HashParams, String> {
+        let given = [
+            ("--cost", options.cost.is_some()),
+            ("--memory", options.memory_kib.is_some()),
+            ("--iterations", options.iterations.is_some()),
+            ("--parallelism", options.parallelism.is_some()),
+        ];
+        let accepted = algorithm.accepted_options();
+        if let Some((flag, _)) = given.iter().find(|(flag, set)| *set && !accepted.contains(flag)) {
+            return Err(format!("{} does not apply to {}; it takes {}", flag, algorithm.name(), accepted.join(", ")));
+        }
+
+        let check = |flag: &str, value: u32, min: u32, max: u32| {
+            if (min..=max).contains(&value) {
+                Ok(value)
+            } else {
+                Err(format!("{} {} is out of range for {}; choose between {} and {}", flag, value, algorithm.name(), min, max))
+            }
+        };
+
+        let params = match algorithm {
+            HashAlgorithm::Bcrypt => HashParams::Bcrypt {
+                cost: check("--cost", options.cost.unwrap_or(DEFAULT_BCRYPT_COST), *BCRYPT_COST_RANGE.start(), *BCRYPT_COST_RANGE.end())?,
+            },
+            HashAlgorithm::Argon2id => {
+                let parallelism = check("--parallelism", options.parallelism.unwrap_or(DEFAULT_ARGON2_PARALLELISM), 1, MAX_PARALLELISM)?;
+                HashParams::Argon2id {
+                    // Argon2 needs at least 8 KiB per lane
+                    memory_kib: check(
+                        "--memory",
+                        options.memory_kib.unwrap_or(DEFAULT_ARGON2_MEMORY_KIB),
+                        8 * parallelism,
+                        MAX_ARGON2_MEMORY_KIB,
+                    )?,
+                    iterations: check("--iterations", options.iterations.unwrap_or(DEFAULT_ARGON2_ITERATIONS), 1, u32::MAX)?,
+                    parallelism,
+                }
+            }
+            HashAlgorithm::Scrypt => HashParams::Scrypt {
+                log_n: check("--cost", options.cost.unwrap_or(DEFAULT_SCRYPT_LOG_N), *SCRYPT_LOG_N_RANGE.start(), *SCRYPT_LOG_N_RANGE.end())?,
+                parallelism: check("--parallelism", options.parallelism.unwrap_or(DEFAULT_SCRYPT_PARALLELISM), 1, MAX_PARALLELISM)?,
+            },
+            HashAlgorithm::Pbkdf2Sha256 => HashParams::Pbkdf2Sha256 {
+                iterations: check("--iterations", options.iterations.unwrap_or(DEFAULT_PBKDF2_ITERATIONS), MIN_PBKDF2_ITERATIONS, u32::MAX)?,
+            },
+        };
+        log::debug!("hash parameters: {:?}", params);
+        Ok(params)
+    }
+}
+
+/// Parses a memory size for `--memory`: KiB, or a number with a `k`, `m` or `g` suffix
+///
+/// # Returns
+/// * `Result<u32, String>` - The size in KiB, e.g. `64m` gives 65536
+pub(crate) fn parse_memory(value: &str) -> Result<u32, String> {
+    let value = value.trim();
+    let (digits, factor) = match value.char_indices().last() {
+        Some((index, 'k' | 'K')) => (&value[..index], 1),
+        Some((index, 'm' | 'M')) => (&value[..index], 1024),
+        Some((index, 'g' | 'G')) => (&value[..index], 1024 * 1024),
+        _ => (value, 1),
+    };
+    let amount: u32 = digits.parse().map_err(|_| format!("'{}' is not a memory size such as 19456, 64m or 1g", value))?;
+    amount.checked_mul(factor).ok_or_else(|| format!("'{}' is too large", value))
+}
+
+// ============================================================================
+// Hashing
+// ============================================================================
+
+/// Hashes a password with a fresh random salt
+///
+/// # Arguments
+/// * `password` - Password to hash
+/// * `params` - Algorithm and validated cost parameters
+///
+/// # Returns
+/// * `Result<String, String>` - The hash in PHC string format (`$argon2id$...`,
+///   `$scrypt$...`, `$pbkdf2-sha256$...`) or, for bcrypt, the modular crypt
+///   format (`$2b$12$...`)
+///
+/// # Notes
+/// bcrypt ignores everything after the first 72 bytes, so longer passwords
+/// are rejected instead of being hashed as if they were shorter.
+pub(crate) fn hash_password(password: &str, params: &HashParams) -> Result<String, String> {
+    hash_with_salt(password, params, &random_salt()?)
+}
+
+/// Hashes a password with bcrypt, refusing passwords bcrypt would truncate
+///
+/// # Arguments
+/// * `password` - Password to hash
+/// * `cost` - Work factor, already validated by `HashParams::resolve`
+/// * `version` - Prefix to write: `TwoB` in general, `TwoY` for Apache's htpasswd;
+///   the algorithm is the same
+pub(crate) fn hash_bcrypt(password: &str, cost: u32, version: bcrypt::Version) -> Result<String, String> {
+    bcrypt_with_salt(password, cost, version, random_salt()?)
+}
+
+/// Hashes a password with Apache's APR1 scheme (MD5-crypt with the `$apr1$` magic)
+///
+/// # Returns
+/// * `Result<String, String>` - `$apr1$` followed by an 8-character salt, `$` and
+///   the 22-character digest
+///
+/// # Notes
+/// APR1 is 1000 rounds of MD5 and falls quickly to a GPU; it only exists for
+/// web servers too old to read bcrypt hashes.
+pub(crate) fn hash_apr1(password: &str) -> Result<String, String> {
+    let random = random_salt()?;
+    let salt: String = random[..APR1_SALT_LEN].iter().map(|b| CRYPT_BASE64[(b & 0x3f) as usize] as char).collect();
+    Ok(md5_crypt(password.as_bytes(), salt.as_bytes(), APR1_MAGIC))
+}
+
+/// Hashes a password with SHA-512 crypt (`$6$`), the format `chpasswd -e` and `/etc/shadow` take
+///
+/// # Returns
+/// * `Result<String, String>` - `$6$`, a 16-character salt, `$` and the digest;
+///   the round count is glibc's default of 5000 and is therefore left out
+pub(crate) fn hash_sha512crypt(password: &str) -> Result<String, String> {
+    let random = random_salt()?;
+    let salt: String = random.iter().map(|b| CRYPT_BASE64[(b & 0x3f) as usize] as char).collect();
+    sha512crypt_with_salt(password, &salt)
+}
+
+/// `hash_password` with a given salt, so the output is reproducible
+fn hash_with_salt(password: &str, params: &HashParams, salt: &[u8]) -> Result<String, String> {
+    match *params {
+        HashParams::Bcrypt { cost } => {
+            let salt = salt.try_into().map_err(|_| format!("bcrypt takes a {}-byte salt", SALT_LEN))?;
+            bcrypt_with_salt(password, cost, bcrypt::Version::TwoB, salt)
+        }
+        HashParams::Argon2id { memory_kib, iterations, parallelism } => {
+            let argon2_params = argon2::Params::new(memory_kib, iterations, parallelism, None)
+                .map_err(|e| format!("invalid argon2id parameters: {}", e))?;
+            argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, argon2_params)
+                .hash_password(password.as_bytes(), &phc_salt(salt)?)
+                .map(|hash| hash.to_string())
+                .map_err(|e| format!("argon2id failed: {}", e))
+        }
+        HashParams::Scrypt { log_n, parallelism } => {
+            let scrypt_params = scrypt::Params::new(log_n as u8, SCRYPT_BLOCK_SIZE, parallelism, OUTPUT_LEN)
+                .map_err(|e| format!("invalid scrypt parameters: {}", e))?;
+            scrypt::Scrypt
+                .hash_password_customized(password.as_bytes(), None, None, scrypt_params, &phc_salt(salt)?)
+                .map(|hash| hash.to_string())
+                .map_err(|e| format!("scrypt failed: {}", e))
+        }
+        HashParams::Pbkdf2Sha256 { iterations } => {
+            let pbkdf2_params = pbkdf2::Params { rounds: iterations, output_length: OUTPUT_LEN };
+            pbkdf2::Pbkdf2
+                .hash_password_customized(
+                    password.as_bytes(),
+                    Some(pbkdf2::Algorithm::Pbkdf2Sha256.ident()),
+                    None,
+                    pbkdf2_params,
+                    &phc_salt(salt)?,
+                )
+                .map(|hash| hash.to_string())
+                .map_err(|e| format!("pbkdf2-sha256 failed: {}", e))
+        }
+    }
+}
+
+// ============================================================================
+// Verification
+// ============================================================================
+
+/// Identifies the algorithm of a hash and checks that the hash is well-formed
+///
+/// # Arguments
+/// * `hash` - A bcrypt hash (`$2b$...`) or a PHC string (`$argon2id$...`,
+///   `$scrypt$...`, `$pbkdf2-sha256$...`)
+///
+/// # Returns
+/// * `Result<HashAlgorithm, String>` - The algorithm, or an error saying what
+///   is wrong with the hash (unknown prefix, missing field, truncated value,
+///   invalid parameters)
+pub(crate) fn check_hash(hash: &str) -> Result<HashAlgorithm, String> {
+    let algorithm = detect_algorithm(hash)?;
+    match algorithm {
+        HashAlgorithm::Bcrypt => check_bcrypt_hash(hash)?,
+        _ => {
+            parse_phc_hash(hash, algorithm)?;
+        }
+    }
+    Ok(algorithm)
+}
+
+/// Checks a password against a hash
+///
+/// # Arguments
+/// * `password` - Password to check
+/// * `hash` - Hash in any format `check_hash` accepts
+///
+/// # Returns
+/// * `Result<bool, String>` - Whether the password matches, or an error for a
+///   malformed hash
+///
+/// # Notes
+/// The comparison is always the hashing crate's own constant-time one. As
+/// every bcrypt implementation does, bcrypt only compares the first 72 bytes
+/// of the password.
+pub(crate) fn verify_password(password: &str, hash: &str) -> Result<bool, String> {
+    let algorithm = check_hash(hash)?;
+    if algorithm == HashAlgorithm::Bcrypt {
+        return bcrypt::verify(password, hash).map_err(|e| format!("malformed bcrypt hash: {}", e));
+    }
+
+    let parsed = parse_phc_hash(hash, algorithm)?;
+    let result = match algorithm {
+        HashAlgorithm::Argon2id => argon2::Argon2::default().verify_password(password.as_bytes(), &parsed),
+        HashAlgorithm::Scrypt => scrypt::Scrypt.verify_password(password.as_bytes(), &parsed),
+        HashAlgorithm::Pbkdf2Sha256 => pbkdf2::Pbkdf2.verify_password(password.as_bytes(), &parsed),
+        HashAlgorithm::Bcrypt => unreachable!("bcrypt is handled above"),
+    };
+    match result {
+        Ok(()) => Ok(true),
+        Err(password_hash::Error::Password) => Ok(false),
+        Err(e) => Err(format!("{} verification failed: {}", algorithm.name(), e)),
+    }
+}
+
+/// Identifies the algorithm of a hash from its prefix
+fn detect_algorithm(hash: &str) -> Result<HashAlgorithm, String> {
+    const SUPPORTED: &str = "supported are $2b$ (bcrypt), $argon2id$, $scrypt$ and $pbkdf2-sha256$";
+
+    if hash.is_empty() {
+        return Err("the hash is empty; if it was typed unquoted, the shell may have expanded its $ signs, so put it in single quotes".to_string());
+    }
+    if !hash.starts_with('$') {
+        return Err(format!("the hash does not start with '$', so its format cannot be recognized; {}", SUPPORTED));
+    }
+    if BCRYPT_PREFIXES.iter().any(|prefix| hash.starts_with(prefix)) {
+        return Ok(HashAlgorithm::Bcrypt);
+    }
+
+    let prefix = match hash[1..].find('$') {
+        Some(end) => &hash[..end + 2],
+        None => hash,
+    };
+    match prefix {
+        "$argon2id$" => Ok(HashAlgorithm::Argon2id),
+        "$scrypt$" => Ok(HashAlgorithm::Scrypt),
+        "$pbkdf2-sha256$" => Ok(HashAlgorithm::Pbkdf2Sha256),
+        _ => Err(format!("unsupported hash format '{}'; {}", prefix, SUPPORTED)),
+    }
+}
+
+/// Checks the layout of a bcrypt hash: `$2b$`, a two-digit cost, `$` and 53 base64 characters
+fn check_bcrypt_hash(hash: &str) -> Result<(), String> {
+    if hash.len() != BCRYPT_HASH_LEN {
+        let problem = if hash.len() < BCRYPT_HASH_LEN { "it looks truncated" } else { "it has trailing characters" };
+        return Err(format!(
+            "malformed bcrypt hash: it is {} characters long instead of {}; {}",
+            hash.len(),
+            BCRYPT_HASH_LEN,
+            problem
+        ));
+    }
+
+    let bytes = hash.as_bytes();
+    if !(bytes[4].is_ascii_digit() && bytes[5].is_ascii_digit() && bytes[6] == b'$') {
+        return Err(format!("malformed bcrypt hash: expected a two-digit cost and '$' after '{}'", &hash[..4]));
+    }
+    let cost = u32::from(bytes[4] - b'0') * 10 + u32::from(bytes[5] - b'0');
+    if !BCRYPT_COST_RANGE.contains(&cost) {
+        return Err(format!(
+            "malformed bcrypt hash: cost {} is outside {}-{}",
+            cost,
+            BCRYPT_COST_RANGE.start(),
+            BCRYPT_COST_RANGE.end()
+        ));
+    }
+
+    if let Some(c) = hash[7..].chars().find(|c| !(c.is_ascii_alphanumeric() || *c == '.' || *c == '/')) {
+        return Err(format!("malformed bcrypt hash: '{}' is not a bcrypt base64 character", c));
+    }
+    Ok(())
+}
+
+/// Parses a PHC string and checks it has everything needed to verify against it
+fn parse_phc_hash(hash: &str, algorithm: HashAlgorithm) -> Result<PasswordHash<'_>, String> {
+    let name = algorithm.name();
+    let parsed = PasswordHash::new(hash).map_err(|e| match e {
+        password_hash::Error::PhcStringField => format!("malformed {} hash: a field is missing; it looks truncated", name),
+        password_hash::Error::OutputSize { .. } => format!("malformed {} hash: the hash value is too short; it looks truncated", name),
+        password_hash::Error::B64Encoding(_) => format!("malformed {} hash: the salt or hash value is not valid base64; it may be truncated", name),
+        e => format!("malformed {} hash: {}", name, e),
+    })?;
+
+    if parsed.salt.is_none() {
+        return Err(format!("malformed {} hash: it has no salt; it looks truncated", name));
+    }
+    let output_len = match parsed.hash {
+        Some(output) => output.len(),
+        None => return Err(format!("malformed {} hash: it has no hash value after the salt; it looks truncated", name)),
+    };
+    if output_len < MIN_HASH_OUTPUT_LEN {
+        return Err(format!(
+            "malformed {} hash: the hash value is only {} bytes (at least {} expected); it looks truncated",
+            name, output_len, MIN_HASH_OUTPUT_LEN
+        ));
+    }
+
+    let invalid = |e: password_hash::Error| format!("malformed {} hash: invalid parameters: {}", name, e);
+    match algorithm {
+        // Refuse costs no hash made by this tool has, rather than allocating whatever the string asks for
+        HashAlgorithm::Argon2id => {
+            let params = argon2::Params::try_from(&parsed).map_err(invalid)?;
+            if params.m_cost() > MAX_ARGON2_MEMORY_KIB {
+                return Err(format!(
+                    "argon2id hash asks for {} KiB of memory, more than the {} KiB limit",
+                    params.m_cost(),
+                    MAX_ARGON2_MEMORY_KIB
+                ));
+            }
+        }
+        HashAlgorithm::Scrypt => {
+            let params = scrypt::Params::try_from(&parsed).map_err(invalid)?;
+            if u32::from(params.log_n()) > *SCRYPT_LOG_N_RANGE.end() || params.p() > MAX_PARALLELISM {
+                return Err(format!(
+                    "scrypt hash asks for ln={} and p={}, more than the limits of ln={} and p={}",
+                    params.log_n(),
+                    params.p(),
+                    SCRYPT_LOG_N_RANGE.end(),
+                    MAX_PARALLELISM
+                ));
+            }
+        }
+        HashAlgorithm::Pbkdf2Sha256 => {
+            if let Some(declared) = parsed.params.get_decimal("l").filter(|l| *l as usize != output_len) {
+                return Err(format!(
+                    "malformed pbkdf2-sha256 hash: the hash value is {} bytes instead of the {} declared by l; it looks truncated",
+                    output_len, declared
+                ));
+            }
+            pbkdf2::Params::try_from(&parsed).map_err(invalid)?;
+        }
+        HashAlgorithm::Bcrypt => {}
+    }
+    Ok(parsed)
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// bcrypt with a given salt; see `hash_bcrypt`
+fn bcrypt_with_salt(password: &str, cost: u32, version: bcrypt::Version, salt: [u8; SALT_LEN]) -> Result<String, String> {
+    if password.len() > BCRYPT_MAX_PASSWORD_BYTES {
+        return Err(format!(
+            "bcrypt only uses a password's first {} bytes (72 with the terminating NUL) and this one has {}; choose another algorithm",
+            BCRYPT_MAX_PASSWORD_BYTES,
+            password.len()
+        ));
+    }
+    bcrypt::non_truncating_hash_with_salt(password, cost, salt)
+        .map(|parts| parts.format_for_version(version))
+        .map_err(|e| format!("bcrypt failed: {}", e))
+}
+
+/// SHA-512 crypt with a given salt of crypt base64 characters; see `hash_sha512crypt`
+fn sha512crypt_with_salt(password: &str, salt: &str) -> Result<String, String> {
+    let digest = sha_crypt::sha512_crypt_b64(password.as_bytes(), salt.as_bytes(), &sha_crypt::Sha512Params::default())
+        .map_err(|e| format!("sha512crypt failed: {:?}", e))?;
+    Ok(format!("$6${}${}", salt, digest))
+}
+
+/// Draws a salt from the operating system's random number generator
+fn random_salt() -> Result<[u8; SALT_LEN], String> {
+    let mut salt = [0u8; SALT_LEN];
+    rand::rngs::OsRng
+        .try_fill_bytes(&mut salt)
+        .map_err(|e| format!("the operating system's random number generator failed: {}", e))?;
+    Ok(salt)
+}
+
+/// MD5-crypt as specified by FreeBSD's `crypt_md5`, with a configurable magic string
+fn md5_crypt(password: &[u8], salt: &[u8], magic: &str) -> String {
+    use md5::{Digest, Md5};
+
+    let mut alternate = Md5::new();
+    alternate.update(password);
+    alternate.update(salt);
+    alternate.update(password);
+    let alternate = alternate.finalize();
+
+    let mut context = Md5::new();
+    context.update(password);
+    context.update(magic.as_bytes());
+    context.update(salt);
+    for chunk in password.chunks(16) {
+        context.update(&alternate[..chunk.len()]);
+    }
+    // Mixes in a zero byte or the first password byte for each bit of the length
+    let mut bits = password.len();
+    while bits > 0 {
+        context.update(if bits & 1 == 1 { &[0u8][..] } else { &password[..1] });
+        bits >>= 1;
+    }
+    let mut digest = context.finalize();
+
+    for round in 0..1000 {
+        let mut context = Md5::new();
+        if round % 2 == 1 {
+            context.update(password);
+        } else {
+            context.update(digest);
+        }
+        if round % 3 != 0 {
+            context.update(salt);
+        }
+        if round % 7 != 0 {
+            context.update(password);
+        }
+        if round % 2 == 1 {
+            context.update(digest);
+        } else {
+            context.update(password);
+        }
+        digest = context.finalize();
+    }
+
+    let mut encoded = format!("{}{}$", magic, String::from_utf8_lossy(salt));
+    let mut push = |value: u32, count: usize| {
+        for shift in 0..count {
+            encoded.push(CRYPT_BASE64[((value >> (6 * shift)) & 0x3f) as usize] as char);
+        }
+    };
+    for (a, b, c) in [(0, 6, 12), (1, 7, 13), (2, 8, 14), (3, 9, 15), (4, 10, 5)] {
+        push(u32::from(digest[a]) << 16 | u32::from(digest[b]) << 8 | u32::from(digest[c]), 4);
+    }
+    push(u32::from(digest[11]), 2);
+    encoded
+}
+
+/// Encodes salt bytes the way PHC strings carry them (unpadded base64)
+fn phc_salt(salt: &[u8]) -> Result<SaltString, String> {
+    SaltString::encode_b64(salt).map_err(|e| format!("cannot encode salt: {}", e))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The cheapest parameters each algorithm accepts, so the round trips stay fast
+    const CHEAP: [HashParams; 4] = [
+        HashParams::Bcrypt { cost: 4 },
+        HashParams::Argon2id { memory_kib: 64, iterations: 1, parallelism: 1 },
+        HashParams::Scrypt { log_n: 10, parallelism: 1 },
+        HashParams::Pbkdf2Sha256 { iterations: 1000 },
+    ];
+
+    /// Salt of the scrypt and PBKDF2 vectors below
+    const SALT: &[u8; SALT_LEN] = b"0123456789abcdef";
+
+    #[test]
+    fn bcrypt_matches_the_openwall_vectors() {
+        // From the crypt_blowfish test suite
+        for (password, hash) in [
+            ("U*U", "$2a$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW"),
+            ("U*U*", "$2a$05$CCCCCCCCCCCCCCCCCCCCC.VGOzA784oUp/Z0DY336zx7pLYAy0lwK"),
+            ("U*U*U", "$2a$05$XXXXXXXXXXXXXXXXXXXXXOAcXxm9kjPGEMsLznoKqmqw7tc8WCx4a"),
+        ] {
+            assert_eq!(verify_password(password, hash), Ok(true), "{}", hash);
+            // $2b$ and $2y$ only differ in the prefix
+            for prefix in ["$2b$", "$2y$"] {
+                assert_eq!(verify_password(password, &format!("{}{}", prefix, &hash[4..])), Ok(true), "{}", prefix);
+            }
+            assert_eq!(verify_password("U*U*U*", hash), Ok(false));
+        }
+    }
+
+    #[test]
+    fn argon2id_matches_the_reference_vector() {
+        // The argon2id vector of the reference implementation's test.c
+        let expected = "$argon2id$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$CTFhFdXPJO1aFaMaO6Mm5c8y7cJHAph8ArZWb2GRPPc";
+        let params = HashParams::Argon2id { memory_kib: 65536, iterations: 2, parallelism: 1 };
+        assert_eq!(hash_with_salt("password", &params, b"somesalt").unwrap(), expected);
+        assert_eq!(verify_password("password", expected), Ok(true));
+        assert_eq!(verify_password("Password", expected), Ok(false));
+    }
+
+    #[test]
+    fn scrypt_matches_the_rfc_7914_vector() {
+        // RFC 7914 section 12, second vector: N=1024, r=8, p=16, dkLen=64
+        let rfc = "$scrypt$ln=10,r=8,p=16$TmFDbA$/bq+HJ00cgB4VucZDQHp/nxq18vII3gw53N2Y0s3MWIurzDZLiKjiG/xCSedmDDaxyevuUqD7m2DYMvfoswGQA";
+        assert_eq!(verify_password("password", rfc), Ok(true));
+        assert_eq!(verify_password("passwore", rfc), Ok(false));
+
+        // The same construction with this tool's r=8 and 32-byte output, as Python's hashlib.scrypt computes it
+        let expected = "$scrypt$ln=10,r=8,p=1$MDEyMzQ1Njc4OWFiY2RlZg$ZEBCzLptWM7dhpNJDU2HbQ945ovKHmVEozHkePPbSqw";
+        assert_eq!(hash_with_salt("password", &HashParams::Scrypt { log_n: 10, parallelism: 1 }, SALT).unwrap(), expected);
+    }
+
+    #[test]
+    fn pbkdf2_sha256_matches_hashlib() {
+        // hashlib.pbkdf2_hmac("sha256", b"password", b"0123456789abcdef", 1000, 32)
+        let expected = "$pbkdf2-sha256$i=1000,l=32$MDEyMzQ1Njc4OWFiY2RlZg$hRRjgXWkW8ResfIvBP99J/T4vkgEmMRV/0tJTOjR59I";
+        assert_eq!(hash_with_salt("password", &HashParams::Pbkdf2Sha256 { iterations: 1000 }, SALT).unwrap(), expected);
+        assert_eq!(verify_password("password", expected), Ok(true));
+        assert_eq!(verify_password("password ", expected), Ok(false));
+    }
+
+    #[test]
+    fn apr1_matches_openssl() {
+        // openssl passwd -apr1 -salt SALT PASSWORD
+        assert_eq!(md5_crypt(b"password", b"saltsalt", APR1_MAGIC), "$apr1$saltsalt$yAAkm4libquA.ZWLHbSBq/");
+        assert_eq!(md5_crypt(b"U*U", b"r31....", APR1_MAGIC), "$apr1$r31....$3x.yNHmWpwrvW/OEXzlUK1");
+    }
+
+    #[test]
+    fn sha512crypt_matches_openssl() {
+        // openssl passwd -6 -salt saltsaltsaltsalt password
+        assert_eq!(
+            sha512crypt_with_salt("password", "saltsaltsaltsalt").unwrap(),
+            "$6$saltsaltsaltsalt$bcXJ8qxwY5sQ4v8MTl.0B1jeZ0z0JlA9jjmbUoCJZ.1wYXiLTU.q2ILyrDJLm890lyfuF7sWAeli0yjOyFPkf0"
+        );
+    }
+
+    #[test]
+    fn every_algorithm_round_trips() {
+        for params in CHEAP {
+            let hash = hash_password("correct horse", &params).unwrap();
+            assert_eq!(verify_password("correct horse", &hash), Ok(true), "{}", hash);
+            assert_eq!(verify_password("correct horsf", &hash), Ok(false), "{}", hash);
+            assert_eq!(verify_password("", &hash), Ok(false), "{}", hash);
+            // A fresh salt every time
+            assert_ne!(hash_password("correct horse", &params).unwrap(), hash);
+        }
+    }
+
+    #[test]
+    fn non_ascii_passwords_round_trip() {
+        for params in CHEAP {
+            let hash = hash_password("pässwörd 🔑", &params).unwrap();
+            assert_eq!(verify_password("pässwörd 🔑", &hash), Ok(true), "{}", hash);
+            assert_eq!(verify_password("passwörd 🔑", &hash), Ok(false), "{}", hash);
+        }
+    }
+
+    #[test]
+    fn apr1_and_sha512crypt_reproduce_from_their_salt() {
+        let apr1 = hash_apr1("secret").unwrap();
+        let salt = &apr1[APR1_MAGIC.len()..APR1_MAGIC.len() + APR1_SALT_LEN];
+        assert_eq!(md5_crypt(b"secret", salt.as_bytes(), APR1_MAGIC), apr1);
+        assert_ne!(md5_crypt(b"Secret", salt.as_bytes(), APR1_MAGIC), apr1);
+
+        let sha512 = hash_sha512crypt("secret").unwrap();
+        let salt = sha512.split('$').nth(2).unwrap();
+        assert_eq!(salt.len(), SALT_LEN);
+        assert_eq!(sha512crypt_with_salt("secret", salt).unwrap(), sha512);
+        assert_ne!(sha512crypt_with_salt("Secret", salt).unwrap(), sha512);
+    }
+
+    #[test]
+    fn bcrypt_refuses_passwords_it_would_truncate() {
+        assert!(hash_bcrypt(&"a".repeat(BCRYPT_MAX_PASSWORD_BYTES), 4, bcrypt::Version::TwoB).is_ok());
+        let error = hash_bcrypt(&"a".repeat(BCRYPT_MAX_PASSWORD_BYTES + 1), 4, bcrypt::Version::TwoB).unwrap_err();
+        assert!(error.contains("first 71 bytes"), "{}", error);
+        // The limit is in bytes, not characters
+        assert!(hash_bcrypt(&"ä".repeat(36), 4, bcrypt::Version::TwoB).is_err());
+    }
+
+    #[test]
+    fn htpasswd_bcrypt_uses_the_2y_prefix() {
+        let hash = hash_bcrypt("secret", 4, bcrypt::Version::TwoY).unwrap();
+        assert!(hash.starts_with("$2y$04$"), "{}", hash);
+        assert_eq!(verify_password("secret", &hash), Ok(true));
+    }
+
+    #[test]
+    fn defaults_follow_owasp() {
+        let defaults = |algorithm| HashParams::resolve(algorithm, &CostOptions::default()).unwrap();
+        assert_eq!(defaults(HashAlgorithm::Bcrypt), HashParams::Bcrypt { cost: 12 });
+        assert_eq!(defaults(HashAlgorithm::Argon2id), HashParams::Argon2id { memory_kib: 19456, iterations: 2, parallelism: 1 });
+        assert_eq!(defaults(HashAlgorithm::Scrypt), HashParams::Scrypt { log_n: 17, parallelism: 1 });
+        assert_eq!(defaults(HashAlgorithm::Pbkdf2Sha256), HashParams::Pbkdf2Sha256 { iterations: 600_000 });
+    }
+
+    #[test]
+    fn options_of_another_algorithm_are_rejected() {
+        let options = CostOptions { memory_kib: Some(65536), ..Default::default() };
+        let error = HashParams::resolve(HashAlgorithm::Bcrypt, &options).unwrap_err();
+        assert_eq!(error, "--memory does not apply to bcrypt; it takes --cost");
+        let options = CostOptions { cost: Some(12), ..Default::default() };
+        assert!(HashParams::resolve(HashAlgorithm::Pbkdf2Sha256, &options).is_err());
+        assert!(HashParams::resolve(HashAlgorithm::Scrypt, &options).is_ok());
+    }
+
+    #[test]
+    fn out_of_range_costs_are_rejected() {
+        let resolve = |algorithm, options| HashParams::resolve(algorithm, &options);
+        for (algorithm, options, ok) in [
+            (HashAlgorithm::Bcrypt, CostOptions { cost: Some(3), ..Default::default() }, false),
+            (HashAlgorithm::Bcrypt, CostOptions { cost: Some(4), ..Default::default() }, true),
+            (HashAlgorithm::Bcrypt, CostOptions { cost: Some(31), ..Default::default() }, true),
+            (HashAlgorithm::Bcrypt, CostOptions { cost: Some(32), ..Default::default() }, false),
+            (HashAlgorithm::Scrypt, CostOptions { cost: Some(9), ..Default::default() }, false),
+            (HashAlgorithm::Scrypt, CostOptions { cost: Some(25), ..Default::default() }, false),
+            (HashAlgorithm::Scrypt, CostOptions { parallelism: Some(0), ..Default::default() }, false),
+            (HashAlgorithm::Argon2id, CostOptions { memory_kib: Some(16), parallelism: Some(2), ..Default::default() }, true),
+            (HashAlgorithm::Argon2id, CostOptions { memory_kib: Some(15), parallelism: Some(2), ..Default::default() }, false),
+            (HashAlgorithm::Argon2id, CostOptions { memory_kib: Some(MAX_ARGON2_MEMORY_KIB + 1), ..Default::default() }, false),
+            (HashAlgorithm::Argon2id, CostOptions { iterations: Some(0), ..Default::default() }, false),
+            (HashAlgorithm::Pbkdf2Sha256, CostOptions { iterations: Some(999), ..Default::default() }, false),
+            (HashAlgorithm::Pbkdf2Sha256, CostOptions { iterations: Some(1000), ..Default::default() }, true),
+        ] {
+            assert_eq!(resolve(algorithm, options).is_ok(), ok, "{:?} {:?}", algorithm, options);
+        }
+    }
+
+    /// One well-formed hash of "password" per format `check_hash` accepts
+    const VALID: [(&str, HashAlgorithm); 4] = [
+        ("$2b$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW", HashAlgorithm::Bcrypt),
+        ("$argon2id$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$CTFhFdXPJO1aFaMaO6Mm5c8y7cJHAph8ArZWb2GRPPc", HashAlgorithm::Argon2id),
+        ("$scrypt$ln=10,r=8,p=1$MDEyMzQ1Njc4OWFiY2RlZg$ZEBCzLptWM7dhpNJDU2HbQ945ovKHmVEozHkePPbSqw", HashAlgorithm::Scrypt),
+        ("$pbkdf2-sha256$i=1000,l=32$MDEyMzQ1Njc4OWFiY2RlZg$hRRjgXWkW8ResfIvBP99J/T4vkgEmMRV/0tJTOjR59I", HashAlgorithm::Pbkdf2Sha256),
+    ];
+
+    #[test]
+    fn check_hash_detects_every_supported_format() {
+        for (hash, algorithm) in VALID {
+            assert_eq!(check_hash(hash), Ok(algorithm), "{}", hash);
+        }
+        for prefix in ["$2a$", "$2x$", "$2y$"] {
+            let hash = format!("{}{}", prefix, &VALID[0].0[4..]);
+            assert_eq!(check_hash(&hash), Ok(HashAlgorithm::Bcrypt), "{}", hash);
+        }
+    }
+
+    #[test]
+    fn wrong_passwords_do_not_match_any_format() {
+        for (hash, _) in VALID {
+            // The bcrypt vector is of "U*U", the others of "password"
+            assert_eq!(verify_password("not the password", hash), Ok(false), "{}", hash);
+        }
+    }
+
+    #[test]
+    fn unknown_prefixes_list_the_supported_formats() {
+        let supported = "supported are $2b$ (bcrypt), $argon2id$, $scrypt$ and $pbkdf2-sha256$";
+        for (hash, prefix) in [
+            ("$argon2i$v=19$m=65536,t=2,p=1$c29tZXNhbHQ$CTFhFdXPJO1aFaMaO6Mm5c8y7cJHAph8ArZWb2GRPPc", "$argon2i$"),
+            ("$6$saltsalt$abcdef", "$6$"),
+            ("$1$", "$1$"),
+            ("$nodollar", "$nodollar"),
+        ] {
+            assert_eq!(check_hash(hash), Err(format!("unsupported hash format '{}'; {}", prefix, supported)), "{}", hash);
+        }
+        assert_eq!(
+            check_hash("plaintext"),
+            Err(format!("the hash does not start with '$', so its format cannot be recognized; {}", supported))
+        );
+        assert!(check_hash("").unwrap_err().starts_with("the hash is empty; if it was typed unquoted"));
+        // verify reports the same error instead of a mismatch
+        assert_eq!(verify_password("password", "$6$saltsalt$abcdef"), Err(check_hash("$6$saltsalt$abcdef").unwrap_err()));
+    }
+
+    #[test]
+    fn malformed_bcrypt_hashes_say_what_is_wrong() {
+        let valid = VALID[0].0;
+        let error = |hash: &str| check_hash(hash).unwrap_err();
+        assert_eq!(error(&valid[..40]), "malformed bcrypt hash: it is 40 characters long instead of 60; it looks truncated");
+        assert_eq!(
+            error(&format!("{}x", valid)),
+            "malformed bcrypt hash: it is 61 characters long instead of 60; it has trailing characters"
+        );
+        assert_eq!(
+            error(&valid.replacen("$05$", "$5$$", 1)),
+            "malformed bcrypt hash: expected a two-digit cost and '$' after '$2b$'"
+        );
+        assert_eq!(error(&valid.replacen("$05$", "$32$", 1)), "malformed bcrypt hash: cost 32 is outside 4-31");
+        assert_eq!(error(&valid.replacen('C', "!", 1)), "malformed bcrypt hash: '!' is not a bcrypt base64 character");
+    }
+
+    #[test]
+    fn truncated_phc_hashes_say_they_look_truncated() {
+        for (hash, algorithm) in &VALID[1..] {
+            let name = algorithm.name();
+            let without_value = &hash[..hash.rfind('$').unwrap()];
+            let error = check_hash(without_value).unwrap_err();
+            assert!(error.starts_with(&format!("malformed {} hash: ", name)), "{}", error);
+            assert!(error.ends_with("it looks truncated"), "{}", error);
+
+            let short_value = &hash[..hash.rfind('$').unwrap() + 8];
+            let error = check_hash(short_value).unwrap_err();
+            assert!(error.starts_with(&format!("malformed {} hash: ", name)), "{}", error);
+        }
+    }
+
+    #[test]
+    fn memory_sizes_take_suffixes() {
+        assert_eq!(parse_memory("19456"), Ok(19456));
+        assert_eq!(parse_memory("64k"), Ok(64));
+        assert_eq!(parse_memory("64m"), Ok(65536));
+        assert_eq!(parse_memory(" 1G "), Ok(1024 * 1024));
+        assert!(parse_memory("64mb").is_err());
+        assert!(parse_memory("m").is_err());
+        assert!(parse_memory("4096g").is_err());
+    }
+}
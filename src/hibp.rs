@@ -0,0 +1,481 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+use sha1::{Digest, Sha1};
+use rusty_password_utility::analyzer::BreachCount;
+use crate::config::APP_NAME;
+use crate::output;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Hex characters of the SHA-1 hash sent to the API (k-anonymity: the rest never leaves the machine)
+const PREFIX_LEN: usize = 5;
+
+/// Pause between two requests, so a large audit stays polite
+const REQUEST_INTERVAL: Duration = Duration::from_millis(100);
+
+/// First wait after a 429 without `Retry-After`; doubled on every further 429
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Retries of one prefix after 429 responses before it is given up
+const MAX_RETRIES: u32 = 5;
+
+/// Failed requests in a row after which the remaining prefixes are not tried
+const MAX_CONSECUTIVE_FAILURES: usize = 3;
+
+/// Timeout of a single request, connection included
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+// ============================================================================
+// Range Sources
+// ============================================================================
+
+/// Why a range could not be fetched
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum FetchError {
+    /// HTTP 429; `retry_after` is the server's `Retry-After`, if it sent one
+    RateLimited { retry_after: Option<Duration> },
+    /// Anything else: connection errors, timeouts, unexpected status codes
+    Failed(String),
+}
+
+/// Where range responses come from: the HTTP API, or a stub that answers without the network
+pub(crate) trait RangeSource {
+    /// Fetches the `SUFFIX:COUNT` lines for a 5-character uppercase hash prefix
+    fn fetch(&mut self, prefix: &str) -> Result<String, FetchError>;
+}
+
+/// Queries the Pwned Passwords range API over HTTPS
+pub(crate) struct HttpRangeSource {
+    agent: ureq::Agent,
+    api: String,
+}
+
+impl HttpRangeSource {
+    /// Creates a client for `api`, the range endpoint the prefix is appended to
+    pub(crate) fn new(api: &str) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(REQUEST_TIMEOUT))
+            .http_status_as_error(false)
+            .user_agent(concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .into();
+        HttpRangeSource { agent, api: api.to_string() }
+    }
+}
+
+impl RangeSource for HttpRangeSource {
+    fn fetch(&mut self, prefix: &str) -> Result<String, FetchError> {
+        let url = format!("{}{}", self.api, prefix);
+        // Padding hides how many suffixes the prefix really has from anyone watching the traffic
+        let mut response = self
+            .agent
+            .get(&url)
+            .header("Add-Padding", "true")
+            .call()
+            .map_err(|e| FetchError::Failed(format!("{}: {}", url, e)))?;
+
+        match response.status().as_u16() {
+            200 => response.body_mut().read_to_string().map_err(|e| FetchError::Failed(format!("{}: {}", url, e))),
+            429 => {
+                let retry_after = response
+                    .headers()
+                    .get("retry-after")
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.trim().parse::<u64>().ok())
+                    .map(Duration::from_secs);
+                Err(FetchError::RateLimited { retry_after })
+            }
+            status => Err(FetchError::Failed(format!("{}: HTTP {}", url, status))),
+        }
+    }
+}
+
+// ============================================================================
+// Cache
+// ============================================================================
+
+/// Range responses kept on disk, one file per prefix
+#[derive(Debug, Clone)]
+pub(crate) struct RangeCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl RangeCache {
+    /// Caches in `dir`; responses older than `ttl` are downloaded again
+    pub(crate) fn new(dir: PathBuf, ttl: Duration) -> Self {
+        RangeCache { dir, ttl }
+    }
+
+    /// The platform cache directory (e.g. `~/.cache/rustypass/hibp` on Linux)
+    pub(crate) fn default_dir() -> Option<PathBuf> {
+        directories::ProjectDirs::from("", "", APP_NAME).map(|dirs| dirs.cache_dir().join("hibp"))
+    }
+
+    /// Returns the cached response for a prefix, unless it is missing or older than the TTL
+    fn load(&self, prefix: &str) -> Option<String> {
+        let path = self.dir.join(prefix);
+        let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()?;
+        // A file from the future (clock changes) counts as fresh only up to the TTL
+        let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+        if age >= self.ttl {
+            return None;
+        }
+        fs::read_to_string(&path).ok()
+    }
+
+    /// Stores a response; a cache that cannot be written only costs a download next time
+    fn store(&self, prefix: &str, body: &str) {
+        let written = fs::create_dir_all(&self.dir).and_then(|()| output::replace_file(&self.dir.join(prefix), body.as_bytes()));
+        if let Err(e) = written {
+            log::debug!("cannot cache range {} in {}: {}", prefix, self.dir.display(), e);
+        }
+    }
+}
+
+// ============================================================================
+// Lookups
+// ============================================================================
+
+/// Timing of the requests
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Pacing {
+    /// Minimum time between two requests
+    pub(crate) interval: Duration,
+    /// First wait after a 429 without `Retry-After`
+    pub(crate) initial_backoff: Duration,
+    /// Retries of one prefix after 429 responses
+    pub(crate) max_retries: u32,
+}
+
+impl Default for Pacing {
+    fn default() -> Self {
+        Pacing { interval: REQUEST_INTERVAL, initial_backoff: INITIAL_BACKOFF, max_retries: MAX_RETRIES }
+    }
+}
+
+/// Result of `check_passwords`
+#[derive(Debug)]
+pub(crate) struct Lookup {
+    /// One count per password, in input order
+    pub(crate) counts: Vec<BreachCount>,
+    /// Prefixes that could not be fetched; their passwords are `Unknown`
+    pub(crate) failed_prefixes: usize,
+    /// Why the first failed prefix failed
+    pub(crate) first_error: Option<String>,
+}
+
+/// Number of distinct hash prefixes among the passwords, i.e. how many ranges `check_passwords` needs
+pub(crate) fn prefix_count(passwords: &[&str]) -> usize {
+    passwords.iter().map(|password| hash_parts(password).0).collect::<BTreeSet<_>>().len()
+}
+
+/// Looks passwords up in the breach corpus, one range per distinct hash prefix
+///
+/// # Arguments
+/// * `passwords` - Passwords to check; duplicates cost nothing extra
+/// * `source` - Where ranges come from when the cache has none
+/// * `cache` - On-disk cache of earlier responses, if any
+/// * `pacing` - Spacing of the requests and backoff after 429 responses
+/// * `progress` - Called once per prefix, e.g. to advance a progress bar
+///
+/// # Returns
+/// * `Lookup` - Counts in input order; passwords whose range could not be
+///   fetched are `Unknown` instead of failing the whole run
+///
+/// # Notes
+/// Only the first five hex characters of each SHA-1 hash are sent. After
+/// three failed requests in a row the remaining prefixes are not tried, so an
+/// offline run ends quickly.
+pub(crate) fn check_passwords(
+    passwords: &[&str],
+    source: &mut dyn RangeSource,
+    cache: Option<&RangeCache>,
+    pacing: &Pacing,
+    mut progress: impl FnMut(),
+) -> Lookup {
+    // Identical prefixes share one request
+    let mut by_prefix: BTreeMap<String, Vec<(usize, String)>> = BTreeMap::new();
+    for (index, password) in passwords.iter().enumerate() {
+        let (prefix, suffix) = hash_parts(password);
+        by_prefix.entry(prefix).or_default().push((index, suffix));
+    }
+    log::debug!("{} password(s), {} range(s) to check", passwords.len(), by_prefix.len());
+
+    let mut lookup = Lookup { counts: vec![BreachCount::Unknown; passwords.len()], failed_prefixes: 0, first_error: None };
+    let mut last_request: Option<Instant> = None;
+    let mut failures_in_a_row = 0;
+
+    for (prefix, members) in &by_prefix {
+        progress();
+        let range = match cache.and_then(|cache| cache.load(prefix)) {
+            Some(body) => parse_range(&body).ok(),
+            None => None,
+        };
+        let range = match range {
+            Some(range) => Ok(range),
+            None if failures_in_a_row >= MAX_CONSECUTIVE_FAILURES => Err(String::from("skipped after repeated failures")),
+            None => fetch_with_backoff(source, prefix, pacing, &mut last_request).and_then(|body| {
+                let range = parse_range(&body)?;
+                if let Some(cache) = cache {
+                    cache.store(prefix, &body);
+                }
+                Ok(range)
+            }),
+        };
+
+        match range {
+            Ok(range) => {
+                failures_in_a_row = 0;
+                for (index, suffix) in members {
+                    lookup.counts[*index] = BreachCount::Seen(range.get(suffix).copied().unwrap_or(0));
+                }
+            }
+            Err(e) => {
+                failures_in_a_row += 1;
+                log::debug!("range {}: {}", prefix, e);
+                lookup.failed_prefixes += 1;
+                lookup.first_error.get_or_insert(e);
+            }
+        }
+    }
+    lookup
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Splits the uppercase hex SHA-1 of a password into the prefix sent and the suffix kept
+fn hash_parts(password: &str) -> (String, String) {
+    let digest = Sha1::digest(password.as_bytes());
+    let hex: String = digest.iter().map(|byte| format!("{:02X}", byte)).collect();
+    let (prefix, suffix) = hex.split_at(PREFIX_LEN);
+    (prefix.to_string(), suffix.to_string())
+}
+
+/// Fetches a range, waiting out the request interval and backing off on 429 responses
+fn fetch_with_backoff(source: &mut dyn RangeSource, prefix: &str, pacing: &Pacing, last_request: &mut Option<Instant>) -> Result<String, String> {
+    let mut backoff = pacing.initial_backoff;
+    let mut retries = 0;
+    loop {
+        if let Some(wait) = last_request.and_then(|last| pacing.interval.checked_sub(last.elapsed())) {
+            std::thread::sleep(wait);
+        }
+        *last_request = Some(Instant::now());
+
+        match source.fetch(prefix) {
+            Ok(body) => return Ok(body),
+            Err(FetchError::RateLimited { .. }) if retries >= pacing.max_retries => {
+                return Err(format!("still rate limited after {} retries", retries));
+            }
+            Err(FetchError::RateLimited { retry_after }) => {
+                let wait = retry_after.unwrap_or(backoff);
+                log::debug!("range {}: rate limited, retrying in {:?}", prefix, wait);
+                std::thread::sleep(wait);
+                backoff *= 2;
+                retries += 1;
+            }
+            Err(FetchError::Failed(e)) => return Err(e),
+        }
+    }
+}
+
+/// Parses `SUFFIX:COUNT` lines into counts by uppercase suffix
+fn parse_range(body: &str) -> Result<HashMap<String, u64>, String> {
+    body.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (suffix, count) = line.split_once(':').ok_or_else(|| format!("unexpected range line '{}'", line.trim()))?;
+            let count = count.trim().parse::<u64>().map_err(|_| format!("unexpected range line '{}'", line.trim()))?;
+            Ok((suffix.trim().to_ascii_uppercase(), count))
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// SHA-1 of "password" is 5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8
+    const PASSWORD_PREFIX: &str = "5BAA6";
+    const PASSWORD_SUFFIX: &str = "1E4C9B93F3F0682250B6CF8331B7EE68FD8";
+
+    /// Answers from queued responses per prefix and records every request
+    #[derive(Default)]
+    struct FakeSource {
+        responses: HashMap<String, VecDeque<Result<String, FetchError>>>,
+        requests: Vec<String>,
+    }
+
+    impl FakeSource {
+        fn respond(mut self, prefix: &str, response: Result<&str, FetchError>) -> Self {
+            self.responses.entry(prefix.to_string()).or_default().push_back(response.map(str::to_string));
+            self
+        }
+    }
+
+    impl RangeSource for FakeSource {
+        fn fetch(&mut self, prefix: &str) -> Result<String, FetchError> {
+            self.requests.push(prefix.to_string());
+            self.responses
+                .get_mut(prefix)
+                .and_then(VecDeque::pop_front)
+                .unwrap_or_else(|| Err(FetchError::Failed(format!("no response queued for {}", prefix))))
+        }
+    }
+
+    fn instant() -> Pacing {
+        Pacing { interval: Duration::ZERO, initial_backoff: Duration::ZERO, max_retries: MAX_RETRIES }
+    }
+
+    fn check(passwords: &[&str], source: &mut FakeSource, cache: Option<&RangeCache>, pacing: &Pacing) -> Lookup {
+        check_passwords(passwords, source, cache, pacing, || {})
+    }
+
+    fn rate_limited() -> Result<&'static str, FetchError> {
+        Err(FetchError::RateLimited { retry_after: None })
+    }
+
+    #[test]
+    fn hash_parts_split_the_uppercase_sha1() {
+        assert_eq!(hash_parts("password"), (PASSWORD_PREFIX.to_string(), PASSWORD_SUFFIX.to_string()));
+    }
+
+    #[test]
+    fn matching_suffix_gives_its_count() {
+        let body = format!("0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n{}:9545824\r\n", PASSWORD_SUFFIX);
+        let mut source = FakeSource::default().respond(PASSWORD_PREFIX, Ok(&body));
+        let lookup = check(&["password"], &mut source, None, &instant());
+        assert_eq!(lookup.counts, [BreachCount::Seen(9545824)]);
+        assert_eq!(source.requests, [PASSWORD_PREFIX]);
+    }
+
+    #[test]
+    fn suffixes_match_case_insensitively() {
+        let body = format!("{}:3", PASSWORD_SUFFIX.to_ascii_lowercase());
+        let mut source = FakeSource::default().respond(PASSWORD_PREFIX, Ok(&body));
+        assert_eq!(check(&["password"], &mut source, None, &instant()).counts, [BreachCount::Seen(3)]);
+    }
+
+    #[test]
+    fn missing_suffix_and_padding_entries_count_as_unseen() {
+        // With Add-Padding the API mixes in made-up suffixes with a count of 0
+        let padded = format!("{}:0\n00000000000000000000000000000000000:0\n", PASSWORD_SUFFIX);
+        let mut source = FakeSource::default().respond(PASSWORD_PREFIX, Ok(&padded));
+        assert_eq!(check(&["password"], &mut source, None, &instant()).counts, [BreachCount::Seen(0)]);
+
+        let mut source = FakeSource::default().respond(PASSWORD_PREFIX, Ok("0018A45C4D1DEF81644B54AB7F969B88D65:0\n"));
+        assert_eq!(check(&["password"], &mut source, None, &instant()).counts, [BreachCount::Seen(0)]);
+    }
+
+    #[test]
+    fn duplicates_share_one_request() {
+        let body = format!("{}:2", PASSWORD_SUFFIX);
+        let mut source = FakeSource::default().respond(PASSWORD_PREFIX, Ok(&body));
+        let lookup = check(&["password", "password"], &mut source, None, &instant());
+        assert_eq!(lookup.counts, [BreachCount::Seen(2); 2]);
+        assert_eq!(source.requests.len(), 1);
+        assert_eq!(prefix_count(&["password", "password"]), 1);
+    }
+
+    #[test]
+    fn rate_limits_are_retried_with_doubling_backoff() {
+        let body = format!("{}:1", PASSWORD_SUFFIX);
+        let mut source = FakeSource::default()
+            .respond(PASSWORD_PREFIX, rate_limited())
+            .respond(PASSWORD_PREFIX, rate_limited())
+            .respond(PASSWORD_PREFIX, Ok(&body));
+        let pacing = Pacing { initial_backoff: Duration::from_millis(20), ..instant() };
+
+        let started = Instant::now();
+        let lookup = check(&["password"], &mut source, None, &pacing);
+        assert_eq!(lookup.counts, [BreachCount::Seen(1)]);
+        assert_eq!(source.requests.len(), 3);
+        // 20ms, then 40ms
+        assert!(started.elapsed() >= Duration::from_millis(60), "{:?}", started.elapsed());
+    }
+
+    #[test]
+    fn retry_after_overrides_the_backoff() {
+        let body = format!("{}:1", PASSWORD_SUFFIX);
+        let mut source = FakeSource::default()
+            .respond(PASSWORD_PREFIX, Err(FetchError::RateLimited { retry_after: Some(Duration::from_millis(50)) }))
+            .respond(PASSWORD_PREFIX, Ok(&body));
+
+        let started = Instant::now();
+        assert_eq!(check(&["password"], &mut source, None, &instant()).counts, [BreachCount::Seen(1)]);
+        assert!(started.elapsed() >= Duration::from_millis(50), "{:?}", started.elapsed());
+    }
+
+    #[test]
+    fn rate_limits_give_up_after_the_last_retry() {
+        let mut source = FakeSource::default();
+        for _ in 0..3 {
+            source = source.respond(PASSWORD_PREFIX, rate_limited());
+        }
+        let lookup = check(&["password"], &mut source, None, &Pacing { max_retries: 2, ..instant() });
+        assert_eq!(lookup.counts, [BreachCount::Unknown]);
+        assert_eq!(lookup.failed_prefixes, 1);
+        assert_eq!(lookup.first_error.as_deref(), Some("still rate limited after 2 retries"));
+        assert_eq!(source.requests.len(), 3);
+    }
+
+    #[test]
+    fn failed_ranges_are_unknown_and_stop_after_repeated_failures() {
+        let passwords = ["one", "two", "three", "four", "five"];
+        assert_eq!(prefix_count(&passwords), passwords.len());
+
+        let mut source = FakeSource::default();
+        let lookup = check(&passwords, &mut source, None, &instant());
+        assert_eq!(lookup.counts, [BreachCount::Unknown; 5]);
+        assert_eq!(lookup.failed_prefixes, 5);
+        assert!(lookup.first_error.unwrap().starts_with("no response queued for "));
+        assert_eq!(source.requests.len(), MAX_CONSECUTIVE_FAILURES);
+    }
+
+    #[test]
+    fn malformed_ranges_fail_the_prefix() {
+        let mut source = FakeSource::default().respond(PASSWORD_PREFIX, Ok("<html>maintenance</html>"));
+        let lookup = check(&["password"], &mut source, None, &instant());
+        assert_eq!(lookup.counts, [BreachCount::Unknown]);
+        assert_eq!(lookup.first_error.as_deref(), Some("unexpected range line '<html>maintenance</html>'"));
+    }
+
+    #[test]
+    fn cached_ranges_are_not_fetched_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = RangeCache::new(dir.path().join("hibp"), Duration::from_secs(3600));
+        let body = format!("{}:7", PASSWORD_SUFFIX);
+
+        let mut source = FakeSource::default().respond(PASSWORD_PREFIX, Ok(&body));
+        assert_eq!(check(&["password"], &mut source, Some(&cache), &instant()).counts, [BreachCount::Seen(7)]);
+        assert_eq!(fs::read_to_string(dir.path().join("hibp").join(PASSWORD_PREFIX)).unwrap(), body);
+
+        // A source with nothing queued fails every request, so the count must come from the cache
+        let mut source = FakeSource::default();
+        assert_eq!(check(&["password"], &mut source, Some(&cache), &instant()).counts, [BreachCount::Seen(7)]);
+        assert!(source.requests.is_empty());
+    }
+
+    #[test]
+    fn expired_ranges_are_fetched_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = RangeCache::new(dir.path().to_path_buf(), Duration::ZERO);
+        fs::write(dir.path().join(PASSWORD_PREFIX), format!("{}:7", PASSWORD_SUFFIX)).unwrap();
+
+        let body = format!("{}:8", PASSWORD_SUFFIX);
+        let mut source = FakeSource::default().respond(PASSWORD_PREFIX, Ok(&body));
+        assert_eq!(check(&["password"], &mut source, Some(&cache), &instant()).counts, [BreachCount::Seen(8)]);
+        assert_eq!(source.requests, [PASSWORD_PREFIX]);
+    }
+}
@@ -0,0 +1,462 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::TryRngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+use crate::generator::GeneratedPassword;
+use crate::output;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// File name of the history under the platform data directory
+const HISTORY_FILE: &str = "history.json";
+
+/// Version of the on-disk format, bumped on incompatible changes
+const FORMAT_VERSION: u32 = 1;
+
+/// Key derivation function recorded in the file
+const KDF_ALGORITHM: &str = "argon2id";
+
+/// Argon2id memory cost in KiB for new histories (OWASP minimum recommendation)
+const ARGON2_MEMORY_KIB: u32 = 19_456;
+
+/// Argon2id passes over memory for new histories
+const ARGON2_ITERATIONS: u32 = 2;
+
+/// Argon2id lanes for new histories
+const ARGON2_LANES: u32 = 1;
+
+/// Length of the random Argon2id salt in bytes
+const SALT_LEN: usize = 16;
+
+/// Length of a ChaCha20-Poly1305 nonce in bytes
+const NONCE_LEN: usize = 12;
+
+/// Length of the derived ChaCha20-Poly1305 key in bytes
+const KEY_LEN: usize = 32;
+
+/// Known plaintext sealed with the key, so a wrong passphrase is caught before anything is written
+const VERIFIER: &[u8] = b"rusty_password_utility history";
+
+/// Associated data of the verifier, keeping it apart from entries
+const VERIFIER_AAD: &[u8] = b"verifier";
+
+// ============================================================================
+// History File
+// ============================================================================
+
+/// The history file as stored on disk
+///
+/// Labels and dates are kept in clear text so `history list` works without
+/// the passphrase; passwords and their settings are only stored encrypted.
+#[derive(Debug, Serialize, Deserialize)]
+struct Store {
+    version: u32,
+    kdf: KdfParams,
+    /// The known plaintext `VERIFIER`, sealed with the key
+    verifier: Sealed,
+    entries: Vec<Entry>,
+}
+
+/// How the key is derived from the master passphrase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KdfParams {
+    algorithm: String,
+    memory_kib: u32,
+    iterations: u32,
+    lanes: u32,
+    /// Base64-encoded random salt
+    salt: String,
+}
+
+/// A ChaCha20-Poly1305 ciphertext with its nonce, both Base64-encoded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Sealed {
+    nonce: String,
+    ciphertext: String,
+}
+
+/// One saved password; the label and date are bound to the ciphertext as associated data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Entry {
+    /// What the password is for, e.g. `example.com`
+    pub(crate) label: String,
+    /// When it was saved (RFC 3339, UTC)
+    pub(crate) created: String,
+    #[serde(flatten)]
+    sealed: Sealed,
+}
+
+impl Entry {
+    /// Associated data that ties the ciphertext to its clear-text label and date
+    fn aad(&self) -> String {
+        format!("{}\n{}", self.label, self.created)
+    }
+}
+
+/// An unlocked history: the decoded file and the key derived from the passphrase
+struct Unlocked {
+    store: Store,
+    cipher: ChaCha20Poly1305,
+}
+
+/// Default location of the history on this platform
+///
+/// `~/.local/share/rustypass/history.json` on Linux (honoring `XDG_DATA_HOME`),
+/// `~/Library/Application Support/rustypass/history.json` on macOS and
+/// `%APPDATA%\rustypass\data\history.json` on Windows.
+pub(crate) fn default_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", crate::config::APP_NAME).map(|dirs| dirs.data_dir().join(HISTORY_FILE))
+}
+
+/// Whether a history exists at `path`, i.e. a master passphrase has been chosen
+pub(crate) fn exists(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Encrypts passwords and appends them to the history, creating it on first use
+///
+/// # Arguments
+/// * `path` - History file
+/// * `passphrase` - Master passphrase; for a new history it becomes the passphrase
+/// * `label` - What the passwords are for
+/// * `passwords` - Passwords to save, with the settings they were generated with
+///
+/// # Errors
+/// Fails without touching the file when the passphrase is wrong, the file
+/// cannot be read or parsed, or the new file cannot be written.
+///
+/// # Notes
+/// The file is replaced atomically with `output::replace_file`, so an
+/// interrupted write never leaves a truncated file.
+pub(crate) fn append(path: &Path, passphrase: &str, label: &str, passwords: &[GeneratedPassword]) -> Result<(), String> {
+    let mut unlocked = match read_store(path)? {
+        Some(store) => unlock(store, passphrase)?,
+        None => create(passphrase)?,
+    };
+
+    let created = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+    for password in passwords {
+        let plaintext = Zeroizing::new(serde_json::to_vec(&password.with_secret()).map_err(|e| e.to_string())?);
+        let mut entry = Entry {
+            label: label.to_string(),
+            created: created.clone(),
+            sealed: Sealed { nonce: String::new(), ciphertext: String::new() },
+        };
+        entry.sealed = seal(&unlocked.cipher, entry.aad().as_bytes(), &plaintext)?;
+        unlocked.store.entries.push(entry);
+    }
+    log::debug!("saving {} entries to {}", unlocked.store.entries.len(), path.display());
+
+    write_store(path, &unlocked.store)
+}
+
+/// Lists the saved entries without decrypting anything
+///
+/// # Returns
+/// * `Result<Vec<Entry>, String>` - Entries oldest first; empty when there is no history
+pub(crate) fn list(path: &Path) -> Result<Vec<Entry>, String> {
+    Ok(read_store(path)?.map(|store| store.entries).unwrap_or_default())
+}
+
+/// Decrypts every entry saved under `label`
+///
+/// # Returns
+/// * `Result<Vec<(Entry, GeneratedPassword)>, String>` - Matching entries oldest
+///   first, or an error for a wrong passphrase or a tampered entry
+pub(crate) fn show(path: &Path, passphrase: &str, label: &str) -> Result<Vec<(Entry, GeneratedPassword)>, String> {
+    let Some(store) = read_store(path)? else {
+        return Ok(Vec::new());
+    };
+    let unlocked = unlock(store, passphrase)?;
+
+    unlocked
+        .store
+        .entries
+        .iter()
+        .filter(|entry| entry.label == label)
+        .map(|entry| {
+            let plaintext = open(&unlocked.cipher, entry.aad().as_bytes(), &entry.sealed)
+                .map_err(|_| format!("the entry '{}' saved {} is damaged", entry.label, entry.created))?;
+            let password = serde_json::from_slice(&plaintext).map_err(|e| format!("the entry '{}' is unreadable: {}", entry.label, e))?;
+            Ok((entry.clone(), password))
+        })
+        .collect()
+}
+
+/// Deletes the history
+///
+/// # Returns
+/// * `Result<usize, String>` - Number of entries deleted; 0 when there was no history
+pub(crate) fn purge(path: &Path) -> Result<usize, String> {
+    let count = list(path)?.len();
+    match fs::remove_file(path) {
+        Ok(()) => Ok(count),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(format!("cannot delete {}: {}", path.display(), e)),
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Reads and parses the history, `None` when it does not exist yet
+fn read_store(path: &Path) -> Result<Option<Store>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("cannot read {}: {}", path.display(), e)),
+    };
+    let store: Store = serde_json::from_str(&text).map_err(|e| format!("{}: not a password history: {}", path.display(), e))?;
+    if store.version != FORMAT_VERSION {
+        return Err(format!("{}: unsupported history version {} (expected {})", path.display(), store.version, FORMAT_VERSION));
+    }
+    Ok(Some(store))
+}
+
+/// Writes the history atomically with `output::replace_file`
+fn write_store(path: &Path, store: &Store) -> Result<(), String> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("cannot create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    output::replace_file(path, format!("{}\n", json).as_bytes()).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+/// Starts a new history with a fresh salt and the default Argon2id costs
+fn create(passphrase: &str) -> Result<Unlocked, String> {
+    create_with_costs(passphrase, ARGON2_MEMORY_KIB, ARGON2_ITERATIONS)
+}
+
+/// Starts a new history with a fresh salt and the given Argon2id costs
+fn create_with_costs(passphrase: &str, memory_kib: u32, iterations: u32) -> Result<Unlocked, String> {
+    let kdf = KdfParams {
+        algorithm: String::from(KDF_ALGORITHM),
+        memory_kib,
+        iterations,
+        lanes: ARGON2_LANES,
+        salt: STANDARD.encode(random_bytes::<SALT_LEN>()?),
+    };
+    let cipher = derive_cipher(passphrase, &kdf)?;
+    let verifier = seal(&cipher, VERIFIER_AAD, VERIFIER)?;
+    Ok(Unlocked { store: Store { version: FORMAT_VERSION, kdf, verifier, entries: Vec::new() }, cipher })
+}
+
+/// Derives the key and checks it against the verifier
+fn unlock(store: Store, passphrase: &str) -> Result<Unlocked, String> {
+    let cipher = derive_cipher(passphrase, &store.kdf)?;
+    match open(&cipher, VERIFIER_AAD, &store.verifier) {
+        Ok(plaintext) if plaintext.as_slice() == VERIFIER => Ok(Unlocked { store, cipher }),
+        _ => Err(String::from("wrong master passphrase")),
+    }
+}
+
+/// Derives the ChaCha20-Poly1305 key from the passphrase with Argon2id
+fn derive_cipher(passphrase: &str, kdf: &KdfParams) -> Result<ChaCha20Poly1305, String> {
+    if kdf.algorithm != KDF_ALGORITHM {
+        return Err(format!("unsupported key derivation '{}'", kdf.algorithm));
+    }
+    let salt = STANDARD.decode(&kdf.salt).map_err(|e| format!("invalid salt: {}", e))?;
+    let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.lanes, Some(KEY_LEN))
+        .map_err(|e| format!("invalid key derivation parameters: {}", e))?;
+
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut *key)
+        .map_err(|e| format!("key derivation failed: {}", e))?;
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&*key)))
+}
+
+/// Encrypts `plaintext` under a fresh random nonce
+fn seal(cipher: &ChaCha20Poly1305, aad: &[u8], plaintext: &[u8]) -> Result<Sealed, String> {
+    let nonce = random_bytes::<NONCE_LEN>()?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+        .map_err(|_| String::from("encryption failed"))?;
+    Ok(Sealed { nonce: STANDARD.encode(nonce), ciphertext: STANDARD.encode(ciphertext) })
+}
+
+/// Decrypts and authenticates a sealed value; fails for a wrong key or any modification
+fn open(cipher: &ChaCha20Poly1305, aad: &[u8], sealed: &Sealed) -> Result<Zeroizing<Vec<u8>>, String> {
+    let nonce = STANDARD.decode(&sealed.nonce).map_err(|e| format!("invalid nonce: {}", e))?;
+    let ciphertext = STANDARD.decode(&sealed.ciphertext).map_err(|e| format!("invalid ciphertext: {}", e))?;
+    if nonce.len() != NONCE_LEN {
+        return Err(String::from("invalid nonce length"));
+    }
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad })
+        .map(Zeroizing::new)
+        .map_err(|_| String::from("decryption failed"))
+}
+
+/// Fills an array from the operating system random number generator
+fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    rand::rngs::OsRng.try_fill_bytes(&mut bytes).map_err(|e| format!("operating system RNG failed: {}", e))?;
+    Ok(bytes)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::GenerationFlags;
+
+    const PASSPHRASE: &str = "correct horse battery staple";
+
+    /// Starts a history at `path` with the cheapest Argon2id costs, so tests stay fast
+    fn cheap_history(path: &Path) {
+        write_store(path, &create_with_costs(PASSPHRASE, 8, 1).unwrap().store).unwrap();
+    }
+
+    fn generated(password: &str) -> GeneratedPassword {
+        let flags = GenerationFlags { uppercase_chars: true, special_chars: false, numbers: true, policy: None };
+        GeneratedPassword::new(password.to_string(), flags, 62)
+    }
+
+    /// The history as a JSON document, for tampering with it
+    fn document(path: &Path) -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    fn rewrite(path: &Path, document: &serde_json::Value) {
+        fs::write(path, serde_json::to_string_pretty(document).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn saved_passwords_show_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        cheap_history(&path);
+        append(&path, PASSPHRASE, "example.com", &[generated("Hunter2aaaa1"), generated("Hunter2bbbb2")]).unwrap();
+        append(&path, PASSPHRASE, "other.org", &[generated("Other0000000")]).unwrap();
+
+        let labels: Vec<String> = list(&path).unwrap().into_iter().map(|entry| entry.label).collect();
+        assert_eq!(labels, ["example.com", "example.com", "other.org"]);
+
+        let found = show(&path, PASSPHRASE, "example.com").unwrap();
+        let passwords: Vec<&GeneratedPassword> = found.iter().map(|(_, password)| password).collect();
+        assert_eq!(passwords, [&generated("Hunter2aaaa1"), &generated("Hunter2bbbb2")]);
+        assert!(show(&path, PASSPHRASE, "missing").unwrap().is_empty());
+        // Only the labels and dates are in clear text
+        assert!(!fs::read_to_string(&path).unwrap().contains("Hunter2"));
+    }
+
+    #[test]
+    fn missing_history_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        assert!(!exists(&path));
+        assert!(list(&path).unwrap().is_empty());
+        assert!(show(&path, PASSPHRASE, "example.com").unwrap().is_empty());
+    }
+
+    #[test]
+    fn wrong_passphrase_fails_and_leaves_the_file_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        cheap_history(&path);
+        append(&path, PASSPHRASE, "example.com", &[generated("Hunter2aaaa1")]).unwrap();
+        let before = fs::read(&path).unwrap();
+
+        assert_eq!(append(&path, "wrong", "example.com", &[generated("Hunter2bbbb2")]).unwrap_err(), "wrong master passphrase");
+        assert_eq!(show(&path, "wrong", "example.com").unwrap_err(), "wrong master passphrase");
+        assert_eq!(fs::read(&path).unwrap(), before);
+    }
+
+    #[test]
+    fn tampered_entries_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        cheap_history(&path);
+        append(&path, PASSPHRASE, "example.com", &[generated("Hunter2aaaa1")]).unwrap();
+        let original = document(&path);
+
+        // The label and date are bound to the ciphertext as associated data
+        let mut relabeled = original.clone();
+        relabeled["entries"][0]["label"] = serde_json::json!("evil.com");
+        rewrite(&path, &relabeled);
+        assert!(show(&path, PASSPHRASE, "evil.com").unwrap_err().contains("is damaged"));
+
+        let mut redated = original.clone();
+        redated["entries"][0]["created"] = serde_json::json!("2000-01-01T00:00:00Z");
+        rewrite(&path, &redated);
+        assert!(show(&path, PASSPHRASE, "example.com").unwrap_err().contains("is damaged"));
+
+        let mut flipped = original.clone();
+        let mut ciphertext = STANDARD.decode(original["entries"][0]["ciphertext"].as_str().unwrap()).unwrap();
+        ciphertext[0] ^= 1;
+        flipped["entries"][0]["ciphertext"] = serde_json::json!(STANDARD.encode(ciphertext));
+        rewrite(&path, &flipped);
+        assert!(show(&path, PASSPHRASE, "example.com").unwrap_err().contains("is damaged"));
+
+        rewrite(&path, &original);
+        assert_eq!(show(&path, PASSPHRASE, "example.com").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn unsupported_versions_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        cheap_history(&path);
+        let mut newer = document(&path);
+        newer["version"] = serde_json::json!(FORMAT_VERSION + 1);
+        rewrite(&path, &newer);
+        assert!(list(&path).unwrap_err().contains("unsupported history version"));
+    }
+
+    #[test]
+    fn purge_reports_how_many_entries_it_deleted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        assert_eq!(purge(&path).unwrap(), 0);
+
+        cheap_history(&path);
+        append(&path, PASSPHRASE, "a", &[generated("Hunter2aaaa1"), generated("Hunter2bbbb2")]).unwrap();
+        append(&path, PASSPHRASE, "b", &[generated("Hunter2cccc3")]).unwrap();
+        assert_eq!(purge(&path).unwrap(), 3);
+        assert!(!exists(&path));
+        assert_eq!(purge(&path).unwrap(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn history_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data").join("history.json");
+        cheap_history(&path);
+        append(&path, PASSPHRASE, "example.com", &[generated("Hunter2aaaa1")]).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        // Nothing is left behind next to the file
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn planted_temporary_files_are_not_followed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        let victim = dir.path().join("victim");
+        fs::write(&victim, "untouched").unwrap();
+        std::os::unix::fs::symlink(&victim, dir.path().join("history.json.tmp")).unwrap();
+
+        cheap_history(&path);
+        append(&path, PASSPHRASE, "example.com", &[generated("Hunter2aaaa1")]).unwrap();
+        assert_eq!(fs::read_to_string(&victim).unwrap(), "untouched");
+        assert_eq!(show(&path, PASSPHRASE, "example.com").unwrap().len(), 1);
+    }
+}
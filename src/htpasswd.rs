@@ -0,0 +1,227 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::hash;
+use crate::output;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Longest username Apache's `htpasswd` accepts, in bytes
+const MAX_USERNAME_BYTES: usize = 255;
+
+// ============================================================================
+// Entries
+// ============================================================================
+
+/// How the password of an htpasswd entry is hashed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HtpasswdScheme {
+    /// bcrypt with the `$2y$` prefix Apache expects (`htpasswd -B`)
+    Bcrypt { cost: u32 },
+    /// Apache's MD5-crypt variant (`htpasswd -m`), for servers that predate bcrypt
+    Apr1,
+}
+
+/// Whether `update_file` added a new entry or replaced an existing one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Update {
+    Added,
+    Replaced,
+}
+
+/// Checks that a username can be stored in an htpasswd file
+///
+/// # Returns
+/// * `Result<(), String>` - An error for an empty name, one containing `:` or a
+///   line break, or one longer than Apache allows
+pub(crate) fn check_username(username: &str) -> Result<(), String> {
+    if username.is_empty() {
+        return Err("the htpasswd username is empty".to_string());
+    }
+    if username.contains(':') {
+        return Err(format!("the htpasswd username '{}' contains ':', which separates it from the hash", username));
+    }
+    if username.contains(['\n', '\r']) {
+        return Err("the htpasswd username contains a line break".to_string());
+    }
+    if username.len() > MAX_USERNAME_BYTES {
+        return Err(format!("the htpasswd username is {} bytes long; the limit is {}", username.len(), MAX_USERNAME_BYTES));
+    }
+    Ok(())
+}
+
+/// Builds an htpasswd line for a user, without a line break
+///
+/// # Arguments
+/// * `username` - User name, checked with `check_username`
+/// * `password` - Password to hash
+/// * `scheme` - Hash scheme
+///
+/// # Returns
+/// * `Result<String, String>` - `username:$2y$...` or `username:$apr1$...`
+pub(crate) fn entry(username: &str, password: &str, scheme: HtpasswdScheme) -> Result<String, String> {
+    check_username(username)?;
+    let hashed = match scheme {
+        HtpasswdScheme::Bcrypt { cost } => hash::hash_bcrypt(password, cost, bcrypt::Version::TwoY)?,
+        HtpasswdScheme::Apr1 => hash::hash_apr1(password)?,
+    };
+    Ok(format!("{}:{}", username, hashed))
+}
+
+// ============================================================================
+// Files
+// ============================================================================
+
+/// Adds an entry to an htpasswd file, or replaces the user's existing entry
+///
+/// # Arguments
+/// * `path` - htpasswd file; created (mode 0600 on Unix) when it does not exist
+/// * `username` - User the entry belongs to
+/// * `entry` - Line from `entry`, without a line break
+///
+/// # Returns
+/// * `Result<Update, String>` - Whether the user was added or replaced
+///
+/// # Notes
+/// The file is replaced atomically with `output::replace_file`. Every other
+/// line keeps its exact bytes; the user's first entry is replaced in place and
+/// any later duplicates are dropped.
+pub(crate) fn update_file(path: &Path, username: &str, entry: &str) -> Result<Update, String> {
+    let existing = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(format!("cannot read {}: {}", path.display(), e)),
+    };
+    let (contents, update) = merge(&existing, username, entry);
+
+    output::replace_file(path, &contents).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+    log::debug!("{:?} '{}' in {}", update, username, path.display());
+    Ok(update)
+}
+
+/// Puts an entry into the contents of an htpasswd file
+///
+/// Lines are compared as bytes up to the first `:`, so a file with other
+/// encodings or line endings passes through unchanged.
+fn merge(existing: &[u8], username: &str, entry: &str) -> (Vec<u8>, Update) {
+    let mut contents = Vec::with_capacity(existing.len() + entry.len() + 1);
+    let mut update = Update::Added;
+
+    for line in existing.split_inclusive(|&b| b == b'\n') {
+        let belongs_to_user = line.starts_with(username.as_bytes()) && line.get(username.len()) == Some(&b':');
+        if !belongs_to_user {
+            contents.extend_from_slice(line);
+            continue;
+        }
+        if update == Update::Added {
+            contents.extend_from_slice(entry.as_bytes());
+            // Keep the line's own ending, CRLF included
+            contents.extend_from_slice(if line.ends_with(b"\r\n") { b"\r\n" } else { b"\n" });
+            update = Update::Replaced;
+        }
+    }
+
+    if update == Update::Added {
+        if contents.last().is_some_and(|&b| b != b'\n') {
+            contents.push(b'\n');
+        }
+        contents.extend_from_slice(entry.as_bytes());
+        contents.push(b'\n');
+    }
+    (contents, update)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merged(existing: &str, username: &str, entry: &str) -> (String, Update) {
+        let (contents, update) = merge(existing.as_bytes(), username, entry);
+        (String::from_utf8(contents).unwrap(), update)
+    }
+
+    #[test]
+    fn new_users_are_appended() {
+        assert_eq!(merged("", "alice", "alice:h"), (String::from("alice:h\n"), Update::Added));
+        assert_eq!(merged("bob:x\n", "alice", "alice:h"), (String::from("bob:x\nalice:h\n"), Update::Added));
+        // A last line without a line break gets one before the new entry
+        assert_eq!(merged("bob:x", "alice", "alice:h"), (String::from("bob:x\nalice:h\n"), Update::Added));
+    }
+
+    #[test]
+    fn existing_users_are_replaced_in_place() {
+        let (contents, update) = merged("bob:x\r\nalice:old\r\n# comment\ncarol:y", "alice", "alice:h");
+        assert_eq!(contents, "bob:x\r\nalice:h\r\n# comment\ncarol:y");
+        assert_eq!(update, Update::Replaced);
+    }
+
+    #[test]
+    fn later_duplicates_are_dropped() {
+        assert_eq!(merged("alice:1\nbob:x\nalice:2\n", "alice", "alice:h"), (String::from("alice:h\nbob:x\n"), Update::Replaced));
+    }
+
+    #[test]
+    fn only_whole_usernames_match() {
+        let (contents, update) = merged("alice2:x\nali:y\n", "alice", "alice:h");
+        assert_eq!(contents, "alice2:x\nali:y\nalice:h\n");
+        assert_eq!(update, Update::Added);
+    }
+
+    #[test]
+    fn usernames_are_checked() {
+        for username in ["", "a:b", "a\nb", &"x".repeat(MAX_USERNAME_BYTES + 1)] {
+            assert!(check_username(username).is_err(), "{:?}", username);
+        }
+        assert!(check_username(&"x".repeat(MAX_USERNAME_BYTES)).is_ok());
+    }
+
+    #[test]
+    fn files_are_created_then_updated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("htpasswd");
+        assert_eq!(update_file(&path, "alice", "alice:1").unwrap(), Update::Added);
+        assert_eq!(update_file(&path, "bob", "bob:2").unwrap(), Update::Added);
+        assert_eq!(update_file(&path, "alice", "alice:3").unwrap(), Update::Replaced);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "alice:3\nbob:2\n");
+        // Nothing is left behind next to the file
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn new_files_are_private_and_existing_ones_keep_their_mode() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        let private = dir.path().join("new");
+        update_file(&private, "alice", "alice:1").unwrap();
+        assert_eq!(mode(&private), 0o600);
+
+        let shared = dir.path().join("shared");
+        fs::write(&shared, "bob:x\n").unwrap();
+        fs::set_permissions(&shared, fs::Permissions::from_mode(0o640)).unwrap();
+        update_file(&shared, "alice", "alice:1").unwrap();
+        assert_eq!(mode(&shared), 0o640);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn planted_temporary_files_are_not_followed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("htpasswd");
+        let victim = dir.path().join("victim");
+        fs::write(&victim, "untouched").unwrap();
+        std::os::unix::fs::symlink(&victim, dir.path().join("htpasswd.tmp")).unwrap();
+
+        update_file(&path, "alice", "alice:1").unwrap();
+        assert_eq!(fs::read_to_string(&victim).unwrap(), "untouched");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "alice:1\n");
+    }
+}
@@ -0,0 +1,980 @@
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+// ============================================================================
+// Locales
+// ============================================================================
+
+/// Languages user-facing messages are available in
+///
+/// Only text meant for people is translated, which includes the warnings of an
+/// analysis report. Field names, values such as grades and pattern kinds, and
+/// the layout of JSON, CSV and raw output stay the same regardless of the
+/// user's language, so scripts keep working.
+#[derive(serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    /// English
+    En,
+    /// German
+    De,
+}
+
+/// Locale selected at startup; English until `set_locale` is called
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Selects the locale for the rest of the run (only the first call has an effect)
+pub fn set_locale(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+/// Resolves the locale from `--lang`, then `LC_ALL`, `LC_MESSAGES` and `LANG`,
+/// then the configuration file
+///
+/// # Arguments
+/// * `flag` - Value of `--lang`, if given
+/// * `configured` - `lang` from the configuration file, if set
+///
+/// # Notes
+/// Values such as `de_DE.UTF-8` or `de` select German; any other language
+/// falls back to English. See `locale_from_env` for when the environment counts as set.
+pub fn detect_locale(flag: Option<Locale>, configured: Option<Locale>) -> Locale {
+    resolve_locale(flag, locale_from_env().map(|(_, value)| value).as_deref(), configured)
+}
+
+/// Resolves the locale like `detect_locale`, from an already read locale variable
+///
+/// # Arguments
+/// * `flag` - Value of `--lang`, if given
+/// * `from_env` - Value of the locale variable in effect, as `locale_from_env` finds it
+/// * `configured` - `lang` from the configuration file, if set
+pub fn resolve_locale(flag: Option<Locale>, from_env: Option<&str>, configured: Option<Locale>) -> Locale {
+    if let Some(locale) = flag {
+        return locale;
+    }
+
+    let Some(value) = from_env else {
+        return configured.unwrap_or(Locale::En);
+    };
+    match language_of(value).as_str() {
+        "de" => Locale::De,
+        _ => Locale::En,
+    }
+}
+
+/// The locale variable in effect, as `(name, value)`
+///
+/// The variables follow POSIX precedence: the first non-empty one wins. The
+/// `C` and `POSIX` locales (e.g. `C.UTF-8` in containers) name no language,
+/// so they count as unset.
+pub fn locale_from_env() -> Option<(&'static str, String)> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()).map(|value| (var, value)))
+        .filter(|(_, value)| !matches!(language_of(value).as_str(), "c" | "posix"))
+}
+
+/// Language part of a locale name, lowercased: `de_DE.UTF-8` -> `de`
+fn language_of(value: &str) -> String {
+    value.split(['_', '.', '@']).next().unwrap_or_default().to_ascii_lowercase()
+}
+
+// ============================================================================
+// Message Catalog
+// ============================================================================
+
+/// Every translatable user-facing message
+///
+/// Positional arguments are written `{0}`, `{1}`, ... and filled in by `trf`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    // Result labels
+    GeneratedPasswordLabel,
+    AnalysisLabel,
+    TokenAnalysisLabel,
+    BatchAnalysisLabel,
+    AuditLabel,
+    PasswordHashLabel,
+    HtpasswdEntryLabel,
+
+    // Message prefixes
+    ErrorPrefix,
+    WarningPrefix,
+
+    // Notices
+    CopiedToClipboard,
+    CopiedViaTerminal,
+    ClipboardFallback,
+    WroteFile,
+    ReportWritten,
+    LengthOutOfRange,
+    Legend,
+    RevealPrompt,
+    HiddenAgain,
+    MaskedSummary,
+    ConfigWritten,
+    UnknownConfigKey,
+    SavedToHistory,
+    StoredInKeyring,
+    DeletedFromKeyring,
+    StoredPasswordLabel,
+    HistoryEmpty,
+    HistoryPurged,
+    HtpasswdAdded,
+    HtpasswdReplaced,
+    EnvFileUpdated,
+    EnvKeyConvention,
+    PlaintextExport,
+    BelowMinBits,
+    BreachCheckIncomplete,
+    SeededPasswords,
+    MaskedPassword,
+    SpellHeading,
+    BenchmarkRunning,
+    NoProfiles,
+    TheConfigurationFile,
+    HistorySettings,
+    HistorySettingsPolicy,
+    EquivalentCommand,
+
+    // Command errors
+    HibpNeedsFile,
+    InvalidTemplate,
+    SinglePasswordOnly,
+    CsvWriteFailed,
+    CannotWriteStdout,
+    FileExists,
+    CannotWrite,
+    CannotCreate,
+    NothingToHash,
+    CannotReadPassword,
+    CsvNeedsFile,
+    CsvNotAvailable,
+    SummaryOnlyFormats,
+    StreamNeedsPipe,
+    AnalyzeStdinFailed,
+    NetworkNotCompiled,
+    NoConfigDirectory,
+    NoDataDirectory,
+    NotInHistory,
+    PurgeNeedsYes,
+    WizardFailed,
+
+    // Strength meter verdicts
+    Weak,
+    Fair,
+    Strong,
+
+    // Analysis report
+    ReportGrade,
+    ReportLength,
+    ReportCharacters,
+    ReportCharactersTyped,
+    ReportCharsetSize,
+    ReportEntropy,
+    ReportEffective,
+    ReportCrackTime,
+    ReportComposition,
+    ReportUnique,
+    ReportWarnings,
+    ClassLowercase,
+    ClassUppercase,
+    ClassDigits,
+    ClassSymbols,
+    ClassWhitespace,
+    ClassNonAscii,
+
+    // Analysis warnings
+    WarningEmpty,
+    WarningDominantClass,
+    WarningPattern,
+    WarningFewUnique,
+    PatternPersonalInfo,
+    PatternDictionary,
+    PatternKeyboardWalk,
+    PatternSequence,
+    PatternRepeat,
+
+    // Analysis details
+    DetailsFindings,
+    DetailsNone,
+    DetailsFinding,
+    DetailsBruteForce,
+    DetailsPenalties,
+    DetailsEffective,
+
+    // Batch analysis
+    ColumnLine,
+    ColumnPassword,
+    ColumnLength,
+    ColumnEntropy,
+    ColumnGrade,
+    ColumnBreaches,
+    SkippedBlank,
+    SkippedInvalidUtf8,
+    ReuseClusters,
+    ReuseNone,
+    ReuseLine,
+    ReuseIdentical,
+    ReuseIgnoringCase,
+    ReuseTrailingCounter,
+    ReuseNearDuplicate,
+    SummaryHeading,
+    SummaryLines,
+    SummaryAnalyzed,
+    SummarySkipped,
+    SummaryBlank,
+    SummaryInvalidUtf8,
+    SummaryAverageEntropy,
+    SummaryGrades,
+    SummaryReuse,
+    SummaryReuseValue,
+    SummaryBreached,
+    SummaryBreachedValue,
+    SummaryWeakestLines,
+    SummaryWeakest,
+
+    // Policy report
+    PolicyHeading,
+    VerdictHeading,
+    Compliant,
+    NotCompliant,
+    PolicyPass,
+    PolicyFail,
+    PolicyInfo,
+    PolicyStrength,
+    GradeAndBits,
+
+    // Hash verification
+    HashMatches,
+    HashMismatch,
+
+    // Benchmark
+    BenchHeading,
+    BenchCase,
+    BenchPasswordsPerSecond,
+    BenchDefault,
+    BenchAllClasses,
+    BenchPolicy,
+
+    // Credential audit
+    AuditBreached,
+    AuditWeak,
+    AuditReused,
+    AuditOk,
+    AuditRow,
+    AuditReusedIn,
+    AuditSeenInBreaches,
+    AuditBreachCheckFailed,
+    AuditSummary,
+    AuditSummaryBreached,
+    AuditUnchecked,
+    AuditWithoutPassword,
+
+    // Keyspace
+    KeyspaceLabel,
+    KeyspaceWordlist,
+    KeyspaceWords,
+    KeyspaceAlphabet,
+    KeyspaceBitsPerWord,
+    KeyspaceBitsPerCharacter,
+    KeyspaceAverage,
+    KeyspaceBits,
+    KeyspaceCombinations,
+    KeyspaceTimeHeading,
+    ScenarioOnlineThrottled,
+    ScenarioOnlineUnthrottled,
+    ScenarioOfflineSlowHash,
+    ScenarioOfflineFastHash,
+
+    // Wizard
+    WizardChooseMode,
+    WizardModeGenerate,
+    WizardModeAnalyze,
+    WizardLength,
+    WizardLengthInvalid,
+    WizardUppercase,
+    WizardDigits,
+    WizardSymbols,
+    WizardRun,
+
+    // Hidden prompts
+    PromptPassword,
+    PromptConfirmPassword,
+    PromptPassphrase,
+    PromptNewPassphrase,
+    PromptConfirmPassphrase,
+    PasswordsDiffer,
+    PassphraseNeedsTerminal,
+    PassphraseEmpty,
+    PassphrasesDiffer,
+}
+
+/// English catalog; every message must be present here
+fn english(msg: Msg) -> &'static str {
+    match msg {
+        Msg::GeneratedPasswordLabel => "Generated Password:",
+        Msg::AnalysisLabel => "Password Strength Analysis:",
+        Msg::TokenAnalysisLabel => "Token Strength Analysis:",
+        Msg::BatchAnalysisLabel => "Batch Password Analysis:",
+        Msg::AuditLabel => "Credential Audit:",
+        Msg::PasswordHashLabel => "Password Hash:",
+        Msg::HtpasswdEntryLabel => "htpasswd Entry:",
+        Msg::ErrorPrefix => "Error:",
+        Msg::WarningPrefix => "Warning:",
+        Msg::CopiedToClipboard => "Password copied to clipboard; it will be cleared in {0} seconds",
+        Msg::CopiedViaTerminal => "Password sent to the terminal clipboard (OSC 52); it is not cleared automatically",
+        Msg::ClipboardFallback => "{0}; copying through the terminal (OSC 52) instead",
+        Msg::WroteFile => "Wrote {0} password(s) to {1}",
+        Msg::ReportWritten => "Report written to {0}",
+        Msg::LengthOutOfRange => "length {0} is outside {1}-{2}, using {3} instead",
+        Msg::Legend => "Legend: {0} uppercase  abc lowercase  {1} digits  {2} symbols",
+        Msg::RevealPrompt => "Press r to reveal, any other key to exit: ",
+        Msg::HiddenAgain => "(hidden again in {0} seconds)",
+        Msg::MaskedSummary => "{0} characters, grade {1}, {2} bits",
+        Msg::ConfigWritten => "Configuration template written to {0}",
+        Msg::UnknownConfigKey => "unknown key '{0}' in {1} is ignored",
+        Msg::SavedToHistory => "Saved {0} password(s) to the history as '{1}'",
+        Msg::StoredInKeyring => "Password stored in the credential store as '{0}'",
+        Msg::DeletedFromKeyring => "Removed '{0}' from the credential store",
+        Msg::StoredPasswordLabel => "Stored Password:",
+        Msg::HistoryEmpty => "No passwords have been saved yet (generate --save --label TEXT)",
+        Msg::HistoryPurged => "Deleted the history with {0} saved password(s)",
+        Msg::HtpasswdAdded => "Added '{0}' to {1}",
+        Msg::HtpasswdReplaced => "Replaced the entry of '{0}' in {1}",
+        Msg::EnvFileUpdated => "Wrote {0} key(s) to {1} ({2} added, {3} replaced)",
+        Msg::EnvKeyConvention => "'{0}' does not follow the [A-Z_][A-Z0-9_]* convention for environment variables; some tools may reject it",
+        Msg::PlaintextExport => "{0} holds the passwords in PLAINTEXT; import it, then delete it securely",
+        Msg::BelowMinBits => "FAIL: {0} bits of effective entropy, below the required {1}",
+        Msg::BreachCheckIncomplete => "{0} of {1} breach lookups failed; those passwords are marked unknown ({2})",
+        Msg::SeededPasswords => "--seed makes the passwords reproducible by anyone who knows the seed; use them for tests only",
+        Msg::MaskedPassword => "Generated Password: {0}  ({1})",
+        Msg::SpellHeading => "Password {0}:",
+        Msg::BenchmarkRunning => "Benchmarking {0} for {1}...",
+        Msg::NoProfiles => "No profiles are defined in {0}",
+        Msg::TheConfigurationFile => "the configuration file",
+        Msg::HistorySettings => "length {0}, {1}",
+        Msg::HistorySettingsPolicy => "length {0}, {1}, policy {2}",
+        Msg::EquivalentCommand => "Equivalent command: {0}",
+        Msg::HibpNeedsFile => "--hibp checks the lines of a password file; use it with --file",
+        Msg::InvalidTemplate => "invalid --format template: {0}",
+        Msg::SinglePasswordOnly => "{0} works with a single password; drop --count",
+        Msg::CsvWriteFailed => "failed to write CSV: {0}",
+        Msg::CannotWriteStdout => "cannot write to stdout: {0}",
+        Msg::FileExists => "{0} already exists; pass --force to overwrite it",
+        Msg::CannotWrite => "cannot write {0}: {1}",
+        Msg::CannotCreate => "cannot create {0}: {1}",
+        Msg::NothingToHash => "the password is empty; there is nothing to hash",
+        Msg::CannotReadPassword => "cannot read password: {0}",
+        Msg::CsvNeedsFile => "CSV output is only available with --file",
+        Msg::CsvNotAvailable => "CSV output is not available for {0}",
+        Msg::SummaryOnlyFormats => "--summary-only prints text or json; drop --output csv",
+        Msg::StreamNeedsPipe => "--stream reads the passwords from a pipe or a redirected file",
+        Msg::AnalyzeStdinFailed => "failed to analyze stdin: {0}",
+        Msg::NetworkNotCompiled => "breach checks are not compiled in; rebuild with `--features network`",
+        Msg::NoConfigDirectory => "no configuration directory is known on this system; pass --config PATH",
+        Msg::NoDataDirectory => "no data directory is known on this system",
+        Msg::NotInHistory => "no password is saved as '{0}'; see `history list`",
+        Msg::PurgeNeedsYes => "this deletes {0} saved password(s) at {1}; pass --yes to confirm",
+        Msg::WizardFailed => "wizard failed: {0}",
+        Msg::Weak => "Weak",
+        Msg::Fair => "Fair",
+        Msg::Strong => "Strong",
+        Msg::ReportGrade => "Grade:",
+        Msg::ReportLength => "Length:",
+        Msg::ReportCharacters => "{0} characters",
+        Msg::ReportCharactersTyped => "{0} characters ({1} as typed)",
+        Msg::ReportCharsetSize => "Charset size:",
+        Msg::ReportEntropy => "Entropy:",
+        Msg::ReportEffective => "Effective:",
+        Msg::ReportCrackTime => "Cracking Time (bcrypt speed assumption: 9*(10^3) attempt/sec):",
+        Msg::ReportComposition => "Composition:",
+        Msg::ReportUnique => "unique characters",
+        Msg::ReportWarnings => "Warnings:",
+        Msg::ClassLowercase => "lowercase letters",
+        Msg::ClassUppercase => "uppercase letters",
+        Msg::ClassDigits => "digits",
+        Msg::ClassSymbols => "symbols",
+        Msg::ClassWhitespace => "whitespace",
+        Msg::ClassNonAscii => "non-ASCII characters",
+        Msg::WarningEmpty => "Password is empty",
+        Msg::WarningDominantClass => "{0}% of characters are {1}",
+        Msg::WarningPattern => "Contains a {0} ({1} characters at position {2})",
+        Msg::WarningFewUnique => "Only {0} unique characters out of {1}",
+        Msg::PatternPersonalInfo => "personal information (e.g. username)",
+        Msg::PatternDictionary => "common word or password",
+        Msg::PatternKeyboardWalk => "keyboard walk",
+        Msg::PatternSequence => "character sequence",
+        Msg::PatternRepeat => "repeated character",
+        Msg::DetailsFindings => "Detector findings (before overlaps are resolved):",
+        Msg::DetailsNone => "none",
+        Msg::DetailsFinding => "characters {0}-{1} ({2} chars, {3} guess bits)",
+        Msg::DetailsBruteForce => "Brute force: {0} x log2({1}) = {0} x {2} = {3} bits",
+        Msg::DetailsPenalties => "Penalties:   {0} covered chars x {1} = -{2} bits, patterns cost +{3} bits",
+        Msg::DetailsEffective => "Effective:   {0} bits",
+        Msg::ColumnLine => "Line",
+        Msg::ColumnPassword => "Password",
+        Msg::ColumnLength => "Length",
+        Msg::ColumnEntropy => "Entropy",
+        Msg::ColumnGrade => "Grade",
+        Msg::ColumnBreaches => "Breaches",
+        Msg::SkippedBlank => "Note: line {0}: blank line skipped",
+        Msg::SkippedInvalidUtf8 => "Note: line {0}: invalid UTF-8, skipped",
+        Msg::ReuseClusters => "Reuse clusters:",
+        Msg::ReuseNone => "none found",
+        Msg::ReuseLine => "line",
+        Msg::ReuseIdentical => "identical",
+        Msg::ReuseIgnoringCase => "identical ignoring case",
+        Msg::ReuseTrailingCounter => "differ only by a trailing counter",
+        Msg::ReuseNearDuplicate => "near-duplicates",
+        Msg::SummaryHeading => "Summary:",
+        Msg::SummaryLines => "Lines:",
+        Msg::SummaryAnalyzed => "Analyzed:",
+        Msg::SummarySkipped => "Skipped:",
+        Msg::SummaryBlank => "Blank:",
+        Msg::SummaryInvalidUtf8 => "Invalid UTF-8:",
+        Msg::SummaryAverageEntropy => "Average entropy:",
+        Msg::SummaryGrades => "Grades:",
+        Msg::SummaryReuse => "Reuse:",
+        Msg::SummaryReuseValue => "{0} entries in {1} clusters",
+        Msg::SummaryBreached => "Breached:",
+        Msg::SummaryBreachedValue => "{0} ({1} could not be checked)",
+        Msg::SummaryWeakestLines => "Weakest lines:",
+        Msg::SummaryWeakest => "Weakest:",
+        Msg::PolicyHeading => "Policy:",
+        Msg::VerdictHeading => "Verdict:",
+        Msg::Compliant => "COMPLIANT",
+        Msg::NotCompliant => "NOT COMPLIANT",
+        Msg::PolicyPass => "PASS",
+        Msg::PolicyFail => "FAIL",
+        Msg::PolicyInfo => "INFO",
+        Msg::PolicyStrength => "strength",
+        Msg::GradeAndBits => "grade {0} ({1} bits)",
+        Msg::HashMatches => "MATCH: the password matches the {0} hash",
+        Msg::HashMismatch => "NO MATCH: the password does not match the {0} hash",
+        Msg::BenchHeading => "Generation benchmark: {0}s per case, {1} thread(s)",
+        Msg::BenchCase => "Case",
+        Msg::BenchPasswordsPerSecond => "passwords/s",
+        Msg::BenchDefault => "16 chars, lowercase",
+        Msg::BenchAllClasses => "32 chars, all classes",
+        Msg::BenchPolicy => "16 chars, all classes, NIST policy",
+        Msg::AuditBreached => "BREACH",
+        Msg::AuditWeak => "WEAK",
+        Msg::AuditReused => "REUSED",
+        Msg::AuditOk => "ok",
+        Msg::AuditRow => "row",
+        Msg::AuditReusedIn => "reused in rows {0}",
+        Msg::AuditSeenInBreaches => "seen {0} times in breaches",
+        Msg::AuditBreachCheckFailed => "breach check failed",
+        Msg::AuditSummary => "Summary: {0}{1} weak, {2} reused, {3} ok ({4} credentials)",
+        Msg::AuditSummaryBreached => "{0} breached, ",
+        Msg::AuditUnchecked => "{0} credential(s) could not be checked for breaches",
+        Msg::AuditWithoutPassword => "{0} row(s) without a password were skipped",
+        Msg::KeyspaceLabel => "Keyspace:",
+        Msg::KeyspaceWordlist => "Word list:",
+        Msg::KeyspaceWords => "{0} words",
+        Msg::KeyspaceAlphabet => "Alphabet size:",
+        Msg::KeyspaceBitsPerWord => "Bits per word:",
+        Msg::KeyspaceBitsPerCharacter => "Bits per character:",
+        Msg::KeyspaceAverage => "(average)",
+        Msg::KeyspaceBits => "{0} bits",
+        Msg::KeyspaceCombinations => "Combinations:",
+        Msg::KeyspaceTimeHeading => "Time to try every combination:",
+        Msg::ScenarioOnlineThrottled => "Online, throttled",
+        Msg::ScenarioOnlineUnthrottled => "Online, unthrottled",
+        Msg::ScenarioOfflineSlowHash => "Offline, slow hash (bcrypt)",
+        Msg::ScenarioOfflineFastHash => "Offline, fast hash (GPU rig)",
+        Msg::WizardChooseMode => "What do you want to do?",
+        Msg::WizardModeGenerate => "Generate a password",
+        Msg::WizardModeAnalyze => "Analyze a password",
+        Msg::WizardLength => "Password length ({0}-{1})",
+        Msg::WizardLengthInvalid => "Length must be a number between {0} and {1}",
+        Msg::WizardUppercase => "Include uppercase letters (A-Z)?",
+        Msg::WizardDigits => "Include digits (0-9)?",
+        Msg::WizardSymbols => "Include symbols?",
+        Msg::WizardRun => "Run `{0}`?",
+        Msg::PromptPassword => "Password: ",
+        Msg::PromptConfirmPassword => "Confirm password: ",
+        Msg::PromptPassphrase => "Master passphrase: ",
+        Msg::PromptNewPassphrase => "New master passphrase: ",
+        Msg::PromptConfirmPassphrase => "Confirm master passphrase: ",
+        Msg::PasswordsDiffer => "passwords do not match",
+        Msg::PassphraseNeedsTerminal => "the master passphrase can only be entered on a terminal",
+        Msg::PassphraseEmpty => "the master passphrase cannot be empty",
+        Msg::PassphrasesDiffer => "passphrases do not match",
+    }
+}
+
+/// German catalog; `None` marks a message that falls back to English
+///
+/// The match is exhaustive on purpose: adding a message does not compile
+/// until each locale either translates it or explicitly falls back.
+fn german(msg: Msg) -> Option<&'static str> {
+    Some(match msg {
+        Msg::GeneratedPasswordLabel => "Generiertes Passwort:",
+        Msg::AnalysisLabel => "Analyse der Passwortstärke:",
+        Msg::TokenAnalysisLabel => "Analyse der Token-Stärke:",
+        Msg::BatchAnalysisLabel => "Stapelanalyse der Passwörter:",
+        Msg::AuditLabel => "Prüfung der Zugangsdaten:",
+        Msg::PasswordHashLabel => "Passwort-Hash:",
+        Msg::HtpasswdEntryLabel => "htpasswd-Eintrag:",
+        Msg::ErrorPrefix => "Fehler:",
+        Msg::WarningPrefix => "Warnung:",
+        Msg::CopiedToClipboard => "Passwort in die Zwischenablage kopiert; sie wird in {0} Sekunden geleert",
+        Msg::CopiedViaTerminal => "Passwort über das Terminal in die Zwischenablage gelegt (OSC 52); sie wird nicht automatisch geleert",
+        Msg::ClipboardFallback => "{0}; stattdessen wird über das Terminal kopiert (OSC 52)",
+        Msg::WroteFile => "{0} Passwort/Passwörter nach {1} geschrieben",
+        Msg::ReportWritten => "Bericht nach {0} geschrieben",
+        Msg::LengthOutOfRange => "Länge {0} liegt außerhalb von {1}-{2}, stattdessen wird {3} verwendet",
+        Msg::Legend => "Legende: {0} Großbuchstaben  abc Kleinbuchstaben  {1} Ziffern  {2} Symbole",
+        Msg::RevealPrompt => "r zum Anzeigen, eine andere Taste zum Beenden: ",
+        Msg::HiddenAgain => "(wird in {0} Sekunden wieder verborgen)",
+        Msg::MaskedSummary => "{0} Zeichen, Note {1}, {2} Bit",
+        Msg::ConfigWritten => "Konfigurationsvorlage nach {0} geschrieben",
+        Msg::UnknownConfigKey => "unbekannter Schlüssel '{0}' in {1} wird ignoriert",
+        Msg::SavedToHistory => "{0} Passwort/Passwörter im Verlauf als '{1}' gespeichert",
+        Msg::StoredInKeyring => "Passwort im Anmeldeinformationsspeicher als '{0}' abgelegt",
+        Msg::DeletedFromKeyring => "'{0}' aus dem Anmeldeinformationsspeicher entfernt",
+        Msg::StoredPasswordLabel => "Gespeichertes Passwort:",
+        Msg::HistoryEmpty => "Es wurden noch keine Passwörter gespeichert (generate --save --label TEXT)",
+        Msg::HistoryPurged => "Verlauf mit {0} gespeicherten Passwort/Passwörtern gelöscht",
+        Msg::HtpasswdAdded => "'{0}' zu {1} hinzugefügt",
+        Msg::HtpasswdReplaced => "Eintrag von '{0}' in {1} ersetzt",
+        Msg::EnvFileUpdated => "{0} Schlüssel nach {1} geschrieben ({2} hinzugefügt, {3} ersetzt)",
+        Msg::EnvKeyConvention => "'{0}' folgt nicht der Konvention [A-Z_][A-Z0-9_]* für Umgebungsvariablen; manche Werkzeuge lehnen ihn womöglich ab",
+        Msg::PlaintextExport => "{0} enthält die Passwörter im KLARTEXT; importieren Sie die Datei und löschen Sie sie danach sicher",
+        Msg::BelowMinBits => "DURCHGEFALLEN: {0} Bit effektive Entropie, gefordert sind {1}",
+        Msg::BreachCheckIncomplete => "{0} von {1} Abfragen der Leak-Datenbank sind fehlgeschlagen; diese Passwörter sind als unbekannt markiert ({2})",
+        Msg::SeededPasswords => "--seed macht die Passwörter für jeden reproduzierbar, der den Seed kennt; nur für Tests verwenden",
+        Msg::MaskedPassword => "Generiertes Passwort: {0}  ({1})",
+        Msg::SpellHeading => "Passwort {0}:",
+        Msg::BenchmarkRunning => "Messe {0} für {1} ...",
+        Msg::NoProfiles => "In {0} sind keine Profile definiert",
+        Msg::TheConfigurationFile => "der Konfigurationsdatei",
+        Msg::HistorySettings => "Länge {0}, {1}",
+        Msg::HistorySettingsPolicy => "Länge {0}, {1}, Richtlinie {2}",
+        Msg::EquivalentCommand => "Entsprechender Befehl: {0}",
+        Msg::HibpNeedsFile => "--hibp prüft die Zeilen einer Passwortdatei; zusammen mit --file verwenden",
+        Msg::InvalidTemplate => "ungültige --format-Vorlage: {0}",
+        Msg::SinglePasswordOnly => "{0} funktioniert nur mit einem einzelnen Passwort; --count weglassen",
+        Msg::CsvWriteFailed => "CSV konnte nicht geschrieben werden: {0}",
+        Msg::CannotWriteStdout => "stdout kann nicht geschrieben werden: {0}",
+        Msg::FileExists => "{0} existiert bereits; mit --force überschreiben",
+        Msg::CannotWrite => "{0} kann nicht geschrieben werden: {1}",
+        Msg::CannotCreate => "{0} kann nicht angelegt werden: {1}",
+        Msg::NothingToHash => "das Passwort ist leer; es gibt nichts zu hashen",
+        Msg::CannotReadPassword => "Passwort kann nicht gelesen werden: {0}",
+        Msg::CsvNeedsFile => "CSV-Ausgabe gibt es nur mit --file",
+        Msg::CsvNotAvailable => "CSV-Ausgabe ist für {0} nicht verfügbar",
+        Msg::SummaryOnlyFormats => "--summary-only gibt Text oder JSON aus; --output csv weglassen",
+        Msg::StreamNeedsPipe => "--stream liest die Passwörter aus einer Pipe oder einer umgeleiteten Datei",
+        Msg::AnalyzeStdinFailed => "stdin konnte nicht analysiert werden: {0}",
+        Msg::NetworkNotCompiled => "Leak-Prüfungen sind nicht einkompiliert; mit `--features network` neu bauen",
+        Msg::NoConfigDirectory => "auf diesem System ist kein Konfigurationsverzeichnis bekannt; --config PATH angeben",
+        Msg::NoDataDirectory => "auf diesem System ist kein Datenverzeichnis bekannt",
+        Msg::NotInHistory => "unter '{0}' ist kein Passwort gespeichert; siehe `history list`",
+        Msg::PurgeNeedsYes => "dies löscht {0} gespeicherte(s) Passwort/Passwörter in {1}; zur Bestätigung --yes angeben",
+        Msg::WizardFailed => "Assistent fehlgeschlagen: {0}",
+        Msg::Weak => "Schwach",
+        Msg::Fair => "Mittel",
+        Msg::Strong => "Stark",
+        Msg::ReportGrade => "Note:",
+        Msg::ReportLength => "Länge:",
+        Msg::ReportCharacters => "{0} Zeichen",
+        Msg::ReportCharactersTyped => "{0} Zeichen ({1} wie eingegeben)",
+        Msg::ReportCharsetSize => "Zeichenvorrat:",
+        Msg::ReportEntropy => "Entropie:",
+        Msg::ReportEffective => "Effektiv:",
+        Msg::ReportCrackTime => "Knackzeit (Annahme bcrypt-Geschwindigkeit: 9*(10^3) Versuche/s):",
+        Msg::ReportComposition => "Zusammensetzung:",
+        Msg::ReportUnique => "verschiedene Zeichen",
+        Msg::ReportWarnings => "Warnungen:",
+        Msg::ClassLowercase => "Kleinbuchstaben",
+        Msg::ClassUppercase => "Großbuchstaben",
+        Msg::ClassDigits => "Ziffern",
+        Msg::ClassSymbols => "Symbole",
+        Msg::ClassWhitespace => "Leerzeichen",
+        Msg::ClassNonAscii => "Nicht-ASCII-Zeichen",
+        Msg::WarningEmpty => "Das Passwort ist leer",
+        Msg::WarningDominantClass => "{0} % der Zeichen sind {1}",
+        Msg::WarningPattern => "Enthält {0} ({1} Zeichen ab Position {2})",
+        Msg::WarningFewUnique => "Nur {0} verschiedene Zeichen von {1}",
+        Msg::PatternPersonalInfo => "persönliche Angaben (z. B. den Benutzernamen)",
+        Msg::PatternDictionary => "ein gängiges Wort oder Passwort",
+        Msg::PatternKeyboardWalk => "eine Tastaturfolge",
+        Msg::PatternSequence => "eine Zeichenfolge",
+        Msg::PatternRepeat => "ein wiederholtes Zeichen",
+        Msg::DetailsFindings => "Funde der Detektoren (vor dem Auflösen von Überschneidungen):",
+        Msg::DetailsNone => "keine",
+        Msg::DetailsFinding => "Zeichen {0}-{1} ({2} Zeichen, {3} Bit zum Erraten)",
+        Msg::DetailsBruteForce => "Brute Force: {0} x log2({1}) = {0} x {2} = {3} Bit",
+        Msg::DetailsPenalties => "Abzüge:      {0} abgedeckte Zeichen x {1} = -{2} Bit, Muster kosten +{3} Bit",
+        Msg::DetailsEffective => "Effektiv:    {0} Bit",
+        Msg::ColumnLine => "Zeile",
+        Msg::ColumnPassword => "Passwort",
+        Msg::ColumnLength => "Länge",
+        Msg::ColumnEntropy => "Entropie",
+        Msg::ColumnGrade => "Note",
+        Msg::ColumnBreaches => "Leaks",
+        Msg::SkippedBlank => "Hinweis: Zeile {0}: leere Zeile übersprungen",
+        Msg::SkippedInvalidUtf8 => "Hinweis: Zeile {0}: ungültiges UTF-8, übersprungen",
+        Msg::ReuseClusters => "Wiederverwendete Passwörter:",
+        Msg::ReuseNone => "keine gefunden",
+        Msg::ReuseLine => "Zeile",
+        Msg::ReuseIdentical => "identisch",
+        Msg::ReuseIgnoringCase => "identisch bis auf Groß-/Kleinschreibung",
+        Msg::ReuseTrailingCounter => "unterscheiden sich nur durch einen Zähler am Ende",
+        Msg::ReuseNearDuplicate => "fast identisch",
+        Msg::SummaryHeading => "Zusammenfassung:",
+        Msg::SummaryLines => "Zeilen:",
+        Msg::SummaryAnalyzed => "Analysiert:",
+        Msg::SummarySkipped => "Übersprungen:",
+        Msg::SummaryBlank => "Leer:",
+        Msg::SummaryInvalidUtf8 => "Ungültiges UTF-8:",
+        Msg::SummaryAverageEntropy => "Mittlere Entropie:",
+        Msg::SummaryGrades => "Noten:",
+        Msg::SummaryReuse => "Wiederverwendung:",
+        Msg::SummaryReuseValue => "{0} Einträge in {1} Gruppen",
+        Msg::SummaryBreached => "Geleakt:",
+        Msg::SummaryBreachedValue => "{0} ({1} nicht prüfbar)",
+        Msg::SummaryWeakestLines => "Schwächste Zeilen:",
+        Msg::SummaryWeakest => "Am schwächsten:",
+        Msg::PolicyHeading => "Richtlinie:",
+        Msg::VerdictHeading => "Ergebnis:",
+        Msg::Compliant => "KONFORM",
+        Msg::NotCompliant => "NICHT KONFORM",
+        Msg::PolicyPass => "OK",
+        Msg::PolicyFail => "FEHLER",
+        Msg::PolicyInfo => "INFO",
+        Msg::PolicyStrength => "Stärke",
+        Msg::GradeAndBits => "Note {0} ({1} Bit)",
+        Msg::HashMatches => "TREFFER: Das Passwort passt zum {0}-Hash",
+        Msg::HashMismatch => "KEIN TREFFER: Das Passwort passt nicht zum {0}-Hash",
+        Msg::BenchHeading => "Generierungs-Benchmark: {0} s pro Fall, {1} Thread(s)",
+        Msg::BenchCase => "Fall",
+        Msg::BenchPasswordsPerSecond => "Passwörter/s",
+        Msg::BenchDefault => "16 Zeichen, Kleinbuchstaben",
+        Msg::BenchAllClasses => "32 Zeichen, alle Klassen",
+        Msg::BenchPolicy => "16 Zeichen, alle Klassen, NIST-Richtlinie",
+        Msg::AuditBreached => "GELEAKT",
+        Msg::AuditWeak => "SCHWACH",
+        Msg::AuditReused => "MEHRFACH",
+        Msg::AuditOk => "ok",
+        Msg::AuditRow => "Zeile",
+        Msg::AuditReusedIn => "mehrfach verwendet in Zeilen {0}",
+        Msg::AuditSeenInBreaches => "{0}-mal in Datenlecks gefunden",
+        Msg::AuditBreachCheckFailed => "Leak-Prüfung fehlgeschlagen",
+        Msg::AuditSummary => "Zusammenfassung: {0}{1} schwach, {2} mehrfach verwendet, {3} ok ({4} Zugangsdaten)",
+        Msg::AuditSummaryBreached => "{0} geleakt, ",
+        Msg::AuditUnchecked => "{0} Zugangsdaten konnten nicht auf Leaks geprüft werden",
+        Msg::AuditWithoutPassword => "{0} Zeile(n) ohne Passwort wurden übersprungen",
+        Msg::KeyspaceLabel => "Schlüsselraum:",
+        Msg::KeyspaceWordlist => "Wortliste:",
+        Msg::KeyspaceWords => "{0} Wörter",
+        Msg::KeyspaceAlphabet => "Alphabetgröße:",
+        Msg::KeyspaceBitsPerWord => "Bit pro Wort:",
+        Msg::KeyspaceBitsPerCharacter => "Bit pro Zeichen:",
+        Msg::KeyspaceAverage => "(Durchschnitt)",
+        Msg::KeyspaceBits => "{0} Bit",
+        Msg::KeyspaceCombinations => "Kombinationen:",
+        Msg::KeyspaceTimeHeading => "Zeit, um jede Kombination durchzuprobieren:",
+        Msg::ScenarioOnlineThrottled => "Online, gedrosselt",
+        Msg::ScenarioOnlineUnthrottled => "Online, ungedrosselt",
+        Msg::ScenarioOfflineSlowHash => "Offline, langsamer Hash (bcrypt)",
+        Msg::ScenarioOfflineFastHash => "Offline, schneller Hash (GPU-Rechner)",
+        Msg::WizardChooseMode => "Was möchten Sie tun?",
+        Msg::WizardModeGenerate => "Ein Passwort erzeugen",
+        Msg::WizardModeAnalyze => "Ein Passwort analysieren",
+        Msg::WizardLength => "Passwortlänge ({0}-{1})",
+        Msg::WizardLengthInvalid => "Die Länge muss eine Zahl zwischen {0} und {1} sein",
+        Msg::WizardUppercase => "Großbuchstaben (A-Z) verwenden?",
+        Msg::WizardDigits => "Ziffern (0-9) verwenden?",
+        Msg::WizardSymbols => "Sonderzeichen verwenden?",
+        Msg::WizardRun => "`{0}` ausführen?",
+        Msg::PromptPassword => "Passwort: ",
+        Msg::PromptConfirmPassword => "Passwort bestätigen: ",
+        Msg::PromptPassphrase => "Master-Passphrase: ",
+        Msg::PromptNewPassphrase => "Neue Master-Passphrase: ",
+        Msg::PromptConfirmPassphrase => "Master-Passphrase bestätigen: ",
+        Msg::PasswordsDiffer => "die Passwörter stimmen nicht überein",
+        Msg::PassphraseNeedsTerminal => "die Master-Passphrase kann nur in einem Terminal eingegeben werden",
+        Msg::PassphraseEmpty => "die Master-Passphrase darf nicht leer sein",
+        Msg::PassphrasesDiffer => "die Passphrasen stimmen nicht überein",
+    })
+}
+
+/// Looks up a message in the selected locale, falling back to English
+pub fn tr(msg: Msg) -> &'static str {
+    let translated = match LOCALE.get().copied().unwrap_or(Locale::En) {
+        Locale::En => None,
+        Locale::De => german(msg),
+    };
+    translated.unwrap_or_else(|| english(msg))
+}
+
+/// Looks up a message and fills in its `{0}`, `{1}`, ... placeholders
+///
+/// Placeholders without a matching argument are left as written.
+pub fn trf(msg: Msg, args: &[&dyn Display]) -> String {
+    let mut message = tr(msg).to_string();
+    for (index, arg) in args.iter().enumerate() {
+        message = message.replace(&format!("{{{}}}", index), &arg.to_string());
+    }
+    message
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every message, in declaration order
+    const ALL: [Msg; 189] = [
+        Msg::GeneratedPasswordLabel,
+        Msg::AnalysisLabel,
+        Msg::TokenAnalysisLabel,
+        Msg::BatchAnalysisLabel,
+        Msg::AuditLabel,
+        Msg::PasswordHashLabel,
+        Msg::HtpasswdEntryLabel,
+        Msg::ErrorPrefix,
+        Msg::WarningPrefix,
+        Msg::CopiedToClipboard,
+        Msg::CopiedViaTerminal,
+        Msg::ClipboardFallback,
+        Msg::WroteFile,
+        Msg::ReportWritten,
+        Msg::LengthOutOfRange,
+        Msg::Legend,
+        Msg::RevealPrompt,
+        Msg::HiddenAgain,
+        Msg::MaskedSummary,
+        Msg::ConfigWritten,
+        Msg::UnknownConfigKey,
+        Msg::SavedToHistory,
+        Msg::StoredInKeyring,
+        Msg::DeletedFromKeyring,
+        Msg::StoredPasswordLabel,
+        Msg::HistoryEmpty,
+        Msg::HistoryPurged,
+        Msg::HtpasswdAdded,
+        Msg::HtpasswdReplaced,
+        Msg::EnvFileUpdated,
+        Msg::EnvKeyConvention,
+        Msg::PlaintextExport,
+        Msg::BelowMinBits,
+        Msg::BreachCheckIncomplete,
+        Msg::SeededPasswords,
+        Msg::MaskedPassword,
+        Msg::SpellHeading,
+        Msg::BenchmarkRunning,
+        Msg::NoProfiles,
+        Msg::TheConfigurationFile,
+        Msg::HistorySettings,
+        Msg::HistorySettingsPolicy,
+        Msg::EquivalentCommand,
+        Msg::HibpNeedsFile,
+        Msg::InvalidTemplate,
+        Msg::SinglePasswordOnly,
+        Msg::CsvWriteFailed,
+        Msg::CannotWriteStdout,
+        Msg::FileExists,
+        Msg::CannotWrite,
+        Msg::CannotCreate,
+        Msg::NothingToHash,
+        Msg::CannotReadPassword,
+        Msg::CsvNeedsFile,
+        Msg::CsvNotAvailable,
+        Msg::SummaryOnlyFormats,
+        Msg::StreamNeedsPipe,
+        Msg::AnalyzeStdinFailed,
+        Msg::NetworkNotCompiled,
+        Msg::NoConfigDirectory,
+        Msg::NoDataDirectory,
+        Msg::NotInHistory,
+        Msg::PurgeNeedsYes,
+        Msg::WizardFailed,
+        Msg::Weak,
+        Msg::Fair,
+        Msg::Strong,
+        Msg::ReportGrade,
+        Msg::ReportLength,
+        Msg::ReportCharacters,
+        Msg::ReportCharactersTyped,
+        Msg::ReportCharsetSize,
+        Msg::ReportEntropy,
+        Msg::ReportEffective,
+        Msg::ReportCrackTime,
+        Msg::ReportComposition,
+        Msg::ReportUnique,
+        Msg::ReportWarnings,
+        Msg::ClassLowercase,
+        Msg::ClassUppercase,
+        Msg::ClassDigits,
+        Msg::ClassSymbols,
+        Msg::ClassWhitespace,
+        Msg::ClassNonAscii,
+        Msg::WarningEmpty,
+        Msg::WarningDominantClass,
+        Msg::WarningPattern,
+        Msg::WarningFewUnique,
+        Msg::PatternPersonalInfo,
+        Msg::PatternDictionary,
+        Msg::PatternKeyboardWalk,
+        Msg::PatternSequence,
+        Msg::PatternRepeat,
+        Msg::DetailsFindings,
+        Msg::DetailsNone,
+        Msg::DetailsFinding,
+        Msg::DetailsBruteForce,
+        Msg::DetailsPenalties,
+        Msg::DetailsEffective,
+        Msg::ColumnLine,
+        Msg::ColumnPassword,
+        Msg::ColumnLength,
+        Msg::ColumnEntropy,
+        Msg::ColumnGrade,
+        Msg::ColumnBreaches,
+        Msg::SkippedBlank,
+        Msg::SkippedInvalidUtf8,
+        Msg::ReuseClusters,
+        Msg::ReuseNone,
+        Msg::ReuseLine,
+        Msg::ReuseIdentical,
+        Msg::ReuseIgnoringCase,
+        Msg::ReuseTrailingCounter,
+        Msg::ReuseNearDuplicate,
+        Msg::SummaryHeading,
+        Msg::SummaryLines,
+        Msg::SummaryAnalyzed,
+        Msg::SummarySkipped,
+        Msg::SummaryBlank,
+        Msg::SummaryInvalidUtf8,
+        Msg::SummaryAverageEntropy,
+        Msg::SummaryGrades,
+        Msg::SummaryReuse,
+        Msg::SummaryReuseValue,
+        Msg::SummaryBreached,
+        Msg::SummaryBreachedValue,
+        Msg::SummaryWeakestLines,
+        Msg::SummaryWeakest,
+        Msg::PolicyHeading,
+        Msg::VerdictHeading,
+        Msg::Compliant,
+        Msg::NotCompliant,
+        Msg::PolicyPass,
+        Msg::PolicyFail,
+        Msg::PolicyInfo,
+        Msg::PolicyStrength,
+        Msg::GradeAndBits,
+        Msg::HashMatches,
+        Msg::HashMismatch,
+        Msg::BenchHeading,
+        Msg::BenchCase,
+        Msg::BenchPasswordsPerSecond,
+        Msg::BenchDefault,
+        Msg::BenchAllClasses,
+        Msg::BenchPolicy,
+        Msg::AuditBreached,
+        Msg::AuditWeak,
+        Msg::AuditReused,
+        Msg::AuditOk,
+        Msg::AuditRow,
+        Msg::AuditReusedIn,
+        Msg::AuditSeenInBreaches,
+        Msg::AuditBreachCheckFailed,
+        Msg::AuditSummary,
+        Msg::AuditSummaryBreached,
+        Msg::AuditUnchecked,
+        Msg::AuditWithoutPassword,
+        Msg::KeyspaceLabel,
+        Msg::KeyspaceWordlist,
+        Msg::KeyspaceWords,
+        Msg::KeyspaceAlphabet,
+        Msg::KeyspaceBitsPerWord,
+        Msg::KeyspaceBitsPerCharacter,
+        Msg::KeyspaceAverage,
+        Msg::KeyspaceBits,
+        Msg::KeyspaceCombinations,
+        Msg::KeyspaceTimeHeading,
+        Msg::ScenarioOnlineThrottled,
+        Msg::ScenarioOnlineUnthrottled,
+        Msg::ScenarioOfflineSlowHash,
+        Msg::ScenarioOfflineFastHash,
+        Msg::WizardChooseMode,
+        Msg::WizardModeGenerate,
+        Msg::WizardModeAnalyze,
+        Msg::WizardLength,
+        Msg::WizardLengthInvalid,
+        Msg::WizardUppercase,
+        Msg::WizardDigits,
+        Msg::WizardSymbols,
+        Msg::WizardRun,
+        Msg::PromptPassword,
+        Msg::PromptConfirmPassword,
+        Msg::PromptPassphrase,
+        Msg::PromptNewPassphrase,
+        Msg::PromptConfirmPassphrase,
+        Msg::PasswordsDiffer,
+        Msg::PassphraseNeedsTerminal,
+        Msg::PassphraseEmpty,
+        Msg::PassphrasesDiffer,
+    ];
+
+    /// Placeholders such as `{0}` a message uses, sorted
+    fn placeholders(message: &str) -> Vec<&str> {
+        let mut found: Vec<&str> = message
+            .match_indices('{')
+            .filter_map(|(start, _)| message[start..].find('}').map(|end| &message[start..=start + end]))
+            .collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    #[test]
+    fn every_message_is_translated_with_the_same_placeholders() {
+        for msg in ALL {
+            let english = english(msg);
+            assert!(!english.is_empty(), "{:?}", msg);
+            let german = german(msg).unwrap_or_else(|| panic!("{:?} has no German text", msg));
+            assert_eq!(placeholders(german), placeholders(english), "{:?}", msg);
+        }
+    }
+
+    #[test]
+    fn placeholders_are_numbered_from_zero() {
+        for msg in ALL {
+            for (index, placeholder) in placeholders(english(msg)).into_iter().enumerate() {
+                assert_eq!(placeholder, format!("{{{}}}", index), "{:?}", msg);
+            }
+        }
+    }
+
+    #[test]
+    fn trf_fills_in_positional_arguments() {
+        // The locale is never set in unit tests, so messages are English
+        assert_eq!(trf(Msg::WarningFewUnique, &[&3, &12]), "Only 3 unique characters out of 12");
+        assert_eq!(trf(Msg::WarningPattern, &[&"keyboard walk", &6, &1]), "Contains a keyboard walk (6 characters at position 1)");
+        assert_eq!(trf(Msg::SummaryReuseValue, &[&4]), "4 entries in {1} clusters");
+    }
+
+    #[test]
+    fn language_ignores_territory_and_encoding() {
+        assert_eq!(language_of("de_DE.UTF-8"), "de");
+        assert_eq!(language_of("DE"), "de");
+        assert_eq!(language_of("en_US@euro"), "en");
+        assert_eq!(language_of("C.UTF-8"), "c");
+        assert_eq!(language_of(""), "");
+    }
+
+    #[test]
+    fn flag_wins_over_everything() {
+        assert_eq!(detect_locale(Some(Locale::De), Some(Locale::En)), Locale::De);
+        assert_eq!(detect_locale(Some(Locale::En), Some(Locale::De)), Locale::En);
+    }
+}
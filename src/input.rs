@@ -0,0 +1,374 @@
+use std::io::{self, IsTerminal, Read, Write};
+use zeroize::Zeroizing;
+use crate::i18n::{self, Msg};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Byte produced by Ctrl-C once signal generation is disabled
+const CTRL_C: u8 = 0x03;
+
+/// Byte produced by Ctrl-D (end of input on Unix terminals)
+const CTRL_D: u8 = 0x04;
+
+/// Byte produced by Ctrl-H / Backspace on some terminals
+const BACKSPACE: u8 = 0x08;
+
+/// Byte produced by Backspace on most terminals
+const DELETE: u8 = 0x7f;
+
+// ============================================================================
+// Secret Input
+// ============================================================================
+
+/// A secret string that is wiped from memory when dropped
+pub(crate) type Secret = Zeroizing<String>;
+
+/// Resolves a secret from the first configured source
+///
+/// # Arguments
+/// * `positional` - Secret given directly on the command line, if any
+/// * `stdin` - Read the secret from standard input
+/// * `env_var` - Read the secret from this environment variable
+/// * `confirm` - Ask twice when falling back to the interactive prompt
+///
+/// # Precedence
+/// 1. Positional argument
+/// 2. `--stdin`
+/// 3. `--password-env`
+/// 4. Hidden interactive prompt, only when stdin is a terminal
+///
+/// The command line rejects combinations of the first three, so in practice
+/// exactly one explicit source is used and the prompt is the fallback.
+///
+/// # Errors
+/// * `ErrorKind::NotFound` - No source was given and stdin is not a terminal
+pub(crate) fn resolve_secret(positional: Option<&str>, stdin: bool, env_var: Option<&str>, confirm: bool) -> io::Result<Secret> {
+    if let Some(secret) = positional {
+        return Ok(Zeroizing::new(secret.to_string()));
+    }
+    if stdin {
+        return read_secret_from_stdin();
+    }
+    if let Some(var) = env_var {
+        return read_secret_from_env(var);
+    }
+    if io::stdin().is_terminal() {
+        return prompt_secret(confirm);
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "no password given; use --stdin or --password-env when input is not a terminal",
+    ))
+}
+
+/// Reads a secret from an environment variable and removes it from the environment
+///
+/// # Arguments
+/// * `var` - Name of the environment variable
+///
+/// # Returns
+/// * `io::Result<Secret>` - The variable's value
+///
+/// # Errors
+/// * `ErrorKind::NotFound` - The variable is not set
+/// * `ErrorKind::InvalidInput` - The variable is set but empty
+/// * `ErrorKind::InvalidData` - The value is not valid Unicode
+///
+/// # Notes
+/// The variable is removed even on error so child processes never inherit it.
+pub(crate) fn read_secret_from_env(var: &str) -> io::Result<Secret> {
+    let value = std::env::var_os(var);
+    // SAFETY: called while reading input, before any thread is spawned, so no
+    // other thread can be reading the environment concurrently
+    unsafe { std::env::remove_var(var) };
+
+    let Some(value) = value else {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("environment variable {} is not set", var)));
+    };
+    let value = Zeroizing::new(value.into_string().map_err(|_| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("environment variable {} is not valid Unicode", var))
+    })?);
+    if value.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("environment variable {} is empty", var)));
+    }
+
+    Ok(value)
+}
+
+/// Reads a secret from standard input
+///
+/// # Returns
+/// * `io::Result<Secret>` - The secret with at most one trailing newline removed
+///
+/// # Notes
+/// When stdin is a terminal there is nothing piped in, so instead of waiting
+/// for EOF the user is prompted for the password with echo disabled.
+pub(crate) fn read_secret_from_stdin() -> io::Result<Secret> {
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        return prompt_secret(false);
+    }
+
+    let mut bytes = Zeroizing::new(Vec::new());
+    stdin.lock().read_to_end(&mut bytes)?;
+
+    Ok(strip_one_newline(utf8_secret(&bytes, "stdin")?))
+}
+
+/// Prompts for a secret on the terminal without echoing it
+///
+/// # Arguments
+/// * `confirm` - Ask a second time and fail if both entries differ
+///
+/// # Returns
+/// * `io::Result<Secret>` - The entered secret
+///
+/// # Errors
+/// * `ErrorKind::Interrupted` - The user pressed Ctrl-C (the terminal mode is restored first)
+/// * `ErrorKind::InvalidInput` - The confirmation did not match
+pub(crate) fn prompt_secret(confirm: bool) -> io::Result<Secret> {
+    let secret = read_hidden(i18n::tr(Msg::PromptPassword))?;
+
+    if confirm {
+        let again = read_hidden(i18n::tr(Msg::PromptConfirmPassword))?;
+        if *secret != *again {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, i18n::tr(Msg::PasswordsDiffer)));
+        }
+    }
+
+    Ok(secret)
+}
+
+/// Prompts for the master passphrase of the password history without echoing it
+///
+/// # Arguments
+/// * `confirm` - Ask a second time, for a passphrase that is being chosen
+///
+/// # Errors
+/// * `ErrorKind::NotFound` - stdin is not a terminal
+/// * `ErrorKind::InvalidInput` - The passphrase is empty or the confirmation did not match
+pub(crate) fn prompt_passphrase(confirm: bool) -> io::Result<Secret> {
+    if !io::stdin().is_terminal() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, i18n::tr(Msg::PassphraseNeedsTerminal)));
+    }
+    let prompt = i18n::tr(if confirm { Msg::PromptNewPassphrase } else { Msg::PromptPassphrase });
+    let passphrase = read_hidden(prompt)?;
+    if passphrase.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, i18n::tr(Msg::PassphraseEmpty)));
+    }
+
+    if confirm {
+        let again = read_hidden(i18n::tr(Msg::PromptConfirmPassphrase))?;
+        if *passphrase != *again {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, i18n::tr(Msg::PassphrasesDiffer)));
+        }
+    }
+
+    Ok(passphrase)
+}
+
+/// Prints `prompt` on stderr and waits for a single key press, without echo
+///
+/// # Returns
+/// * `io::Result<Option<u8>>` - The first byte of the key, or `None` at end of input
+pub(crate) fn read_key(prompt: &str) -> io::Result<Option<u8>> {
+    let mut stderr = io::stderr();
+    write!(stderr, "{}", prompt)?;
+    stderr.flush()?;
+
+    let key = {
+        let _raw = terminal::RawModeGuard::enable()?;
+        terminal::read_byte()?
+    };
+
+    writeln!(stderr)?;
+    Ok(key)
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Prints `prompt` on stderr and reads one line from the terminal in raw mode
+///
+/// Bytes go straight into a zeroized buffer and are never echoed. Backspace
+/// removes a whole (possibly multi-byte) character.
+fn read_hidden(prompt: &str) -> io::Result<Secret> {
+    let mut stderr = io::stderr();
+    write!(stderr, "{}", prompt)?;
+    stderr.flush()?;
+
+    let mut buffer = Zeroizing::new(Vec::new());
+    {
+        // Restores the original terminal mode when dropped, on every return path
+        let _raw = terminal::RawModeGuard::enable()?;
+
+        while let Some(byte) = terminal::read_byte()? {
+            match byte {
+                b'\r' | b'\n' => break,
+                CTRL_C => {
+                    drop(_raw);
+                    writeln!(stderr)?;
+                    return Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted"));
+                }
+                CTRL_D if buffer.is_empty() => break,
+                BACKSPACE | DELETE => {
+                    // Drop UTF-8 continuation bytes, then the leading byte
+                    while buffer.last().is_some_and(|b| b & 0xC0 == 0x80) {
+                        buffer.pop();
+                    }
+                    buffer.pop();
+                }
+                _ => buffer.push(byte),
+            }
+        }
+    }
+
+    // Enter was not echoed either, so move past the prompt line ourselves
+    writeln!(stderr)?;
+    utf8_secret(&buffer, "password")
+}
+
+/// Converts raw bytes into a `Secret`, failing on invalid UTF-8
+fn utf8_secret(bytes: &[u8], source: &str) -> io::Result<Secret> {
+    std::str::from_utf8(bytes)
+        .map(|s| Zeroizing::new(s.to_string()))
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("{} is not valid UTF-8", source)))
+}
+
+/// Removes a single trailing `\n` (or `\r\n`), preserving all other whitespace
+fn strip_one_newline(mut secret: Secret) -> Secret {
+    if secret.ends_with('\n') {
+        secret.pop();
+        if secret.ends_with('\r') {
+            secret.pop();
+        }
+    }
+    secret
+}
+
+// ============================================================================
+// Platform Terminal Handling
+// ============================================================================
+
+#[cfg(unix)]
+mod terminal {
+    use std::io;
+    use std::mem::MaybeUninit;
+
+    /// Puts the terminal in raw mode (no echo, no line buffering, no signals) until dropped
+    ///
+    /// Disabling signal generation means Ctrl-C arrives as a byte instead of
+    /// killing the process, so the original mode can always be restored.
+    pub(super) struct RawModeGuard {
+        original: libc::termios,
+    }
+
+    impl RawModeGuard {
+        pub(super) fn enable() -> io::Result<Self> {
+            let mut original = MaybeUninit::<libc::termios>::uninit();
+            // SAFETY: tcgetattr fully initializes `original` when it returns 0
+            let original = unsafe {
+                if libc::tcgetattr(libc::STDIN_FILENO, original.as_mut_ptr()) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                original.assume_init()
+            };
+
+            let mut raw = original;
+            raw.c_lflag &= !(libc::ECHO | libc::ICANON | libc::ISIG);
+            raw.c_cc[libc::VMIN] = 1;
+            raw.c_cc[libc::VTIME] = 0;
+
+            // SAFETY: `raw` is a valid termios derived from the current settings
+            if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &raw) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(RawModeGuard { original })
+        }
+    }
+
+    impl Drop for RawModeGuard {
+        fn drop(&mut self) {
+            // SAFETY: restores the settings captured in `enable`
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &self.original);
+            }
+        }
+    }
+
+    /// Reads a single byte from stdin, bypassing std's buffer so no copy of the secret lingers there
+    pub(super) fn read_byte() -> io::Result<Option<u8>> {
+        let mut byte = 0u8;
+        loop {
+            // SAFETY: reads at most one byte into a valid, writable location
+            let n = unsafe { libc::read(libc::STDIN_FILENO, (&mut byte as *mut u8).cast(), 1) };
+            match n {
+                1 => return Ok(Some(byte)),
+                0 => return Ok(None),
+                _ => {
+                    let err = io::Error::last_os_error();
+                    if err.kind() != io::ErrorKind::Interrupted {
+                        return Err(err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+mod terminal {
+    use std::io::{self, Read};
+    use windows_sys::Win32::Foundation::HANDLE;
+    use windows_sys::Win32::System::Console::{
+        CONSOLE_MODE, ENABLE_ECHO_INPUT, ENABLE_LINE_INPUT, ENABLE_PROCESSED_INPUT, GetConsoleMode,
+        GetStdHandle, STD_INPUT_HANDLE, SetConsoleMode,
+    };
+
+    /// Disables console echo, line input and Ctrl-C processing until dropped
+    pub(super) struct RawModeGuard {
+        handle: HANDLE,
+        original: CONSOLE_MODE,
+    }
+
+    impl RawModeGuard {
+        pub(super) fn enable() -> io::Result<Self> {
+            // SAFETY: plain Win32 console calls on the process' own stdin handle
+            unsafe {
+                let handle = GetStdHandle(STD_INPUT_HANDLE);
+                let mut original: CONSOLE_MODE = 0;
+                if GetConsoleMode(handle, &mut original) == 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                let raw = original & !(ENABLE_ECHO_INPUT | ENABLE_LINE_INPUT | ENABLE_PROCESSED_INPUT);
+                if SetConsoleMode(handle, raw) == 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(RawModeGuard { handle, original })
+            }
+        }
+    }
+
+    impl Drop for RawModeGuard {
+        fn drop(&mut self) {
+            // SAFETY: restores the mode captured in `enable`
+            unsafe {
+                SetConsoleMode(self.handle, self.original);
+            }
+        }
+    }
+
+    /// Reads a single byte from the console
+    pub(super) fn read_byte() -> io::Result<Option<u8>> {
+        let mut byte = [0u8; 1];
+        match io::stdin().read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+//! # Rusty Password Utility
+//!
+//! The password generation and analysis core of the `rusty_password_utility`
+//! command-line program, usable on its own.
+//!
+//! ## Cargo Features
+//! - `cli` (default) - The command-line program and everything terminal- or OS-specific
+//! - `clipboard` (default) - System clipboard access for `generate --copy`
+//! - `network` - Have I Been Pwned breach checks for `analyze --file --hibp` and `audit --hibp`
+//! - `qr`, `wizard`, `keyring` - Optional parts of the command-line program
+//! - `wasm` - `generate` and `analyze` exported to JavaScript through wasm-bindgen
+//! - `ffi` - `rustypass_generate` and `rustypass_analyze` exported to C (`include/rustypass.h`)
+//!
+//! Without default features only this library is built, and it compiles for
+//! `wasm32-unknown-unknown`, where randomness comes from the JavaScript
+//! crypto API through `getrandom`:
+//!
+//! ```bash
+//! cargo build --target wasm32-unknown-unknown --no-default-features
+//! ```
+//!
+//! ## Usage
+//! ```ignore
+//! use rusty_password_utility::generator::{os_rng, GeneratorOptions, PasswordGenerator};
+//!
+//! let options = GeneratorOptions { length: 24, include_digits: true, ..GeneratorOptions::default() };
+//! let password = PasswordGenerator::new(&options, os_rng()?)?.next_password()?;
+//! ```
+
+pub mod analyzer;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod generator;
+pub mod i18n;
+pub mod policy;
+pub mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -17,14 +17,32 @@
 //! cargo run -- -l 24 -u -n -s
 //! ```
 
-use clap::Parser;
-use colored::Colorize;
-use figlet_rs::FIGfont;
+use std::io::{IsTerminal, Write};
+use rusty_password_utility::{analyzer, error, generator, i18n, policy, utils};
 
-mod generator;
-mod utils;
+mod hash;
+mod history;
+mod htpasswd;
+mod chpasswd;
 mod cli;
-mod analyzer;
+mod clipboard;
+mod config;
+mod credentials;
+mod doctor;
+mod dotenv;
+mod export;
+mod banner;
+mod audit;
+mod bench;
+#[cfg(feature = "network")]
+mod hibp;
+mod input;
+mod output;
+mod qr;
+#[cfg(feature = "wizard")]
+mod wizard;
+mod report;
+mod template;
 
 // ============================================================================
 // Main Entry Point
@@ -33,30 +51,119 @@ mod analyzer;
 /// Main entry point for the password generator application
 ///
 /// # Workflow
-/// 1. Display the application logo
-/// 2. Parse command-line arguments
+/// 1. Parse command-line arguments
+/// 2. Display the application logo (unless `--no-logo` or `--quiet`)
 /// 3. Validate and set password length
 /// 4. Create character set based on user preferences
 /// 5. Generate the random password
 /// 6. Calculate estimated time to crack
 /// 7. Display results to the user
 fn main() {
+    // Parse command-line arguments first so the logo flags can take effect
+    let (mut cli, bare, matches) = cli::Cli::parse_bare(std::env::args_os());
+    init_logging(cli.verbose);
+    // RUSTYPASS_* variables sit between the configuration file and the command line
+    let env = config::EnvConfig::from_env().unwrap_or_else(|e| output::Context::new(&cli).fail(&e));
+    if cli.config.is_none() {
+        cli.config = env.config.clone();
+    }
+    let config_result = match cli.command {
+        // `config init` must work even while the existing file is broken
+        Some(cli::Commands::Config(cli::ConfigArgs { action: cli::ConfigAction::Init(_) })) => {
+            Ok(config::LoadedConfig::default())
+        }
+        _ => config::load(cli.config.as_deref()),
+    };
+    let loaded = match &config_result {
+        Ok(loaded) => loaded.clone(),
+        // The doctor reports a broken file as a failed check instead
+        Err(_) if matches!(cli.command, Some(cli::Commands::Doctor(_))) => config::LoadedConfig::default(),
+        Err(e) => output::Context::new(&cli).fail(e),
+    };
+    log::debug!("configuration file: {:?} (found: {})", loaded.path, loaded.found);
+    load_options_document(&mut cli);
+    // The configuration file and environment fill in whatever the command line left at its default
+    let mut settings = loaded.config.apply(&env, &mut cli, &matches).unwrap_or_else(|e| output::Context::new(&cli).fail(&e));
+    // Messages are translated from here on
+    i18n::set_locale(i18n::detect_locale(cli.lang, loaded.config.lang));
+    for key in &loaded.unknown_keys {
+        let path = loaded.path.as_deref().unwrap_or(std::path::Path::new("")).display();
+        output::warn(&i18n::trf(i18n::Msg::UnknownConfigKey, &[key, &path]));
+    }
+    if bare {
+        match loaded.config.default_command.unwrap_or_default() {
+            // A bare invocation on a terminal may start the wizard instead
+            config::DefaultCommand::Wizard => {
+                if let Some(argv) = prompt_for_command().unwrap_or_else(|e| output::Context::new(&cli).fail(&e)) {
+                    let (wizard_cli, _, wizard_matches) = cli::Cli::parse_bare(argv);
+                    cli = wizard_cli;
+                    load_options_document(&mut cli);
+                    settings = loaded
+                        .config
+                        .apply(&env, &mut cli, &wizard_matches)
+                        .unwrap_or_else(|e| output::Context::new(&cli).fail(&e));
+                }
+            }
+            config::DefaultCommand::Generate => {}
+            config::DefaultCommand::Help => {
+                let _ = <cli::Cli as clap::CommandFactory>::command().write_help(&mut std::io::stderr());
+                std::process::exit(cli::EXIT_USAGE);
+            }
+        }
+    }
+    // Without a subcommand, generate with the options given at the top level
+    if cli.command.is_none() {
+        cli.command = Some(cli::Commands::Generate(Box::new(cli.generate.clone())));
+    }
+    // Logo, colors and labels only make sense on an interactive terminal
+    let ctx = output::Context::new(&cli);
+    log::debug!("command: {}, output: {:?}, context: {:?}", command_name(&cli), cli.output_format(), ctx);
+    // Every colorize call, the logo included, follows the resolved color mode
+    colored::control::set_override(ctx.color);
     // Display the ASCII art logo
-    print_logo();
-    // Parse command-line arguments
-    let cli = cli::Cli::parse();
+    if ctx.logo {
+        banner::print_banner(cli.banner_font, &cli.banner_text);
+    }
+    if ctx.verbose {
+        output::info(&config::render_settings(&loaded, &settings));
+    }
 
-    match &cli.command {
-        cli::Commands::Generate(args) => {
-            // Generate the random password
-            let password = generator::compute_password(args.length, args.uppercase_chars, args.special_chars, args.numbers);
-            // Display the generated password and security information
-            println!("Generated Password: {}", password);
-        },
+    let Some(command) = &cli.command else { unreachable!("a subcommand is resolved above") };
+    match command {
+        cli::Commands::Generate(args) => run_generate(args, &ctx),
         cli::Commands::Analyze(args) => {
-            let strength = analyzer::analyze_password(&args.password);
-            println!("Password Strength Analysis:\n{}", strength);
+            let options = analyzer::AnalyzerOptions { grade_thresholds: args.grade_thresholds, ..Default::default() };
+            match &args.file {
+                Some(path) => analyze_file(path, args, &options, &ctx),
+                // clap waives `requires = "file"` as soon as an argument conflicting with --file is given
+                None if args.hibp.hibp => ctx.fail(i18n::tr(i18n::Msg::HibpNeedsFile)),
+                None if args.stream => analyze_stream(args, &options, &ctx),
+                None => {
+                    let password = read_password(args.password.as_deref(), &args.secret, &ctx);
+                    analyze_single(&password, args, &options, &ctx);
+                }
+            }
+        }
+        cli::Commands::Audit(args) => run_audit(args, &ctx),
+        cli::Commands::Check(args) => run_check(args, &ctx),
+        cli::Commands::Entropy(args) => run_entropy(args, &ctx),
+        cli::Commands::Bench(args) => run_bench(args, &ctx),
+        cli::Commands::Hash(args) => run_hash(args, &ctx),
+        cli::Commands::Verify(args) => run_verify(args, &ctx),
+        cli::Commands::Doctor(args) => run_doctor(args, cli.lang, &config_result, &ctx),
+        cli::Commands::Config(args) => run_config(args, cli.config.as_deref(), &loaded, &ctx),
+        cli::Commands::History(args) => run_history(args, &ctx),
+        cli::Commands::Retrieve(args) => {
+            let password = credentials::retrieve(&args.name).unwrap_or_else(|e| ctx.fail(&e));
+            ctx.print_labeled(i18n::tr(i18n::Msg::StoredPasswordLabel), &password);
         }
+        cli::Commands::Delete(args) => {
+            credentials::delete(&args.name).unwrap_or_else(|e| ctx.fail(&e));
+            if !ctx.quiet {
+                output::info(&i18n::trf(i18n::Msg::DeletedFromKeyring, &[&credentials::service_name(&args.name)]));
+            }
+        }
+        cli::Commands::ClipboardHolder(args) => clipboard::run_holder(std::time::Duration::from_secs(args.clear_after)),
     }
 }
 
@@ -64,38 +171,1049 @@ fn main() {
 // Helper Functions
 // ============================================================================
 
-/// Prints the application logo using ASCII art
+/// Generates the requested passwords and prints them in the requested format
+fn run_generate(args: &cli::GenerateArgs, ctx: &output::Context) {
+    let policy = load_policy(args.policy, args.policy_file.as_deref(), ctx);
+    // Parse the template before generating anything, so a typo fails fast
+    let template = args.format.as_deref().map(|source| {
+        template::Template::parse(source).unwrap_or_else(|e| ctx.fail(&i18n::trf(i18n::Msg::InvalidTemplate, &[&e])))
+    });
+    // One password per --env-key name, checked before anything is generated
+    let env_keys = (!args.env_key.is_empty()).then(|| {
+        let keys = dotenv::expand_keys(&args.env_key, args.count).unwrap_or_else(|e| ctx.fail(&e));
+        for key in &keys {
+            if !dotenv::check_key(key).unwrap_or_else(|e| ctx.fail(&e)) {
+                output::warn(&i18n::trf(i18n::Msg::EnvKeyConvention, &[key]));
+            }
+        }
+        keys
+    });
+    let users = args.users_file.as_deref().map(|path| chpasswd::read_users(path).unwrap_or_else(|e| ctx.fail(&e)));
+    let count = match (&env_keys, &users) {
+        (Some(keys), _) => keys.len() as u32,
+        (_, Some(users)) => users.len() as u32,
+        _ => args.count,
+    };
+    let options = generator::GeneratorOptions::from(args);
+    // --seed is the one way to a predictable RNG, and it says so every time
+    let rng: Box<dyn rand::CryptoRng + Send> = match args.seed {
+        Some(seed) => {
+            output::warn(i18n::tr(i18n::Msg::SeededPasswords));
+            Box::new(generator::seeded_rng(seed))
+        }
+        None => Box::new(generator::os_rng().unwrap_or_else(|e| ctx.fail_with(&e))),
+    };
+    // Honor the policy when one is given
+    let mut stream = match &policy {
+        Some(policy) => generator::PasswordGenerator::with_policy(&options, policy, rng),
+        None => generator::PasswordGenerator::new(&options, rng),
+    }
+    .unwrap_or_else(|e| ctx.fail_with(&e));
+
+    let started = std::time::Instant::now();
+    let progress = ctx.progress_bar(count as u64, "Generating");
+    let mut passwords: Vec<_> = stream.by_ref().take(count as usize).inspect(|_| progress.inc(1)).collect();
+    // Clear the bar before anything is printed, so it never mixes with the results
+    progress.finish_and_clear();
+    if let Some(e) = stream.failure() {
+        ctx.fail_with(e);
+    }
+
+    let metadata = ctx.verbose.then(|| {
+        let policy = policy.as_ref().map(|p| p.name.clone());
+        let metadata = generator::GenerationMetadata::new(stream.settings(), policy, count, started.elapsed());
+        if args.seed.is_some() { metadata.seeded() } else { metadata }
+    });
+    // JSON carries the metadata itself; every other mode explains it on stderr
+    let json = !args.raw && args.output == cli::OutputFormat::Json;
+    if let Some(metadata) = metadata.as_ref().filter(|_| !json) {
+        output::info(&generator::render_metadata(metadata));
+    }
+
+    if args.qr && passwords.len() > 1 {
+        ctx.fail(&i18n::trf(i18n::Msg::SinglePasswordOnly, &[&"--qr"]));
+    }
+
+    // Saved before anything is shown or copied, so a failed save never leaves an unrecorded password in use
+    if args.save {
+        let label = args.label.as_deref().unwrap_or_default();
+        let path = history_path(ctx);
+        let passphrase = input::prompt_passphrase(!history::exists(&path)).unwrap_or_else(|e| ctx.fail(&e.to_string()));
+        if let Err(e) = history::append(&path, &passphrase, label, &passwords) {
+            ctx.fail(&e);
+        }
+        output::info(&i18n::trf(i18n::Msg::SavedToHistory, &[&passwords.len(), &label]));
+    }
+
+    if args.qr {
+        // The QR code goes to stderr so stdout stays the bare password
+        match qr::render_qr(&passwords[0].password, args.qr_invert) {
+            Ok(code) => output::info(&code),
+            Err(e) => ctx.fail(&e),
+        }
+    }
+
+    if let Some(keys) = &env_keys {
+        let entries: Vec<_> = keys.iter().zip(&passwords).map(|(key, generated)| (key.clone(), dotenv::line(key, &generated.password))).collect();
+        match &args.append_env {
+            Some(path) => {
+                let summary = dotenv::update_file(path, &entries).unwrap_or_else(|e| ctx.fail(&e));
+                if !ctx.quiet {
+                    let notice = i18n::trf(i18n::Msg::EnvFileUpdated, &[&entries.len(), &path.display(), &summary.added, &summary.replaced]);
+                    output::info(&notice);
+                }
+            }
+            None => entries.iter().for_each(|(_, line)| output::out(line)),
+        }
+        return;
+    }
+
+    if let Some(format) = args.export {
+        let title_prefix = args.title_prefix.as_deref().unwrap_or(export::DEFAULT_TITLE_PREFIX);
+        let entries = export::entries(&passwords, users.as_deref(), title_prefix, args.url.as_deref().unwrap_or_default());
+        let policy = policy.as_ref().map(|p| p.name.clone());
+        let settings = generator::GenerationMetadata::new(stream.settings(), policy, count, started.elapsed());
+        let timestamp = humantime::format_rfc3339_seconds(std::time::SystemTime::now()).to_string();
+        let contents = export::render(&entries, format, &export::notes(&settings, &timestamp)).unwrap_or_else(|e| ctx.fail(&e));
+        match &args.output_file {
+            Some(path) => {
+                write_output_file(path, contents.as_bytes(), args.force, ctx);
+                output::info(&i18n::trf(i18n::Msg::WroteFile, &[&entries.len(), &path.display()]));
+                output::warn(&i18n::trf(i18n::Msg::PlaintextExport, &[&path.display()]));
+            }
+            None => output::out_raw(contents.as_str()),
+        }
+        return;
+    }
+
+    // The records go to --output-file while stdout carries what chpasswd reads
+    if let Some(users) = &users {
+        let credentials = chpasswd::credentials(users, &passwords, args.hash_alg).unwrap_or_else(|e| ctx.fail(&e));
+        let records = || chpasswd::render_records(&credentials, args.output, !args.no_header).unwrap_or_else(|e| ctx.fail(&e));
+        match &args.output_file {
+            Some(path) => {
+                write_output_file(path, records().as_bytes(), args.force, ctx);
+                output::info(&i18n::trf(i18n::Msg::WroteFile, &[&credentials.len(), &path.display()]));
+                output::out_raw(chpasswd::render_lines(&credentials).as_str());
+            }
+            None if args.output == cli::OutputFormat::Text => output::out_raw(chpasswd::render_lines(&credentials).as_str()),
+            None => output::out_raw(records().as_str()),
+        }
+        return;
+    }
+
+    if let Some(username) = &args.htpasswd {
+        if passwords.len() > 1 {
+            ctx.fail(&i18n::trf(i18n::Msg::SinglePasswordOnly, &[&"--htpasswd"]));
+        }
+        let scheme = if args.apr1 {
+            htpasswd::HtpasswdScheme::Apr1
+        } else {
+            htpasswd::HtpasswdScheme::Bcrypt { cost: hash::DEFAULT_BCRYPT_COST }
+        };
+        let staged = zeroize::Zeroizing::new(std::mem::take(&mut passwords[0].password));
+        return print_htpasswd(username, &staged, true, scheme, args.append.as_deref(), ctx);
+    }
+
+    // Handed to the credential store instead of stdout; --quiet also drops the notice
+    if let Some(name) = &args.store {
+        if passwords.len() > 1 {
+            ctx.fail(&i18n::trf(i18n::Msg::SinglePasswordOnly, &[&"--store"]));
+        }
+        let staged = zeroize::Zeroizing::new(std::mem::take(&mut passwords[0].password));
+        credentials::store(name, &staged).unwrap_or_else(|e| ctx.fail(&e));
+        if args.show {
+            ctx.print_labeled(i18n::tr(i18n::Msg::GeneratedPasswordLabel), &staged);
+        }
+        if !ctx.quiet {
+            output::info(&i18n::trf(i18n::Msg::StoredInKeyring, &[&credentials::service_name(name)]));
+        }
+        return;
+    }
+
+    if args.copy || args.copy_osc52 {
+        if passwords.len() > 1 {
+            ctx.fail(&i18n::trf(i18n::Msg::SinglePasswordOnly, &[&"--copy"]));
+        }
+        // Move the password into a buffer that is wiped once it has been handed over
+        let staged = zeroize::Zeroizing::new(std::mem::take(&mut passwords[0].password));
+        let clear_after = std::time::Duration::from_secs(args.clear_clipboard);
+        let copied = if args.copy_osc52 {
+            clipboard::copy_osc52(&staged).map(|_| i18n::tr(i18n::Msg::CopiedViaTerminal).to_string())
+        } else {
+            // Without a local clipboard (e.g. over SSH), let the terminal take it instead
+            match clipboard::copy_with_clear(&staged, clear_after) {
+                Ok(()) => Ok(i18n::trf(i18n::Msg::CopiedToClipboard, &[&args.clear_clipboard])),
+                Err(e) => match clipboard::copy_osc52(&staged) {
+                    Ok(()) => {
+                        output::warn(&i18n::trf(i18n::Msg::ClipboardFallback, &[&e]));
+                        Ok(i18n::tr(i18n::Msg::CopiedViaTerminal).to_string())
+                    }
+                    Err(_) => Err(e),
+                },
+            }
+        };
+        let notice = copied.unwrap_or_else(|e| ctx.fail(&e));
+        if args.show {
+            ctx.print_labeled(i18n::tr(i18n::Msg::GeneratedPasswordLabel), &staged);
+        }
+        output::info(&notice);
+        return;
+    }
+
+    // Analyzed in memory, so the password never passes through argv or a file
+    let analyses: Option<Vec<analyzer::AnalysisReport>> = args.analyze.then(|| {
+        let options = analyzer::AnalyzerOptions::default();
+        passwords.iter().map(|generated| analyzer::analyze_password(&generated.password, &options)).collect()
+    });
+    let formatted = template.map(|template| zeroize::Zeroizing::new(render_template(&template, &passwords, analyses.as_deref())));
+
+    if let Some(path) = &args.output_file {
+        let contents = zeroize::Zeroizing::new(match args.output {
+            _ if let Some(formatted) = &formatted => formatted.to_string(),
+            _ if args.raw => join_raw(&passwords, args.print0),
+            cli::OutputFormat::Json => format!("{}\n", generated_json(&passwords, None, metadata.as_ref())),
+            cli::OutputFormat::Csv => {
+                let mut csv = zeroize::Zeroizing::new(Vec::new());
+                if let Err(e) = generator::write_generated_csv(&passwords, None, !args.no_header, &mut *csv) {
+                    ctx.fail(&i18n::trf(i18n::Msg::CsvWriteFailed, &[&e]));
+                }
+                String::from_utf8_lossy(&csv).into_owned()
+            }
+            _ => format!("{}\n", join_raw(&passwords, false)),
+        });
+        write_output_file(path, contents.as_bytes(), args.force, ctx);
+        output::info(&i18n::trf(i18n::Msg::WroteFile, &[&passwords.len(), &path.display()]));
+        return;
+    }
+
+    if args.raw {
+        output::out_raw(join_raw(&passwords, args.print0).as_str());
+        return;
+    }
+
+    if let Some(formatted) = &formatted {
+        output::out_raw(formatted.as_str());
+        return;
+    }
+
+    // Masking is for human eyes only: piped and quiet output always carry the real value
+    if args.mask && ctx.labels {
+        return show_masked(&passwords, args.reveal);
+    }
+
+    match args.output {
+        cli::OutputFormat::Text => {
+            // Bare when piped or quiet so scripts can capture it; colored by class on a terminal
+            let colorize = ctx.labels && ctx.color;
+            for (index, generated) in passwords.iter().enumerate() {
+                if colorize {
+                    ctx.print_labeled(i18n::tr(i18n::Msg::GeneratedPasswordLabel), &output::colorize_password(&generated.password));
+                } else {
+                    ctx.print_labeled(i18n::tr(i18n::Msg::GeneratedPasswordLabel), &generated.password);
+                }
+                if let Some(report) = analyses.as_ref().map(|analyses| &analyses[index]) {
+                    let rendered = analyzer::render_report(report, analyzer::DEFAULT_METER_WIDTH, ctx.color);
+                    ctx.print_labeled(i18n::tr(i18n::Msg::AnalysisLabel), &rendered);
+                }
+            }
+            if colorize {
+                output::info(&output::password_legend());
+            }
+        }
+        cli::OutputFormat::Json => output::out(&generated_json(&passwords, analyses.as_deref(), metadata.as_ref())),
+        cli::OutputFormat::Csv => {
+            if let Err(e) = generator::write_generated_csv(&passwords, analyses.as_deref(), !args.no_header, std::io::stdout()) {
+                output::csv_stdout_failed(e);
+            }
+        }
+    }
+
+    // The spell-out is commentary for a human reader, so it goes to stderr
+    if args.spell && !ctx.quiet {
+        for (index, generated) in passwords.iter().enumerate() {
+            if passwords.len() > 1 {
+                output::info(&i18n::trf(i18n::Msg::SpellHeading, &[&(index + 1)]));
+            }
+            for c in generated.password.chars() {
+                output::info(&format!("  {}", utils::spell_char(c)));
+            }
+        }
+    }
+}
+
+/// Prints masked passwords with their length and strength, then offers to reveal them
 ///
-/// Displays "Rusty Password Generator" in green using FIGfont.
-/// Falls back to simple text if FIGfont conversion fails.
-fn print_logo() {
-    // Load the standard FIGfont
-    let standard_font = FIGfont::standard().expect("Failed to read standard font");
+/// A revealed password is erased from the screen after `utils::REVEAL_SECONDS`.
+fn show_masked(passwords: &[generator::GeneratedPassword], reveal: bool) {
+    for generated in passwords {
+        let report = analyzer::analyze_password(&generated.password, &analyzer::AnalyzerOptions::default());
+        let summary = i18n::trf(
+            i18n::Msg::MaskedSummary,
+            &[&generated.length, &report.grade, &format!("{:.1}", report.effective_entropy_bits)],
+        );
+        output::out(&i18n::trf(i18n::Msg::MaskedPassword, &[&utils::mask_password(&generated.password), &summary]));
+    }
+
+    let reveal = reveal
+        || (std::io::stdin().is_terminal()
+            && matches!(input::read_key(i18n::tr(i18n::Msg::RevealPrompt)), Ok(Some(b'r' | b'R'))));
+    if reveal {
+        let lines: Vec<&str> = passwords.iter().map(|g| g.password.as_str()).collect();
+        output::reveal_temporarily(&lines, std::time::Duration::from_secs(utils::REVEAL_SECONDS));
+    }
+}
 
-    // Convert "Rusty" to ASCII art
-    let figure = standard_font.convert("Rusty");
+/// Joins raw passwords with newlines (or NUL), with no separator after the last one
+fn join_raw(passwords: &[generator::GeneratedPassword], print0: bool) -> String {
+    let separator = if print0 { "\0" } else { "\n" };
+    let raw: Vec<&str> = passwords.iter().map(|g| g.password.as_str()).collect();
+    raw.join(separator)
+}
+
+/// Renders every password through a `--format` template, one record per line
+///
+/// `{grade}` is graded like `analyze` grades: on the effective entropy after
+/// pattern penalties. The `--analyze` reports are reused when there are any.
+fn render_template(
+    template: &template::Template,
+    passwords: &[generator::GeneratedPassword],
+    analyses: Option<&[analyzer::AnalysisReport]>,
+) -> String {
+    let options = analyzer::AnalyzerOptions::default();
+    let timestamp = humantime::format_rfc3339_seconds(std::time::SystemTime::now()).to_string();
+    let humanize = template.uses_crack_time();
+    let grades = template.uses_grade();
 
-    // Convert "Password Generator" to ASCII art
-    let figure1 = standard_font.convert("Password Utility");
+    let mut rendered = String::new();
+    for (index, generated) in passwords.iter().enumerate() {
+        let crack_time = if humanize { analyzer::format_crack_time(generated.crack_time_seconds) } else { String::new() };
+        let grade = match analyses.and_then(|analyses| analyses.get(index)) {
+            _ if !grades => None,
+            Some(report) => Some(report.grade),
+            None => Some(analyzer::analyze_password(&generated.password, &options).grade),
+        };
+        let values = template::TemplateValues {
+            password: &generated.password,
+            length: generated.length,
+            entropy_bits: generated.entropy_bits,
+            crack_time: &crack_time,
+            crack_time_seconds: generated.crack_time_seconds,
+            grade,
+            timestamp: &timestamp,
+            index: index + 1,
+        };
+        template.render_into(&values, &mut rendered);
+        rendered.push('\n');
+    }
+    rendered
+}
 
-    // Display "Rusty" (with fallback)
-    match figure {
-        Some(f) => {
-            println!("{}", f.to_string().green());
+/// Serializes generated passwords: a single object for one password, an array otherwise
+///
+/// With `--verbose` the result is wrapped as `{"metadata": ..., "passwords": [...]}`;
+/// with `--analyze` every password carries its report under `analysis`.
+fn generated_json(
+    passwords: &[generator::GeneratedPassword],
+    analyses: Option<&[analyzer::AnalysisReport]>,
+    metadata: Option<&generator::GenerationMetadata>,
+) -> String {
+    match analyses {
+        Some(analyses) => {
+            let analyzed: Vec<generator::AnalyzedPassword> = passwords
+                .iter()
+                .zip(analyses)
+                .map(|(generated, analysis)| generator::AnalyzedPassword { generated: generated.with_secret(), analysis })
+                .collect();
+            passwords_json(&analyzed, metadata)
         }
         None => {
-            println!("[+] Rusty [+]");
+            let revealed: Vec<generator::WithSecret> = passwords.iter().map(generator::GeneratedPassword::with_secret).collect();
+            passwords_json(&revealed, metadata)
         }
     }
+}
 
-    // Display "Password Generator" (with fallback)
-    match figure1 {
-        Some(f) => {
-            println!("{}", f.to_string().green());
+/// Serializes one entry as an object and several as an array, wrapped with the metadata if any
+fn passwords_json<T: serde::Serialize>(entries: &[T], metadata: Option<&generator::GenerationMetadata>) -> String {
+    let json = match metadata {
+        Some(metadata) => serde_json::to_string_pretty(&serde_json::json!({ "metadata": metadata, "passwords": entries })),
+        None if entries.len() == 1 => serde_json::to_string_pretty(&entries[0]),
+        None => serde_json::to_string_pretty(entries),
+    };
+    json.expect("Failed to serialize passwords")
+}
+
+/// Writes `generate --output-file` or `--report-file`, refusing to replace an existing file without `--force`
+fn write_output_file(path: &std::path::Path, contents: &[u8], force: bool, ctx: &output::Context) {
+    match output::write_private_file(path, contents, force) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            ctx.fail(&i18n::trf(i18n::Msg::FileExists, &[&path.display()]));
         }
-        None => {
-            println!("[>] Password Generator [<]");
+        Err(e) => ctx.fail(&i18n::trf(i18n::Msg::CannotWrite, &[&path.display(), &e])),
+    }
+}
+
+/// Loads `generate --options-file`, if given, so the configuration can layer it
+///
+/// Exits with status 3 when the document is invalid and 5 when it cannot be read.
+fn load_options_document(cli: &mut cli::Cli) {
+    let Some(path) = cli.generate_args_mut().and_then(|args| args.options_file.clone()) else {
+        return;
+    };
+    let document = generator::GeneratorOptions::from_file(&path).unwrap_or_else(|e| output::Context::new(cli).fail_with(&e));
+    log::debug!("options document {}: {:?}", path.display(), document);
+    if let Some(args) = cli.generate_args_mut() {
+        args.options_document = Some(document);
+    }
+}
+
+/// Resolves the policy selected by `--policy` or `--policy-file`, if any
+///
+/// Exits with status 3 when the policy file is invalid and 5 when it cannot be read.
+fn load_policy(preset: Option<policy::PolicyPreset>, file: Option<&std::path::Path>, ctx: &output::Context) -> Option<policy::Policy> {
+    if let Some(preset) = preset {
+        return Some(preset.policy());
+    }
+    let file = file?;
+    Some(policy::Policy::from_file(file).unwrap_or_else(|e| ctx.fail_with(&e)))
+}
+
+/// Checks a password against a policy and exits with 0 (compliant) or 2 (not compliant)
+fn run_check(args: &cli::CheckArgs, ctx: &output::Context) {
+    let Some(policy) = load_policy(args.policy, args.policy_file.as_deref(), ctx) else {
+        unreachable!("clap requires --policy or --policy-file");
+    };
+    let password = read_password(args.password.as_deref(), &args.secret, ctx);
+
+    let mut result = policy.evaluate(&password);
+    result.strength = Some(analyzer::analyze_password(&password, &analyzer::AnalyzerOptions::default()));
+
+    match args.output {
+        cli::OutputFormat::Json => {
+            output::out(&serde_json::to_string_pretty(&result).expect("Failed to serialize report"));
         }
+        _ => output::out(&policy::render_policy_result(&result)),
+    }
+
+    std::process::exit(if result.passed { 0 } else { 2 });
+}
+
+/// Prints the keyspace and crack-time table of a character set, passphrase or pattern
+fn run_entropy(args: &cli::EntropyArgs, ctx: &output::Context) {
+    let keyspace = match (&args.pattern, args.words) {
+        (Some(pattern), _) => analyzer::Keyspace::pattern(pattern).unwrap_or_else(|e| ctx.fail(&e)),
+        (None, Some(words)) => analyzer::Keyspace::passphrase(args.wordlist_name, words as usize),
+        (None, None) => {
+            let charset = generator::Charset::from_flags(args.uppercase_chars, args.special_chars, args.numbers);
+            analyzer::Keyspace::charset(&charset, args.length as usize)
+        }
+    };
+
+    match args.output {
+        cli::OutputFormat::Json => {
+            output::out(&serde_json::to_string_pretty(&keyspace).expect("Failed to serialize keyspace"));
+        }
+        _ => output::out(&analyzer::render_keyspace(&keyspace)),
+    }
+}
+
+/// Runs every benchmark case and prints the throughput table or JSON report
+fn run_bench(args: &cli::BenchArgs, ctx: &output::Context) {
+    // Checked before the cases run, which takes a while
+    if args.output == cli::OutputFormat::Csv {
+        ctx.fail(&i18n::trf(i18n::Msg::CsvNotAvailable, &[&"bench"]));
+    }
+    let cases = bench::default_cases();
+    let mut results = Vec::with_capacity(cases.len());
+    for case in &cases {
+        if ctx.progress {
+            output::info(&i18n::trf(i18n::Msg::BenchmarkRunning, &[&i18n::tr(case.label), &humantime::format_duration(args.duration)]));
+        }
+        results.push(bench::run_case(case, args.duration, args.threads).unwrap_or_else(|e| ctx.fail_with(&e)));
+    }
+    let report = bench::BenchReport {
+        version: env!("CARGO_PKG_VERSION"),
+        duration_seconds: args.duration.as_secs_f64(),
+        threads: args.threads,
+        cases: results,
+    };
+
+    match args.output {
+        cli::OutputFormat::Json => {
+            output::out(&serde_json::to_string_pretty(&report).expect("Failed to serialize benchmark"));
+        }
+        _ => output::out(&bench::render_report(&report)),
     }
 }
 
+/// Hashes a prompted, piped or freshly generated password and prints the hash
+///
+/// With `--generate` the password is printed first (unless `--quiet`), so it
+/// can be handed to its owner while the hash goes into the configuration.
+fn run_hash(args: &cli::HashArgs, ctx: &output::Context) {
+    // htpasswd lines are always bcrypt, unless --apr1 asks for the legacy scheme
+    let algorithm = if args.htpasswd.is_some() { hash::HashAlgorithm::Bcrypt } else { args.alg };
+    let params = hash::HashParams::resolve(algorithm, &args.cost_options()).unwrap_or_else(|e| ctx.fail(&e));
+    if let Some(username) = &args.htpasswd {
+        htpasswd::check_username(username).unwrap_or_else(|e| ctx.fail(&e));
+    }
+
+    let password = if args.generate {
+        let options = generator::GeneratorOptions::from(args);
+        let rng = generator::os_rng().unwrap_or_else(|e| ctx.fail_with(&e));
+        let password = generator::PasswordGenerator::new(&options, rng)
+            .and_then(|mut stream| stream.next_password())
+            .unwrap_or_else(|e| ctx.fail_with(&e));
+        zeroize::Zeroizing::new(password)
+    } else {
+        read_password(None, &args.secret, ctx)
+    };
+    if password.is_empty() {
+        ctx.fail(i18n::tr(i18n::Msg::NothingToHash));
+    }
+
+    if let Some(username) = &args.htpasswd {
+        let scheme = match params {
+            _ if args.apr1 => htpasswd::HtpasswdScheme::Apr1,
+            hash::HashParams::Bcrypt { cost } => htpasswd::HtpasswdScheme::Bcrypt { cost },
+            _ => unreachable!("--htpasswd hashes with bcrypt"),
+        };
+        return print_htpasswd(username, &password, args.generate, scheme, args.append.as_deref(), ctx);
+    }
+
+    let hash = hash::hash_password(&password, &params).unwrap_or_else(|e| ctx.fail(&e));
+    if args.generate && !ctx.quiet {
+        ctx.print_labeled(i18n::tr(i18n::Msg::GeneratedPasswordLabel), &password);
+    }
+    ctx.print_labeled(i18n::tr(i18n::Msg::PasswordHashLabel), &hash);
+}
+
+/// Prints an htpasswd line for a password, or writes it into the `--append` file
+///
+/// A generated password is printed as well: before the line (unless `--quiet`),
+/// or on its own when the line goes into the file, since it is then the only result.
+fn print_htpasswd(
+    username: &str,
+    password: &str,
+    generated: bool,
+    scheme: htpasswd::HtpasswdScheme,
+    append: Option<&std::path::Path>,
+    ctx: &output::Context,
+) {
+    let line = htpasswd::entry(username, password, scheme).unwrap_or_else(|e| ctx.fail(&e));
+    let Some(path) = append else {
+        if generated && !ctx.quiet {
+            ctx.print_labeled(i18n::tr(i18n::Msg::GeneratedPasswordLabel), password);
+        }
+        return ctx.print_labeled(i18n::tr(i18n::Msg::HtpasswdEntryLabel), &line);
+    };
+
+    let update = htpasswd::update_file(path, username, &line).unwrap_or_else(|e| ctx.fail(&e));
+    if generated {
+        ctx.print_labeled(i18n::tr(i18n::Msg::GeneratedPasswordLabel), password);
+    }
+    if !ctx.quiet {
+        let notice = match update {
+            htpasswd::Update::Added => i18n::Msg::HtpasswdAdded,
+            htpasswd::Update::Replaced => i18n::Msg::HtpasswdReplaced,
+        };
+        output::info(&i18n::trf(notice, &[&username, &path.display()]));
+    }
+}
+
+/// Checks a password against a hash and exits with 0 (match) or 2 (no match)
+///
+/// The hash is checked before the password is asked for, so a malformed one
+/// fails (status 1) without a pointless prompt.
+fn run_verify(args: &cli::VerifyArgs, ctx: &output::Context) {
+    let algorithm = hash::check_hash(&args.hash).unwrap_or_else(|e| ctx.fail(&e));
+    let password = read_password(None, &args.secret, ctx);
+
+    let matches = hash::verify_password(&password, &args.hash).unwrap_or_else(|e| ctx.fail(&e));
+    let verdict = if matches { i18n::Msg::HashMatches } else { i18n::Msg::HashMismatch };
+    output::out(&i18n::trf(verdict, &[&algorithm.name()]));
+
+    std::process::exit(if matches { 0 } else { 2 });
+}
+
+/// Reads a password from the positional argument or the configured secret source
+///
+/// See `input::resolve_secret` for the precedence between sources. Exits with
+/// status 130 when the prompt is interrupted with Ctrl-C.
+fn read_password(positional: Option<&str>, secret: &cli::SecretInputArgs, ctx: &output::Context) -> input::Secret {
+    let result = input::resolve_secret(positional, secret.stdin, secret.password_env.as_deref(), secret.confirm);
+
+    match result {
+        Ok(password) => password,
+        Err(e) if e.kind() == std::io::ErrorKind::Interrupted => std::process::exit(130),
+        Err(e) => {
+            ctx.fail(&i18n::trf(i18n::Msg::CannotReadPassword, &[&e]));
+        }
+    }
+}
+
+/// Analyzes a single password and prints the report in the requested format
+fn analyze_single(password: &str, args: &cli::AnalyzeArgs, options: &analyzer::AnalyzerOptions, ctx: &output::Context) {
+    if args.token {
+        return analyze_token(password, args, options, ctx);
+    }
+
+    let report = analyzer::analyze_password(password, options);
+    if ctx.verbose {
+        output::info(&analyzer::render_analysis_details(password, &report, options));
+    }
+
+    let below_min_bits = args.min_bits.filter(|min_bits| report.effective_entropy_bits < *min_bits);
+    // Gate mode: silent on success, one line on failure, and the exit code says which
+    if args.check {
+        if let Some(min_bits) = below_min_bits {
+            if !ctx.quiet {
+                output::info(&i18n::trf(i18n::Msg::BelowMinBits, &[&format!("{:.1}", report.effective_entropy_bits), &min_bits]));
+            }
+            std::process::exit(2);
+        }
+        return;
+    }
+
+    match (args.report, args.output) {
+        (Some(format), _) => write_report(&report::analysis_document(&report), format, args.report_file.as_deref(), args.force, ctx),
+        (None, cli::OutputFormat::Text) => {
+            ctx.print_labeled(i18n::tr(i18n::Msg::AnalysisLabel), &analyzer::render_report(&report, args.meter_width, ctx.color));
+        }
+        (None, cli::OutputFormat::Json) => {
+            output::out(&serde_json::to_string_pretty(&report).expect("Failed to serialize report"));
+        }
+        (None, cli::OutputFormat::Csv) => {
+            ctx.fail(i18n::tr(i18n::Msg::CsvNeedsFile));
+        }
+    }
+    // A report is still gated: --min-bits decides the exit code whatever the output
+    if below_min_bits.is_some() {
+        std::process::exit(2);
+    }
+}
+
+/// Analyzes an API key or token and prints the report in the requested format
+fn analyze_token(token: &str, args: &cli::AnalyzeArgs, options: &analyzer::AnalyzerOptions, ctx: &output::Context) {
+    let report = analyzer::analyze_token(token, options);
+
+    match args.output {
+        cli::OutputFormat::Text => {
+            ctx.print_labeled(i18n::tr(i18n::Msg::TokenAnalysisLabel), &analyzer::render_token_report(&report, args.meter_width, ctx.color));
+        }
+        cli::OutputFormat::Json => {
+            output::out(&serde_json::to_string_pretty(&report).expect("Failed to serialize report"));
+        }
+        cli::OutputFormat::Csv => {
+            ctx.fail(i18n::tr(i18n::Msg::CsvNeedsFile));
+        }
+    }
+}
+
+/// Analyzes every line of a password file and prints the results in the requested format
+fn analyze_file(path: &std::path::Path, args: &cli::AnalyzeArgs, options: &analyzer::AnalyzerOptions, ctx: &output::Context) {
+    let contents = match std::fs::read(path) {
+        Ok(contents) => contents,
+        Err(source) => {
+            ctx.fail_with(&error::RustyPassError::Io { path: path.to_path_buf(), source });
+        }
+    };
+    // Counted like `analyze_lines` does: a trailing newline does not start a new line
+    let lines = contents.strip_suffix(b"\n").unwrap_or(&contents).split(|b| *b == b'\n').count() as u64;
+    let progress = ctx.progress_bar(lines, "Analyzing");
+    let mut report = analyzer::analyze_lines(&contents, options, args.show_passwords, args.find_reuse, || progress.inc(1));
+    progress.finish_and_clear();
+    if let Some(lookup) = breach_lookup(&args.hibp, ctx) {
+        report.check_breaches(&contents, lookup);
+    }
+
+    if let Some(format) = args.report {
+        return write_report(&report::batch_document(&report), format, args.report_file.as_deref(), args.force, ctx);
+    }
+
+    match args.output {
+        cli::OutputFormat::Text => {
+            ctx.print_labeled(i18n::tr(i18n::Msg::BatchAnalysisLabel), &analyzer::render_batch(&report));
+        }
+        cli::OutputFormat::Json => {
+            output::out(&serde_json::to_string_pretty(&report).expect("Failed to serialize report"));
+        }
+        cli::OutputFormat::Csv => {
+            if let Err(e) = analyzer::write_batch_csv(&report, !args.no_header, std::io::stdout()) {
+                output::csv_stdout_failed(e);
+            }
+        }
+    }
+}
+
+/// Analyzes stdin line by line, writing each result as soon as it is scored
+fn analyze_stream(args: &cli::AnalyzeArgs, options: &analyzer::AnalyzerOptions, ctx: &output::Context) {
+    if args.summary_only && args.output == cli::OutputFormat::Csv {
+        ctx.fail(i18n::tr(i18n::Msg::SummaryOnlyFormats));
+    }
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        ctx.fail(i18n::tr(i18n::Msg::StreamNeedsPipe));
+    }
+    let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+    // Each row goes through its own in-memory CSV writer, so nothing waits in a second buffer
+    let csv_row = |write: &dyn Fn(&mut csv::Writer<Vec<u8>>) -> csv::Result<()>| -> std::io::Result<Vec<u8>> {
+        let mut csv = csv::Writer::from_writer(Vec::new());
+        write(&mut csv).map_err(std::io::Error::other)?;
+        csv.into_inner().map_err(|e| e.into_error())
+    };
+    if args.output == cli::OutputFormat::Csv && !args.no_header && !args.summary_only {
+        let header = csv_row(&|csv| analyzer::write_batch_csv_header(csv)).expect("writing CSV to memory cannot fail");
+        if let Err(e) = out.write_all(&header) {
+            output::stdout_failed(e);
+        }
+    }
+
+    let result = analyzer::analyze_stream(stdin.lock(), options, args.show_passwords, |entry| {
+        if args.summary_only {
+            return Ok(());
+        }
+        match args.output {
+            cli::OutputFormat::Csv => out.write_all(&csv_row(&|csv| analyzer::write_batch_csv_entry(csv, entry))?),
+            cli::OutputFormat::Json => {
+                serde_json::to_writer(&mut out, entry)?;
+                out.write_all(b"\n")
+            }
+            cli::OutputFormat::Text => writeln!(
+                out,
+                "{:>8}  {:>6}  {:>8.1}  {}  {}",
+                entry.line, entry.report.length, entry.report.effective_entropy_bits, entry.report.grade, entry.password
+            ),
+        }
+    });
+
+    let summary = match result {
+        Ok(summary) => summary,
+        // The reader went away (e.g. `| head`); stop like other filters do
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => std::process::exit(0),
+        Err(e) => ctx.fail(&i18n::trf(i18n::Msg::AnalyzeStdinFailed, &[&e])),
+    };
+    let written = match args.output {
+        cli::OutputFormat::Json if args.summary_only => {
+            writeln!(out, "{}", serde_json::to_string_pretty(&summary).expect("Failed to serialize summary"))
+        }
+        cli::OutputFormat::Json => {
+            // The last line of the JSON Lines output, told apart from the entries by its key
+            #[derive(serde::Serialize)]
+            struct Trailer<'a> {
+                summary: &'a analyzer::StreamSummary,
+            }
+            writeln!(out, "{}", serde_json::to_string(&Trailer { summary: &summary }).expect("Failed to serialize summary"))
+        }
+        // The summary would break the CSV, so it goes to stderr
+        cli::OutputFormat::Csv => {
+            if !ctx.quiet {
+                output::info(&analyzer::render_stream_summary(&summary));
+            }
+            Ok(())
+        }
+        cli::OutputFormat::Text if args.summary_only => write!(out, "{}", analyzer::render_stream_summary(&summary)),
+        cli::OutputFormat::Text => write!(out, "\n{}", analyzer::render_stream_summary(&summary)),
+    };
+    if let Err(e) = written.and_then(|()| out.flush()) {
+        output::stdout_failed(e);
+    }
+}
+
+/// Audits a credentials CSV and prints the prioritized findings
+fn run_audit(args: &cli::AuditArgs, ctx: &output::Context) {
+    let file = match std::fs::File::open(&args.file) {
+        Ok(file) => file,
+        Err(source) => {
+            ctx.fail_with(&error::RustyPassError::Io { path: args.file.clone(), source });
+        }
+    };
+    let columns = audit::ColumnOverrides {
+        password: args.password_col.clone(),
+        username: args.username_col.clone(),
+    };
+    let options = analyzer::AnalyzerOptions { grade_thresholds: args.grade_thresholds, ..Default::default() };
+
+    let lookup = breach_lookup(&args.hibp, ctx);
+    let report = match audit::audit_csv(file, &columns, &options, args.show_passwords, lookup.as_deref()) {
+        Ok(report) => report,
+        Err(e) => {
+            ctx.fail(&format!("{}: {}", args.file.display(), e));
+        }
+    };
+
+    if let Some(format) = args.report {
+        return write_report(&report::audit_document(&report), format, args.report_file.as_deref(), args.force, ctx);
+    }
+
+    match args.output {
+        cli::OutputFormat::Text => ctx.print_labeled(i18n::tr(i18n::Msg::AuditLabel), &audit::render_audit(&report)),
+        cli::OutputFormat::Json => {
+            output::out(&serde_json::to_string_pretty(&report).expect("Failed to serialize report"));
+        }
+        cli::OutputFormat::Csv => {
+            ctx.fail(&i18n::trf(i18n::Msg::CsvNotAvailable, &[&"audit"]));
+        }
+    }
+}
+
+/// Breach lookup for `--hibp`, or `None` when the flag is not given
+///
+/// Ranges are fetched one distinct hash prefix at a time and cached on disk
+/// for `--hibp-cache-ttl`. Lookups that fail leave their passwords `unknown`
+/// and are summed up in one warning instead of aborting the run.
+#[cfg(feature = "network")]
+fn breach_lookup<'a>(args: &'a cli::HibpArgs, ctx: &'a output::Context) -> Option<Box<audit::BreachLookup<'a>>> {
+    if !args.hibp {
+        return None;
+    }
+    let cache = match hibp::RangeCache::default_dir() {
+        Some(dir) if !args.hibp_cache_ttl.is_zero() => Some(hibp::RangeCache::new(dir, args.hibp_cache_ttl)),
+        _ => None,
+    };
+    Some(Box::new(move |passwords: &[&str]| {
+        let ranges = hibp::prefix_count(passwords);
+        let progress = ctx.progress_bar(ranges as u64, "Checking breaches");
+        let mut source = hibp::HttpRangeSource::new(&args.hibp_api);
+        let lookup = hibp::check_passwords(passwords, &mut source, cache.as_ref(), &hibp::Pacing::default(), || progress.inc(1));
+        progress.finish_and_clear();
+        if let Some(error) = &lookup.first_error {
+            output::warn(&i18n::trf(i18n::Msg::BreachCheckIncomplete, &[&lookup.failed_prefixes, &ranges, error]));
+        }
+        lookup.counts
+    }))
+}
+
+/// Without the `network` feature, `--hibp` is refused with a hint to rebuild
+#[cfg(not(feature = "network"))]
+fn breach_lookup<'a>(args: &'a cli::HibpArgs, ctx: &'a output::Context) -> Option<Box<audit::BreachLookup<'a>>> {
+    if args.hibp {
+        ctx.fail(i18n::tr(i18n::Msg::NetworkNotCompiled));
+    }
+    None
+}
+
+/// Renders a report document and writes it to `path`, or to stdout when no path is given
+///
+/// The file is private like `--output-file`, since a report made with
+/// `--show-passwords` holds the passwords in clear text, and an existing one
+/// is only replaced with `--force`.
+fn write_report(
+    document: &report::Document,
+    format: cli::ReportFormat,
+    path: Option<&std::path::Path>,
+    force: bool,
+    ctx: &output::Context,
+) {
+    let rendered = match format {
+        cli::ReportFormat::Md => report::render_markdown(document),
+        cli::ReportFormat::Html => report::render_html(document),
+    };
+
+    match path {
+        Some(path) => {
+            write_output_file(path, rendered.as_bytes(), force, ctx);
+            output::info(&i18n::trf(i18n::Msg::ReportWritten, &[&path.display()]));
+        }
+        None => output::out_raw(&rendered),
+    }
+}
+
+/// Probes the runtime environment and reports each check
+///
+/// Exits with status 1 when any check failed, so it can gate CI jobs.
+fn run_doctor(
+    args: &cli::DoctorArgs,
+    lang: Option<i18n::Locale>,
+    config: &Result<config::LoadedConfig, String>,
+    ctx: &output::Context,
+) {
+    let checks = doctor::run_checks(&doctor::SystemProbe, ctx.color, lang, config);
+
+    match args.output {
+        cli::OutputFormat::Text => output::out(doctor::render_checks(&checks).trim_end()),
+        cli::OutputFormat::Json => {
+            output::out(&serde_json::to_string_pretty(&checks).expect("Failed to serialize checks"));
+        }
+        cli::OutputFormat::Csv => ctx.fail(&i18n::trf(i18n::Msg::CsvNotAvailable, &[&"doctor"])),
+    }
+
+    if checks.iter().any(|c| c.status == doctor::Status::Fail) {
+        std::process::exit(1);
+    }
+}
+
+/// Runs a `config` action
+///
+/// `config init` writes the commented template to `--config PATH` or the
+/// platform default, creating the directory, and refuses to overwrite an
+/// existing file without `--force`. `config list-profiles` prints the
+/// profiles of the loaded file.
+fn run_config(args: &cli::ConfigArgs, path: Option<&std::path::Path>, loaded: &config::LoadedConfig, ctx: &output::Context) {
+    match &args.action {
+        cli::ConfigAction::ListProfiles if loaded.config.profile.is_empty() => {
+            let file = loaded.path.as_deref().map_or_else(|| String::from(i18n::tr(i18n::Msg::TheConfigurationFile)), |p| p.display().to_string());
+            output::info(&i18n::trf(i18n::Msg::NoProfiles, &[&file]));
+        }
+        cli::ConfigAction::ListProfiles => output::out(&config::render_profiles(&loaded.config)),
+        cli::ConfigAction::Init(init) => {
+            let Some(path) = path.map(std::path::Path::to_path_buf).or_else(config::default_path) else {
+                ctx.fail(i18n::tr(i18n::Msg::NoConfigDirectory));
+            };
+            if let Some(parent) = path.parent()
+                && let Err(e) = std::fs::create_dir_all(parent)
+            {
+                ctx.fail(&i18n::trf(i18n::Msg::CannotCreate, &[&parent.display(), &e]));
+            }
+            match output::write_private_file(&path, config::TEMPLATE.as_bytes(), init.force) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    ctx.fail(&i18n::trf(i18n::Msg::FileExists, &[&path.display()]));
+                }
+                Err(e) => ctx.fail(&i18n::trf(i18n::Msg::CannotWrite, &[&path.display(), &e])),
+            }
+            output::info(&i18n::trf(i18n::Msg::ConfigWritten, &[&path.display()]));
+        }
+    }
+}
+
+/// Runs a `history` action
+///
+/// `list` reads only the clear-text labels and dates; `show` asks for the
+/// master passphrase and prints the passwords saved under a label (just the
+/// passwords with `--quiet`); `purge` deletes the file and requires `--yes`.
+fn run_history(args: &cli::HistoryArgs, ctx: &output::Context) {
+    let path = history_path(ctx);
+    match &args.action {
+        cli::HistoryAction::List => {
+            let entries = history::list(&path).unwrap_or_else(|e| ctx.fail(&e));
+            if entries.is_empty() {
+                output::info(i18n::tr(i18n::Msg::HistoryEmpty));
+            }
+            for entry in &entries {
+                output::out(&format!("{}  {}", entry.created, entry.label));
+            }
+        }
+        cli::HistoryAction::Show(show) => {
+            if !history::exists(&path) {
+                ctx.fail(i18n::tr(i18n::Msg::HistoryEmpty));
+            }
+            let passphrase = input::prompt_passphrase(false).unwrap_or_else(|e| ctx.fail(&e.to_string()));
+            let found = history::show(&path, &passphrase, &show.label).unwrap_or_else(|e| ctx.fail(&e));
+            if found.is_empty() {
+                ctx.fail(&i18n::trf(i18n::Msg::NotInHistory, &[&show.label]));
+            }
+            for (entry, generated) in &found {
+                if ctx.quiet {
+                    output::out(&generated.password);
+                    continue;
+                }
+                let mut classes = vec![i18n::tr(i18n::Msg::ClassLowercase)];
+                for (enabled, class) in [
+                    (generated.flags.uppercase_chars, i18n::Msg::ClassUppercase),
+                    (generated.flags.numbers, i18n::Msg::ClassDigits),
+                    (generated.flags.special_chars, i18n::Msg::ClassSymbols),
+                ] {
+                    if enabled {
+                        classes.push(i18n::tr(class));
+                    }
+                }
+                let classes = classes.join(", ");
+                let settings = match &generated.flags.policy {
+                    Some(policy) => i18n::trf(i18n::Msg::HistorySettingsPolicy, &[&generated.length, &classes, policy]),
+                    None => i18n::trf(i18n::Msg::HistorySettings, &[&generated.length, &classes]),
+                };
+                output::out(&format!("{} ({})", entry.label, entry.created));
+                output::out(&format!("  {}", generated.password));
+                output::info(&format!("  {}", settings));
+            }
+        }
+        cli::HistoryAction::Purge(purge) => {
+            let count = history::list(&path).map(|entries| entries.len()).unwrap_or_else(|e| ctx.fail(&e));
+            if !purge.yes {
+                ctx.fail(&i18n::trf(i18n::Msg::PurgeNeedsYes, &[&count, &path.display()]));
+            }
+            let deleted = history::purge(&path).unwrap_or_else(|e| ctx.fail(&e));
+            output::info(&i18n::trf(i18n::Msg::HistoryPurged, &[&deleted]));
+        }
+    }
+}
+
+/// Location of the password history, failing when the platform has no data directory
+fn history_path(ctx: &output::Context) -> std::path::PathBuf {
+    history::default_path().unwrap_or_else(|| ctx.fail(i18n::tr(i18n::Msg::NoDataDirectory)))
+}
+
+/// Sets up diagnostic logging on stderr
+///
+/// `-v` enables debug and `-vv` trace records for this program; `RUSTYPASS_LOG`
+/// (same syntax as `RUST_LOG`, e.g. `trace` or `rusty_password_utility=debug`)
+/// overrides both. Records carry no timestamps and never contain secrets.
+fn init_logging(verbosity: u8) {
+    let level = match verbosity {
+        0 => log::LevelFilter::Off,
+        1 => log::LevelFilter::Debug,
+        _ => log::LevelFilter::Trace,
+    };
+    let mut builder = env_logger::Builder::new();
+    match std::env::var("RUSTYPASS_LOG") {
+        Ok(filters) if !filters.is_empty() => builder.parse_filters(&filters),
+        _ => builder.filter_module(env!("CARGO_CRATE_NAME"), level),
+    };
+    builder.format_timestamp(None).init();
+}
+
+/// Name of the selected subcommand, for logging
+///
+/// The parsed arguments themselves are never logged, since some of them
+/// (the positional `PASSWORD`) are secrets.
+fn command_name(cli: &cli::Cli) -> &'static str {
+    match &cli.command {
+        Some(cli::Commands::Generate(_)) => "generate",
+        Some(cli::Commands::Analyze(_)) => "analyze",
+        Some(cli::Commands::Audit(_)) => "audit",
+        Some(cli::Commands::Check(_)) => "check",
+        Some(cli::Commands::Entropy(_)) => "entropy",
+        Some(cli::Commands::Bench(_)) => "bench",
+        Some(cli::Commands::Hash(_)) => "hash",
+        Some(cli::Commands::Verify(_)) => "verify",
+        Some(cli::Commands::Doctor(_)) => "doctor",
+        Some(cli::Commands::Config(_)) => "config",
+        Some(cli::Commands::History(_)) => "history",
+        Some(cli::Commands::Retrieve(_)) => "retrieve",
+        Some(cli::Commands::Delete(_)) => "delete",
+        Some(cli::Commands::ClipboardHolder(_)) => "clipboard-holder",
+        None => "none",
+    }
+}
+
+/// Asks for a command when the program was run without a subcommand or `generate` options
+///
+/// On an interactive terminal (with the `wizard` feature) the user is walked
+/// through the options and the equivalent command line is printed, so it can
+/// be reused in scripts.
+///
+/// # Returns
+/// * `Ok(Some(argv))` - The full command line chosen in the wizard
+/// * `Ok(None)` - No wizard is available and the caller should fall back to `generate`
+/// * `Err(String)` - A prompt failed
+fn prompt_for_command() -> Result<Option<Vec<String>>, String> {
+    #[cfg(feature = "wizard")]
+    if std::io::stdin().is_terminal() && std::io::stderr().is_terminal() {
+        match wizard::run(&mut wizard::TerminalPrompter) {
+            Ok(Some(args)) => {
+                // Keep global flags such as --no-color given before the (missing) subcommand
+                let argv: Vec<String> = std::env::args().chain(args).collect();
+                output::info(&i18n::trf(i18n::Msg::EquivalentCommand, &[&argv.join(" ")]));
+                return Ok(Some(argv));
+            }
+            Ok(None) => std::process::exit(0),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => std::process::exit(130),
+            Err(e) => return Err(i18n::trf(i18n::Msg::WizardFailed, &[&e])),
+        }
+    }
+
+    Ok(None)
+}
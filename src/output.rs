@@ -0,0 +1,381 @@
+use std::fs::OpenOptions;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::time::Duration;
+use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
+use crate::cli::{Cli, ColorMode, OutputFormat};
+use crate::error::RustyPassError;
+use crate::i18n::{self, Msg};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Batch size from which `generate --count` and `analyze --file` show a progress bar
+pub(crate) const PROGRESS_THRESHOLD: u64 = 1000;
+
+// ============================================================================
+// Output Context
+// ============================================================================
+
+/// How results should be presented, resolved once at startup
+///
+/// Interactive runs get the logo, colors and human labels. When stdout is
+/// not a terminal (piped or redirected) all three are dropped so the output
+/// can be consumed by other programs, e.g. `generate | xclip`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Context {
+    /// Print the ASCII art logo
+    pub(crate) logo: bool,
+    /// Use ANSI colors
+    pub(crate) color: bool,
+    /// Print human labels such as `Generated Password:` around results
+    pub(crate) labels: bool,
+    /// Report errors as JSON objects, because the output is machine-readable
+    pub(crate) json_errors: bool,
+    /// Only the result itself may be printed (`--quiet`)
+    pub(crate) quiet: bool,
+    /// Explain results on stderr (`--verbose`)
+    pub(crate) verbose: bool,
+    /// Show progress bars on stderr for large batches
+    pub(crate) progress: bool,
+}
+
+impl Context {
+    /// Resolves the output context from the parsed flags and the terminal state
+    pub(crate) fn new(cli: &Cli) -> Self {
+        let stdout_is_terminal = std::io::stdout().is_terminal();
+        // JSON and CSV must not be mixed with decoration, even on a terminal
+        let human = stdout_is_terminal && !cli.machine_readable();
+
+        Context {
+            logo: human && !cli.no_logo && !cli.quiet,
+            color: resolve_color(cli, stdout_is_terminal),
+            labels: human && !cli.quiet,
+            json_errors: cli.output_format() == OutputFormat::Json,
+            quiet: cli.quiet,
+            verbose: cli.verbose > 0,
+            // A bar is noise in logs and could be mistaken for output next to JSON or CSV
+            progress: std::io::stderr().is_terminal() && !cli.quiet && !cli.machine_readable(),
+        }
+    }
+
+    /// Prints an error on stderr and exits with status 1
+    ///
+    /// In machine-readable mode the error is a JSON object `{"error": "..."}`
+    /// so callers parsing the output can handle it.
+    pub(crate) fn fail(&self, message: &str) -> ! {
+        self.exit_with(message, 1)
+    }
+
+    /// Prints a typed error on stderr and exits with its own status (see `RustyPassError::exit_code`)
+    pub(crate) fn fail_with(&self, error: &RustyPassError) -> ! {
+        log::debug!("{:?}", error);
+        self.exit_with(&error.to_string(), error.exit_code())
+    }
+
+    /// Prints an error, plain or as JSON, and exits with `code`
+    fn exit_with(&self, message: &str, code: i32) -> ! {
+        if self.json_errors {
+            eprintln!("{}", serde_json::json!({ "error": message }));
+        } else {
+            eprintln!("{} {}", i18n::tr(Msg::ErrorPrefix).red().bold(), message);
+        }
+        std::process::exit(code);
+    }
+
+    /// Creates a progress bar on stderr for a batch of `total` items
+    ///
+    /// Returns a hidden bar when progress is disabled or the batch is smaller
+    /// than `PROGRESS_THRESHOLD`, so callers can update it unconditionally.
+    /// The handle is cheap to clone and shared between clones.
+    pub(crate) fn progress_bar(&self, total: u64, message: &'static str) -> ProgressBar {
+        if !self.progress || total < PROGRESS_THRESHOLD {
+            return ProgressBar::hidden();
+        }
+
+        let style = ProgressStyle::with_template("{msg} [{bar:40.cyan/blue}] {pos}/{len} ({rate}, ETA {eta})")
+            .expect("progress template is valid")
+            .with_key("rate", |state: &indicatif::ProgressState, w: &mut dyn std::fmt::Write| {
+                let _ = write!(w, "{:.0}/s", state.per_sec());
+            })
+            .progress_chars("=> ");
+        ProgressBar::new(total).with_style(style).with_message(message)
+    }
+
+    /// Prints a result on stdout, preceded by its label (on stderr) on interactive terminals
+    ///
+    /// Single-line results stay on the same line as their label.
+    pub(crate) fn print_labeled(&self, label: &str, body: &str) {
+        if self.labels {
+            let separator = if body.contains('\n') { '\n' } else { ' ' };
+            eprint!("{}{}", label, separator);
+        }
+        out(body);
+    }
+}
+
+// ============================================================================
+// Stream Helpers
+// ============================================================================
+
+/// Writes a result to stdout
+///
+/// Stdout only ever carries results (passwords, reports, JSON), so it can be
+/// piped safely; everything else goes through `info` or `warn` to stderr.
+pub(crate) fn out(result: &str) {
+    let mut stdout = io::stdout().lock();
+    if let Err(e) = writeln!(stdout, "{}", result).and_then(|()| stdout.flush()) {
+        stdout_failed(e);
+    }
+}
+
+/// Writes a result to stdout exactly as given, without adding a newline
+pub(crate) fn out_raw(result: &str) {
+    let mut stdout = io::stdout().lock();
+    if let Err(e) = stdout.write_all(result.as_bytes()).and_then(|()| stdout.flush()) {
+        stdout_failed(e);
+    }
+}
+
+/// Ends the program after a failed write to stdout
+///
+/// A reader that went away (e.g. `| head`) is not an error: the program stops
+/// quietly with status 0, like other filters do. Anything else exits with 1.
+pub(crate) fn stdout_failed(error: io::Error) -> ! {
+    if error.kind() == io::ErrorKind::BrokenPipe {
+        std::process::exit(0);
+    }
+    eprintln!("{} {}", i18n::tr(Msg::ErrorPrefix).red().bold(), i18n::trf(Msg::CannotWriteStdout, &[&error]));
+    std::process::exit(1);
+}
+
+/// `stdout_failed` for the CSV writers, whose errors wrap the I/O error
+pub(crate) fn csv_stdout_failed(error: csv::Error) -> ! {
+    match error.into_kind() {
+        csv::ErrorKind::Io(e) => stdout_failed(e),
+        kind => stdout_failed(io::Error::other(format!("{:?}", kind))),
+    }
+}
+
+/// Writes an informational message to stderr
+pub(crate) fn info(message: &str) {
+    eprintln!("{}", message);
+}
+
+/// Writes a warning to stderr
+pub(crate) fn warn(message: &str) {
+    eprintln!("{} {}", i18n::tr(Msg::WarningPrefix).yellow(), message);
+}
+
+/// Colors each character of a password by class so look-alikes such as `l1I0O` stand out
+///
+/// Digits are cyan, symbols yellow, uppercase letters green and lowercase
+/// letters keep the default color. Anything outside ASCII is left uncolored.
+/// Coloring follows the global `colored` override, so the result is plain
+/// text whenever colors are disabled.
+pub(crate) fn colorize_password(password: &str) -> String {
+    password
+        .chars()
+        .map(|c| {
+            let text = c.to_string();
+            if c.is_ascii_digit() {
+                text.cyan().to_string()
+            } else if c.is_ascii_uppercase() {
+                text.green().to_string()
+            } else if c.is_ascii_punctuation() {
+                text.yellow().to_string()
+            } else {
+                text
+            }
+        })
+        .collect()
+}
+
+/// Legend explaining the colors used by `colorize_password`
+pub(crate) fn password_legend() -> String {
+    let (uppercase, digits, symbols) = ("ABC".green(), "123".cyan(), "#$%".yellow());
+    i18n::trf(Msg::Legend, &[&uppercase, &digits, &symbols])
+}
+
+/// Shows lines on the terminal for `duration`, then erases them
+///
+/// Meant for revealing a secret on an interactive terminal only: the erase
+/// relies on ANSI cursor movement, and the caller blocks until it happens.
+pub(crate) fn reveal_temporarily(lines: &[&str], duration: Duration) {
+    for line in lines {
+        out(line);
+    }
+    info(&i18n::trf(Msg::HiddenAgain, &[&duration.as_secs()]));
+    std::thread::sleep(duration);
+
+    // Move up over the revealed lines and the notice, clearing each one
+    let mut stdout = io::stdout();
+    for _ in 0..=lines.len() {
+        let _ = write!(stdout, "\x1b[1A\x1b[2K");
+    }
+    let _ = stdout.flush();
+}
+
+/// Writes secrets to a file only the current user can read
+///
+/// # Arguments
+/// * `path` - Destination file
+/// * `contents` - Bytes to write
+/// * `force` - Overwrite an existing file instead of failing
+///
+/// # Errors
+/// * `ErrorKind::AlreadyExists` - The file exists and `force` is false
+///
+/// # Notes
+/// On Unix the file is created with mode 0600, and an overwritten file is
+/// reset to 0600 as well. On Windows the file inherits the ACL of its
+/// directory, which is private to the user inside the user profile. The data
+/// is flushed to disk before returning.
+pub(crate) fn write_private_file(path: &Path, contents: &[u8], force: bool) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true);
+    if force {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    {
+        // `mode` only applies to newly created files
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+/// Replaces a file's contents atomically: a temporary file next to it, then a rename
+///
+/// # Arguments
+/// * `path` - File to replace or create; a symlink is followed, not replaced
+/// * `contents` - Complete new contents
+///
+/// # Notes
+/// Readers see either the old or the new contents, never a partial write. The
+/// temporary file gets a fresh random name and is created exclusively (mode
+/// 0600 on Unix), so a file or symlink planted next to the target is never
+/// followed or overwritten, and concurrent writers do not share it. An
+/// existing file keeps its permissions (but not an owner other than the
+/// current user); a new one is private, as with `write_private_file`.
+pub(crate) fn replace_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let permissions = std::fs::metadata(&path).ok().map(|metadata| metadata.permissions());
+    let directory = path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+
+    // Removed again on drop unless it has been renamed over the target
+    let mut temporary = tempfile::Builder::new().prefix(&format!(".{}.", name)).suffix(".tmp").tempfile_in(directory)?;
+    temporary.write_all(contents)?;
+    temporary.as_file().sync_all()?;
+    if let Some(permissions) = permissions {
+        temporary.as_file().set_permissions(permissions)?;
+    }
+    temporary.persist(&path).map(drop).map_err(|e| e.error)
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Decides whether colors are used
+///
+/// # Precedence
+/// 1. `--color always|never` (and `--no-color`)
+/// 2. `NO_COLOR` (disables) then `CLICOLOR_FORCE` (enables), when set to a non-empty value
+///    other than `0` for the latter; see <https://no-color.org>
+/// 3. Auto-detection: colors only when stdout is a terminal
+fn resolve_color(cli: &Cli, stdout_is_terminal: bool) -> bool {
+    if cli.no_color {
+        return false;
+    }
+    match cli.color {
+        ColorMode::Always => return true,
+        ColorMode::Never => return false,
+        ColorMode::Auto => {}
+    }
+
+    color_from_env().unwrap_or(stdout_is_terminal)
+}
+
+/// Color preference set in the environment: `NO_COLOR` disables, `CLICOLOR_FORCE` forces
+///
+/// # Returns
+/// * `Option<bool>` - `None` when neither variable is set (to a non-empty, non-`0` value)
+pub(crate) fn color_from_env() -> Option<bool> {
+    if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return Some(false);
+    }
+    if std::env::var_os("CLICOLOR_FORCE").is_some_and(|v| !v.is_empty() && v != "0") {
+        return Some(true);
+    }
+    None
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Serializes the tests, since the `colored` override is global
+    static OVERRIDE: Mutex<()> = Mutex::new(());
+
+    /// Runs `f` with colors forced on or off
+    fn with_colors<T>(enabled: bool, f: impl FnOnce() -> T) -> T {
+        let _guard = OVERRIDE.lock().unwrap_or_else(|e| e.into_inner());
+        colored::control::set_override(enabled);
+        let result = f();
+        colored::control::unset_override();
+        result
+    }
+
+    #[test]
+    fn colorize_snapshots() {
+        let cases = [
+            ("abc", "abc"),
+            ("B", "\u{1b}[32mB\u{1b}[0m"),
+            ("7", "\u{1b}[36m7\u{1b}[0m"),
+            ("#", "\u{1b}[33m#\u{1b}[0m"),
+            ("l1I0O", "l\u{1b}[36m1\u{1b}[0m\u{1b}[32mI\u{1b}[0m\u{1b}[36m0\u{1b}[0m\u{1b}[32mO\u{1b}[0m"),
+            ("a B", "a \u{1b}[32mB\u{1b}[0m"),
+            // Only ASCII is colored: the accented capital and the emoji stay plain
+            ("É🔑!", "É🔑\u{1b}[33m!\u{1b}[0m"),
+            ("", ""),
+        ];
+        for (password, expected) in cases {
+            assert_eq!(with_colors(true, || colorize_password(password)), expected, "{:?}", password);
+        }
+    }
+
+    #[test]
+    fn colorize_is_plain_when_colors_are_off() {
+        for password in ["l1I0O", "kV9#mQ2$xL7!pR4&zT8*", "É🔑!"] {
+            assert_eq!(with_colors(false, || colorize_password(password)), password);
+        }
+    }
+
+    #[test]
+    fn legend_snapshots() {
+        assert_eq!(
+            with_colors(true, password_legend),
+            "Legend: \u{1b}[32mABC\u{1b}[0m uppercase  abc lowercase  \u{1b}[36m123\u{1b}[0m digits  \u{1b}[33m#$%\u{1b}[0m symbols"
+        );
+    }
+}
@@ -0,0 +1,396 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::analyzer::{self, AnalysisReport, AnalyzerOptions, Composition, PatternKind};
+use crate::error::RustyPassError;
+use crate::i18n::{self, Msg};
+
+// ============================================================================
+// Password Policy
+// ============================================================================
+
+/// Concrete, checkable password rules
+///
+/// The same struct drives both `check` (is this password compliant?) and
+/// `generate --policy` (produce a compliant password), so the two can never
+/// disagree. Every rule is optional; unset rules are not evaluated.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    /// Display name of the policy
+    pub name: String,
+    /// Minimum length in characters
+    pub min_length: usize,
+    /// Maximum length in characters
+    pub max_length: Option<usize>,
+    /// Minimum number of lowercase letters
+    pub min_lowercase: usize,
+    /// Minimum number of uppercase letters
+    pub min_uppercase: usize,
+    /// Minimum number of letters of either case
+    pub min_letters: usize,
+    /// Minimum number of digits
+    pub min_digits: usize,
+    /// Minimum number of symbols
+    pub min_symbols: usize,
+    /// Minimum number of distinct classes among lowercase, uppercase, digits and symbols
+    pub min_classes: usize,
+    /// Characters that may not appear at all
+    pub banned_chars: String,
+    /// Substrings that may not appear (case insensitive)
+    pub banned_substrings: Vec<String>,
+    /// Maximum run length of the same character
+    pub max_repeats: Option<usize>,
+    /// Reject passwords containing common words or passwords
+    pub reject_common: bool,
+}
+
+/// Built-in policies selectable with `--policy`
+#[derive(serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyPreset {
+    /// NIST SP 800-63B: 8-64 characters, no composition rules, no common passwords
+    Nist,
+    /// PCI DSS v4.0: at least 12 characters with letters and digits
+    Pci,
+    /// Active Directory complexity: at least 8 characters from 3 of 4 classes
+    Ad,
+}
+
+impl PolicyPreset {
+    /// Builds the concrete rules of the preset
+    pub fn policy(&self) -> Policy {
+        match self {
+            PolicyPreset::Nist => Policy {
+                name: String::from("NIST SP 800-63B"),
+                min_length: 8,
+                max_length: Some(64),
+                reject_common: true,
+                ..Default::default()
+            },
+            PolicyPreset::Pci => Policy {
+                name: String::from("PCI DSS v4.0"),
+                min_length: 12,
+                min_letters: 1,
+                min_digits: 1,
+                ..Default::default()
+            },
+            PolicyPreset::Ad => Policy {
+                name: String::from("Active Directory complexity"),
+                min_length: 8,
+                max_length: Some(256),
+                min_classes: 3,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl Policy {
+    /// Loads a policy from a TOML file
+    ///
+    /// # Returns
+    /// * `Result<Policy, RustyPassError>` - The policy, `Io` if the file cannot be read,
+    ///   or `InvalidPolicy` naming the file and the offending key
+    pub fn from_file(path: &Path) -> Result<Policy, RustyPassError> {
+        let text = std::fs::read_to_string(path).map_err(|source| RustyPassError::Io { path: path.to_path_buf(), source })?;
+        let mut policy: Policy = toml::from_str(&text)
+            .map_err(|e| RustyPassError::InvalidPolicy { path: path.to_path_buf(), message: e.to_string() })?;
+        if policy.name.is_empty() {
+            policy.name = path.display().to_string();
+        }
+        Ok(policy)
+    }
+
+    /// Evaluates every configured rule against a password
+    ///
+    /// # Arguments
+    /// * `password` - Password to check
+    ///
+    /// # Returns
+    /// * `PolicyResult` - One line per rule plus the overall verdict
+    pub fn evaluate(&self, password: &str) -> PolicyResult {
+        let composition = Composition::from_password(password);
+        let length = composition.total;
+        let mut rules = Vec::new();
+
+        let mut rule = |name: String, passed: bool, detail: String| rules.push(RuleResult { rule: name, passed, detail });
+
+        rule(
+            format!("length >= {}", self.min_length),
+            length >= self.min_length,
+            format!("{} characters", length),
+        );
+        if let Some(max) = self.max_length {
+            rule(format!("length <= {}", max), length <= max, format!("{} characters", length));
+        }
+
+        // Class minimums, only evaluated when required
+        let letters = composition.lowercase + composition.uppercase;
+        for (label, required, actual) in [
+            ("lowercase letters", self.min_lowercase, composition.lowercase),
+            ("uppercase letters", self.min_uppercase, composition.uppercase),
+            ("letters", self.min_letters, letters),
+            ("digits", self.min_digits, composition.digits),
+            ("symbols", self.min_symbols, composition.symbols),
+        ] {
+            if required > 0 {
+                rule(format!("at least {} {}", required, label), actual >= required, format!("{} found", actual));
+            }
+        }
+
+        if self.min_classes > 0 {
+            let classes = [composition.lowercase, composition.uppercase, composition.digits, composition.symbols]
+                .iter()
+                .filter(|count| **count > 0)
+                .count();
+            rule(
+                format!("at least {} of 4 character classes", self.min_classes),
+                classes >= self.min_classes,
+                format!("{} classes used", classes),
+            );
+        }
+
+        if !self.banned_chars.is_empty() {
+            let found = password.chars().filter(|c| self.banned_chars.contains(*c)).count();
+            rule(
+                String::from("no banned characters"),
+                found == 0,
+                format!("{} banned characters found", found),
+            );
+        }
+
+        if !self.banned_substrings.is_empty() {
+            let lowered = password.to_lowercase();
+            let found = self
+                .banned_substrings
+                .iter()
+                .filter(|s| !s.is_empty() && lowered.contains(&s.to_lowercase()))
+                .count();
+            rule(
+                String::from("no banned substrings"),
+                found == 0,
+                format!("{} banned substrings found", found),
+            );
+        }
+
+        if let Some(max) = self.max_repeats {
+            let longest = longest_run(password);
+            rule(
+                format!("no character repeated more than {} times in a row", max),
+                longest <= max,
+                format!("longest run is {}", longest),
+            );
+        }
+
+        if self.reject_common {
+            let report = analyzer::analyze_password(password, &AnalyzerOptions::default());
+            let common = report.patterns.iter().filter(|p| p.kind == PatternKind::Dictionary).count();
+            rule(
+                String::from("no common words or passwords"),
+                common == 0,
+                format!("{} found", common),
+            );
+        }
+
+        // Rule names and details only describe counts, never the password
+        for rule in &rules {
+            log::trace!("policy '{}': {} -> {} ({})", self.name, rule.rule, if rule.passed { "pass" } else { "fail" }, rule.detail);
+        }
+        let passed = rules.iter().all(|r| r.passed);
+        PolicyResult { policy: self.name.clone(), rules, passed, strength: None }
+    }
+}
+
+// ============================================================================
+// Policy Results
+// ============================================================================
+
+/// Outcome of a single policy rule
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleResult {
+    pub rule: String,
+    pub passed: bool,
+    /// What was measured, never the password itself
+    pub detail: String,
+}
+
+/// Outcome of evaluating a password against a policy
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyResult {
+    /// Name of the policy
+    pub policy: String,
+    pub rules: Vec<RuleResult>,
+    /// True when every rule passed
+    pub passed: bool,
+    /// Informational strength analysis; never affects the verdict
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strength: Option<AnalysisReport>,
+}
+
+/// Renders a policy result as a pass/fail table with an overall verdict
+pub fn render_policy_result(result: &PolicyResult) -> String {
+    let width = result.rules.iter().map(|r| r.rule.len()).max().unwrap_or(0);
+    let mut out = format!("{} {}\n\n", i18n::tr(Msg::PolicyHeading), result.policy);
+
+    let tag_width = [Msg::PolicyPass, Msg::PolicyFail, Msg::PolicyInfo]
+        .iter()
+        .map(|tag| i18n::tr(*tag).chars().count())
+        .max()
+        .unwrap_or(0);
+
+    for rule in &result.rules {
+        let status = if rule.passed { Msg::PolicyPass } else { Msg::PolicyFail };
+        out.push_str(&format!("  [{:<tag_width$}] {:<width$}  {}\n", i18n::tr(status), rule.rule, rule.detail));
+    }
+    if let Some(strength) = &result.strength {
+        let value = i18n::trf(
+            Msg::GradeAndBits,
+            &[&strength.grade, &format!("{:.1}", strength.effective_entropy_bits)],
+        );
+        out.push_str(&format!(
+            "  [{:<tag_width$}] {:<width$}  {}\n",
+            i18n::tr(Msg::PolicyInfo),
+            i18n::tr(Msg::PolicyStrength),
+            value
+        ));
+    }
+
+    let verdict = if result.passed { Msg::Compliant } else { Msg::NotCompliant };
+    out.push_str(&format!("\n{} {}\n", i18n::tr(Msg::VerdictHeading), i18n::tr(verdict)));
+    out
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Length of the longest run of the same character
+fn longest_run(password: &str) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    let mut previous: Option<char> = None;
+
+    for c in password.chars() {
+        current = if previous == Some(c) { current + 1 } else { 1 };
+        longest = longest.max(current);
+        previous = Some(c);
+    }
+
+    longest
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A policy with every rule set
+    fn full() -> Policy {
+        Policy {
+            name: String::from("Corporate"),
+            min_length: 14,
+            max_length: Some(64),
+            min_lowercase: 1,
+            min_uppercase: 1,
+            min_letters: 4,
+            min_digits: 2,
+            min_symbols: 1,
+            min_classes: 3,
+            banned_chars: String::from(" '\""),
+            banned_substrings: vec![String::from("acme"), String::from("password")],
+            max_repeats: Some(2),
+            reject_common: true,
+        }
+    }
+
+    #[test]
+    fn policies_round_trip_through_toml_and_json() {
+        let policy = full();
+        assert_eq!(toml::from_str::<Policy>(&toml::to_string(&policy).unwrap()).unwrap(), policy);
+        assert_eq!(serde_json::from_str::<Policy>(&serde_json::to_string(&policy).unwrap()).unwrap(), policy);
+    }
+
+    #[test]
+    fn presets_round_trip() {
+        for preset in [PolicyPreset::Nist, PolicyPreset::Pci, PolicyPreset::Ad] {
+            let policy = preset.policy();
+            assert_eq!(toml::from_str::<Policy>(&toml::to_string(&policy).unwrap()).unwrap(), policy, "{:?}", preset);
+        }
+    }
+
+    #[test]
+    fn unset_rules_stay_unset() {
+        let policy: Policy = toml::from_str("name = \"short\"\nmin_length = 10\n").unwrap();
+        assert_eq!(policy, Policy { name: String::from("short"), min_length: 10, ..Default::default() });
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        for document in ["min_lenght = 12\n", "name = \"x\"\n[extra]\nkey = 1\n"] {
+            let error = toml::from_str::<Policy>(document).unwrap_err().to_string();
+            assert!(error.contains("unknown field"), "{}: {}", document, error);
+        }
+        assert!(serde_json::from_str::<Policy>(r#"{"max_repeat": 2}"#).is_err());
+    }
+
+    #[test]
+    fn each_rule_fails_alone() {
+        let only = |rule: fn(&mut Policy)| {
+            let mut policy = Policy { name: String::from("one rule"), ..Default::default() };
+            rule(&mut policy);
+            policy
+        };
+        let cases: [(Policy, &str, &str, &str, &str); 12] = [
+            (only(|p| p.min_length = 8), "abcdefgh", "abcdefg", "length >= 8", "7 characters"),
+            (only(|p| p.max_length = Some(8)), "abcdefgh", "abcdefghi", "length <= 8", "9 characters"),
+            (only(|p| p.min_lowercase = 2), "aBc", "aBC", "at least 2 lowercase letters", "1 found"),
+            (only(|p| p.min_uppercase = 2), "AbC", "Abc", "at least 2 uppercase letters", "1 found"),
+            (only(|p| p.min_letters = 3), "aB1c", "aB12", "at least 3 letters", "2 found"),
+            (only(|p| p.min_digits = 2), "a12", "a1b", "at least 2 digits", "1 found"),
+            (only(|p| p.min_symbols = 1), "a!", "ab", "at least 1 symbols", "0 found"),
+            (only(|p| p.min_classes = 3), "aA1", "aAb", "at least 3 of 4 character classes", "2 classes used"),
+            (only(|p| p.banned_chars = String::from(" '")), "ab", "a b", "no banned characters", "1 banned characters found"),
+            (only(|p| p.banned_substrings = vec![String::from("acme")]), "ac-me", "myACMEpw", "no banned substrings", "1 banned substrings found"),
+            (only(|p| p.max_repeats = Some(2)), "aab", "aaab", "no character repeated more than 2 times in a row", "longest run is 3"),
+            (only(|p| p.reject_common = true), "Kx9#mQ2vLr8tZp", "password123", "no common words or passwords", "1 found"),
+        ];
+
+        for (policy, compliant, violating, rule, detail) in cases {
+            let result = policy.evaluate(compliant);
+            assert!(result.passed, "{}: {:?}", rule, result.rules);
+
+            let result = policy.evaluate(violating);
+            assert!(!result.passed, "{}", rule);
+            let failed: Vec<(&str, &str)> =
+                result.rules.iter().filter(|r| !r.passed).map(|r| (r.rule.as_str(), r.detail.as_str())).collect();
+            assert_eq!(failed, [(rule, detail)]);
+        }
+    }
+
+    #[test]
+    fn unset_rules_are_not_evaluated() {
+        let result = Policy::default().evaluate("");
+        let rules: Vec<&str> = result.rules.iter().map(|r| r.rule.as_str()).collect();
+        assert_eq!(rules, ["length >= 0"]);
+        assert!(result.passed);
+    }
+
+    #[test]
+    fn compliant_password_passes_every_rule_of_a_full_policy() {
+        let result = full().evaluate("Kx9#mQ2vLr8tZp");
+        assert_eq!(result.rules.len(), 12);
+        assert!(result.passed, "{:?}", result.rules);
+
+        // Among all the rules, a banned space fails only its own
+        let result = full().evaluate("Kx9#mQ2vLr8t Z");
+        let failed: Vec<&str> = result.rules.iter().filter(|r| !r.passed).map(|r| r.rule.as_str()).collect();
+        assert_eq!(failed, ["no banned characters"]);
+        // Every broken rule is reported, not just the first
+        let result = full().evaluate("acme");
+        assert!(result.rules.iter().filter(|r| !r.passed).count() > 1);
+    }
+}
@@ -0,0 +1,33 @@
+// ============================================================================
+// Terminal QR Codes
+// ============================================================================
+
+/// Renders a payload as a QR code made of Unicode half-block characters
+///
+/// # Arguments
+/// * `payload` - Text to encode
+/// * `invert` - Draw for a light terminal background instead of a dark one
+///
+/// # Returns
+/// * `Result<String, String>` - The QR code, one text line per two module rows,
+///   or an error when the payload exceeds QR capacity
+#[cfg(feature = "qr")]
+pub(crate) fn render_qr(payload: &str, invert: bool) -> Result<String, String> {
+    use qrcode::render::unicode::Dense1x2;
+
+    let code = qrcode::QrCode::new(payload.as_bytes()).map_err(|e| format!("cannot encode QR code: {}", e))?;
+
+    // On a dark background the light modules are the ones that must be drawn
+    let (dark, light) = if invert {
+        (Dense1x2::Dark, Dense1x2::Light)
+    } else {
+        (Dense1x2::Light, Dense1x2::Dark)
+    };
+    Ok(code.render::<Dense1x2>().dark_color(dark).light_color(light).quiet_zone(true).build())
+}
+
+/// Fallback when the binary was built without the `qr` feature
+#[cfg(not(feature = "qr"))]
+pub(crate) fn render_qr(_payload: &str, _invert: bool) -> Result<String, String> {
+    Err(String::from("QR support is not compiled in; rebuild with `--features qr`"))
+}
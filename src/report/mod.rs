@@ -0,0 +1,463 @@
+use std::collections::BTreeMap;
+use crate::analyzer::{AnalysisReport, BatchReport, Grade};
+use crate::audit::{AuditReport, Severity};
+use crate::utils;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Inline stylesheet of the HTML report, so the file has no external assets
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:60em;margin:2em auto;padding:0 1em;color:#222}\
+h1{border-bottom:2px solid #b7410e}h2{margin-top:1.5em;color:#b7410e}\
+table{border-collapse:collapse;margin:.5em 0}th,td{border:1px solid #ccc;padding:.3em .7em;text-align:left}\
+th{background:#f4f4f4}td.num{text-align:right}";
+
+/// Names of the character classes, in `Composition::classes` order
+const CLASS_NAMES: [&str; 6] =
+    ["lowercase letters", "uppercase letters", "digits", "symbols", "whitespace", "non-ASCII characters"];
+
+// ============================================================================
+// Report Document
+// ============================================================================
+
+/// A format-neutral report: a title followed by titled sections
+///
+/// Markdown and HTML are rendered from the same document, so both always
+/// contain the same information.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Document {
+    pub(crate) title: String,
+    pub(crate) sections: Vec<Section>,
+}
+
+/// A titled group of blocks
+#[derive(Debug, Clone)]
+pub(crate) struct Section {
+    pub(crate) heading: String,
+    pub(crate) blocks: Vec<Block>,
+}
+
+/// Content of a section
+#[derive(Debug, Clone)]
+pub(crate) enum Block {
+    /// A paragraph of plain text
+    Paragraph(String),
+    /// A bullet list of plain text items
+    List(Vec<String>),
+    /// A table; `numeric` marks right-aligned columns
+    Table {
+        headers: Vec<&'static str>,
+        numeric: Vec<bool>,
+        rows: Vec<Vec<String>>,
+    },
+}
+
+impl Document {
+    fn new(title: &str) -> Self {
+        Document { title: title.to_string(), sections: Vec::new() }
+    }
+
+    fn section(&mut self, heading: &str, blocks: Vec<Block>) {
+        self.sections.push(Section { heading: heading.to_string(), blocks });
+    }
+}
+
+/// Builds a table block; columns whose header is listed in `numeric` are right-aligned
+fn table(headers: &[&'static str], numeric: &[&str], rows: Vec<Vec<String>>) -> Block {
+    Block::Table {
+        headers: headers.to_vec(),
+        numeric: headers.iter().map(|h| numeric.contains(h)).collect(),
+        rows,
+    }
+}
+
+// ============================================================================
+// Document Builders
+// ============================================================================
+
+/// Builds the report of a single password analysis
+///
+/// The password itself is never included, only its measurements.
+pub(crate) fn analysis_document(report: &AnalysisReport) -> Document {
+    let mut doc = Document::new("Password Strength Report");
+
+    doc.section(
+        "Summary",
+        vec![table(
+            &["Metric", "Value"],
+            &[],
+            vec![
+                vec![String::from("Grade"), report.grade.to_string()],
+                vec![String::from("Score"), format!("{}/100", report.score)],
+                vec![String::from("Length"), format!("{} characters", report.length)],
+                vec![String::from("Character set"), format!("{} symbols", report.charset_size)],
+                vec![String::from("Entropy"), format!("{:.1} bits", report.entropy_bits)],
+                vec![String::from("Effective entropy"), format!("{:.1} bits", report.effective_entropy_bits)],
+            ],
+        )],
+    );
+
+    let composition = report
+        .composition
+        .classes()
+        .into_iter()
+        .zip(CLASS_NAMES)
+        .map(|(count, name)| vec![name.to_string(), count.to_string(), format!("{:.0}%", report.composition.percent(count))])
+        .collect();
+    doc.section("Composition", vec![table(&["Class", "Count", "Share"], &["Count", "Share"], composition)]);
+
+    let findings = if report.warnings.is_empty() {
+        Block::Paragraph(String::from("No weaknesses found."))
+    } else {
+        Block::List(report.warnings.clone())
+    };
+    doc.section("Findings", vec![findings]);
+
+    doc.section("Crack Time", crack_time_blocks(&[("Password", report)]));
+    doc
+}
+
+/// Builds the report of a password file analysis
+pub(crate) fn batch_document(report: &BatchReport) -> Document {
+    let mut doc = Document::new("Password File Report");
+    let summary = &report.summary;
+
+    let mut rows = vec![
+        vec![String::from("Analyzed"), summary.analyzed.to_string()],
+        vec![String::from("Skipped"), summary.skipped.to_string()],
+        vec![String::from("Average effective entropy"), format!("{:.1} bits", summary.average_entropy_bits)],
+    ];
+    if let (Some(clusters), Some(entries)) = (summary.reuse_clusters, summary.reused_entries) {
+        rows.push(vec![String::from("Reuse"), format!("{} entries in {} clusters", entries, clusters)]);
+    }
+    if let (Some(breached), Some(unknown)) = (summary.breached_entries, summary.breach_unknown) {
+        rows.push(vec![String::from("Breached"), format!("{} entries ({} could not be checked)", breached, unknown)]);
+    }
+    doc.section("Summary", vec![table(&["Metric", "Value"], &[], rows)]);
+    doc.section("Grade Distribution", vec![grade_table(&summary.grade_counts, summary.analyzed)]);
+
+    let findings = report
+        .entries
+        .iter()
+        .map(|entry| {
+            vec![
+                entry.line.to_string(),
+                entry.password.clone(),
+                entry.report.grade.to_string(),
+                format!("{:.1}", entry.report.effective_entropy_bits),
+                entry.report.warnings.join("; "),
+            ]
+        })
+        .collect();
+    doc.section(
+        "Findings",
+        vec![table(&["Line", "Password", "Grade", "Entropy (bits)", "Warnings"], &["Line", "Entropy (bits)"], findings)],
+    );
+
+    let weakest: Vec<(String, &AnalysisReport)> = summary
+        .weakest_lines
+        .iter()
+        .filter_map(|line| report.entries.iter().find(|e| e.line == *line))
+        .map(|e| (format!("Line {}", e.line), &e.report))
+        .collect();
+    let weakest: Vec<(&str, &AnalysisReport)> = weakest.iter().map(|(label, r)| (label.as_str(), *r)).collect();
+    doc.section("Crack Time (weakest entries)", crack_time_blocks(&weakest));
+    doc
+}
+
+/// Builds the report of a credentials audit
+pub(crate) fn audit_document(report: &AuditReport) -> Document {
+    let mut doc = Document::new("Credential Audit Report");
+
+    let mut grade_counts: BTreeMap<Grade, usize> = [Grade::A, Grade::B, Grade::C, Grade::D, Grade::F]
+        .into_iter()
+        .map(|g| (g, 0))
+        .collect();
+    for finding in &report.findings {
+        *grade_counts.entry(finding.report.grade).or_insert(0) += 1;
+    }
+
+    let mut rows = vec![vec![String::from("Credentials"), report.findings.len().to_string()]];
+    if let (Some(breached), Some(unknown)) = (report.breached, report.breach_unknown) {
+        rows.push(vec![String::from("Breached"), format!("{} ({} could not be checked)", breached, unknown)]);
+    }
+    rows.extend([
+        vec![String::from("Weak"), report.weak.to_string()],
+        vec![String::from("Reused"), report.reused.to_string()],
+        vec![String::from("OK"), report.ok.to_string()],
+    ]);
+    if report.without_password > 0 {
+        rows.push(vec![String::from("Without password (skipped)"), report.without_password.to_string()]);
+    }
+    doc.section("Summary", vec![table(&["Metric", "Value"], &[], rows)]);
+    doc.section("Grade Distribution", vec![grade_table(&grade_counts, report.findings.len())]);
+
+    let findings = report
+        .findings
+        .iter()
+        .map(|finding| {
+            let severity = match finding.severity {
+                Severity::Breached => "breached",
+                Severity::Weak => "weak",
+                Severity::Reused => "reused",
+                Severity::Ok => "ok",
+            };
+            let reused: Vec<String> = finding.reused_with.iter().map(|r| r.to_string()).collect();
+            vec![
+                severity.to_string(),
+                finding.row.to_string(),
+                finding.url.clone(),
+                finding.username.clone(),
+                finding.password.clone(),
+                finding.report.grade.to_string(),
+                format!("{:.1}", finding.report.effective_entropy_bits),
+                reused.join(", "),
+            ]
+        })
+        .collect();
+    doc.section(
+        "Findings",
+        vec![table(
+            &["Severity", "Row", "URL", "Username", "Password", "Grade", "Entropy (bits)", "Reused in rows"],
+            &["Row", "Entropy (bits)"],
+            findings,
+        )],
+    );
+
+    let labels: Vec<String> = report.findings.iter().map(|f| format!("Row {}", f.row)).collect();
+    let flagged: Vec<(&str, &AnalysisReport)> = report
+        .findings
+        .iter()
+        .zip(&labels)
+        .filter(|(f, _)| f.severity != Severity::Ok)
+        .map(|(f, label)| (label.as_str(), &f.report))
+        .collect();
+    doc.section("Crack Time (flagged credentials)", crack_time_blocks(&flagged));
+    doc
+}
+
+/// Grade distribution table with counts and shares
+fn grade_table(counts: &BTreeMap<Grade, usize>, total: usize) -> Block {
+    let rows = counts
+        .iter()
+        .map(|(grade, count)| {
+            let share = if total == 0 { 0.0 } else { *count as f64 * 100.0 / total as f64 };
+            vec![grade.to_string(), count.to_string(), format!("{:.0}%", share)]
+        })
+        .collect();
+    table(&["Grade", "Count", "Share"], &["Count", "Share"], rows)
+}
+
+/// Crack-time table for the given entries, preceded by the attack scenario it assumes
+fn crack_time_blocks(entries: &[(&str, &AnalysisReport)]) -> Vec<Block> {
+    let scenario = Block::Paragraph(format!(
+        "Scenario: offline attack against bcrypt hashes at {} guesses per second, using the effective entropy.",
+        utils::BCRYPT_CRACKING_SPEED
+    ));
+    if entries.is_empty() {
+        return vec![scenario, Block::Paragraph(String::from("Nothing to report."))];
+    }
+
+    let rows = entries
+        .iter()
+        .map(|(label, report)| {
+            vec![label.to_string(), format!("{:.1}", report.effective_entropy_bits), report.crack_time_display.clone()]
+        })
+        .collect();
+    vec![scenario, table(&["Entry", "Entropy (bits)", "Estimated time"], &["Entropy (bits)"], rows)]
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+/// Renders a document as GitHub-flavored Markdown
+pub(crate) fn render_markdown(doc: &Document) -> String {
+    let mut out = format!("# {}\n", escape_markdown(&doc.title));
+
+    for section in &doc.sections {
+        out.push_str(&format!("\n## {}\n", escape_markdown(&section.heading)));
+        for block in &section.blocks {
+            out.push('\n');
+            match block {
+                Block::Paragraph(text) => out.push_str(&format!("{}\n", escape_markdown(text))),
+                Block::List(items) => {
+                    for item in items {
+                        out.push_str(&format!("- {}\n", escape_markdown(item)));
+                    }
+                }
+                Block::Table { headers, numeric, rows } => {
+                    out.push_str(&format!("| {} |\n", headers.join(" | ")));
+                    let align: Vec<&str> = numeric.iter().map(|n| if *n { "---:" } else { "---" }).collect();
+                    out.push_str(&format!("| {} |\n", align.join(" | ")));
+                    for row in rows {
+                        let cells: Vec<String> = row.iter().map(|c| escape_markdown(c)).collect();
+                        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+                    }
+                }
+            }
+        }
+    }
+
+    out
+}
+
+/// Renders a document as a self-contained HTML page with inline CSS
+pub(crate) fn render_html(doc: &Document) -> String {
+    let title = escape_html(&doc.title);
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        title, HTML_STYLE, title
+    );
+
+    for section in &doc.sections {
+        out.push_str(&format!("<h2>{}</h2>\n", escape_html(&section.heading)));
+        for block in &section.blocks {
+            match block {
+                Block::Paragraph(text) => out.push_str(&format!("<p>{}</p>\n", escape_html(text))),
+                Block::List(items) => {
+                    out.push_str("<ul>\n");
+                    for item in items {
+                        out.push_str(&format!("<li>{}</li>\n", escape_html(item)));
+                    }
+                    out.push_str("</ul>\n");
+                }
+                Block::Table { headers, numeric, rows } => {
+                    out.push_str("<table>\n<tr>");
+                    for header in headers {
+                        out.push_str(&format!("<th>{}</th>", escape_html(header)));
+                    }
+                    out.push_str("</tr>\n");
+                    for row in rows {
+                        out.push_str("<tr>");
+                        for (cell, numeric) in row.iter().zip(numeric) {
+                            let class = if *numeric { " class=\"num\"" } else { "" };
+                            out.push_str(&format!("<td{}>{}</td>", class, escape_html(cell)));
+                        }
+                        out.push_str("</tr>\n");
+                    }
+                    out.push_str("</table>\n");
+                }
+            }
+        }
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Escapes characters that Markdown would interpret, including the `*` of masked passwords
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '|' | '[' | ']' | '<' | '>' | '#' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' => out.push(' '),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escapes the characters that are special in HTML text and attribute values
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_password, AnalyzerOptions};
+
+    #[test]
+    fn analysis_markdown_snapshot() {
+        let report = analyze_password("Summer2023!", &AnalyzerOptions::default());
+        let expected = "\
+# Password Strength Report
+
+## Summary
+
+| Metric | Value |
+| --- | --- |
+| Grade | F |
+| Score | 40/100 |
+| Length | 11 characters |
+| Character set | 95 symbols |
+| Entropy | 72.3 bits |
+| Effective entropy | 39.9 bits |
+
+## Composition
+
+| Class | Count | Share |
+| --- | ---: | ---: |
+| lowercase letters | 5 | 45% |
+| uppercase letters | 1 | 9% |
+| digits | 4 | 36% |
+| symbols | 1 | 9% |
+| whitespace | 0 | 0% |
+| non-ASCII characters | 0 | 0% |
+
+## Findings
+
+- Contains a common word or password (6 characters at position 1)
+
+## Crack Time
+
+Scenario: offline attack against bcrypt hashes at 9000 guesses per second, using the effective entropy.
+
+| Entry | Entropy (bits) | Estimated time |
+| --- | ---: | --- |
+| Password | 39.9 | 3years 8months 14days 1h 12m 49s |
+";
+        assert_eq!(render_markdown(&analysis_document(&report)), expected);
+    }
+
+    #[test]
+    fn markdown_escapes_cells_and_aligns_numbers() {
+        let mut doc = Document::new("A *bold* | title");
+        doc.section(
+            "Table",
+            vec![
+                Block::Paragraph(String::from("line one\nline two")),
+                table(&["Name", "Count"], &["Count"], vec![vec![String::from("a|b_c**"), String::from("3")]]),
+                Block::List(vec![String::from("[link](x) #1")]),
+            ],
+        );
+        let expected = r"# A \*bold\* \| title
+
+## Table
+
+line one line two
+
+| Name | Count |
+| --- | ---: |
+| a\|b\_c\*\* | 3 |
+
+- \[link\](x) \#1
+";
+        assert_eq!(render_markdown(&doc), expected);
+    }
+}
@@ -0,0 +1,305 @@
+use std::fmt::Write;
+use crate::analyzer::Grade;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Placeholder names accepted by `--format`, in the order they are documented
+pub(crate) const PLACEHOLDERS: [&str; 8] =
+    ["password", "length", "entropy", "crack_time", "crack_seconds", "grade", "timestamp", "index"];
+
+/// Decimals used for `{entropy}` without an explicit precision
+const DEFAULT_ENTROPY_PRECISION: usize = 1;
+
+/// Decimals used for `{crack_seconds}` without an explicit precision
+const DEFAULT_SECONDS_PRECISION: usize = 0;
+
+// ============================================================================
+// Output Templates
+// ============================================================================
+
+/// Value a placeholder stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Password,
+    Length,
+    Entropy,
+    CrackTime,
+    CrackSeconds,
+    Grade,
+    Timestamp,
+    Index,
+}
+
+/// Piece of a parsed template
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// Text copied as is, with escapes already resolved
+    Literal(String),
+    /// Placeholder with an optional number of decimals
+    Field(Field, Option<usize>),
+}
+
+/// Values available to a template for one password
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TemplateValues<'a> {
+    pub(crate) password: &'a str,
+    pub(crate) length: usize,
+    pub(crate) entropy_bits: f64,
+    /// Humanized crack time, e.g. `3years 2months`
+    pub(crate) crack_time: &'a str,
+    pub(crate) crack_time_seconds: f64,
+    /// Grade of the effective entropy, as `analyze` reports it; `None` when the template has no `{grade}`
+    pub(crate) grade: Option<Grade>,
+    /// RFC 3339 time of the run
+    pub(crate) timestamp: &'a str,
+    /// Position in the batch, starting at 1
+    pub(crate) index: usize,
+}
+
+/// An output template such as `{password}\t{entropy:.1}`, parsed once and rendered per password
+///
+/// # Syntax
+/// * `{name}` inserts a placeholder (see `PLACEHOLDERS`)
+/// * `{entropy:.N}` and `{crack_seconds:.N}` set the number of decimals
+/// * `{{` and `}}` are literal braces
+/// * `\t`, `\n` and `\\` are a tab, a newline and a backslash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    /// Parses a template
+    ///
+    /// # Returns
+    /// * `Result<Template, String>` - The template, or a message naming the
+    ///   offending placeholder or brace and its position (1-based, in characters)
+    pub(crate) fn parse(source: &str) -> Result<Template, String> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = source.chars().enumerate().peekable();
+
+        while let Some((index, c)) = chars.next() {
+            let position = index + 1;
+            match c {
+                '\\' => match chars.peek().map(|(_, next)| *next) {
+                    Some('t') => literal.push('\t'),
+                    Some('n') => literal.push('\n'),
+                    Some('\\') => literal.push('\\'),
+                    // Anything else is kept as typed, including the backslash
+                    _ => {
+                        literal.push('\\');
+                        continue;
+                    }
+                },
+                '{' if chars.peek().is_some_and(|(_, next)| *next == '{') => literal.push('{'),
+                '}' if chars.peek().is_some_and(|(_, next)| *next == '}') => literal.push('}'),
+                '}' => return Err(format!("unmatched '}}' at position {} (write '}}}}' for a literal brace)", position)),
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '}')) => break,
+                            Some((_, c)) => placeholder.push(c),
+                            None => return Err(format!("unclosed '{{' at position {}", position)),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(parse_placeholder(&placeholder, position)?);
+                    continue;
+                }
+                _ => {
+                    literal.push(c);
+                    continue;
+                }
+            }
+            // Two-character sequence: skip its second character
+            chars.next();
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(Template { segments })
+    }
+
+    /// Appends the template rendered for one password to `out`
+    ///
+    /// Taking the buffer lets batch runs reuse a single allocation.
+    pub(crate) fn render_into(&self, values: &TemplateValues, out: &mut String) {
+        for segment in &self.segments {
+            // Writing to a String cannot fail
+            let _ = match segment {
+                Segment::Literal(text) => {
+                    out.push_str(text);
+                    Ok(())
+                }
+                Segment::Field(Field::Password, _) => {
+                    out.push_str(values.password);
+                    Ok(())
+                }
+                Segment::Field(Field::Length, _) => write!(out, "{}", values.length),
+                Segment::Field(Field::Entropy, precision) => {
+                    write!(out, "{:.*}", precision.unwrap_or(DEFAULT_ENTROPY_PRECISION), values.entropy_bits)
+                }
+                Segment::Field(Field::CrackTime, _) => {
+                    out.push_str(values.crack_time);
+                    Ok(())
+                }
+                Segment::Field(Field::CrackSeconds, precision) => {
+                    write!(out, "{:.*}", precision.unwrap_or(DEFAULT_SECONDS_PRECISION), values.crack_time_seconds)
+                }
+                Segment::Field(Field::Grade, _) => values.grade.map_or(Ok(()), |grade| write!(out, "{}", grade)),
+                Segment::Field(Field::Timestamp, _) => {
+                    out.push_str(values.timestamp);
+                    Ok(())
+                }
+                Segment::Field(Field::Index, _) => write!(out, "{}", values.index),
+            };
+        }
+    }
+
+    /// Whether the template needs the humanized crack time, which is comparatively costly to build
+    pub(crate) fn uses_crack_time(&self) -> bool {
+        self.segments.contains(&Segment::Field(Field::CrackTime, None))
+    }
+
+    /// Whether the template needs the grade, which takes a full analysis of each password
+    pub(crate) fn uses_grade(&self) -> bool {
+        self.segments.contains(&Segment::Field(Field::Grade, None))
+    }
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Parses the inside of `{...}`: a placeholder name and an optional `:.N` precision
+fn parse_placeholder(placeholder: &str, position: usize) -> Result<Segment, String> {
+    let (name, spec) = match placeholder.split_once(':') {
+        Some((name, spec)) => (name, Some(spec)),
+        None => (placeholder, None),
+    };
+
+    let field = match name {
+        "password" => Field::Password,
+        "length" => Field::Length,
+        "entropy" => Field::Entropy,
+        "crack_time" => Field::CrackTime,
+        "crack_seconds" => Field::CrackSeconds,
+        "grade" => Field::Grade,
+        "timestamp" => Field::Timestamp,
+        "index" => Field::Index,
+        _ => {
+            return Err(format!(
+                "unknown placeholder '{{{}}}' at position {} (available: {})",
+                name,
+                position,
+                PLACEHOLDERS.join(", ")
+            ));
+        }
+    };
+
+    let Some(spec) = spec else {
+        return Ok(Segment::Field(field, None));
+    };
+    if !matches!(field, Field::Entropy | Field::CrackSeconds) {
+        return Err(format!("'{{{}}}' at position {} does not take a format spec", name, position));
+    }
+    spec.strip_prefix('.')
+        .and_then(|digits| digits.parse::<usize>().ok())
+        .map(|precision| Segment::Field(field, Some(precision)))
+        .ok_or_else(|| format!("invalid format spec ':{}' at position {} (expected ':.N')", spec, position))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> TemplateValues<'static> {
+        TemplateValues {
+            password: "p@ss{word}",
+            length: 10,
+            entropy_bits: 52.4567,
+            crack_time: "3years 2months",
+            crack_time_seconds: 100_000_000.25,
+            grade: Some(Grade::D),
+            timestamp: "2026-01-02T03:04:05Z",
+            index: 7,
+        }
+    }
+
+    fn render(source: &str) -> String {
+        let mut out = String::new();
+        Template::parse(source).unwrap().render_into(&values(), &mut out);
+        out
+    }
+
+    #[test]
+    fn every_placeholder_renders() {
+        let source = PLACEHOLDERS.iter().map(|name| format!("{{{}}}", name)).collect::<Vec<_>>().join("|");
+        assert_eq!(
+            render(&source),
+            "p@ss{word}|10|52.5|3years 2months|100000000|D|2026-01-02T03:04:05Z|7"
+        );
+    }
+
+    #[test]
+    fn precision_applies_to_entropy_and_seconds() {
+        assert_eq!(render("{entropy:.3} {crack_seconds:.2} {entropy:.0}"), "52.457 100000000.25 52");
+    }
+
+    #[test]
+    fn doubled_braces_are_literal() {
+        assert_eq!(render("{{password}} {{{password}}}"), "{password} {p@ss{word}}");
+        assert_eq!(render("}}{{"), "}{");
+    }
+
+    #[test]
+    fn escapes_are_resolved() {
+        assert_eq!(render(r"{index}\t{length}\n\\ \x"), "7\t10\n\\ \\x");
+    }
+
+    #[test]
+    fn braces_in_the_password_are_not_interpreted() {
+        assert_eq!(render("{password}"), "p@ss{word}");
+    }
+
+    #[test]
+    fn unknown_placeholders_report_their_position() {
+        let error = Template::parse("ab{nope}").unwrap_err();
+        assert!(error.contains("'{nope}' at position 3"), "{}", error);
+        assert!(error.contains("password, length"), "{}", error);
+    }
+
+    #[test]
+    fn malformed_templates_are_rejected() {
+        assert!(Template::parse("a}b").unwrap_err().contains("position 2"));
+        assert!(Template::parse("ab{password").unwrap_err().contains("unclosed '{' at position 3"));
+        assert!(Template::parse("{length:.2}").unwrap_err().contains("does not take a format spec"));
+        assert!(Template::parse("{entropy:2}").is_err());
+    }
+
+    #[test]
+    fn costly_fields_are_only_flagged_when_used() {
+        assert!(Template::parse("{grade}").unwrap().uses_grade());
+        assert!(!Template::parse("{password}").unwrap().uses_grade());
+        assert!(Template::parse("{crack_time}").unwrap().uses_crack_time());
+        assert!(!Template::parse("{crack_seconds}").unwrap().uses_crack_time());
+    }
+
+    #[test]
+    fn missing_grade_renders_empty() {
+        let mut out = String::new();
+        Template::parse("[{grade}]").unwrap().render_into(&TemplateValues { grade: None, ..values() }, &mut out);
+        assert_eq!(out, "[]");
+    }
+}
@@ -21,4 +21,213 @@ pub const UPPERCASE_CHARS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
 pub const NUMBERS: &str = "0123456789";
 
 /// Special characters for enhanced password security
-pub const SPECIAL_CHARS: &str = "!@#$%^&*_-+=<>?";
\ No newline at end of file
+pub const SPECIAL_CHARS: &str = "!@#$%^&*_-+=<>?";
+
+/// Seconds a revealed password stays on screen before it is erased
+pub const REVEAL_SECONDS: u64 = 10;
+
+/// Assumed attacker speed against bcrypt hashes, in attempts per second
+pub const BCRYPT_CRACKING_SPEED: u128 = 9 * 10u128.pow(3);
+
+/// NATO phonetic alphabet, indexed by letter (`A` = 0)
+pub const NATO_ALPHABET: [&str; 26] = [
+    "Alfa", "Bravo", "Charlie", "Delta", "Echo", "Foxtrot", "Golf", "Hotel", "India", "Juliett", "Kilo", "Lima",
+    "Mike", "November", "Oscar", "Papa", "Quebec", "Romeo", "Sierra", "Tango", "Uniform", "Victor", "Whiskey",
+    "X-ray", "Yankee", "Zulu",
+];
+
+/// Spoken digit names, indexed by digit
+pub const DIGIT_NAMES: [&str; 10] = ["Zero", "One", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine"];
+
+/// Spoken names of the symbols in `SPECIAL_CHARS`
+pub const SYMBOL_NAMES: &[(char, &str)] = &[
+    ('!', "Exclamation mark"),
+    ('@', "At sign"),
+    ('#', "Hash symbol"),
+    ('$', "Dollar sign"),
+    ('%', "Percent sign"),
+    ('^', "Caret"),
+    ('&', "Ampersand"),
+    ('*', "Asterisk"),
+    ('_', "Underscore"),
+    ('-', "Hyphen"),
+    ('+', "Plus sign"),
+    ('=', "Equals sign"),
+    ('<', "Less-than sign"),
+    ('>', "Greater-than sign"),
+    ('?', "Question mark"),
+];
+
+// Adding a symbol to SPECIAL_CHARS without a spoken name fails the build
+const _: () = assert!(all_symbols_named(), "every character in SPECIAL_CHARS needs an entry in SYMBOL_NAMES");
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Checks at compile time that every character in `SPECIAL_CHARS` has a spoken name
+const fn all_symbols_named() -> bool {
+    let symbols = SPECIAL_CHARS.as_bytes();
+    let mut i = 0;
+    while i < symbols.len() {
+        let mut found = false;
+        let mut j = 0;
+        while j < SYMBOL_NAMES.len() {
+            if SYMBOL_NAMES[j].0 as u32 == symbols[i] as u32 {
+                found = true;
+            }
+            j += 1;
+        }
+        if !found {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Spells out a character for reading it aloud
+///
+/// # Arguments
+/// * `c` - Character to spell
+///
+/// # Returns
+/// * `String` - e.g. `K — Kilo (uppercase)`, `7 — Seven` or `# — Hash symbol`;
+///   characters without a spoken name are given as their Unicode code point
+pub fn spell_char(c: char) -> String {
+    let name = if c.is_ascii_uppercase() {
+        format!("{} (uppercase)", NATO_ALPHABET[(c as u8 - b'A') as usize])
+    } else if c.is_ascii_lowercase() {
+        format!("{} (lowercase)", NATO_ALPHABET[(c as u8 - b'a') as usize])
+    } else if c.is_ascii_digit() {
+        DIGIT_NAMES[(c as u8 - b'0') as usize].to_string()
+    } else if let Some((_, name)) = SYMBOL_NAMES.iter().find(|(symbol, _)| *symbol == c) {
+        name.to_string()
+    } else {
+        format!("U+{:04X}", c as u32)
+    };
+
+    format!("{} — {}", c, name)
+}
+
+/// Masks a password, keeping only its first and last characters visible
+///
+/// # Arguments
+/// * `password` - Password to mask
+///
+/// # Returns
+/// * `String` - e.g. `S******!`; passwords of one or two characters are fully masked
+///
+/// # Notes
+/// Masking operates on characters, not bytes, so multi-byte input is safe.
+pub fn mask_password(password: &str) -> String {
+    let chars: Vec<char> = password.chars().collect();
+
+    if chars.len() <= 2 {
+        return "*".repeat(chars.len());
+    }
+
+    let mut masked = String::with_capacity(chars.len());
+    masked.push(chars[0]);
+    masked.push_str(&"*".repeat(chars.len() - 2));
+    masked.push(chars[chars.len() - 1]);
+    masked
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The spelling of every letter, written out rather than derived from `NATO_ALPHABET`
+    const LETTERS: [(char, &str); 26] = [
+        ('a', "Alfa"),
+        ('b', "Bravo"),
+        ('c', "Charlie"),
+        ('d', "Delta"),
+        ('e', "Echo"),
+        ('f', "Foxtrot"),
+        ('g', "Golf"),
+        ('h', "Hotel"),
+        ('i', "India"),
+        ('j', "Juliett"),
+        ('k', "Kilo"),
+        ('l', "Lima"),
+        ('m', "Mike"),
+        ('n', "November"),
+        ('o', "Oscar"),
+        ('p', "Papa"),
+        ('q', "Quebec"),
+        ('r', "Romeo"),
+        ('s', "Sierra"),
+        ('t', "Tango"),
+        ('u', "Uniform"),
+        ('v', "Victor"),
+        ('w', "Whiskey"),
+        ('x', "X-ray"),
+        ('y', "Yankee"),
+        ('z', "Zulu"),
+    ];
+
+    #[test]
+    fn every_letter_is_spelled_in_both_cases() {
+        for (letter, name) in LETTERS {
+            assert_eq!(spell_char(letter), format!("{} — {} (lowercase)", letter, name));
+            let upper = letter.to_ascii_uppercase();
+            assert_eq!(spell_char(upper), format!("{} — {} (uppercase)", upper, name));
+        }
+    }
+
+    #[test]
+    fn every_digit_is_spelled() {
+        let spelled: Vec<String> = NUMBERS.chars().map(spell_char).collect();
+        assert_eq!(
+            spelled,
+            [
+                "0 — Zero", "1 — One", "2 — Two", "3 — Three", "4 — Four", "5 — Five", "6 — Six", "7 — Seven",
+                "8 — Eight", "9 — Nine",
+            ]
+        );
+    }
+
+    #[test]
+    fn every_special_character_is_spelled() {
+        let spelled: Vec<String> = SPECIAL_CHARS.chars().map(spell_char).collect();
+        assert_eq!(
+            spelled,
+            [
+                "! — Exclamation mark",
+                "@ — At sign",
+                "# — Hash symbol",
+                "$ — Dollar sign",
+                "% — Percent sign",
+                "^ — Caret",
+                "& — Ampersand",
+                "* — Asterisk",
+                "_ — Underscore",
+                "- — Hyphen",
+                "+ — Plus sign",
+                "= — Equals sign",
+                "< — Less-than sign",
+                "> — Greater-than sign",
+                "? — Question mark",
+            ]
+        );
+    }
+
+    #[test]
+    fn unnamed_characters_are_given_as_code_points() {
+        for (c, expected) in [
+            ('~', "~ — U+007E"),
+            (' ', "  — U+0020"),
+            ('é', "é — U+00E9"),
+            ('Ж', "Ж — U+0416"),
+            ('🔑', "🔑 — U+1F511"),
+        ] {
+            assert_eq!(spell_char(c), expected);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use wasm_bindgen::prelude::*;
+use crate::analyzer::{self, AnalyzerOptions};
+use crate::generator::{os_rng, GeneratorOptions, PasswordGenerator};
+
+// ============================================================================
+// JavaScript Bindings
+// ============================================================================
+
+/// Generates one password
+///
+/// # Arguments
+/// * `options` - Generator options as a JSON document, with the same keys as
+///   `generate --options-file` (e.g. `{"length": 24, "numbers": true}`); an
+///   empty string uses the defaults
+///
+/// # Returns
+/// * `Result<String, JsError>` - The password, or an error (thrown in JavaScript)
+///   for an invalid document or unusable options
+#[wasm_bindgen]
+pub fn generate(options: &str) -> Result<String, JsError> {
+    let options: GeneratorOptions = if options.trim().is_empty() {
+        GeneratorOptions::default()
+    } else {
+        serde_json::from_str(options)?
+    };
+    Ok(PasswordGenerator::new(&options, os_rng()?)?.next_password()?)
+}
+
+/// Analyzes the strength of a password
+///
+/// # Arguments
+/// * `password` - Password to analyze
+///
+/// # Returns
+/// * `Result<String, JsError>` - The report as JSON, shaped exactly like the
+///   output of `analyze --output json`
+#[wasm_bindgen]
+pub fn analyze(password: &str) -> Result<String, JsError> {
+    let report = analyzer::analyze_password(password, &AnalyzerOptions::default());
+    Ok(serde_json::to_string(&report)?)
+}
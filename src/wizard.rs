@@ -0,0 +1,293 @@
+use std::io;
+use crate::i18n::{self, Msg};
+use crate::utils::{DEFAULT_LENGTH, MAX_LENGTH, MIN_LENGTH};
+
+// ============================================================================
+// Prompter Abstraction
+// ============================================================================
+
+/// Source of answers for the wizard
+///
+/// The wizard only talks to this trait, so the state machine can be driven
+/// by scripted answers as well as by a terminal.
+pub(crate) trait Prompter {
+    /// Lets the user pick one of `items` and returns its index
+    fn select(&mut self, prompt: &str, items: &[&str], default: usize) -> io::Result<usize>;
+
+    /// Asks for a line of text
+    fn input(&mut self, prompt: &str, default: &str) -> io::Result<String>;
+
+    /// Asks a yes/no question
+    fn confirm(&mut self, prompt: &str, default: bool) -> io::Result<bool>;
+
+    /// Tells the user an answer was rejected
+    fn error(&mut self, message: &str);
+}
+
+/// Prompter backed by interactive `dialoguer` prompts on stderr
+pub(crate) struct TerminalPrompter;
+
+impl Prompter for TerminalPrompter {
+    fn select(&mut self, prompt: &str, items: &[&str], default: usize) -> io::Result<usize> {
+        dialoguer::Select::new()
+            .with_prompt(prompt)
+            .items(items)
+            .default(default)
+            .interact()
+            .map_err(dialoguer_error)
+    }
+
+    fn input(&mut self, prompt: &str, default: &str) -> io::Result<String> {
+        dialoguer::Input::new()
+            .with_prompt(prompt)
+            .default(default.to_string())
+            .interact_text()
+            .map_err(dialoguer_error)
+    }
+
+    fn confirm(&mut self, prompt: &str, default: bool) -> io::Result<bool> {
+        dialoguer::Confirm::new()
+            .with_prompt(prompt)
+            .default(default)
+            .interact()
+            .map_err(dialoguer_error)
+    }
+
+    fn error(&mut self, message: &str) {
+        crate::output::warn(message);
+    }
+}
+
+// ============================================================================
+// Wizard State Machine
+// ============================================================================
+
+/// Subcommands the wizard can set up
+const MODES: [Msg; 2] = [Msg::WizardModeGenerate, Msg::WizardModeAnalyze];
+
+/// Current step of the wizard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    ChooseMode,
+    Length,
+    Classes,
+    Confirm,
+    Done,
+}
+
+/// Answers collected so far
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Answers {
+    analyze: bool,
+    length: u32,
+    uppercase_chars: bool,
+    numbers: bool,
+    special_chars: bool,
+}
+
+impl Default for Answers {
+    fn default() -> Self {
+        Answers { analyze: false, length: DEFAULT_LENGTH, uppercase_chars: true, numbers: true, special_chars: true }
+    }
+}
+
+impl Answers {
+    /// Arguments (after the program name) of the command the answers describe
+    fn to_args(&self) -> Vec<String> {
+        if self.analyze {
+            // The password is read by the hidden prompt of `analyze`, never placed on the command line
+            return vec![String::from("analyze")];
+        }
+
+        let mut args = vec![String::from("generate"), String::from("-l"), self.length.to_string()];
+        for (enabled, flag) in [(self.uppercase_chars, "-u"), (self.numbers, "-n"), (self.special_chars, "-s")] {
+            if enabled {
+                args.push(flag.to_string());
+            }
+        }
+        args
+    }
+}
+
+/// Walks the user through building a command
+///
+/// # Arguments
+/// * `prompter` - Source of the answers
+///
+/// # Returns
+/// * `io::Result<Option<Vec<String>>>` - Arguments of the chosen command (without the
+///   program name), or `None` when the user declined at the confirmation step
+///
+/// # Notes
+/// An out-of-range length is reported and asked again rather than clamped,
+/// so the printed command is exactly what runs.
+pub(crate) fn run(prompter: &mut dyn Prompter) -> io::Result<Option<Vec<String>>> {
+    let mut answers = Answers::default();
+    let mut step = Step::ChooseMode;
+
+    while step != Step::Done {
+        step = match step {
+            Step::ChooseMode => {
+                let modes = MODES.map(i18n::tr);
+                answers.analyze = prompter.select(i18n::tr(Msg::WizardChooseMode), &modes, 0)? == 1;
+                if answers.analyze { Step::Confirm } else { Step::Length }
+            }
+            Step::Length => {
+                let prompt = i18n::trf(Msg::WizardLength, &[&MIN_LENGTH, &MAX_LENGTH]);
+                match parse_length(&prompter.input(&prompt, &answers.length.to_string())?) {
+                    Ok(length) => {
+                        answers.length = length;
+                        Step::Classes
+                    }
+                    Err(message) => {
+                        prompter.error(&message);
+                        Step::Length
+                    }
+                }
+            }
+            Step::Classes => {
+                answers.uppercase_chars = prompter.confirm(i18n::tr(Msg::WizardUppercase), true)?;
+                answers.numbers = prompter.confirm(i18n::tr(Msg::WizardDigits), true)?;
+                answers.special_chars = prompter.confirm(i18n::tr(Msg::WizardSymbols), true)?;
+                Step::Confirm
+            }
+            Step::Confirm => {
+                let command = answers.to_args().join(" ");
+                if !prompter.confirm(&i18n::trf(Msg::WizardRun, &[&command]), true)? {
+                    return Ok(None);
+                }
+                Step::Done
+            }
+            Step::Done => Step::Done,
+        };
+    }
+
+    Ok(Some(answers.to_args()))
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Parses a length answer, accepting only values the generator supports
+fn parse_length(answer: &str) -> Result<u32, String> {
+    match answer.trim().parse::<u32>() {
+        Ok(length) if (MIN_LENGTH..=MAX_LENGTH).contains(&length) => Ok(length),
+        _ => Err(i18n::trf(Msg::WizardLengthInvalid, &[&MIN_LENGTH, &MAX_LENGTH])),
+    }
+}
+
+/// Converts a prompt failure into an I/O error, keeping Ctrl-C recognizable
+fn dialoguer_error(error: dialoguer::Error) -> io::Error {
+    match error {
+        dialoguer::Error::IO(e) => e,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, Write};
+
+    /// Prompter reading one answer per line and echoing every prompt, like a plain terminal
+    ///
+    /// An empty line takes the default; the end of the input stands in for Ctrl-C.
+    struct LinePrompter<R, W> {
+        reader: R,
+        writer: W,
+    }
+
+    impl<R: BufRead, W: Write> LinePrompter<R, W> {
+        fn answer(&mut self, prompt: &str) -> io::Result<String> {
+            writeln!(self.writer, "{}", prompt)?;
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(io::Error::from(io::ErrorKind::Interrupted));
+            }
+            Ok(line.trim_end_matches(['\r', '\n']).to_string())
+        }
+    }
+
+    impl<R: BufRead, W: Write> Prompter for LinePrompter<R, W> {
+        fn select(&mut self, prompt: &str, items: &[&str], default: usize) -> io::Result<usize> {
+            match self.answer(&format!("{} [{}]", prompt, items.join(" / ")))?.as_str() {
+                "" => Ok(default),
+                answer => answer.parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, answer.to_string())),
+            }
+        }
+
+        fn input(&mut self, prompt: &str, default: &str) -> io::Result<String> {
+            let answer = self.answer(prompt)?;
+            Ok(if answer.is_empty() { default.to_string() } else { answer })
+        }
+
+        fn confirm(&mut self, prompt: &str, default: bool) -> io::Result<bool> {
+            match self.answer(prompt)?.as_str() {
+                "" => Ok(default),
+                "y" => Ok(true),
+                "n" => Ok(false),
+                answer => Err(io::Error::new(io::ErrorKind::InvalidInput, answer.to_string())),
+            }
+        }
+
+        fn error(&mut self, message: &str) {
+            writeln!(self.writer, "error: {}", message).unwrap();
+        }
+    }
+
+    /// Runs the wizard on scripted answers, returning its result and everything it wrote
+    fn run_with(answers: &str) -> (io::Result<Option<Vec<String>>>, String) {
+        let mut prompter = LinePrompter { reader: answers.as_bytes(), writer: Vec::new() };
+        let result = run(&mut prompter);
+        (result, String::from_utf8(prompter.writer).unwrap())
+    }
+
+    #[test]
+    fn defaults_generate_with_every_class() {
+        let (result, transcript) = run_with("\n\n\n\n\n\n");
+        assert_eq!(result.unwrap().unwrap(), ["generate", "-l", "16", "-u", "-n", "-s"]);
+        assert!(transcript.contains("Run `generate -l 16 -u -n -s`?"), "{}", transcript);
+        assert_eq!(transcript.lines().count(), 6, "{}", transcript);
+    }
+
+    #[test]
+    fn answers_become_flags() {
+        let (result, _) = run_with("0\n24\nn\ny\nn\ny\n");
+        assert_eq!(result.unwrap().unwrap(), ["generate", "-l", "24", "-n"]);
+    }
+
+    #[test]
+    fn analyze_skips_the_generate_steps() {
+        let (result, transcript) = run_with("1\n\n");
+        assert_eq!(result.unwrap().unwrap(), ["analyze"]);
+        assert!(!transcript.contains("length"), "{}", transcript);
+        assert!(transcript.contains("Run `analyze`?"), "{}", transcript);
+    }
+
+    #[test]
+    fn invalid_lengths_are_asked_again() {
+        let (result, transcript) = run_with("0\n7\nabc\n129\n128\n\n\n\n\n");
+        assert_eq!(result.unwrap().unwrap(), ["generate", "-l", "128", "-u", "-n", "-s"]);
+        assert_eq!(transcript.matches("error: Length must be a number between 8 and 128").count(), 3, "{}", transcript);
+        assert_eq!(transcript.matches("Password length (8-128)").count(), 4, "{}", transcript);
+    }
+
+    #[test]
+    fn declining_the_command_cancels() {
+        let (result, _) = run_with("\n\n\n\n\nn\n");
+        assert_eq!(result.unwrap(), None);
+        let (result, _) = run_with("1\nn\n");
+        assert_eq!(result.unwrap(), None);
+    }
+
+    #[test]
+    fn interrupting_a_prompt_is_an_error() {
+        let (result, transcript) = run_with("0\n20\n");
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::Interrupted);
+        assert!(transcript.contains("Include uppercase letters (A-Z)?"), "{}", transcript);
+    }
+}
@@ -0,0 +1,83 @@
+//! Colors: `--color` beats `RUSTYPASS_COLOR`, which beats `NO_COLOR`/`CLICOLOR_FORCE`, which beat
+//! the configuration file and auto-detection
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::Sandbox;
+
+/// A password whose report has a colored meter and grade when colors are on
+const PASSWORD: &str = "Tr0ub4dour&3";
+
+/// Whether `analyze` printed ANSI escapes with the given flags, environment and configuration
+fn colored(args: &[&str], env: &[(&str, &str)], config: Option<&str>) -> bool {
+    let sandbox = Sandbox::new();
+    let mut command = sandbox.command();
+    if let Some(config) = config {
+        command.arg("--config").arg(sandbox.write_config(config));
+    }
+    command.envs(env.iter().copied()).args(args).args(["analyze", PASSWORD]);
+    let output = command.assert().code(0).get_output().stdout.clone();
+    String::from_utf8(output).unwrap().contains('\u{1b}')
+}
+
+#[test]
+fn auto_detection_disables_colors_on_a_pipe() {
+    assert!(!colored(&[], &[], None));
+    assert!(!colored(&["--color", "auto"], &[], None));
+}
+
+#[test]
+fn color_flag_forces_either_way() {
+    assert!(colored(&["--color", "always"], &[], None));
+    assert!(!colored(&["--color", "never"], &[], None));
+    assert!(!colored(&["--no-color"], &[], None));
+}
+
+#[test]
+fn environment_decides_when_no_flag_is_given() {
+    assert!(!colored(&[], &[("NO_COLOR", "1")], None));
+    assert!(colored(&[], &[("CLICOLOR_FORCE", "1")], None));
+    // NO_COLOR wins when both are set
+    assert!(!colored(&[], &[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")], None));
+}
+
+#[test]
+fn empty_and_zero_values_are_ignored() {
+    assert!(!colored(&[], &[("CLICOLOR_FORCE", "0")], None));
+    assert!(!colored(&[], &[("CLICOLOR_FORCE", "")], None));
+    assert!(colored(&[], &[("NO_COLOR", ""), ("CLICOLOR_FORCE", "1")], None));
+}
+
+#[test]
+fn flag_beats_the_environment() {
+    assert!(colored(&["--color", "always"], &[("NO_COLOR", "1")], None));
+    assert!(!colored(&["--color", "never"], &[("CLICOLOR_FORCE", "1")], None));
+    assert!(!colored(&["--no-color"], &[("CLICOLOR_FORCE", "1")], None));
+    assert!(!colored(&["--color", "never"], &[("RUSTYPASS_COLOR", "always")], None));
+}
+
+#[test]
+fn rustypass_color_beats_the_standard_variables() {
+    assert!(colored(&[], &[("RUSTYPASS_COLOR", "always"), ("NO_COLOR", "1")], None));
+    assert!(!colored(&[], &[("RUSTYPASS_COLOR", "never"), ("CLICOLOR_FORCE", "1")], None));
+}
+
+#[test]
+fn environment_beats_the_configuration_file() {
+    assert!(colored(&[], &[], Some("color = \"always\"\n")));
+    assert!(!colored(&[], &[("NO_COLOR", "1")], Some("color = \"always\"\n")));
+    assert!(colored(&[], &[("CLICOLOR_FORCE", "1")], Some("color = \"never\"\n")));
+    assert!(!colored(&["--color", "never"], &[], Some("color = \"always\"\n")));
+}
+
+#[test]
+fn errors_follow_the_color_mode() {
+    for (args, escapes) in [(&["--color", "always"][..], true), (&[][..], false)] {
+        let output = Sandbox::new().command().args(args).args(["analyze", "--output", "csv", PASSWORD]).assert().code(1);
+        let stderr = String::from_utf8(output.get_output().stderr.clone()).unwrap();
+        assert_eq!(stderr.contains('\u{1b}'), escapes, "{:?}: {:?}", args, stderr);
+        assert!(stderr.contains("CSV output is only available with --file"), "{:?}", stderr);
+    }
+}
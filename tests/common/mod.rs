@@ -0,0 +1,93 @@
+//! Helpers shared by the integration tests
+
+#![allow(dead_code)]
+
+use std::path::Path;
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+/// Variables the program reads that must not leak in from the developer's shell
+const INHERITED_VARIABLES: [&str; 21] = [
+    "RUSTYPASS_COLOR",
+    "RUSTYPASS_CONFIG",
+    "RUSTYPASS_COUNT",
+    "RUSTYPASS_GRADE_THRESHOLDS",
+    "RUSTYPASS_LENGTH",
+    "RUSTYPASS_LOG",
+    "RUSTYPASS_NO_LOGO",
+    "RUSTYPASS_NUMBERS",
+    "RUSTYPASS_OUTPUT",
+    "RUSTYPASS_POLICY",
+    "RUSTYPASS_PROFILE",
+    "RUSTYPASS_QUIET",
+    "RUSTYPASS_SPECIAL",
+    "RUSTYPASS_UPPERCASE",
+    "NO_COLOR",
+    "CLICOLOR_FORCE",
+    "LANG",
+    "LC_ALL",
+    "LC_MESSAGES",
+    "XDG_CONFIG_HOME",
+    "XDG_DATA_HOME",
+];
+
+/// An isolated home: configuration, history and cache all live in a temporary directory
+pub struct Sandbox {
+    pub home: TempDir,
+}
+
+impl Sandbox {
+    pub fn new() -> Self {
+        Sandbox { home: tempfile::tempdir().expect("temporary directory") }
+    }
+
+    /// The program with a clean environment whose home is the sandbox
+    pub fn command(&self) -> Command {
+        Command::from_std(command_in(self.home.path()))
+    }
+
+    /// Path of a file inside the sandbox
+    pub fn path(&self, name: &str) -> std::path::PathBuf {
+        self.home.path().join(name)
+    }
+
+    /// Writes a configuration file, to be passed with `--config` or `RUSTYPASS_CONFIG`
+    pub fn write_config(&self, contents: &str) -> std::path::PathBuf {
+        let path = self.path("rustypass.toml");
+        std::fs::write(&path, contents).expect("config file is written");
+        path
+    }
+}
+
+/// The program with a clean environment and a home that does not exist, for tests that write nothing
+pub fn rustypass() -> Command {
+    Command::from_std(rustypass_std())
+}
+
+/// `rustypass` as a plain `std::process::Command`, for tests that manage the child process themselves
+pub fn rustypass_std() -> std::process::Command {
+    command_in(&std::env::temp_dir().join("rustypass-tests-no-home"))
+}
+
+/// The program with a clean environment; stdin is empty and stdout is a pipe
+fn command_in(home: &Path) -> std::process::Command {
+    let mut command = std::process::Command::new(assert_cmd::cargo::cargo_bin("rusty_password_utility"));
+    for variable in INHERITED_VARIABLES {
+        command.env_remove(variable);
+    }
+    command
+        .env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("XDG_DATA_HOME", home.join("data"))
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env("APPDATA", home.join("config"))
+        .env("LOCALAPPDATA", home.join("data"));
+    command
+}
+
+/// Whether a file is readable and writable by its owner only
+#[cfg(unix)]
+pub fn is_private(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).expect("file exists").permissions().mode() & 0o777 == 0o600
+}
@@ -0,0 +1,38 @@
+//! Without a subcommand the program generates, with `generate` options at the top level
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::rustypass;
+use predicates::prelude::*;
+
+/// The single password printed on stdout
+fn password(command: &mut assert_cmd::Command) -> String {
+    let output = String::from_utf8(command.assert().code(0).get_output().stdout.clone()).unwrap();
+    assert_eq!(output.lines().count(), 1, "{:?}", output);
+    output.strip_suffix('\n').expect("one line ending in a newline").to_string()
+}
+
+#[test]
+fn bare_invocation_generates_one_default_password() {
+    let password = password(&mut rustypass());
+    assert_eq!(password.chars().count(), 16, "{}", password);
+    assert!(password.chars().all(|c| c.is_ascii_lowercase()), "{}", password);
+}
+
+#[test]
+fn generate_options_work_without_the_subcommand() {
+    let password = password(rustypass().args(["-l", "24", "-u", "-n"]));
+    assert_eq!(password.chars().count(), 24, "{}", password);
+    assert!(password.chars().all(|c| c.is_ascii_alphanumeric()), "{}", password);
+}
+
+#[test]
+fn help_still_lists_the_subcommands() {
+    let mut help = rustypass().arg("--help").assert().code(0);
+    for subcommand in ["generate", "analyze", "audit", "check", "hash", "verify", "doctor"] {
+        help = help.stdout(predicate::str::contains(format!("  {}", subcommand)));
+    }
+    help.stdout(predicate::str::contains("Generate Options (without a subcommand)"));
+}
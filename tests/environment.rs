@@ -0,0 +1,71 @@
+//! `RUSTYPASS_*` variables sit between the configuration file and the command line
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{rustypass, Sandbox};
+use predicates::prelude::*;
+
+/// The single password printed on stdout
+fn password(command: &mut assert_cmd::Command) -> String {
+    let output = String::from_utf8(command.assert().code(0).get_output().stdout.clone()).unwrap();
+    output.trim_end_matches('\n').to_string()
+}
+
+#[test]
+fn length_variable_wins_over_the_config_file_and_loses_to_the_flag() {
+    let sandbox = Sandbox::new();
+    let config = sandbox.write_config("[generate]\nlength = 20\n");
+    let command = || {
+        let mut command = sandbox.command();
+        command.env("RUSTYPASS_CONFIG", &config);
+        command
+    };
+
+    assert_eq!(password(&mut command()).len(), 20);
+    assert_eq!(password(command().env("RUSTYPASS_LENGTH", "24")).len(), 24);
+    assert_eq!(password(command().env("RUSTYPASS_LENGTH", "24").args(["-l", "12"])).len(), 12);
+}
+
+#[test]
+fn profile_variable_selects_a_profile() {
+    let sandbox = Sandbox::new();
+    let config = sandbox.write_config("[profile.pin]\nlength = 10\nnumbers = true\n");
+    let password = password(sandbox.command().env("RUSTYPASS_CONFIG", &config).env("RUSTYPASS_PROFILE", "pin"));
+    assert_eq!(password.len(), 10, "{}", password);
+}
+
+#[test]
+fn boolean_variables_switch_classes_off() {
+    let sandbox = Sandbox::new();
+    let config = sandbox.write_config("[generate]\nnumbers = true\nuppercase_chars = true\n");
+    for off in ["0", "false", "NO", "Off"] {
+        let password = password(
+            sandbox.command().env("RUSTYPASS_CONFIG", &config).env("RUSTYPASS_NUMBERS", off).env("RUSTYPASS_UPPERCASE", off),
+        );
+        assert!(password.chars().all(|c| c.is_ascii_lowercase()), "RUSTYPASS_NUMBERS={}: {}", off, password);
+    }
+}
+
+#[test]
+fn empty_variables_are_ignored() {
+    assert_eq!(password(rustypass().env("RUSTYPASS_LENGTH", "")).len(), 16);
+}
+
+#[test]
+fn invalid_values_name_the_variable() {
+    for (variable, value, reason) in [
+        ("RUSTYPASS_LENGTH", "4", "length must be between"),
+        ("RUSTYPASS_COUNT", "0", "must be at least 1"),
+        ("RUSTYPASS_NUMBERS", "maybe", "expected one of 1, true, yes, on, 0, false, no, off"),
+        ("RUSTYPASS_OUTPUT", "xml", "expected one of text, json, csv"),
+    ] {
+        rustypass()
+            .env(variable, value)
+            .assert()
+            .failure()
+            .stdout("")
+            .stderr(predicate::str::contains(format!("invalid value '{}' for {}: {}", value, variable, reason)));
+    }
+}
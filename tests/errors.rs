@@ -0,0 +1,111 @@
+//! Generation errors end in a message and an exit code, never a panic
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::Sandbox;
+use predicates::prelude::*;
+
+/// Runs `generate --options-file` with the given document
+fn generate_with(document: &str, extra: &[&str]) -> assert_cmd::assert::Assert {
+    let sandbox = Sandbox::new();
+    let path = sandbox.path("options.json");
+    std::fs::write(&path, document).unwrap();
+    sandbox.command().args(extra).arg("generate").arg("--options-file").arg(&path).assert()
+}
+
+#[test]
+fn empty_charset_exits_with_three() {
+    generate_with(r#"{"lowercase_chars": false}"#, &[])
+        .code(3)
+        .stdout("")
+        .stderr(predicate::str::contains("the character set is empty"))
+        .stderr(predicate::str::contains("panicked").not());
+}
+
+#[test]
+fn conflicting_constraints_exit_with_three() {
+    generate_with(r#"{"min_digits": 2}"#, &[]).code(3).stderr(predicate::str::contains("which are not included"));
+    generate_with(r#"{"length": 8, "numbers": true, "min_digits": 9}"#, &[])
+        .code(3)
+        // Every enabled class contributes at least one character, lowercase included
+        .stderr(predicate::str::contains("the minimum counts need 10 characters"));
+    generate_with(r#"{"excluded_chars": "abcdefghijklmnopqrstuvwxyz"}"#, &[])
+        .code(3)
+        .stderr(predicate::str::contains("every lowercase character is excluded"));
+}
+
+#[test]
+fn out_of_range_document_lengths_exit_with_three() {
+    for length in ["4", "129"] {
+        generate_with(&format!(r#"{{"length": {}}}"#, length), &[])
+            .code(3)
+            .stdout("")
+            .stderr(predicate::str::contains(format!("a length of {} is not supported", length)));
+    }
+}
+
+#[test]
+fn out_of_range_length_flags_fall_back_to_the_default() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.path("options.json");
+    std::fs::write(&path, r#"{"length": 4}"#).unwrap();
+    // The flag wins over the document, and only the flag is forgiven
+    for args in [&["generate", "-l", "200"][..], &["generate", "-l", "200", "--options-file", path.to_str().unwrap()]] {
+        let output = sandbox
+            .command()
+            .args(args)
+            .assert()
+            .code(0)
+            .stderr(predicate::str::contains("length 200 is outside 8-128, using 16 instead"));
+        assert_eq!(output.get_output().stdout.len(), 17, "{:?}", args);
+    }
+}
+
+#[test]
+fn broken_options_documents_exit_with_three() {
+    generate_with(r#"{"bogus": 1}"#, &[]).code(3).stderr(predicate::str::contains("unknown field `bogus`"));
+    generate_with("{", &[]).code(3).stderr(predicate::str::contains("options.json"));
+}
+
+#[test]
+fn unreadable_files_exit_with_five() {
+    let sandbox = Sandbox::new();
+    let missing = sandbox.path("missing.json");
+    sandbox
+        .command()
+        .arg("generate")
+        .arg("--options-file")
+        .arg(&missing)
+        .assert()
+        .code(5)
+        .stderr(predicate::str::contains("cannot read"))
+        .stderr(predicate::str::contains("missing.json"));
+}
+
+#[test]
+fn unsatisfiable_policies_exit_with_four() {
+    let sandbox = Sandbox::new();
+    let policy = sandbox.path("policy.toml");
+    std::fs::write(&policy, "name = \"huge\"\nmin_length = 300\n").unwrap();
+    sandbox
+        .command()
+        .arg("generate")
+        .arg("--policy-file")
+        .arg(&policy)
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("policy 'huge'"));
+}
+
+#[test]
+fn machine_readable_errors_are_json() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.path("options.json");
+    std::fs::write(&path, r#"{"lowercase_chars": false}"#).unwrap();
+    let output = sandbox.command().args(["generate", "--output", "json", "--options-file"]).arg(&path).assert().code(3);
+    assert!(output.get_output().stdout.is_empty());
+    let error: serde_json::Value = serde_json::from_slice(&output.get_output().stderr).unwrap();
+    assert!(error["error"].as_str().unwrap().contains("character set is empty"));
+}
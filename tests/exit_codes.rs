@@ -0,0 +1,94 @@
+//! The exit-code contract of `analyze --min-bits` and friends
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{rustypass, rustypass_std};
+use predicates::prelude::*;
+
+#[test]
+fn report_is_still_gated_by_min_bits() {
+    rustypass().args(["analyze", "--report", "md", "--min-bits", "200", "abc"]).assert().code(2);
+    rustypass().args(["analyze", "--report", "md", "--min-bits", "2", "abc"]).assert().success().stdout(predicate::str::contains("# "));
+}
+
+#[test]
+fn strong_password_passes_silently() {
+    rustypass()
+        .args(["analyze", "--min-bits", "70", "--check", "kV9#mQ2$xL7!pR4&zT8*"])
+        .assert()
+        .code(0)
+        .stdout("")
+        .stderr("");
+}
+
+#[test]
+fn weak_password_fails_with_two() {
+    rustypass()
+        .args(["analyze", "--min-bits", "70", "--check", "abc"])
+        .assert()
+        .code(2)
+        .stdout("")
+        .stderr(predicate::str::starts_with("FAIL:"));
+}
+
+#[test]
+fn empty_password_fails_with_two() {
+    rustypass().args(["analyze", "--min-bits", "70", "--check", ""]).assert().code(2);
+    rustypass().args(["analyze", "--min-bits", "70", "--check", "--stdin"]).write_stdin("").assert().code(2);
+}
+
+#[test]
+fn quiet_check_prints_nothing_on_failure() {
+    rustypass().args(["--quiet", "analyze", "--min-bits", "70", "--check", "abc"]).assert().code(2).stdout("").stderr("");
+}
+
+#[test]
+fn min_bits_without_check_still_prints_the_report() {
+    rustypass()
+        .args(["analyze", "--min-bits", "70", "abc"])
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains("Entropy"));
+    rustypass().args(["analyze", "--min-bits", "70", "--output", "json", "kV9#mQ2$xL7!pR4&zT8*"]).assert().code(0);
+}
+
+#[test]
+fn usage_errors_exit_with_one() {
+    rustypass().args(["analyze", "--check"]).assert().code(1).stderr(predicate::str::contains("--min-bits"));
+    rustypass().args(["analyze", "--min-bits"]).assert().code(1);
+    rustypass().args(["analyze", "--min-bits", "many", "abc"]).assert().code(1);
+    rustypass().args(["analyze", "--no-such-flag"]).assert().code(1);
+    rustypass().args(["no-such-command"]).assert().code(1);
+}
+
+#[test]
+fn help_documents_the_exit_codes() {
+    rustypass().args(["analyze", "--help"]).assert().code(0).stdout(predicate::str::contains("Exit codes"));
+}
+
+#[test]
+fn closed_stdout_is_not_a_panic() {
+    use std::process::Stdio;
+
+    // Enough output to fill the pipe buffer, so the program writes after the reader is gone
+    for args in [
+        &["generate", "--count", "8000"][..],
+        &["generate", "--count", "8000", "--output", "csv"],
+        &["generate", "--count", "8000", "--raw"],
+        &["generate", "--count", "4000", "--output", "json"],
+    ] {
+        let mut child = rustypass_std()
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        drop(child.stdout.take());
+        let output = child.wait_with_output().unwrap();
+        assert_eq!(output.status.code(), Some(0), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        assert!(!String::from_utf8_lossy(&output.stderr).contains("panicked"));
+    }
+}
@@ -0,0 +1,53 @@
+//! `generate` output modes, end to end
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{rustypass, Sandbox};
+
+fn stdout(command: &mut assert_cmd::Command) -> String {
+    String::from_utf8(command.assert().success().get_output().stdout.clone()).unwrap()
+}
+
+#[test]
+fn template_grade_matches_analyze() {
+    // Two letters make 64 bits for the generator but far more for the analyzer,
+    // which sees a lowercase password: raw and effective grades disagree
+    let sandbox = Sandbox::new();
+    let options = sandbox.path("options.json");
+    std::fs::write(&options, r#"{"excluded_chars": "cdefghijklmnopqrstuvwxyz"}"#).unwrap();
+    let lines = stdout(sandbox.command().args(["generate", "-l", "64", "-c", "5", "--format", "{password}\t{entropy}\t{grade}"]).arg("--options-file").arg(&options));
+
+    for line in lines.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        assert_eq!(fields[1], "64.0");
+        let report: serde_json::Value = serde_json::from_str(&stdout(rustypass().args(["analyze", "--output", "json", fields[0]]))).unwrap();
+        assert_eq!(report["grade"], fields[2], "{}", fields[0]);
+    }
+}
+
+#[test]
+fn seed_reproduces_the_pinned_passwords() {
+    // Same seed and options as the library's pinned default-options test
+    rustypass()
+        .args(["generate", "--seed", "42", "-c", "2"])
+        .assert()
+        .success()
+        .stdout("vxeceiqzrnevaukl\nbtojzycikszdhstq\n")
+        .stderr(predicates::str::contains("--seed"));
+}
+
+#[test]
+fn seed_is_reported_in_the_metadata() {
+    let output = stdout(rustypass().args(["--verbose", "generate", "--seed", "1", "--output", "json"]));
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert!(json["metadata"]["rng"].as_str().unwrap().contains("not secret"), "{}", output);
+}
+
+#[test]
+fn seeded_passwords_are_never_stored() {
+    for args in [["--store", "name"], ["--htpasswd", "alice"], ["--env-key", "TOKEN"]] {
+        rustypass().args(["generate", "--seed", "1"]).args(args).assert().code(1);
+    }
+}
@@ -0,0 +1,52 @@
+//! Human-readable output follows `--lang`; machine-readable output does not
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{rustypass, Sandbox};
+use predicates::prelude::*;
+
+#[test]
+fn analysis_warnings_are_translated() {
+    rustypass()
+        .args(["--lang", "de", "analyze", "aaaaaaaaqwerty"])
+        .assert()
+        .stdout(predicate::str::contains("100 % der Zeichen sind Kleinbuchstaben"))
+        .stdout(predicate::str::contains("Enthält eine Tastaturfolge (6 Zeichen ab Position 9)"))
+        .stdout(predicate::str::contains("Nur 7 verschiedene Zeichen von 14"));
+    rustypass()
+        .args(["--lang", "en", "analyze", "aaaaaaaaqwerty"])
+        .assert()
+        .stdout(predicate::str::contains("100% of characters are lowercase letters"))
+        .stdout(predicate::str::contains("Contains a keyboard walk (6 characters at position 9)"));
+}
+
+#[test]
+fn batch_table_and_summary_are_translated() {
+    let sandbox = Sandbox::new();
+    let file = sandbox.path("passwords.txt");
+    std::fs::write(&file, "password\n\nSummer2023\n").unwrap();
+
+    let german = rustypass().args(["--lang", "de", "analyze", "--show-passwords", "--file"]).arg(&file).assert().success();
+    let german = String::from_utf8(german.get_output().stdout.clone()).unwrap();
+    for expected in [" Zeile  Passwort", "Hinweis: Zeile 2: leere Zeile übersprungen", "Zusammenfassung:", "  Analysiert:        2\n"] {
+        assert!(german.contains(expected), "{:?} missing from\n{}", expected, german);
+    }
+
+    let english = rustypass().args(["--lang", "en", "analyze", "--show-passwords", "--file"]).arg(&file).assert().success();
+    let english = String::from_utf8(english.get_output().stdout.clone()).unwrap();
+    for expected in ["  Line  Password", "Note: line 2: blank line skipped", "Summary:", "  Analyzed:        2\n"] {
+        assert!(english.contains(expected), "{:?} missing from\n{}", expected, english);
+    }
+}
+
+#[test]
+fn json_grades_do_not_depend_on_the_language() {
+    let grade = |lang: &str| {
+        let output = rustypass().args(["--lang", lang, "analyze", "--output", "json", "Summer2023"]).assert().success();
+        let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+        report["grade"].clone()
+    };
+    assert_eq!(grade("de"), grade("en"));
+}
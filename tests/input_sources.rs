@@ -0,0 +1,125 @@
+//! Where a password to analyze comes from: the argument, `--stdin`, `--password-env` or the prompt
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::rustypass;
+use predicates::prelude::*;
+
+/// Given as the positional argument
+const POSITIONAL: &str = "positional-pw";
+/// Piped to stdin
+const PIPED: &str = "from-stdin-password";
+/// Set in `SECRET_VAR`
+const ENVIRONMENT: &str = "environment-secret";
+
+/// Length of the password `analyze` ended up analyzing, with `PIPED` on stdin and `ENVIRONMENT` set
+fn analyzed_length(args: &[&str]) -> u64 {
+    let output = rustypass()
+        .env("SECRET_VAR", ENVIRONMENT)
+        .args(["analyze", "--output", "json"])
+        .args(args)
+        .write_stdin(PIPED)
+        .assert()
+        .code(0);
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    report["length"].as_u64().unwrap()
+}
+
+#[test]
+fn only_the_requested_source_is_read() {
+    // Piped data and a set variable are always present, but only read when asked for
+    assert_eq!(analyzed_length(&[POSITIONAL]), POSITIONAL.len() as u64);
+    assert_eq!(analyzed_length(&["--stdin"]), PIPED.len() as u64);
+    assert_eq!(analyzed_length(&["--from-stdin"]), PIPED.len() as u64);
+    assert_eq!(analyzed_length(&["--password-env", "SECRET_VAR"]), ENVIRONMENT.len() as u64);
+}
+
+#[test]
+fn explicit_sources_exclude_each_other() {
+    for args in [
+        &[POSITIONAL, "--stdin"][..],
+        &[POSITIONAL, "--password-env", "SECRET_VAR"],
+        &["--stdin", "--password-env", "SECRET_VAR"],
+        &[POSITIONAL, "--stdin", "--password-env", "SECRET_VAR"],
+        &[POSITIONAL, "--confirm"],
+        &["--stdin", "--confirm"],
+        &["--password-env", "SECRET_VAR", "--confirm"],
+    ] {
+        for command in ["analyze", "check"] {
+            rustypass()
+                .env("SECRET_VAR", ENVIRONMENT)
+                .arg(command)
+                .args(if command == "check" { &["--policy", "nist"][..] } else { &[] })
+                .args(args)
+                .write_stdin(PIPED)
+                .assert()
+                .code(1)
+                .stdout("")
+                .stderr(predicate::str::contains("cannot be used with"));
+        }
+    }
+}
+
+#[test]
+fn file_excludes_every_single_password_source() {
+    for args in [&[POSITIONAL][..], &["--stdin"], &["--password-env", "SECRET_VAR"], &["--confirm"]] {
+        rustypass()
+            .args(["analyze", "--file", "passwords.txt"])
+            .args(args)
+            .assert()
+            .code(1)
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+}
+
+#[test]
+fn without_a_source_or_terminal_there_is_no_password() {
+    rustypass()
+        .arg("analyze")
+        .write_stdin(PIPED)
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(predicate::str::contains("no password given; use --stdin or --password-env"));
+}
+
+#[test]
+fn stdin_loses_exactly_one_trailing_newline() {
+    for (input, length) in [("abc\n", 3), ("abc\r\n", 3), ("abc\n\n", 4), (" abc ", 5)] {
+        let output = rustypass().args(["analyze", "--stdin", "--output", "json"]).write_stdin(input).assert().code(0);
+        let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+        assert_eq!(report["length"], length, "{:?}", input);
+    }
+}
+
+#[test]
+fn unusable_variables_are_errors() {
+    rustypass()
+        .args(["analyze", "--password-env", "SECRET_VAR"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("environment variable SECRET_VAR is not set"));
+    rustypass()
+        .env("SECRET_VAR", "")
+        .args(["analyze", "--password-env", "SECRET_VAR"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("environment variable SECRET_VAR is empty"));
+}
+
+#[test]
+fn hash_and_verify_read_the_same_sources() {
+    let output = rustypass()
+        .env("SECRET_VAR", ENVIRONMENT)
+        .args(["hash", "--alg", "pbkdf2-sha256", "--iterations", "1000", "--password-env", "SECRET_VAR"])
+        .assert()
+        .code(0);
+    let hash = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let hash = hash.trim_end();
+
+    rustypass().args(["verify", "--stdin", hash]).write_stdin(ENVIRONMENT).assert().code(0);
+    rustypass().env("SECRET_VAR", ENVIRONMENT).args(["verify", "--password-env", "SECRET_VAR", hash]).assert().code(0);
+    rustypass().args(["verify", "--stdin", "--password-env", "SECRET_VAR", hash]).assert().code(1);
+}
@@ -0,0 +1,101 @@
+//! `generate --output json` prints one document on stdout, and errors as JSON on stderr
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{rustypass, Sandbox};
+use rusty_password_utility::generator::GeneratedPassword;
+
+/// Stdout of a successful run, after checking that stderr stayed empty
+fn json_stdout(command: &mut assert_cmd::Command) -> String {
+    let output = command.assert().code(0).stderr("").get_output().stdout.clone();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn single_password_round_trips_through_the_schema() {
+    let output = json_stdout(rustypass().args(["generate", "--output", "json", "-l", "20", "-u", "-n"]));
+    // Unknown fields are rejected, so this locks the schema in both directions
+    let generated: GeneratedPassword = serde_json::from_str(&output).unwrap();
+    let reparsed: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(serde_json::to_value(generated.with_secret()).unwrap(), reparsed);
+
+    assert_eq!(generated.length, 20);
+    assert_eq!(generated.password.chars().count(), 20);
+    assert!(generated.flags.uppercase_chars && generated.flags.numbers && !generated.flags.special_chars);
+    assert_eq!(generated.charset_size, 62);
+    assert_eq!(generated.entropy_bits, 20.0 * 62f64.log2());
+    assert!(generated.crack_time_seconds > 0.0);
+}
+
+#[test]
+fn document_has_exactly_the_documented_fields() {
+    let output = json_stdout(rustypass().args(["generate", "--output", "json"]));
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let mut keys: Vec<&str> = json.as_object().unwrap().keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    assert_eq!(keys, ["charset_size", "crack_time_seconds", "entropy_bits", "flags", "length", "password"]);
+    let mut flags: Vec<&str> = json["flags"].as_object().unwrap().keys().map(String::as_str).collect();
+    flags.sort_unstable();
+    assert_eq!(flags, ["numbers", "special_chars", "uppercase_chars"]);
+}
+
+#[test]
+fn policy_name_appears_in_the_flags() {
+    let output = json_stdout(rustypass().args(["generate", "--output", "json", "--policy", "nist"]));
+    let generated: GeneratedPassword = serde_json::from_str(&output).unwrap();
+    assert_eq!(generated.flags.policy.as_deref(), Some("NIST SP 800-63B"));
+}
+
+#[test]
+fn count_prints_an_array() {
+    let output = json_stdout(rustypass().args(["generate", "--output", "json", "--count", "3", "-l", "10"]));
+    let generated: Vec<GeneratedPassword> = serde_json::from_str(&output).unwrap();
+    assert_eq!(generated.len(), 3);
+    assert!(generated.iter().all(|g| g.length == 10 && g.password.len() == 10));
+}
+
+#[test]
+fn stdout_is_the_document_alone() {
+    // Forced colors must not leak into the document either
+    let output = json_stdout(rustypass().args(["--color", "always", "generate", "--output", "json", "--count", "2"]));
+    assert!(output.starts_with('[') && output.ends_with("]\n"), "{:?}", output);
+    assert!(!output.contains('\u{1b}'), "{:?}", output);
+}
+
+#[test]
+fn output_can_be_chosen_in_the_environment() {
+    let output = json_stdout(Sandbox::new().command().env("RUSTYPASS_OUTPUT", "json"));
+    serde_json::from_str::<GeneratedPassword>(&output).unwrap();
+}
+
+#[test]
+fn errors_are_a_json_object_on_stderr() {
+    let sandbox = Sandbox::new();
+    let missing = sandbox.path("missing.toml");
+    let options = sandbox.path("options.json");
+    std::fs::write(&options, r#"{"lowercase_chars": false}"#).unwrap();
+    for (command, code) in [
+        (sandbox.command().env("RUSTYPASS_COUNT", "0").args(["generate", "--output", "json"]), 1),
+        (sandbox.command().args(["generate", "--output", "json", "--policy-file"]).arg(&missing), 5),
+        (sandbox.command().args(["generate", "--output", "json", "--options-file"]).arg(&options), 3),
+    ] {
+        let output = command.assert().code(code);
+        assert!(output.get_output().stdout.is_empty());
+        let stderr = String::from_utf8(output.get_output().stderr.clone()).unwrap();
+        assert_eq!(stderr.lines().count(), 1, "{:?}", stderr);
+        let error: serde_json::Value = serde_json::from_str(&stderr).unwrap();
+        let object = error.as_object().unwrap();
+        assert_eq!(object.len(), 1, "{}", error);
+        assert!(!object["error"].as_str().unwrap().is_empty(), "{}", error);
+    }
+}
+
+#[test]
+fn text_errors_stay_plain() {
+    let output = rustypass().env("RUSTYPASS_COUNT", "0").arg("generate").assert().code(1);
+    let stderr = String::from_utf8(output.get_output().stderr.clone()).unwrap();
+    assert!(stderr.starts_with("Error: "), "{:?}", stderr);
+    assert!(serde_json::from_str::<serde_json::Value>(&stderr).is_err());
+}
@@ -0,0 +1,87 @@
+//! `generate -O/--output-file`: a private file that is never replaced by accident
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::Sandbox;
+use predicates::prelude::*;
+
+#[test]
+fn passwords_go_to_the_file_only() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.path("passwords.txt");
+    sandbox.command().args(["generate", "-c", "3", "--output-file"]).arg(&path).assert().success().stdout("");
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(contents.lines().count(), 3, "{:?}", contents);
+    assert!(contents.lines().all(|line| line.len() == 16), "{:?}", contents);
+}
+
+#[test]
+fn short_flag_keeps_the_output_format() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.path("passwords.json");
+    sandbox.command().args(["generate", "-c", "2", "-o", "json", "-O"]).arg(&path).assert().success();
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(json.as_array().map(Vec::len), Some(2));
+}
+
+#[cfg(unix)]
+#[test]
+fn files_are_created_with_mode_0600() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.path("secret.txt");
+    sandbox.command().args(["generate", "-O"]).arg(&path).assert().success();
+    assert!(common::is_private(&path));
+}
+
+#[test]
+fn existing_files_need_force() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.path("passwords.txt");
+    std::fs::write(&path, "keep me\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+    }
+
+    sandbox
+        .command()
+        .args(["generate", "--output-file"])
+        .arg(&path)
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(predicate::str::contains("already exists; pass --force to overwrite it"));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me\n");
+
+    sandbox.command().args(["generate", "--force", "--output-file"]).arg(&path).assert().success();
+    assert_ne!(std::fs::read_to_string(&path).unwrap(), "keep me\n");
+    // An overwritten file is made private as well
+    #[cfg(unix)]
+    assert!(common::is_private(&path));
+}
+
+#[test]
+fn force_requires_an_output_file() {
+    Sandbox::new().command().args(["generate", "--force"]).assert().code(1);
+}
+
+#[test]
+fn keepass_export_goes_to_the_short_flag_file() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.path("import.csv");
+    sandbox
+        .command()
+        .args(["generate", "-c", "2", "--export", "keepass-csv", "-O"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("");
+
+    let mut reader = csv::Reader::from_path(&path).unwrap();
+    assert_eq!(reader.headers().unwrap(), vec!["Group", "Title", "Username", "Password", "URL", "Notes"]);
+    assert_eq!(reader.records().count(), 2);
+}
@@ -0,0 +1,83 @@
+//! With stdout piped the program drops the logo, colors and labels on its own
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{rustypass, Sandbox};
+
+/// Both streams of a successful run
+fn streams(command: &mut assert_cmd::Command) -> (String, String) {
+    let output = command.assert().code(0).get_output().clone();
+    (String::from_utf8(output.stdout).unwrap(), String::from_utf8(output.stderr).unwrap())
+}
+
+/// Checks that neither stream carries ANSI escapes or human labels
+fn assert_undecorated(stdout: &str, stderr: &str) {
+    for (name, stream) in [("stdout", stdout), ("stderr", stderr)] {
+        assert!(!stream.contains('\u{1b}'), "{} has ANSI escapes: {:?}", name, stream);
+        for label in ["Generated Password:", "Password Strength Analysis:", "Token Strength Analysis:"] {
+            assert!(!stream.contains(label), "{} has a label: {:?}", name, stream);
+        }
+    }
+}
+
+#[test]
+fn piped_generate_prints_bare_lines() {
+    let (stdout, stderr) = streams(rustypass().args(["generate", "-l", "20", "-u", "-n", "-s"]));
+    assert_undecorated(&stdout, &stderr);
+    assert_eq!(stderr, "", "the logo is skipped");
+    let password = stdout.strip_suffix('\n').expect("one line ending in a newline");
+    assert_eq!(password.chars().count(), 20, "{:?}", stdout);
+    assert!(!password.contains(char::is_whitespace), "{:?}", stdout);
+}
+
+#[test]
+fn piped_generate_count_prints_one_password_per_line() {
+    let (stdout, stderr) = streams(rustypass().args(["generate", "--count", "5", "-u", "-n"]));
+    assert_undecorated(&stdout, &stderr);
+    assert_eq!(stdout.lines().count(), 5, "{:?}", stdout);
+    assert!(stdout.lines().all(|line| line.len() == 16 && line.chars().all(|c| c.is_ascii_alphanumeric())), "{:?}", stdout);
+}
+
+#[test]
+fn piped_analyze_prints_the_report_without_colors() {
+    let (stdout, stderr) = streams(rustypass().args(["analyze", "Tr0ub4dour&3"]));
+    assert_undecorated(&stdout, &stderr);
+    assert_eq!(stderr, "");
+    assert!(stdout.starts_with("Grade: "), "{:?}", stdout);
+    assert!(stdout.contains("Entropy:"), "{:?}", stdout);
+}
+
+#[test]
+fn piped_token_analysis_prints_the_report_without_colors() {
+    let (stdout, stderr) = streams(rustypass().args(["analyze", "--token", "9f86d081884c7d659a2feaa0c55ad015"]));
+    assert_undecorated(&stdout, &stderr);
+    assert!(stdout.contains("Encoding:      hex"), "{:?}", stdout);
+}
+
+#[test]
+fn piped_commentary_on_stderr_is_undecorated_too() {
+    let (stdout, stderr) = streams(rustypass().args(["generate", "--spell", "--count", "2", "-l", "8"]));
+    assert_undecorated(&stdout, &stderr);
+    assert_eq!(stdout.lines().count(), 2, "{:?}", stdout);
+    assert!(stderr.contains("Password 2:"), "{:?}", stderr);
+}
+
+#[test]
+fn piped_mask_prints_the_real_password() {
+    // Masking is for human eyes; a pipe always gets the value itself
+    let (stdout, stderr) = streams(rustypass().args(["generate", "--mask", "-l", "12"]));
+    assert_undecorated(&stdout, &stderr);
+    assert_eq!(stdout.len(), 13, "{:?}", stdout);
+    assert!(!stdout.contains('*'), "{:?}", stdout);
+}
+
+#[test]
+fn piped_output_ignores_the_logo_setting() {
+    let sandbox = Sandbox::new();
+    let config = sandbox.write_config("no_logo = false\n");
+    let (stdout, stderr) = streams(sandbox.command().arg("--config").arg(&config).arg("generate"));
+    assert_undecorated(&stdout, &stderr);
+    assert_eq!(stderr, "");
+}
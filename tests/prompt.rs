@@ -0,0 +1,202 @@
+//! The hidden password prompt of `analyze`, driven through a pseudo-terminal
+
+#![cfg(all(feature = "cli", unix))]
+
+mod common;
+
+use std::ffi::CStr;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::mem::MaybeUninit;
+use std::os::fd::{AsRawFd, FromRawFd};
+use std::os::unix::fs::OpenOptionsExt;
+use std::process::{Child, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use common::rustypass_std;
+
+/// How long to wait for the program before failing the test
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serializes `ptsname`, which returns a pointer into a shared static buffer
+static PTSNAME: Mutex<()> = Mutex::new(());
+
+/// A pseudo-terminal: the test types on the master side, the program reads from the slave side
+struct Pty {
+    master: File,
+    slave: File,
+    /// Everything the program wrote to the terminal, collected in the background
+    transcript: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Pty {
+    fn open() -> Self {
+        // SAFETY: plain libc calls on a descriptor owned by the returned `File`
+        let master = unsafe {
+            let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+            assert!(fd >= 0, "posix_openpt: {}", std::io::Error::last_os_error());
+            assert_eq!(libc::grantpt(fd), 0, "grantpt: {}", std::io::Error::last_os_error());
+            assert_eq!(libc::unlockpt(fd), 0, "unlockpt: {}", std::io::Error::last_os_error());
+            File::from_raw_fd(fd)
+        };
+        let path = {
+            let _guard = PTSNAME.lock().unwrap_or_else(|e| e.into_inner());
+            // SAFETY: `master` is an unlocked pty master; the name is copied out while the lock is held
+            let name = unsafe { libc::ptsname(master.as_raw_fd()) };
+            assert!(!name.is_null(), "ptsname: {}", std::io::Error::last_os_error());
+            unsafe { CStr::from_ptr(name) }.to_str().unwrap().to_string()
+        };
+        let slave = OpenOptions::new().read(true).write(true).custom_flags(libc::O_NOCTTY).open(path).unwrap();
+
+        let transcript = Arc::new(Mutex::new(Vec::new()));
+        let mut reader = master.try_clone().unwrap();
+        let sink = Arc::clone(&transcript);
+        // Never joined: the read blocks for as long as the test holds the slave side open
+        std::thread::spawn(move || {
+            let mut buffer = [0u8; 256];
+            while let Ok(n @ 1..) = reader.read(&mut buffer) {
+                sink.lock().unwrap().extend_from_slice(&buffer[..n]);
+            }
+        });
+
+        Pty { master, slave, transcript }
+    }
+
+    /// Starts the program with the terminal as stdin and stderr; stdout is a pipe
+    fn spawn(&self, args: &[&str]) -> Child {
+        rustypass_std()
+            .args(args)
+            .stdin(self.slave.try_clone().unwrap())
+            .stderr(self.slave.try_clone().unwrap())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap()
+    }
+
+    /// Whether the terminal currently echoes what is typed
+    fn echo(&self) -> bool {
+        let mut termios = MaybeUninit::<libc::termios>::uninit();
+        // SAFETY: tcgetattr fully initializes `termios` when it returns 0
+        let termios = unsafe {
+            assert_eq!(libc::tcgetattr(self.slave.as_raw_fd(), termios.as_mut_ptr()), 0);
+            termios.assume_init()
+        };
+        termios.c_lflag & libc::ECHO != 0
+    }
+
+    /// What the program wrote to the terminal so far
+    fn transcript(&self) -> String {
+        String::from_utf8_lossy(&self.transcript.lock().unwrap()).into_owned()
+    }
+
+    /// Waits until `prompt` is shown and the terminal is in raw mode, so typing is not echoed
+    fn wait_for_prompt(&self, prompt: &str) {
+        let started = Instant::now();
+        while !self.transcript().contains(prompt) || self.echo() {
+            assert!(started.elapsed() < TIMEOUT, "no {:?} prompt: {:?}", prompt, self.transcript());
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Types bytes as if on a keyboard
+    fn type_bytes(&mut self, bytes: &[u8]) {
+        self.master.write_all(bytes).unwrap();
+        self.master.flush().unwrap();
+    }
+}
+
+/// Waits for the program to exit and returns its exit code and stdout
+fn finish(mut child: Child) -> (i32, String) {
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        if started.elapsed() > TIMEOUT {
+            child.kill().unwrap();
+            panic!("the program did not exit");
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    let mut stdout = String::new();
+    child.stdout.take().unwrap().read_to_string(&mut stdout).unwrap();
+    (status.code().expect("exited normally"), stdout)
+}
+
+/// The `length` of an `analyze --output json` report
+fn reported_length(stdout: &str) -> u64 {
+    let report: serde_json::Value = serde_json::from_str(stdout).unwrap();
+    report["length"].as_u64().unwrap()
+}
+
+#[test]
+fn prompt_reads_the_password_without_echo() {
+    let mut pty = Pty::open();
+    let child = pty.spawn(&["analyze", "--output", "json"]);
+    pty.wait_for_prompt("Password: ");
+    pty.type_bytes(b"Tr0ub4dour&3\r");
+
+    let (code, stdout) = finish(child);
+    assert_eq!(code, 0, "{:?}", pty.transcript());
+    assert_eq!(reported_length(&stdout), 12);
+    assert!(!pty.transcript().contains("Tr0ub4dour"), "{:?}", pty.transcript());
+    assert!(pty.echo(), "echo is restored");
+}
+
+#[test]
+fn confirm_asks_twice() {
+    let mut pty = Pty::open();
+    let child = pty.spawn(&["analyze", "--confirm", "--output", "json"]);
+    pty.wait_for_prompt("Password: ");
+    pty.type_bytes(b"Tr0ub4dour&3\r");
+    pty.wait_for_prompt("Confirm password: ");
+    pty.type_bytes(b"Tr0ub4dour&3\r");
+
+    let (code, stdout) = finish(child);
+    assert_eq!(code, 0, "{:?}", pty.transcript());
+    assert_eq!(reported_length(&stdout), 12);
+    assert!(!pty.transcript().contains("Tr0ub4dour"), "{:?}", pty.transcript());
+}
+
+#[test]
+fn mismatched_confirmation_fails() {
+    let mut pty = Pty::open();
+    let child = pty.spawn(&["analyze", "--confirm"]);
+    pty.wait_for_prompt("Password: ");
+    pty.type_bytes(b"Tr0ub4dour&3\r");
+    pty.wait_for_prompt("Confirm password: ");
+    pty.type_bytes(b"Tr0ub4dour&4\r");
+
+    let (code, stdout) = finish(child);
+    assert_eq!(code, 1);
+    assert_eq!(stdout, "");
+    assert!(pty.transcript().contains("passwords do not match"), "{:?}", pty.transcript());
+    assert!(pty.echo(), "echo is restored");
+}
+
+#[test]
+fn ctrl_c_restores_the_terminal_and_exits_with_130() {
+    let mut pty = Pty::open();
+    let child = pty.spawn(&["analyze"]);
+    pty.wait_for_prompt("Password: ");
+    pty.type_bytes(b"Tr0ub\x03");
+
+    let (code, stdout) = finish(child);
+    assert_eq!(code, 130);
+    assert_eq!(stdout, "");
+    assert!(pty.echo(), "echo is restored");
+    assert!(!pty.transcript().contains("Tr0ub"), "{:?}", pty.transcript());
+}
+
+#[test]
+fn backspace_removes_a_whole_character() {
+    let mut pty = Pty::open();
+    let child = pty.spawn(&["analyze", "--output", "json"]);
+    pty.wait_for_prompt("Password: ");
+    // "Pässwö", then DEL erases the two-byte "ö", then "rd"
+    pty.type_bytes("Pässwö\x7frd\r".as_bytes());
+
+    let (code, stdout) = finish(child);
+    assert_eq!(code, 0, "{:?}", pty.transcript());
+    assert_eq!(reported_length(&stdout), 7);
+}
@@ -0,0 +1,71 @@
+//! `--quiet` prints the result and nothing else, so `PASS=$(rustypass --quiet)` captures just the password
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{rustypass, Sandbox};
+use predicates::prelude::*;
+
+/// Stdout of a successful run, after checking that stderr stayed empty
+fn quiet_stdout(command: &mut assert_cmd::Command) -> String {
+    let output = command.assert().code(0).stderr("").get_output().stdout.clone();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn quiet_output_is_exactly_the_password_and_one_newline() {
+    for args in [&["--quiet"][..], &["--quiet", "generate"], &["generate", "--quiet"], &["-q", "generate"]] {
+        let output = quiet_stdout(rustypass().args(args));
+        let password = output.strip_suffix('\n').unwrap_or_else(|| panic!("{:?}: {:?}", args, output));
+        assert_eq!(password.len(), 16, "{:?}: {:?}", args, output);
+        assert!(password.chars().all(|c| c.is_ascii_lowercase()), "{:?}: {:?}", args, output);
+    }
+}
+
+#[test]
+fn quiet_output_is_the_raw_password_plus_a_newline() {
+    // The seed warning is deliberately not silenced by --quiet, so only stdout is compared
+    let run = |extra: &str| {
+        let args = ["generate", "-l", "20", "-u", "-n", "-s", "--seed", "7", extra];
+        rustypass().args(args).assert().code(0).get_output().stdout.clone()
+    };
+    let mut raw = run("--raw");
+    raw.push(b'\n');
+    assert_eq!(run("--quiet"), raw);
+}
+
+#[test]
+fn quiet_count_prints_one_password_per_line() {
+    let output = quiet_stdout(rustypass().args(["--quiet", "generate", "--count", "3", "-l", "12"]));
+    assert!(output.ends_with('\n'), "{:?}", output);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 3, "{:?}", output);
+    assert!(lines.iter().all(|line| line.len() == 12), "{:?}", output);
+}
+
+#[test]
+fn quiet_drops_the_commentary_on_stderr() {
+    // Without --quiet the spell-out goes to stderr
+    let output = quiet_stdout(rustypass().args(["--quiet", "generate", "--spell"]));
+    assert_eq!(output.lines().count(), 1, "{:?}", output);
+    rustypass().args(["generate", "--spell"]).assert().code(0).stderr(predicate::str::is_empty().not());
+}
+
+#[test]
+fn quiet_can_be_set_in_the_environment() {
+    let sandbox = Sandbox::new();
+    let output = quiet_stdout(sandbox.command().env("RUSTYPASS_QUIET", "1").args(["generate", "--spell"]));
+    assert_eq!(output.lines().count(), 1, "{:?}", output);
+}
+
+#[test]
+fn no_logo_leaves_the_output_alone() {
+    let output = quiet_stdout(rustypass().args(["--no-logo", "generate", "-l", "10"]));
+    assert_eq!(output.len(), 11, "{:?}", output);
+}
+
+#[test]
+fn quiet_and_verbose_conflict() {
+    rustypass().args(["--quiet", "--verbose", "generate"]).assert().code(1).stdout("");
+}
@@ -0,0 +1,80 @@
+//! `--raw` writes exactly the password bytes: no label, no color, no trailing newline
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{rustypass, Sandbox};
+use predicates::prelude::*;
+
+/// Stdout bytes of a successful run, after checking that stderr stayed empty
+fn raw_stdout(command: &mut assert_cmd::Command) -> Vec<u8> {
+    command.assert().code(0).stderr("").get_output().stdout.clone()
+}
+
+#[test]
+fn raw_output_is_the_password_without_a_newline() {
+    let output = raw_stdout(rustypass().args(["generate", "--raw", "-l", "24", "-u", "-n", "-s"]));
+    assert_eq!(output.len(), 24, "{:?}", String::from_utf8_lossy(&output));
+    assert!(output.iter().all(|b| b.is_ascii_graphic()), "{:?}", String::from_utf8_lossy(&output));
+}
+
+#[test]
+fn raw_output_is_byte_identical_to_the_generated_value() {
+    // The seed pins the value, so the JSON document tells what the bytes must be
+    let args = ["generate", "-l", "20", "-u", "-n", "-s", "--seed", "42"];
+    let raw = rustypass().args(args).arg("--raw").assert().code(0).get_output().stdout.clone();
+    let json = rustypass().args(args).args(["--output", "json"]).assert().code(0).get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(raw, json["password"].as_str().unwrap().as_bytes());
+}
+
+#[test]
+fn raw_count_separates_with_newlines_and_ends_without_one() {
+    let output = raw_stdout(rustypass().args(["generate", "--raw", "--count", "3", "-l", "10"]));
+    assert_eq!(output.len(), 3 * 10 + 2, "{:?}", String::from_utf8_lossy(&output));
+    assert_eq!(output.iter().filter(|b| **b == b'\n').count(), 2);
+    assert_ne!(output.last(), Some(&b'\n'));
+    let entries: Vec<&[u8]> = output.split(|b| *b == b'\n').collect();
+    assert!(entries.iter().all(|entry| entry.len() == 10 && entry.iter().all(u8::is_ascii_lowercase)));
+}
+
+#[test]
+fn print0_separates_with_nul_and_ends_without_one() {
+    let output = raw_stdout(rustypass().args(["generate", "--raw", "--print0", "--count", "3", "-l", "10"]));
+    assert_eq!(output.len(), 3 * 10 + 2);
+    assert!(!output.contains(&b'\n'));
+    assert_ne!(output.last(), Some(&0));
+    let entries: Vec<&[u8]> = output.split(|b| *b == 0).collect();
+    assert_eq!(entries.len(), 3);
+    assert!(entries.iter().all(|entry| entry.len() == 10 && entry.iter().all(u8::is_ascii_lowercase)));
+}
+
+#[test]
+fn single_password_has_no_separator_with_print0() {
+    let output = raw_stdout(rustypass().args(["generate", "--raw", "--print0", "-l", "12"]));
+    assert_eq!(output.len(), 12);
+    assert!(!output.contains(&0));
+}
+
+#[test]
+fn raw_ignores_forced_colors() {
+    let output = raw_stdout(rustypass().args(["--color", "always", "generate", "--raw", "-u", "-n", "-s"]));
+    assert_eq!(output.len(), 16, "{:?}", String::from_utf8_lossy(&output));
+    assert!(!output.contains(&0x1b));
+}
+
+#[test]
+fn raw_file_has_no_trailing_newline() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.path("passwords.txt");
+    sandbox.command().args(["generate", "--raw", "--count", "2", "-l", "9", "--output-file"]).arg(&path).assert().code(0).stdout("");
+    let contents = std::fs::read(&path).unwrap();
+    assert_eq!(contents.len(), 9 + 1 + 9);
+    assert_ne!(contents.last(), Some(&b'\n'));
+}
+
+#[test]
+fn print0_requires_raw() {
+    rustypass().args(["generate", "--print0"]).assert().code(1).stdout("").stderr(predicate::str::contains("--raw"));
+}
@@ -0,0 +1,52 @@
+//! `--report-file` is written like `--output-file`: private and never clobbered by accident
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::Sandbox;
+use predicates::prelude::*;
+
+#[test]
+fn report_files_are_private_and_need_force_to_replace() {
+    let sandbox = Sandbox::new();
+    let passwords = sandbox.path("passwords.txt");
+    std::fs::write(&passwords, "password\nSummer2023\n").unwrap();
+    let report = sandbox.path("report.md");
+    let write = |extra: &[&str]| {
+        let mut command = sandbox.command();
+        command.args(["analyze", "--show-passwords", "--report", "md", "--file"]).arg(&passwords).arg("--report-file").arg(&report);
+        command.args(extra).assert()
+    };
+
+    write(&[]).success().stdout("");
+    assert!(std::fs::read_to_string(&report).unwrap().contains("Summer2023"));
+    #[cfg(unix)]
+    assert!(common::is_private(&report));
+
+    std::fs::write(&report, "keep me").unwrap();
+    write(&[]).code(1).stderr(predicate::str::contains("already exists; pass --force"));
+    assert_eq!(std::fs::read_to_string(&report).unwrap(), "keep me");
+
+    write(&["--force"]).success();
+    assert!(std::fs::read_to_string(&report).unwrap().starts_with("# Password File Report"));
+}
+
+#[test]
+fn audit_reports_follow_the_same_rules() {
+    let sandbox = Sandbox::new();
+    let export = sandbox.path("export.csv");
+    std::fs::write(&export, "url,username,password\nhttps://example.com,alice,hunter2\n").unwrap();
+    let report = sandbox.path("audit.html");
+    std::fs::write(&report, "keep me").unwrap();
+
+    let audit = |extra: &[&str]| {
+        let mut command = sandbox.command();
+        command.arg("audit").arg(&export).args(["--report", "html", "--report-file"]).arg(&report);
+        command.args(extra).assert()
+    };
+    audit(&[]).code(1);
+    assert_eq!(std::fs::read_to_string(&report).unwrap(), "keep me");
+    audit(&["--force"]).success();
+    assert!(std::fs::read_to_string(&report).unwrap().starts_with("<!DOCTYPE html>"));
+}
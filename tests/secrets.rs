@@ -0,0 +1,80 @@
+//! Passwords never reach logs, verbose output or error messages
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::rustypass;
+
+/// Runs with every log enabled and returns (stdout, stderr)
+fn traced(args: &[&str], stdin: &str) -> (String, String) {
+    let output = rustypass().env("RUSTYPASS_LOG", "trace").arg("-vv").args(args).write_stdin(stdin).output().unwrap();
+    (String::from_utf8(output.stdout).unwrap(), String::from_utf8(output.stderr).unwrap())
+}
+
+fn assert_not_leaked(secret: &str, stderr: &str) {
+    assert!(!stderr.contains(secret), "{:?} leaked into\n{}", secret, stderr);
+}
+
+#[test]
+fn generated_passwords_stay_out_of_trace_logs() {
+    let (stdout, stderr) = traced(&["generate", "--policy", "ad", "-u", "-n", "-s", "-c", "50"], "");
+    // The policy traces every rule decision, so the logs are as detailed as they get
+    assert!(stderr.contains("[TRACE"), "{}", stderr);
+    assert!(stderr.contains("-> pass"), "{}", stderr);
+
+    let passwords: Vec<&str> = stdout.lines().collect();
+    assert_eq!(passwords.len(), 50);
+    for password in passwords {
+        assert_not_leaked(password, &stderr);
+    }
+}
+
+#[test]
+fn json_and_analyze_runs_stay_out_of_trace_logs() {
+    let (stdout, stderr) = traced(&["generate", "--analyze", "--output", "json", "-c", "5"], "");
+    let document: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    for entry in document["passwords"].as_array().unwrap() {
+        assert_not_leaked(entry["password"].as_str().unwrap(), &stderr);
+    }
+}
+
+#[test]
+fn analyzed_passwords_stay_out_of_logs_and_errors() {
+    let secret = "hunter2-Correct-Horse";
+    // The report describes the password without repeating it, on either stream
+    let (stdout, stderr) = traced(&["analyze", secret], "");
+    assert_not_leaked(secret, &stdout);
+    assert_not_leaked(secret, &stderr);
+
+    // A failed --min-bits check explains itself without quoting the password
+    let (_, stderr) = traced(&["analyze", "--check", "--min-bits", "200", secret], "");
+    assert!(stderr.contains("below the required 200"), "{}", stderr);
+    assert_not_leaked(secret, &stderr);
+
+    let (stdout, stderr) = traced(&["analyze", "--stdin", "--output", "json"], secret);
+    assert_not_leaked(secret, &stdout);
+    assert_not_leaked(secret, &stderr);
+}
+
+#[test]
+fn hashed_and_verified_passwords_stay_out_of_logs() {
+    let (stdout, stderr) = traced(&["hash", "--generate", "--alg", "pbkdf2-sha256", "--iterations", "1000"], "");
+    let password = stdout.lines().next().unwrap();
+    assert_not_leaked(password, &stderr);
+
+    let hash = stdout.lines().last().unwrap();
+    let (stdout, stderr) = traced(&["verify", "--stdin", hash], "wrong-guess-1234\n");
+    assert!(stdout.contains("NO MATCH"), "{}", stdout);
+    assert_not_leaked("wrong-guess-1234", &stdout);
+    assert_not_leaked("wrong-guess-1234", &stderr);
+}
+
+#[test]
+fn rejected_input_is_not_echoed() {
+    // A password bcrypt would truncate is refused by its length alone
+    let secret = "x".repeat(80);
+    let (_, stderr) = traced(&["hash", "--stdin", "--alg", "bcrypt", "--cost", "4"], &secret);
+    assert!(stderr.contains("80"), "{}", stderr);
+    assert_not_leaked(&secret, &stderr);
+}
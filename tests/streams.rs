@@ -0,0 +1,101 @@
+//! Stdout carries only results; warnings, commentary and progress go to stderr
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{rustypass, Sandbox};
+
+/// Both streams of a run that exited with `code`
+fn streams(command: &mut assert_cmd::Command, code: i32) -> (String, String) {
+    let output = command.assert().code(code).get_output().clone();
+    (String::from_utf8(output.stdout).unwrap(), String::from_utf8(output.stderr).unwrap())
+}
+
+/// Checks that stdout is a single password of `length` characters and nothing else
+fn assert_password_only(stdout: &str, length: usize) {
+    let password = stdout.strip_suffix('\n').unwrap_or_else(|| panic!("no trailing newline: {:?}", stdout));
+    assert_eq!(password.chars().count(), length, "{:?}", stdout);
+    assert!(!password.contains(char::is_whitespace), "{:?}", stdout);
+}
+
+#[test]
+fn length_warning_goes_to_stderr() {
+    let (stdout, stderr) = streams(rustypass().args(["generate", "-l", "200"]), 0);
+    assert_password_only(&stdout, 16);
+    assert!(stderr.starts_with("Warning: "), "{:?}", stderr);
+    assert!(stderr.contains("length 200 is outside 8-128, using 16 instead"), "{:?}", stderr);
+}
+
+#[test]
+fn seed_warning_goes_to_stderr() {
+    let (stdout, stderr) = streams(rustypass().args(["generate", "--seed", "3", "-l", "12"]), 0);
+    assert_password_only(&stdout, 12);
+    assert!(stderr.contains("--seed"), "{:?}", stderr);
+}
+
+#[test]
+fn verbose_generation_details_go_to_stderr() {
+    let (stdout, stderr) = streams(rustypass().args(["--verbose", "generate", "-l", "14"]), 0);
+    assert_password_only(&stdout, 14);
+    assert!(stderr.lines().count() > 1, "{:?}", stderr);
+    assert!(stderr.contains("length"), "{:?}", stderr);
+}
+
+#[test]
+fn spell_out_goes_to_stderr() {
+    let (stdout, stderr) = streams(rustypass().args(["generate", "--spell", "-l", "8"]), 0);
+    assert_password_only(&stdout, 8);
+    assert_eq!(stderr.lines().count(), 8, "{:?}", stderr);
+}
+
+#[test]
+fn file_notice_goes_to_stderr() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.path("passwords.txt");
+    let (stdout, stderr) = streams(sandbox.command().args(["generate", "--count", "2", "--output-file"]).arg(&path), 0);
+    assert_eq!(stdout, "");
+    assert!(stderr.contains("passwords.txt"), "{:?}", stderr);
+}
+
+#[test]
+fn analyze_report_is_alone_on_stdout() {
+    let (stdout, stderr) = streams(rustypass().args(["analyze", "qwerty123"]), 0);
+    assert!(stdout.starts_with("Grade: "), "{:?}", stdout);
+    assert_eq!(stderr, "");
+}
+
+#[test]
+fn verbose_analysis_details_go_to_stderr() {
+    let (stdout, stderr) = streams(rustypass().args(["--verbose", "analyze", "qwerty123"]), 0);
+    assert!(stdout.starts_with("Grade: "), "{:?}", stdout);
+    assert!(!stdout.contains("Detector findings"), "{:?}", stdout);
+    assert!(stderr.contains("Detector findings"), "{:?}", stderr);
+}
+
+#[test]
+fn verbose_json_analysis_keeps_stdout_parseable() {
+    let (stdout, stderr) = streams(rustypass().args(["--verbose", "analyze", "--output", "json", "qwerty123"]), 0);
+    let report: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(report["length"], 9);
+    assert!(stderr.contains("Detector findings"), "{:?}", stderr);
+}
+
+#[test]
+fn failed_gate_reports_on_stderr_only() {
+    let (stdout, stderr) = streams(rustypass().args(["analyze", "--min-bits", "70", "--check", "abc"]), 2);
+    assert_eq!(stdout, "");
+    assert!(stderr.starts_with("FAIL:"), "{:?}", stderr);
+}
+
+#[test]
+fn errors_leave_stdout_empty() {
+    for (args, code) in [
+        (&["analyze", "--output", "csv", "abc"][..], 1),
+        (&["generate", "--policy-file", "/nonexistent/policy.toml"], 5),
+    ] {
+        let (stdout, stderr) = streams(rustypass().args(args), code);
+        assert_eq!(stdout, "", "{:?}", args);
+        assert!(stderr.starts_with("Error: "), "{:?}: {:?}", args, stderr);
+    }
+}